@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::PathBuilder;
+use bevy_time::TimePlugin;
+use big_brain::prelude::{Actor, Score};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use tug_of_ball::{
+    ai_config::AiConfig,
+    ai_player_controller::{self, AiPlayerInputs, MoveToBallScorer},
+    ball::{self, Ball, BallBounce, BallCrossedNetEvt, BALL_MAX_SPEED},
+    level::NetOffset,
+    player::{AiPlayer, Player, PlayerDash, PlayerSwing},
+    trail::{self, Trail},
+};
+
+const ENTITY_COUNTS: [usize; 3] = [10, 100, 1000];
+
+fn get_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins).add_plugin(TimePlugin);
+    app
+}
+
+// balls move away from the net on both sides, so they never hit it
+fn get_ball_dir(i: usize) -> Vec2 {
+    let x = if i % 2 == 0 { -1. } else { 1. };
+    Vec2::new(x, 0.3)
+}
+
+fn spawn_balls(world: &mut World, count: usize) -> Vec<Entity> {
+    (0..count)
+        .map(|i| {
+            let dir = get_ball_dir(i);
+            let bounce_e = world
+                .spawn()
+                .insert(BallBounce::default())
+                .insert(Transform::default())
+                .id();
+            let trail_e = world.spawn().id();
+            let pos = (dir * 100.).extend(0.);
+
+            world
+                .spawn()
+                .insert(Ball {
+                    dir,
+                    max_speed: BALL_MAX_SPEED,
+                    bounce_e: Some(bounce_e),
+                    trail_e: Some(trail_e),
+                    ..Default::default()
+                })
+                .insert(Transform::from_translation(pos))
+                .insert(GlobalTransform::from_translation(pos))
+                .id()
+        })
+        .collect()
+}
+
+// drag would eventually stop the balls and skew later iterations
+fn keep_balls_moving(mut ball_q: Query<(&mut Ball, &mut Transform)>) {
+    for (i, (mut ball, mut t)) in ball_q.iter_mut().enumerate() {
+        ball.dir = get_ball_dir(i);
+        t.translation = (ball.dir * 100.).extend(0.);
+    }
+}
+
+fn bench_ball_movement(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ball_movement");
+
+    for count in ENTITY_COUNTS {
+        let mut app = get_app();
+        app.insert_resource(NetOffset(0.))
+            .add_event::<BallCrossedNetEvt>()
+            .add_system(keep_balls_moving.before("movement"))
+            .add_system(ball::movement.label("movement"));
+        spawn_balls(&mut app.world, count);
+
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| app.update())
+        });
+    }
+
+    group.finish();
+}
+
+fn move_trail_targets(mut target_q: Query<&mut GlobalTransform, Without<Trail>>, time: Res<Time>) {
+    let offset = time.seconds_since_startup().sin() as f32 * 300.;
+    for (i, mut t) in target_q.iter_mut().enumerate() {
+        t.translation = Vec3::new(offset, i as f32, 0.);
+    }
+}
+
+fn bench_trail(c: &mut Criterion) {
+    let mut group = c.benchmark_group("trail");
+
+    for count in ENTITY_COUNTS {
+        let mut app = get_app();
+        app.add_system(move_trail_targets.before("store"))
+            .add_system(trail::store_path_points.label("store"))
+            .add_system(trail::draw_trail.after("store"));
+
+        for _ in 0..count {
+            let target_e = app.world.spawn().insert(GlobalTransform::default()).id();
+            app.world
+                .spawn()
+                .insert(PathBuilder::new().build())
+                .insert(Trail {
+                    points: Vec::new(),
+                    transform_e: target_e,
+                    duration_sec: 0.3,
+                    max_width: 30.,
+                });
+        }
+
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| app.update())
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_ai_scoring(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ai_scoring");
+
+    for count in ENTITY_COUNTS {
+        let mut app = get_app();
+        app.insert_resource(NetOffset(0.))
+            .init_resource::<AiConfig>()
+            .add_system(ai_player_controller::collect_inputs.label("inputs"))
+            .add_system(ai_player_controller::score_move_to_ball.after("inputs"));
+
+        spawn_balls(&mut app.world, count);
+
+        for id in 1..=2 {
+            let x = if id == 1 { -400. } else { 400. };
+            let aim_e = app.world.spawn().id();
+            let aim_charge_e = app.world.spawn().id();
+            let player_e = app
+                .world
+                .spawn()
+                .insert(Player::new(id, aim_e, aim_charge_e))
+                .insert(AiPlayer)
+                .insert(AiPlayerInputs::default())
+                .insert(PlayerSwing::default())
+                .insert(PlayerDash::default())
+                .insert(GlobalTransform::from_xyz(x, 0., 0.))
+                .id();
+            app.world
+                .spawn()
+                .insert(Actor(player_e))
+                .insert(Score::default())
+                .insert(MoveToBallScorer);
+        }
+
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| app.update())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_ball_movement, bench_trail, bench_ai_scoring);
+criterion_main!(benches);
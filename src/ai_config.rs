@@ -0,0 +1,150 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::ai_player_controller::{AiDifficulty, AiPlayerInputs};
+
+// the hot reload itself is enabled by the ConfigPlugin
+pub struct AiConfigPlugin;
+impl Plugin for AiConfigPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_asset::<AiConfig>()
+            .init_asset_loader::<AiConfigLoader>()
+            .init_resource::<AiConfig>()
+            .add_startup_system(setup)
+            .add_system(on_config_loaded);
+    }
+}
+
+// ai tuning - see assets/opponent.ai.ron
+#[derive(Deserialize, TypeUuid, Clone)]
+#[uuid = "9d1e3b57-6a2c-4c8e-b0f4-73a5d2e9c614"]
+pub struct AiConfig {
+    // thinkers pick the first action scoring above this, applies to the next match
+    pub pick_threshold: f32,
+    pub reaction_delay: ReactionDelayConfig,
+    pub risk: RiskConfig,
+    // a chased ball keeps being chased a bit past the threshold and vice versa
+    pub move_to_ball_hysteresis: f32,
+    // chasing a ball that can't be hit yet is less urgent than getting back in position
+    pub swing_cooldown_move_to_ball_mult: f32,
+    // the ai slows down closer to the ball than this
+    pub move_to_ball_slowdown_dist: f32,
+    pub center_line_reached_dist: f32,
+    pub swing_reach_dist: f32,
+}
+
+// time before a new ball (or a ball turning towards the player) is noticed
+#[derive(Deserialize, Clone)]
+pub struct ReactionDelayConfig {
+    pub easy_sec: f32,
+    pub normal_sec: f32,
+    pub hard_sec: f32,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct RiskConfig {
+    // risk taking when the match is level
+    pub base: f32,
+    // playing it safe when the opponent is a point away from the game
+    pub game_point_against: f32,
+    pub per_game_behind: f32,
+    pub per_point_behind: f32,
+    pub per_net_squeeze: f32,
+}
+
+impl AiConfig {
+    pub fn get_reaction_delay_sec(&self, difficulty: AiDifficulty) -> f32 {
+        match difficulty {
+            AiDifficulty::Easy => self.reaction_delay.easy_sec,
+            AiDifficulty::Normal => self.reaction_delay.normal_sec,
+            AiDifficulty::Hard => self.reaction_delay.hard_sec,
+        }
+    }
+}
+
+// used until the asset is loaded
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            pick_threshold: 0.2,
+            reaction_delay: ReactionDelayConfig {
+                easy_sec: 0.3,
+                normal_sec: 0.15,
+                hard_sec: 0.05,
+            },
+            risk: RiskConfig {
+                base: 0.5,
+                game_point_against: 0.1,
+                per_game_behind: 0.15,
+                per_point_behind: 0.05,
+                per_net_squeeze: 0.2,
+            },
+            move_to_ball_hysteresis: 0.1,
+            swing_cooldown_move_to_ball_mult: 0.5,
+            move_to_ball_slowdown_dist: 50.,
+            center_line_reached_dist: 15.,
+            swing_reach_dist: 100.,
+        }
+    }
+}
+
+struct AiConfigHandle(Handle<AiConfig>);
+
+#[derive(Default)]
+pub struct AiConfigLoader;
+
+impl AssetLoader for AiConfigLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let config: AiConfig = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(config));
+            Ok(())
+        })
+    }
+
+    // not tuning.ron, that one's taken by the game config
+    fn extensions(&self) -> &[&str] {
+        &["ai.ron"]
+    }
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(AiConfigHandle(asset_server.load("opponent.ai.ron")));
+}
+
+fn on_config_loaded(
+    mut ev_asset: EventReader<AssetEvent<AiConfig>>,
+    assets: Res<Assets<AiConfig>>,
+    handle: Res<AiConfigHandle>,
+    mut config: ResMut<AiConfig>,
+    difficulty: Res<AiDifficulty>,
+    mut inputs_q: Query<&mut AiPlayerInputs>,
+) {
+    for ev in ev_asset.iter() {
+        match ev {
+            AssetEvent::Created { handle: h } | AssetEvent::Modified { handle: h }
+                if *h == handle.0 =>
+            {
+                if let Some(loaded) = assets.get(h) {
+                    *config = loaded.clone();
+                    info!("Ai tuning loaded");
+
+                    // the delay is copied into the inputs on spawn
+                    for mut inputs in inputs_q.iter_mut() {
+                        inputs.reaction_delay_sec = config.get_reaction_delay_sec(*difficulty);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
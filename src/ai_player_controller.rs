@@ -1,44 +1,93 @@
 use crate::{
+    ai_config::AiConfig,
     animation::inverse_lerp,
-    ball::{Ball, BallBounce, BallHitEvt, BALL_MAX_SPEED},
+    asset::SpriteAtlas,
+    ball::{is_ball_reachable, Ball, BallBounce, BallStatus, BALL_MAX_SPEED},
+    config::GameConfig,
+    cosmetic::Loadouts,
     input_binding::{InputAction, InputAxis, PlayerInput},
-    level::{InitialRegion, NetOffset},
+    level::{CourtSettings, InitialRegion, NetOffset},
+    mode::GameMode,
     player::{
-        get_swing_multiplier_clamped, spawn_player, Player, PlayerAim, PlayerDash, PlayerMovement,
-        PlayerSwing, SWING_LABEL,
+        get_swing_multiplier_clamped, spawn_player, AiPlayer, Player, PlayerAim, PlayerDash,
+        PlayerJump, PlayerMovement, PlayerSwing, SWING_LABEL,
     },
-    player_action::PlayerActionStatus,
+    player_action::{get_time_until_ready, ActionTimer, PlayerActionStatus},
+    score::{MatchRules, Score as MatchScore},
     GameState,
 };
 use bevy::prelude::*;
+#[cfg(feature = "inspector")]
 use bevy_inspector_egui::Inspectable;
+use bevy_time::{ScaledTime, ScaledTimeDelta};
 use big_brain::prelude::*;
 
 pub struct AiPlayerControllerPlugin;
 impl Plugin for AiPlayerControllerPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_system_set(SystemSet::on_enter(GameState::Game).with_system(setup))
+        app.init_resource::<AiDifficulty>()
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(setup))
             .add_system_set(SystemSet::on_update(GameState::Game).with_system(collect_inputs))
             .add_system_to_stage(BigBrainStage::Actions, stand_still)
             .add_system_to_stage(BigBrainStage::Scorers, score_move_to_ball)
             .add_system_to_stage(BigBrainStage::Actions, move_to_ball_action)
+            .add_system_to_stage(BigBrainStage::Scorers, score_move_to_center_line)
+            .add_system_to_stage(BigBrainStage::Actions, move_to_center_line_action)
             .add_system_to_stage(BigBrainStage::Scorers, score_swing)
             .add_system_to_stage(BigBrainStage::Actions, swing_action);
     }
 }
 
-#[derive(Debug, Clone, Component)]
-pub struct AiPlayer;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiDifficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Default for AiDifficulty {
+    fn default() -> Self {
+        AiDifficulty::Normal
+    }
+}
 
-#[derive(Debug, Clone, Inspectable)]
+impl AiDifficulty {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "easy" => Some(AiDifficulty::Easy),
+            "normal" => Some(AiDifficulty::Normal),
+            "hard" => Some(AiDifficulty::Hard),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct BallData {
     entity: Entity,
     distance: f32,
+    // within the swing height reach right now
+    reachable: bool,
 }
 
-#[derive(Component, Default, Inspectable)]
+#[derive(Component, Default)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct AiPlayerInputs {
     closest_incoming_ball: Option<BallData>,
+    // 0 - safe shots through the middle, 1 - fast shots along the lines
+    risk: f32,
+    // time before a new ball (or a ball turning towards the player) is noticed
+    pub reaction_delay_sec: f32,
+    #[cfg_attr(feature = "inspector", inspectable(ignore))]
+    noticed_ball: Option<(Entity, bool)>,
+    reaction_timer_sec: f32,
+    // 0 when the action can be used right away
+    pub swing_ready_in_sec: f32,
+    pub dash_ready_in_sec: f32,
+    pub dashing: bool,
+    // the last move to ball decision, see AiConfig::move_to_ball_hysteresis
+    chasing_ball: bool,
 }
 
 #[derive(Debug, Clone, Component)]
@@ -80,72 +129,122 @@ pub struct SwingAction;
 // swing thinker
 // dodge thinker
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>, region: Res<InitialRegion>) {
-    if cfg!(feature = "debug") {
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    atlas: Res<SpriteAtlas>,
+    region: Res<InitialRegion>,
+    config: Res<GameConfig>,
+    mode: Res<GameMode>,
+    loadouts: Res<Loadouts>,
+    difficulty: Res<AiDifficulty>,
+    ai_config: Res<AiConfig>,
+) {
+    if cfg!(feature = "debug") && mode.has_opponent() {
         let move_thinker = Thinker::build()
-            .picker(FirstToScore::new(0.2))
+            .picker(FirstToScore::new(ai_config.pick_threshold))
             .when(MoveToBallScorer, MoveToBallAction)
             // .when(MoveDiagonallyToPlayerScorer, MoveDiagonallyToPlayerAction)
             // .when(MoveToOuterLineScorer, MoveToOuterLineAction)
-            // .when(MoveToCenterLineScorer, MoveToCenterLineAction)
-            // .otherwise(MoveToBallAction);
+            .when(MoveToCenterLineScorer, MoveToCenterLineAction)
             .otherwise(StandStillAction);
 
         let swing_thinker = Thinker::build()
-            .picker(FirstToScore::new(0.2))
+            .picker(FirstToScore::new(ai_config.pick_threshold))
             .when(SwingScorer, SwingAction);
 
-        spawn_player(2, &mut commands, &asset_server, &region)
-            .insert(AiPlayerInputs::default())
-            .insert(AiPlayer)
-            .insert(move_thinker)
-            .with_children(|b| {
-                b.spawn().insert(swing_thinker);
-            });
+        spawn_player(
+            2,
+            &mut commands,
+            &asset_server,
+            &atlas,
+            &region,
+            &config,
+            &loadouts,
+        )
+        .insert(AiPlayerInputs {
+            reaction_delay_sec: ai_config.get_reaction_delay_sec(*difficulty),
+            ..Default::default()
+        })
+        .insert(AiPlayer)
+        .insert(move_thinker)
+        .with_children(|b| {
+            b.spawn().insert(swing_thinker);
+        });
     }
 }
 
-fn on_ball_hit(
-    mut ball_hit_er: EventReader<BallHitEvt>,
-    ball_q: Query<&Ball>,
-    ai_q: Query<&Player, With<AiPlayer>>,
+pub fn collect_inputs(
+    mut ai_q: Query<
+        (
+            &mut AiPlayerInputs,
+            &GlobalTransform,
+            &Player,
+            &PlayerSwing,
+            &PlayerDash,
+            Option<&PlayerJump>,
+        ),
+        With<AiPlayer>,
+    >,
+    ball_q: Query<(Entity, &Ball, &BallStatus, &GlobalTransform), Without<AiPlayer>>,
+    bounce_q: Query<&Transform, With<BallBounce>>,
+    score: Res<MatchScore>,
+    rules: Res<MatchRules>,
+    net: Res<NetOffset>,
+    court: Res<CourtSettings>,
+    ai_config: Res<AiConfig>,
+    time: ScaledTime,
 ) {
-    for ev in ball_hit_er.iter() {
-        if let Ok(ball) = ball_q.get(ev.ball_e) {
-            // ball.dir
-
-            for p in ai_q.iter() {
-                if p.id != ev.player_id {
-                    // todo: calc an intersection
-                    // pick a point on the trajectory of the ball and calc how long it would take the player to get there
-                    // pick one of the closest points taking the ball travel time into consideration
-                }
-            }
-        }
-    }
-}
+    for (mut inputs, ai_t, player, swing, dash, jump) in ai_q.iter_mut() {
+        inputs.risk = get_risk(&score, &rules, &ai_config, player, net.0, court.right);
+        inputs.swing_ready_in_sec =
+            get_time_until_ready(&swing.status, &swing.timer, swing.get_cooldown_sec());
+        inputs.dash_ready_in_sec =
+            get_time_until_ready(&dash.status, &dash.timer, dash.get_cooldown_sec());
+        inputs.dashing = matches!(dash.status, PlayerActionStatus::Active(_));
 
-fn collect_inputs(
-    mut ai_q: Query<(&mut AiPlayerInputs, &GlobalTransform, &Player), With<AiPlayer>>,
-    ball_q: Query<(Entity, &Ball, &GlobalTransform), Without<AiPlayer>>,
-) {
-    for (mut inputs, ai_t, player) in ai_q.iter_mut() {
-        if let Some((e, ball, ball_t)) = ball_q
+        // polled every frame, so serves, drops and multiple balls are picked up too
+        let closest_ball = ball_q
             .iter()
-            .filter(|(_, b, _)| {
-                (player.is_left() && b.dir.x < 0.) || (!player.is_left() && b.dir.x > 0.)
-            })
-            .max_by(|(_, _, t1), (_, _, t2)| {
-                if player.is_left() {
-                    t1.translation.x.partial_cmp(&t2.translation.x).unwrap()
-                } else {
-                    t2.translation.x.partial_cmp(&t1.translation.x).unwrap()
-                }
+            .filter_map(|(e, b, status, t)| {
+                let on_own_side = (t.translation.x - net.0) * player.get_sign() > 0.;
+                let approaching = b.dir.x * player.get_sign() > 0.;
+                let in_play = match status {
+                    BallStatus::Serve(_, _, server_id) => *server_id == player.id,
+                    BallStatus::Drop => true,
+                    // a ball bounced back from the net is still in play
+                    BallStatus::Rally(hitter_id) => *hitter_id != player.id || on_own_side,
+                    BallStatus::Fault(..) | BallStatus::Used => false,
+                };
+
+                (in_play && (approaching || on_own_side)).then(|| (e, b, t, approaching))
             })
-        {
+            .min_by(|(_, _, t1, _), (_, _, t2, _)| {
+                let d1 = t1.translation.distance_squared(ai_t.translation);
+                let d2 = t2.translation.distance_squared(ai_t.translation);
+                d1.partial_cmp(&d2).unwrap()
+            });
+
+        let noticed_ball = closest_ball.map(|(e, _, _, approaching)| (e, approaching));
+        if noticed_ball != inputs.noticed_ball {
+            inputs.noticed_ball = noticed_ball;
+            inputs.reaction_timer_sec = 0.;
+        } else {
+            inputs.reaction_timer_sec += time.scaled_delta_seconds();
+        }
+
+        let reacted = inputs.reaction_timer_sec >= inputs.reaction_delay_sec;
+        if let Some((e, ball, ball_t, _)) = closest_ball.filter(|_| reacted) {
+            let ball_height = ball
+                .bounce_e
+                .and_then(|bounce_e| bounce_q.get(bounce_e).ok())
+                .map_or(0., |t| t.translation.y);
+            let player_height = jump.map_or(0., |j| j.height);
+
             inputs.closest_incoming_ball = Some(BallData {
                 entity: e,
                 distance: (ball_t.translation - ai_t.translation).length(),
+                reachable: is_ball_reachable(ball_height, player_height),
             });
         } else {
             inputs.closest_incoming_ball = None;
@@ -153,6 +252,37 @@ fn collect_inputs(
     }
 }
 
+// riskier when behind or squeezed by the net, safer when ahead
+fn get_risk(
+    score: &MatchScore,
+    rules: &MatchRules,
+    ai_config: &AiConfig,
+    player: &Player,
+    net_offset: f32,
+    court_half_width: f32,
+) -> f32 {
+    if score.is_game_point(rules, !player.is_left()) {
+        return ai_config.risk.game_point_against;
+    }
+
+    let (own, other) = if player.is_left() {
+        (&score.left_player, &score.right_player)
+    } else {
+        (&score.right_player, &score.left_player)
+    };
+    let games_behind = other.games as f32 - own.games as f32;
+    let points_behind = other.points as f32 - own.points as f32;
+    // the net moved into own half leaves less court to play with
+    let net_squeeze = (net_offset * player.get_sign() / court_half_width).clamp(-1., 1.);
+
+    let risk = &ai_config.risk;
+    (risk.base
+        + games_behind * risk.per_game_behind
+        + points_behind * risk.per_point_behind
+        + net_squeeze * risk.per_net_squeeze)
+        .clamp(0., 1.)
+}
+
 fn stand_still(
     mut action_q: Query<(&Actor, &mut ActionState), With<StandStillAction>>,
     mut move_q: Query<&mut PlayerMovement>,
@@ -173,15 +303,21 @@ fn stand_still(
     }
 }
 
-fn score_move_to_ball(
+pub fn score_move_to_ball(
     mut score_q: Query<(&Actor, &mut Score), With<MoveToBallScorer>>,
-    inputs_q: Query<(&AiPlayerInputs, &Player, &GlobalTransform)>,
+    mut inputs_q: Query<(&mut AiPlayerInputs, &Player, &GlobalTransform)>,
     ball_q: Query<(&Ball, &GlobalTransform), Without<Player>>,
     ball_bounce_q: Query<&BallBounce>,
     net: Res<NetOffset>,
+    ai_config: Res<AiConfig>,
 ) {
     for (Actor(actor), mut score) in score_q.iter_mut() {
-        if let Ok((inputs, player, t)) = inputs_q.get(*actor) {
+        if let Ok((mut inputs, player, t)) = inputs_q.get_mut(*actor) {
+            // a dash can't be steered, so the decision made before it stands
+            if inputs.dashing {
+                continue;
+            }
+
             match &inputs.closest_incoming_ball {
                 Some(ball_data) => {
                     if let Ok((ball, ball_t)) = ball_q.get(ball_data.entity) {
@@ -213,6 +349,21 @@ fn score_move_to_ball(
                 }
                 None => score.set(0.),
             }
+
+            let mut chase_score = score.get();
+            if inputs.swing_ready_in_sec > 0. {
+                chase_score *= ai_config.swing_cooldown_move_to_ball_mult;
+            }
+            chase_score = if inputs.chasing_ball {
+                chase_score + ai_config.move_to_ball_hysteresis
+            } else {
+                chase_score - ai_config.move_to_ball_hysteresis
+            }
+            .clamp(0., 1.);
+
+            inputs.chasing_ball =
+                inputs.closest_incoming_ball.is_some() && chase_score >= ai_config.pick_threshold;
+            score.set(if inputs.chasing_ball { chase_score } else { 0. });
         }
     }
 }
@@ -221,6 +372,7 @@ fn move_to_ball_action(
     mut action_q: Query<(&Actor, &mut ActionState), With<MoveToBallAction>>,
     mut q: Query<(&mut PlayerMovement, &AiPlayerInputs, &GlobalTransform)>,
     ball_q: Query<&GlobalTransform, (With<Ball>, Without<Player>)>,
+    ai_config: Res<AiConfig>,
 ) {
     for (Actor(actor), mut state) in action_q.iter_mut() {
         if let Ok((mut movement, inputs, t)) = q.get_mut(*actor) {
@@ -229,7 +381,7 @@ fn move_to_ball_action(
                     match &inputs.closest_incoming_ball {
                         Some(ball_data) => {
                             if let Ok(ball_t) = ball_q.get(ball_data.entity) {
-                                let dist_clamp_max = 50.;
+                                let dist_clamp_max = ai_config.move_to_ball_slowdown_dist;
                                 let dist_mult = inverse_lerp(
                                     0.,
                                     dist_clamp_max,
@@ -253,18 +405,92 @@ fn move_to_ball_action(
     }
 }
 
+// back to the middle of own half while there's no ball to chase or the swing is recovering
+fn score_move_to_center_line(
+    mut score_q: Query<(&Actor, &mut Score), With<MoveToCenterLineScorer>>,
+    inputs_q: Query<(&AiPlayerInputs, &Player, &GlobalTransform)>,
+    net: Res<NetOffset>,
+    court: Res<CourtSettings>,
+    ai_config: Res<AiConfig>,
+) {
+    for (Actor(actor), mut score) in score_q.iter_mut() {
+        if let Ok((inputs, player, t)) = inputs_q.get(*actor) {
+            if inputs.dashing {
+                continue;
+            }
+
+            let dist = t
+                .translation
+                .truncate()
+                .distance(get_center_line_pos(player, net.0, &court));
+
+            if dist < ai_config.center_line_reached_dist || inputs.chasing_ball {
+                score.set(0.);
+            } else if inputs.closest_incoming_ball.is_none() || inputs.swing_ready_in_sec > 0. {
+                score.set(1.);
+            } else {
+                score.set(0.);
+            }
+        }
+    }
+}
+
+fn move_to_center_line_action(
+    mut action_q: Query<(&Actor, &mut ActionState), With<MoveToCenterLineAction>>,
+    mut q: Query<(&mut PlayerMovement, &Player, &GlobalTransform)>,
+    net: Res<NetOffset>,
+    court: Res<CourtSettings>,
+    ai_config: Res<AiConfig>,
+) {
+    for (Actor(actor), mut state) in action_q.iter_mut() {
+        if let Ok((mut movement, player, t)) = q.get_mut(*actor) {
+            match *state {
+                ActionState::Requested | ActionState::Executing => {
+                    let to_center =
+                        get_center_line_pos(player, net.0, &court) - t.translation.truncate();
+
+                    if to_center.length() < ai_config.center_line_reached_dist {
+                        movement.raw_dir = Vec2::ZERO;
+                        *state = ActionState::Success;
+                    } else {
+                        movement.raw_dir = to_center.normalize();
+                        *state = ActionState::Executing;
+                    }
+                }
+                ActionState::Cancelled => {
+                    *state = ActionState::Failure;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn get_center_line_pos(player: &Player, net_offset: f32, court: &CourtSettings) -> Vec2 {
+    let baseline_x = if player.is_left() {
+        court.left
+    } else {
+        court.right
+    };
+
+    Vec2::new((net_offset + baseline_x) * 0.5, 0.)
+}
+
 fn score_swing(
     mut score_q: Query<(&Actor, &mut Score), With<SwingScorer>>,
     parent_q: Query<&Parent>,
     inputs_q: Query<&AiPlayerInputs>,
+    ai_config: Res<AiConfig>,
 ) {
     for (Actor(actor), mut score) in score_q.iter_mut() {
         if let Ok(parent) = parent_q.get(*actor) {
             if let Ok(inputs) = inputs_q.get(parent.0) {
                 match &inputs.closest_incoming_ball {
+                    // a swing on cooldown would just fail
+                    Some(_) if inputs.swing_ready_in_sec > 0. => score.set(0.),
                     Some(ball_data) => {
-                        // todo: get treshold value from swing or somewhere
-                        if ball_data.distance < 100. {
+                        // wait for the ball to drop within reach
+                        if ball_data.distance < ai_config.swing_reach_dist && ball_data.reachable {
                             score.set(1.);
                         } else {
                             score.set(0.);
@@ -280,17 +506,33 @@ fn score_swing(
 fn swing_action(
     mut action_q: Query<(&Actor, &mut ActionState), With<SwingAction>>,
     parent_q: Query<&Parent>,
-    mut swing_q: Query<&mut PlayerSwing>,
+    mut swing_q: Query<(&mut PlayerSwing, &Player, &AiPlayerInputs)>,
+    mut aim_q: Query<&mut PlayerAim>,
+    player_q: Query<(&Player, &GlobalTransform)>,
 ) {
     for (Actor(actor), mut state) in action_q.iter_mut() {
         if let Ok(parent) = parent_q.get(*actor) {
-            if let Ok(mut swing) = swing_q.get_mut(parent.0) {
+            if let Ok((mut swing, player, inputs)) = swing_q.get_mut(parent.0) {
                 match *state {
                     ActionState::Requested | ActionState::Executing => {
                         match swing.status {
                             PlayerActionStatus::Ready => {
+                                // aim away from the opponent
+                                let opponent_y = player_q
+                                    .iter()
+                                    .find(|(p, _)| p.id != player.id)
+                                    .map_or(0., |(_, t)| t.translation.y);
+                                let side = if opponent_y > 0. { -1. } else { 1. };
+
+                                if let Ok(mut aim) = aim_q.get_mut(player.aim_e) {
+                                    aim.raw_dir = Vec2::new(
+                                        -player.get_sign(),
+                                        side * (0.1 + 0.7 * inputs.risk),
+                                    );
+                                }
+
                                 // todo: charge
-                                swing.status = PlayerActionStatus::Active(0.3);
+                                swing.status = PlayerActionStatus::Active(0.4 + 0.6 * inputs.risk);
                                 *state = ActionState::Success;
                             }
                             _ => {
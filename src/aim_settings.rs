@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input_binding::{InputAction, PlayerInput},
+    player::Player,
+    score::Score,
+    storage,
+};
+
+const AIM_SETTINGS_STORAGE_KEY: &str = "settings/aim.ron";
+// cycled through by the aim setting actions, the 1st ones are the defaults
+const ROTATION_SPEEDS_DEG: [f32; 4] = [260., 400., 600., 180.];
+const SMOOTHING_VALUES: [f32; 3] = [0., 0.5, 0.8];
+
+pub struct AimSettingsPlugin;
+impl Plugin for AimSettingsPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.insert_resource(AimSettings::load())
+            .add_system(toggle_aim_settings)
+            .add_system(apply_aim_settings);
+    }
+}
+
+// indexed by player, kept between matches
+#[derive(Default, Serialize, Deserialize)]
+pub struct AimSettings(pub [PlayerAimSettings; 2]);
+
+impl AimSettings {
+    fn load() -> Self {
+        storage::load(AIM_SETTINGS_STORAGE_KEY)
+            .ok()
+            .and_then(|bytes| ron::de::from_bytes(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let result = ron::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|data| {
+                storage::save(AIM_SETTINGS_STORAGE_KEY, data.as_bytes()).map_err(|e| e.to_string())
+            });
+
+        if let Err(e) = result {
+            error!("Failed to save the aim settings: {}", e);
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Serialize, Deserialize)]
+pub struct PlayerAimSettings {
+    // at full stick tilt
+    pub rotation_speed_deg: f32,
+    // 0 follows the stick right away, closer to 1 eases into it
+    pub smoothing: f32,
+    // snaps to the aim direction, e.g. for keyboard players
+    pub instant: bool,
+}
+
+impl Default for PlayerAimSettings {
+    fn default() -> Self {
+        Self {
+            rotation_speed_deg: ROTATION_SPEEDS_DEG[0],
+            smoothing: SMOOTHING_VALUES[0],
+            instant: false,
+        }
+    }
+}
+
+fn get_next(values: &[f32], current: f32) -> f32 {
+    let i = values
+        .iter()
+        .position(|v| (v - current).abs() < f32::EPSILON)
+        .unwrap_or(0);
+    values[(i + 1) % values.len()]
+}
+
+fn toggle_aim_settings(
+    mut input: ResMut<PlayerInput>,
+    score: Res<Score>,
+    mut aim_settings: ResMut<AimSettings>,
+) {
+    if !score.is_match_start() {
+        return;
+    }
+
+    let mut changed = false;
+    // only borrowed mutably on a press, the players get the settings reapplied on every change
+    for id in 1..=aim_settings.0.len() {
+        if input.just_pressed(id, InputAction::CycleAimSpeed) {
+            input.use_button_action(id, InputAction::CycleAimSpeed);
+            let settings = &mut aim_settings.0[id - 1];
+            settings.rotation_speed_deg =
+                get_next(&ROTATION_SPEEDS_DEG, settings.rotation_speed_deg);
+            debug!("Player {} aim speed: {}", id, settings.rotation_speed_deg);
+            changed = true;
+        }
+
+        if input.just_pressed(id, InputAction::CycleAimSmoothing) {
+            input.use_button_action(id, InputAction::CycleAimSmoothing);
+            let settings = &mut aim_settings.0[id - 1];
+            settings.smoothing = get_next(&SMOOTHING_VALUES, settings.smoothing);
+            debug!("Player {} aim smoothing: {}", id, settings.smoothing);
+            changed = true;
+        }
+
+        if input.just_pressed(id, InputAction::ToggleInstantAim) {
+            input.use_button_action(id, InputAction::ToggleInstantAim);
+            let settings = &mut aim_settings.0[id - 1];
+            settings.instant = !settings.instant;
+            debug!("Player {} instant aim: {}", id, settings.instant);
+            changed = true;
+        }
+    }
+
+    if changed {
+        aim_settings.save();
+    }
+}
+
+// players are respawned every game
+fn apply_aim_settings(
+    mut commands: Commands,
+    aim_settings: Res<AimSettings>,
+    player_q: Query<(Entity, &Player, ChangeTrackers<Player>)>,
+) {
+    for (player_e, player, player_tracker) in player_q.iter() {
+        if aim_settings.is_changed() || player_tracker.is_added() {
+            if let Some(settings) = aim_settings.0.get(player.id - 1) {
+                commands.entity(player_e).insert(*settings);
+            }
+        }
+    }
+}
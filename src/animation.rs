@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+#[cfg(feature = "inspector")]
 use bevy_inspector_egui::Inspectable;
 use bevy_time::{ScaledTime, ScaledTimeDelta};
 use bevy_tweening::TweenCompleted;
@@ -6,12 +7,15 @@ use bevy_tweening::TweenCompleted;
 pub struct AnimationPlugin;
 impl Plugin for AnimationPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_system(rotate).add_system(on_tween_completed);
+        app.add_system(rotate)
+            .add_system(on_tween_completed)
+            .add_system(update_ground_shadows);
     }
 }
 
 // todo: struct
-#[derive(Default, Component, Inspectable)]
+#[derive(Default, Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct TransformRotation {
     pub rotation_rad: f32,
     pub rotation_max_rad: f32,
@@ -26,6 +30,20 @@ impl TransformRotation {
     }
 }
 
+// shadow of a sibling entity that can leave the ground (the caster's y is its height)
+// shrinks and drifts away as the caster rises, converging on landing
+#[derive(Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
+pub struct GroundShadow {
+    pub caster_e: Entity,
+    pub offset: Vec2,
+    pub scale: Vec2,
+    // height with the smallest shadow
+    pub max_height: f32,
+}
+
+const SHADOW_MIN_SCALE_MULT: f32 = 0.5;
+
 #[repr(u64)]
 pub enum TweenDoneAction {
     None = 0,
@@ -63,6 +81,23 @@ fn rotate(mut q: Query<(&TransformRotation, &mut Transform)>, time: ScaledTime)
     }
 }
 
+fn update_ground_shadows(
+    mut shadow_q: Query<(&GroundShadow, &mut Transform)>,
+    caster_q: Query<&Transform, Without<GroundShadow>>,
+) {
+    // away from the light in the top right
+    let max_drift = Vec2::new(-14., -7.);
+
+    for (shadow, mut t) in shadow_q.iter_mut() {
+        if let Ok(caster_t) = caster_q.get(shadow.caster_e) {
+            let height_t = (caster_t.translation.y / shadow.max_height).clamp(0., 1.);
+            let scale_mult = 1. - height_t * (1. - SHADOW_MIN_SCALE_MULT);
+            t.scale = (shadow.scale * scale_mult).extend(1.);
+            t.translation = (shadow.offset + max_drift * height_t).extend(t.translation.z);
+        }
+    }
+}
+
 pub fn inverse_lerp(a: f32, b: f32, t: f32) -> f32 {
     (t - a) / (b - a)
 }
@@ -1,10 +1,120 @@
-use bevy::{prelude::*, render::render_resource::FilterMode};
+use std::collections::HashMap;
+
+use bevy::{asset::LoadState, prelude::*, render::render_resource::FilterMode};
+use bevy_tweening::{component_animator_system, Lens};
+
+use crate::{get_initial_state, replay::ReplayPlayback, GameState};
+
+// packed into a single atlas at startup, so they share a draw call
+const ATLAS_SPRITES: [&str; 5] = [
+    "art-ish/ball.png",
+    "art-ish/face_happy.png",
+    "art-ish/net_post.png",
+    "art-ish/player_body.png",
+    "art-ish/player_circle.png",
+];
 
 pub struct AssetPlugin;
 impl Plugin for AssetPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_system(set_img_sampler_filter);
+        app.add_startup_system(load_atlas_sprites)
+            .add_system(set_img_sampler_filter)
+            .add_system(component_animator_system::<TextureAtlasSprite>)
+            .add_system_set(SystemSet::on_update(GameState::Loading).with_system(build_atlas));
+    }
+}
+
+pub struct SpriteAtlas {
+    pub handle: Handle<TextureAtlas>,
+    // atlas index and the source image size
+    sprites: HashMap<&'static str, (usize, Vec2)>,
+}
+
+// both panic for sprites missing in ATLAS_SPRITES
+impl SpriteAtlas {
+    pub fn get_sprite(&self, path: &str) -> TextureAtlasSprite {
+        TextureAtlasSprite::new(self.sprites[path].0)
     }
+
+    // atlas sprites have no custom size, so they're scaled to the size instead
+    pub fn get_scale(&self, path: &str, size: Vec2) -> Vec3 {
+        (size / self.sprites[path].1).extend(1.)
+    }
+}
+
+struct AtlasSources(Vec<Handle<Image>>);
+
+// the atlas counterpart of SpriteColorLens
+pub struct AtlasSpriteColorLens {
+    pub start: Color,
+    pub end: Color,
+}
+
+impl Lens<TextureAtlasSprite> for AtlasSpriteColorLens {
+    fn lerp(&mut self, target: &mut TextureAtlasSprite, ratio: f32) {
+        let start: Vec4 = self.start.into();
+        let end: Vec4 = self.end.into();
+        target.color = start.lerp(end, ratio).into();
+    }
+}
+
+fn load_atlas_sprites(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(AtlasSources(
+        ATLAS_SPRITES
+            .iter()
+            .map(|path| asset_server.load(*path))
+            .collect(),
+    ));
+}
+
+// the game starts once the atlas is ready
+fn build_atlas(
+    mut commands: Commands,
+    sources: Res<AtlasSources>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+    mut state: ResMut<State<GameState>>,
+    playback: Res<ReplayPlayback>,
+) {
+    if asset_server.get_group_load_state(sources.0.iter().map(|h| h.id)) != LoadState::Loaded {
+        return;
+    }
+
+    let mut builder = TextureAtlasBuilder::default();
+    for handle in sources.0.iter() {
+        if let Some(image) = images.get(handle) {
+            builder.add_texture(handle.clone(), image);
+        }
+    }
+
+    let atlas = match builder.finish(&mut images) {
+        Ok(atlas) => atlas,
+        Err(e) => {
+            error!("Failed to build the sprite atlas: {:?}", e);
+            return;
+        }
+    };
+
+    let sprites = ATLAS_SPRITES
+        .iter()
+        .zip(sources.0.iter())
+        .filter_map(|(path, handle)| {
+            atlas
+                .get_texture_index(handle)
+                .map(|i| (*path, (i, atlas.textures[i].size())))
+        })
+        .collect();
+
+    commands.insert_resource(SpriteAtlas {
+        handle: atlases.add(atlas),
+        sprites,
+    });
+    commands.remove_resource::<AtlasSources>();
+    // a replay passed on the command line is played right away
+    state
+        .set(get_initial_state(playback.replay.is_some()))
+        .unwrap();
 }
 
 fn set_img_sampler_filter(
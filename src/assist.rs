@@ -0,0 +1,260 @@
+use bevy::prelude::*;
+
+use crate::{
+    ball::{Ball, BallStatus},
+    config::BallConfig,
+    level::{get_region_bounds, CourtSettings, HighlightedRegion, NetOffset},
+    locale::Locale,
+    palette::PaletteColor,
+    player::{Player, PlayerAim, AIM_LABEL},
+    profile::Profiles,
+    score::Score,
+    trajectory::{get_landing_pos, Shot},
+    ui_text::{get_text_style, ScaledText, TextSettings},
+    GameState,
+};
+
+// assist toggle keys, indexed by player
+const SERVE_AIM_KEYS: [KeyCode; 2] = [KeyCode::Key3, KeyCode::Key4];
+const MAGNET_KEYS: [KeyCode; 2] = [KeyCode::Key5, KeyCode::Key6];
+const TWO_BOUNCE_KEYS: [KeyCode; 2] = [KeyCode::F6, KeyCode::F7];
+// wheelchair tennis rule
+const TWO_BOUNCE_LIMIT: usize = 2;
+// keeps the assisted aim off the lines
+const SERVE_AIM_MARGIN: f32 = 30.;
+// only shots landing this far out get bent
+const MAGNET_OUT_MARGIN: f32 = 40.;
+const MAGNET_TARGET_MARGIN: f32 = 50.;
+
+pub struct AssistPlugin;
+impl Plugin for AssistPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<Assists>()
+            .add_startup_system(setup)
+            .add_system(toggle_assists)
+            .add_system(apply_assists)
+            .add_system(update_assist_ui)
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(highlight_serve_target)
+                    .with_system(clamp_serve_aim.after(AIM_LABEL)),
+            );
+    }
+}
+
+// indexed by player, kept between matches
+#[derive(Default)]
+pub struct Assists(pub [PlayerAssist; 2]);
+
+#[derive(Component, Default, Clone, Copy)]
+pub struct PlayerAssist {
+    // keeps the serve aim within the target region
+    pub serve_aim: bool,
+    // bends shots going clearly wide back towards the court
+    pub magnetize: bool,
+    // the ball may bounce twice on the player's side before the point is lost
+    pub two_bounce: bool,
+}
+
+impl PlayerAssist {
+    pub fn get_bounce_limit(&self, rules_limit: usize) -> usize {
+        if self.two_bounce {
+            rules_limit.max(TWO_BOUNCE_LIMIT)
+        } else {
+            rules_limit
+        }
+    }
+}
+
+#[derive(Component)]
+struct AssistText;
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, text_settings: Res<TextSettings>) {
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(10.),
+                    left: Val::Percent(45.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                get_text_style(&asset_server, &text_settings, 18.0),
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(PaletteColor::Text)
+        .insert(ScaledText(18.0))
+        .insert(AssistText)
+        .insert(Name::new("AssistText"));
+}
+
+fn toggle_assists(keys: Res<Input<KeyCode>>, score: Res<Score>, mut assists: ResMut<Assists>) {
+    if !score.is_match_start() {
+        return;
+    }
+
+    for (i, key) in SERVE_AIM_KEYS.iter().enumerate() {
+        if keys.just_pressed(*key) {
+            let assist = &mut assists.0[i];
+            assist.serve_aim = !assist.serve_aim;
+            debug!("Player {} serve assist: {}", i + 1, assist.serve_aim);
+        }
+    }
+
+    for (i, key) in MAGNET_KEYS.iter().enumerate() {
+        if keys.just_pressed(*key) {
+            let assist = &mut assists.0[i];
+            assist.magnetize = !assist.magnetize;
+            debug!("Player {} magnet assist: {}", i + 1, assist.magnetize);
+        }
+    }
+
+    for (i, key) in TWO_BOUNCE_KEYS.iter().enumerate() {
+        if keys.just_pressed(*key) {
+            let assist = &mut assists.0[i];
+            assist.two_bounce = !assist.two_bounce;
+            debug!("Player {} two bounce assist: {}", i + 1, assist.two_bounce);
+        }
+    }
+}
+
+// kept small, so it's there without calling the assisted player out
+fn update_assist_ui(
+    assists: Res<Assists>,
+    profiles: Res<Profiles>,
+    locale: Res<Locale>,
+    mut text_q: Query<&mut Text, With<AssistText>>,
+) {
+    if assists.is_changed() || profiles.is_changed() || locale.is_changed() {
+        for mut text in text_q.iter_mut() {
+            text.sections[0].value = assists
+                .0
+                .iter()
+                .enumerate()
+                .flat_map(|(i, assist)| {
+                    [
+                        (assist.magnetize, "magnet-assist"),
+                        (assist.two_bounce, "two-bounce-assist"),
+                    ]
+                    .into_iter()
+                    .filter(|(on, _)| *on)
+                    .map(move |(_, key)| (i, key))
+                })
+                .map(|(i, key)| locale.format(key, &[&profiles.get_player_name(i + 1, &locale)]))
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+    }
+}
+
+// players are respawned every game
+fn apply_assists(
+    mut commands: Commands,
+    assists: Res<Assists>,
+    player_q: Query<(Entity, &Player, ChangeTrackers<Player>)>,
+) {
+    for (player_e, player, player_tracker) in player_q.iter() {
+        if assists.is_changed() || player_tracker.is_added() {
+            commands.entity(player_e).insert(assists.0[player.id - 1]);
+        }
+    }
+}
+
+fn highlight_serve_target(
+    ball_q: Query<(&Ball, &BallStatus)>,
+    mut highlighted: ResMut<HighlightedRegion>,
+) {
+    // only until the serve is hit
+    let target = ball_q.iter().find_map(|(ball, status)| match status {
+        BallStatus::Serve(region, ..) if ball.dir == Vec2::ZERO => region.get_inverse(),
+        _ => None,
+    });
+
+    if highlighted.0 != target {
+        highlighted.0 = target;
+    }
+}
+
+// shots fly straight, so the aim is clamped to the slopes towards the target region's edges
+fn clamp_serve_aim(
+    highlighted: Res<HighlightedRegion>,
+    ball_q: Query<(&Ball, &BallStatus)>,
+    player_q: Query<(&Player, &PlayerAssist, &GlobalTransform)>,
+    mut aim_q: Query<(&mut PlayerAim, &mut Transform)>,
+    court: Res<CourtSettings>,
+    net: Res<NetOffset>,
+) {
+    let target = match highlighted.0 {
+        Some(target) => target,
+        None => return,
+    };
+
+    let server_id = match ball_q.iter().find_map(|(_, status)| match status {
+        BallStatus::Serve(_, _, player_id) => Some(*player_id),
+        _ => None,
+    }) {
+        Some(server_id) => server_id,
+        None => return,
+    };
+
+    for (player, assist, player_t) in player_q.iter() {
+        if player.id != server_id || !assist.serve_aim {
+            continue;
+        }
+
+        if let Ok((mut aim, mut aim_t)) = aim_q.get_mut(player.aim_e) {
+            let (center, half_size) = get_region_bounds(target, &court, net.0);
+            let pos = player_t.translation.truncate();
+            let dist_x = (center.x - pos.x).abs().max(1.);
+            let min_y = (center.y - half_size.y + SERVE_AIM_MARGIN - pos.y) / dist_x;
+            let max_y = (center.y + half_size.y - SERVE_AIM_MARGIN - pos.y) / dist_x;
+            let y = aim.dir.y.max(min_y).min(max_y).clamp(-1., 1.);
+
+            if (y - aim.dir.y).abs() > f32::EPSILON {
+                let dir = Vec2::new(-player.get_sign() * (1. - y * y).sqrt(), y);
+                aim.dir = dir;
+                aim_t.rotation = Quat::from_axis_angle(-Vec3::Z, dir.angle_between(Vec2::Y));
+            }
+        }
+    }
+}
+
+// rotates the shot by up to max_deg, so it lands inside of the sidelines
+// long shots are left alone, as bending doesn't shorten them
+pub fn get_magnetized_dir(
+    shot: &Shot,
+    court: &CourtSettings,
+    max_deg: f32,
+    config: &BallConfig,
+) -> Vec2 {
+    let landing = match get_landing_pos(shot, config) {
+        Some(landing) => landing,
+        None => return shot.dir,
+    };
+
+    if landing.y < court.top + MAGNET_OUT_MARGIN && landing.y > court.bottom - MAGNET_OUT_MARGIN {
+        return shot.dir;
+    }
+
+    // the path is straight, so the landing spot rotates with the dir
+    let target = Vec2::new(
+        landing.x,
+        landing.y.clamp(
+            court.bottom + MAGNET_TARGET_MARGIN,
+            court.top - MAGNET_TARGET_MARGIN,
+        ),
+    );
+    let max_rad = max_deg.to_radians();
+    let angle = (landing - shot.pos)
+        .angle_between(target - shot.pos)
+        .clamp(-max_rad, max_rad);
+    trace!("Magnetized shot by {}°", angle.to_degrees());
+
+    (Quat::from_rotation_z(angle) * shot.dir.extend(0.)).truncate()
+}
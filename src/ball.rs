@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{collections::VecDeque, time::Duration};
 
 use bevy::{
     math::Vec2,
@@ -8,17 +8,32 @@ use bevy::{
 use bevy_extensions::Vec2Conversion;
 
 use crate::{
-    animation::TweenDoneAction,
+    animation::{GroundShadow, TweenDoneAction},
+    asset::SpriteAtlas,
+    assist::{get_magnetized_dir, PlayerAssist},
+    config::GameConfig,
+    cosmetic::Loadouts,
     extra::TransformBundle,
+    fatigue::PlayerFatigue,
     level::{CourtRegion, CourtSettings, InitialRegion, NetOffset, ServingRegion},
+    mode::GameMode,
+    momentum::{Momentum, POWER_SERVE_BOUNCE_MULT, POWER_SERVE_SPEED_MULT},
     palette::{Palette, PaletteColor},
     physics::PhysLayer,
-    player::{Player, PlayerAim, PlayerSwing},
+    player::{
+        apply_overcharge_jitter, is_left_player_id, Player, PlayerAim, PlayerJump, PlayerSwing,
+    },
     player_action::PlayerActionStatus,
     render::{BALL_Z, PLAYER_Z, SHADOW_Z},
+    reset::DespawnOnExit,
+    rng::GameRng,
+    score::{MatchRules, PointStart},
     trail::{FadeOutTrail, Trail},
+    trajectory::Shot,
+    weather::{get_puddle_at, Puddle},
     GameSetupPhase, GameState,
 };
+#[cfg(feature = "inspector")]
 use bevy_inspector_egui::Inspectable;
 use bevy_prototype_lyon::prelude::*;
 use bevy_time::{ScaledTime, ScaledTimeDelta};
@@ -28,27 +43,72 @@ use heron::*;
 use rand::*;
 
 pub const BALL_MAX_SPEED: f32 = 1100.;
-const BALL_SIZE: f32 = 35.;
+pub const BALL_SIZE: f32 = 35.;
+// bounce marks and dust of balls called out, there's no red in the palettes
+pub const OUT_CALL_COLOR: Color = Color::rgb(0.85, 0.22, 0.2);
+// clearance needed by a slow shot, faster ones need more
+const NET_HEIGHT: f32 = 20.;
+// clears the net, but gets slowed down
+const NET_CLIP_MARGIN: f32 = 15.;
+// how high a grounded player can reach, jumping extends it
+const PLAYER_REACH_HEIGHT: f32 = 40.;
+// a swing this high in the air is a smash
+const SMASH_MIN_HEIGHT: f32 = 30.;
+const BALL_MOVE_LABEL: &str = "ball_move";
+// bounce marks that stay for the whole match, spots hit over and over get darker
+const WEAR_MAX_DECALS: usize = 60;
+// bounces closer than this wear the same decal
+const WEAR_MERGE_DIST: f32 = 25.;
+const WEAR_ALPHA_PER_HIT: f32 = 0.04;
+const WEAR_MAX_ALPHA: f32 = 0.3;
+const WEAR_FADE_MS: u64 = 2000;
+// serves start just inside the baseline, so the server can reach them from behind the line
+const SERVE_DEPTH: f32 = 60.;
+const SERVE_MIN_Y: f32 = 120.;
+const SERVE_MAX_Y: f32 = 280.;
+// long frames (a hitch, resuming after a pause) are simulated in steps of at most this
+const MAX_STEP_SEC: f32 = 1. / 120.;
+// anything longer is dropped, so even at the max speed the ball moves less than a region per frame
+// and can't skip the region sensors
+const MAX_FRAME_SEC: f32 = 0.1;
 
 pub struct BallPlugin;
 impl Plugin for BallPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_system_set(
-            SystemSet::on_enter(GameState::Game).with_system(setup.label(GameSetupPhase::Ball)),
-        )
-        .add_system_to_stage(CoreStage::PostUpdate, handle_collisions)
-        .add_system_to_stage(CoreStage::PostUpdate, handle_regions)
-        .add_system_set(
-            SystemSet::on_update(GameState::Game)
-                .with_system(movement)
-                .with_system(bounce),
-        )
-        .add_event::<BallBouncedEvt>()
-        .add_event::<BallHitEvt>();
+        app.init_resource::<CourtWear>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Game)
+                    .with_system(
+                        setup
+                            .label(GameSetupPhase::Ball)
+                            .after(GameSetupPhase::Level),
+                    )
+                    .with_system(reset_court_wear),
+            )
+            .add_system_to_stage(CoreStage::PostUpdate, handle_collisions)
+            .add_system_to_stage(CoreStage::PostUpdate, handle_regions)
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(movement.label(BALL_MOVE_LABEL))
+                    .with_system(reset_bounce_count.after(BALL_MOVE_LABEL))
+                    .with_system(apply_net_interaction.after(BALL_MOVE_LABEL))
+                    .with_system(bounce)
+                    .with_system(keep_drop_ball_on_net)
+                    .with_system(place_serve_ball)
+                    .with_system(tint_trail_on_hit)
+                    .with_system(color_wear_decals)
+                    .with_system(apply_ball_config),
+            )
+            .add_event::<BallBouncedEvt>()
+            .add_event::<BallHitEvt>()
+            .add_event::<BallCrossedNetEvt>()
+            .add_event::<ServeLetEvt>()
+            .add_event::<WallHitEvt>();
     }
 }
 
-#[derive(Default, Component, Inspectable)]
+#[derive(Default, Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct Ball {
     pub dir: Vec2,
     pub max_speed: f32,
@@ -56,11 +116,17 @@ pub struct Ball {
     pub region: CourtRegion,
     pub bounce_e: Option<Entity>,
     pub trail_e: Option<Entity>,
+    pub net_clipped: bool,
+    // used to classify the point once the ball is out of play
+    pub hit_count: usize,
+    pub served: bool,
+    pub last_swing_mult: f32,
     prev_pos: Vec3,
     size: f32,
 }
 
-#[derive(Default, Component, Inspectable)]
+#[derive(Default, Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct BallBounce {
     pub count: usize,
     gravity: f32,
@@ -68,19 +134,61 @@ pub struct BallBounce {
     max_velocity: f32,
 }
 
-#[derive(Default, Component, Inspectable)]
+#[derive(Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub enum BallStatus {
     Serve(CourtRegion, u8, usize),
+    // neutral rally start, up for grabs until hit
+    Drop,
     Fault(u8, usize),
     Rally(usize),
-    #[default]
     Used,
 }
 
+impl Default for BallStatus {
+    fn default() -> Self {
+        BallStatus::Used
+    }
+}
+
+impl BallStatus {
+    // a used ball is on its way out and can't be hit anymore
+    pub fn is_in_play(&self) -> bool {
+        !matches!(self, BallStatus::Used)
+    }
+}
+
+// pool of the persistent bounce marks, the least recently worn one goes first
+#[derive(Default)]
+pub struct CourtWear {
+    decals: VecDeque<WearMark>,
+}
+
+struct WearMark {
+    decal_e: Entity,
+    pos: Vec2,
+    hits: u32,
+}
+
+#[derive(Component)]
+struct WearDecal {
+    hits: u32,
+}
+
+fn get_wear_color(palette: &Palette, hits: u32) -> Color {
+    let mut color = palette.get_color(&PaletteColor::Shadow);
+    color.set_a((hits as f32 * WEAR_ALPHA_PER_HIT).min(WEAR_MAX_ALPHA));
+    color
+}
+
 pub struct BallBouncedEvt {
     pub ball_e: Entity,
     pub bounce_count: usize,
     pub side: f32,
+    // the bounce decided the point against the hitter, i.e. a fault or an out ball
+    pub out: bool,
+    // the in-court bounce of a good serve
+    pub serve_bounce: bool,
 }
 
 pub struct BallHitEvt {
@@ -88,67 +196,282 @@ pub struct BallHitEvt {
     pub player_id: usize,
 }
 
+// sent by movement for every net (or wall) crossing, before the net gets to react
+pub struct BallCrossedNetEvt {
+    pub ball_e: Entity,
+    // where the ball crossed the net line
+    pub pos: Vec2,
+    // height above the ground
+    pub height: f32,
+    // speed relative to the max ball speed
+    pub speed: f32,
+}
+
+impl BallCrossedNetEvt {
+    // None when the ball went around the post
+    pub fn get_shot_risk(&self, net_extent: f32) -> Option<ShotRisk> {
+        if self.pos.y.abs() > net_extent {
+            None
+        } else {
+            Some(get_shot_risk(self.height, self.speed))
+        }
+    }
+}
+
+pub struct ServeLetEvt {
+    pub ball_e: Entity,
+    pub fault_count: u8,
+}
+
+pub struct WallHitEvt {
+    pub ball_e: Entity,
+}
+
 fn setup(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    atlas: Res<SpriteAtlas>,
     region: Res<InitialRegion>,
     court_set: Res<CourtSettings>,
+    config: Res<GameConfig>,
+    rules: Res<MatchRules>,
+    net_offset: Res<NetOffset>,
+    loadouts: Res<Loadouts>,
+    mut rng: ResMut<GameRng>,
 ) {
-    spawn_ball(
+    spawn_point_ball(
         &mut commands,
-        &asset_server,
+        &atlas,
+        &rules,
         region.0,
         0,
-        region.0.get_player_id(),
         &court_set,
+        &config,
+        &loadouts,
+        &mut rng,
+        net_offset.0,
     );
     commands.insert_resource(ServingRegion(region.0));
 }
 
 // nice2have: try - slowly speedup during rally?
-fn movement(
-    mut ball_q: Query<(&mut Ball, &mut Transform)>,
-    mut bounce_q: Query<&mut BallBounce>,
+pub fn movement(
+    mut ball_q: Query<(Entity, &mut Ball, &mut Transform)>,
+    bounce_q: Query<&Transform, (With<BallBounce>, Without<Ball>)>,
+    mut ev_w_crossed_net: EventWriter<BallCrossedNetEvt>,
     time: ScaledTime,
     net: Res<NetOffset>,
 ) {
-    for (mut ball, mut ball_t) in ball_q.iter_mut() {
-        if ball.dir == Vec2::ZERO {
-            continue;
+    let steps = get_step_secs(time.scaled_delta_seconds());
+
+    for (ball_e, mut ball, mut ball_t) in ball_q.iter_mut() {
+        for step_sec in steps.iter() {
+            if ball.dir == Vec2::ZERO {
+                break;
+            }
+
+            ball.dir = apply_drag(ball.dir, *step_sec);
+            if ball.dir == Vec2::ZERO {
+                break;
+            }
+
+            // move
+            let vel = ball.dir.to_vec3() * ball.max_speed;
+            ball_t.translation += vel * *step_sec;
+            ball.speed = vel.length();
+
+            let net_x = net.0;
+            let ball_x = ball_t.translation.x;
+            let ball_prev_x = ball.prev_pos.x;
+            if has_crossed_net(ball_prev_x, ball_x, net_x) {
+                if let Ok(bounce_t) = bounce_q.get(ball.bounce_e.unwrap()) {
+                    trace!("Crossed net");
+                    trace!("height over net {}", bounce_t.translation.y);
+
+                    // y where the ball crossed the net line
+                    let crossing_t = (net_x - ball_prev_x) / (ball_x - ball_prev_x);
+                    let crossing_y =
+                        ball.prev_pos.y + (ball_t.translation.y - ball.prev_pos.y) * crossing_t;
+
+                    ev_w_crossed_net.send(BallCrossedNetEvt {
+                        ball_e,
+                        pos: Vec2::new(net_x, crossing_y),
+                        height: bounce_t.translation.y,
+                        speed: ball.dir.length(),
+                    });
+                }
+            }
+
+            ball.prev_pos = ball_t.translation;
         }
+    }
+}
+
+// the clamped frame split into equal steps
+// shared by the movement and the bounce, so both stay in step
+pub fn get_step_secs(delta_sec: f32) -> Vec<f32> {
+    let delta_sec = delta_sec.min(MAX_FRAME_SEC);
+    if delta_sec <= 0. {
+        return Vec::new();
+    }
+
+    let step_count = (delta_sec / MAX_STEP_SEC).ceil().max(1.) as usize;
+    vec![delta_sec / step_count as f32; step_count]
+}
 
-        let speed = ball.dir.length();
+fn has_crossed_net(prev_x: f32, x: f32, net_x: f32) -> bool {
+    (prev_x < net_x && x > net_x) || (prev_x > net_x && x < net_x)
+}
 
-        if speed < 0.025 {
-            ball.dir = Vec2::ZERO;
-            return;
+// bounces are counted per side
+fn reset_bounce_count(
+    mut ev_r_crossed_net: EventReader<BallCrossedNetEvt>,
+    ball_q: Query<&Ball>,
+    mut bounce_q: Query<&mut BallBounce>,
+) {
+    for ev in ev_r_crossed_net.iter() {
+        if let Some(mut bounce) = ball_q
+            .get(ev.ball_e)
+            .ok()
+            .and_then(|ball| ball.bounce_e)
+            .and_then(|bounce_e| bounce_q.get_mut(bounce_e).ok())
+        {
+            bounce.count = 0;
         }
+    }
+}
+
+fn apply_net_interaction(
+    mut commands: Commands,
+    mut ev_r_crossed_net: EventReader<BallCrossedNetEvt>,
+    mut ball_q: Query<(&mut Ball, &mut Transform)>,
+    mut bounce_q: Query<&mut BallBounce>,
+    entity_q: Query<Entity>,
+    mut ev_w_wall_hit: EventWriter<WallHitEvt>,
+    court_set: Res<CourtSettings>,
+    mode: Res<GameMode>,
+) {
+    for ev in ev_r_crossed_net.iter() {
+        let (mut ball, mut ball_t) = match ball_q.get_mut(ev.ball_e) {
+            Ok(ball) => ball,
+            Err(_) => continue,
+        };
+        let mut bounce = match bounce_q.get_mut(ball.bounce_e.unwrap()) {
+            Ok(bounce) => bounce,
+            Err(_) => continue,
+        };
 
-        // very simple drag
-        let drag_mult = if speed < 0.25 { 1. } else { 0.35 };
-        // todo: figure out a stable drag curve fn
-        ball.dir *= 1. - drag_mult * time.scaled_delta_seconds();
-
-        // move
-        let vel = ball.dir.to_vec3() * ball.max_speed;
-        ball_t.translation += vel * time.scaled_delta_seconds();
-        ball.speed = vel.length();
-
-        let net_x = net.0;
-        let ball_x = ball_t.translation.x;
-        let ball_prev_x = ball.prev_pos.x;
-        if (ball_prev_x < net_x && ball_x > net_x) || (ball_prev_x > net_x && ball_x < net_x) {
-            if let Ok(mut bounce) = bounce_q.get_mut(ball.bounce_e.unwrap()) {
-                bounce.count = 0;
-                info!("crossed net extra check");
+        if mode.has_wall() {
+            // rebound off the wall, the ball keeps its height, but loses some bounce
+            ball_t.translation.x = ev.pos.x - (ball_t.translation.x - ev.pos.x);
+            ball.prev_pos = ball_t.translation;
+            ball.dir.x *= -0.85;
+            bounce.velocity *= 0.8;
+            ev_w_wall_hit.send(WallHitEvt { ball_e: ev.ball_e });
+            debug!("hit wall");
+        } else {
+            match ev.get_shot_risk(court_set.net_extent) {
+                None => debug!("went around the post"),
+                Some(ShotRisk::Net) => {
+                    debug!("hit net");
+                    let hit_vel_mult = 0.25;
+                    ball.dir *= Vec2::new(-hit_vel_mult, hit_vel_mult);
+                    bounce.velocity *= 0.5;
+
+                    if let Ok(e) = entity_q.get(ball.trail_e.unwrap()) {
+                        commands.entity(e).insert(FadeOutTrail {
+                            stop_trail: true,
+                            ..Default::default()
+                        });
+                    }
+                }
+                Some(ShotRisk::Clip) => {
+                    debug!("clipped net");
+                    ball.dir *= 0.8;
+                    ball.net_clipped = true;
+                }
+                Some(ShotRisk::Clear) => {}
             }
         }
+    }
+}
+
+// shows whose shot is in flight
+fn tint_trail_on_hit(
+    mut ev_r_hit: EventReader<BallHitEvt>,
+    ball_q: Query<&Ball>,
+    mut trail_col_q: Query<&mut PaletteColor, With<Trail>>,
+) {
+    for ev in ev_r_hit.iter() {
+        if let Some(mut col) = ball_q
+            .get(ev.ball_e)
+            .ok()
+            .and_then(|ball| ball.trail_e)
+            .and_then(|trail_e| trail_col_q.get_mut(trail_e).ok())
+        {
+            *col = if is_left_player_id(ev.player_id) {
+                PaletteColor::PlayerLeft
+            } else {
+                PaletteColor::PlayerRight
+            };
+        }
+    }
+}
 
-        ball.prev_pos = ball_t.translation;
+pub fn apply_drag(dir: Vec2, delta_sec: f32) -> Vec2 {
+    let speed = dir.length();
+
+    if speed < 0.025 {
+        return Vec2::ZERO;
     }
+
+    // very simple drag
+    let drag_mult = if speed < 0.25 { 1. } else { 0.35 };
+    // todo: figure out a stable drag curve fn
+    dir * (1. - drag_mult * delta_sec)
 }
 
-fn get_bounce_velocity(dir_len: f32, max_velocity: f32) -> f32 {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShotRisk {
+    Clear,
+    Clip,
+    Net,
+}
+
+// fast, flat shots need more clearance over the net
+pub fn get_shot_risk(height_at_net: f32, speed: f32) -> ShotRisk {
+    let net_height = NET_HEIGHT * (0.75 + 0.5 * speed.min(1.));
+
+    if height_at_net < net_height {
+        ShotRisk::Net
+    } else if height_at_net < net_height + NET_CLIP_MARGIN {
+        ShotRisk::Clip
+    } else {
+        ShotRisk::Clear
+    }
+}
+
+// keeps the shot going forward
+pub fn get_shot_dir(player: &Player, aim_dir: Vec2) -> Vec2 {
+    let clamp_x = 1.;
+    let clamp_y = 0.8;
+
+    let player_sign = player.get_sign();
+    if aim_dir == Vec2::new(player_sign, 0.) {
+        // player aiming into their own court - just aim straight
+        Vec2::new(-player_sign, 0.)
+    } else if player.is_left() {
+        aim_dir.clamp(Vec2::new(clamp_x, -clamp_y), Vec2::new(clamp_x, clamp_y))
+    } else {
+        aim_dir.clamp(Vec2::new(-clamp_x, -clamp_y), Vec2::new(-clamp_x, clamp_y))
+    }
+}
+
+// a ball at the apex flies over a grounded player, an airborne one can't reach low balls
+pub fn is_ball_reachable(ball_height: f32, player_height: f32) -> bool {
+    (ball_height - player_height).abs() <= PLAYER_REACH_HEIGHT
+}
+
+pub fn get_bounce_velocity(dir_len: f32, max_velocity: f32) -> f32 {
     dir_len.sqrt().min(1.) * max_velocity
 }
 
@@ -159,70 +482,114 @@ fn bounce(
     >,
     mut ball_q: Query<(Entity, &mut Ball, &mut BallStatus, &Transform)>,
     mut ev_w_bounce: EventWriter<BallBouncedEvt>,
+    mut ev_w_let: EventWriter<ServeLetEvt>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     palette: Res<Palette>,
+    puddle_q: Query<(&Puddle, &GlobalTransform)>,
     time: ScaledTime,
     net: Res<NetOffset>,
+    mut wear: ResMut<CourtWear>,
 ) {
+    let steps = get_step_secs(time.scaled_delta_seconds());
+
     for (mut ball_bounce, mut t, _bounce_global_t, p) in bounce_query.iter_mut() {
         if let Ok((ball_e, ball, mut ball_status, ball_t)) = ball_q.get_mut(p.0) {
             if ball.dir == Vec2::ZERO {
                 continue;
             }
 
-            ball_bounce.velocity += ball_bounce.gravity * time.scaled_delta_seconds();
-            t.translation.y += ball_bounce.velocity * time.scaled_delta_seconds();
-
-            if t.translation.y <= 0. {
-                t.translation.y = 0.01;
-                ball_bounce.velocity =
-                    get_bounce_velocity(ball.dir.length(), ball_bounce.max_velocity);
-                ball_bounce.count += 1;
-                trace!("Bounce {}", ball_bounce.count);
-
-                // eval serve on bounce
-                if let BallStatus::Serve(region, fault_count, player_id) = *ball_status {
-                    if ball.region != region.get_inverse().unwrap() {
-                        // fault
-                        *ball_status = BallStatus::Fault(fault_count + 1, player_id);
-                        debug!("Bad serve {:?} => {:?}", region, ball.region);
-                    } else {
-                        // good serve
-                        *ball_status = BallStatus::Rally(player_id);
-                        debug!("Good serve {:?} => {:?}", region, ball.region);
+            for step_sec in steps.iter() {
+                ball_bounce.velocity += ball_bounce.gravity * *step_sec;
+                t.translation.y += ball_bounce.velocity * *step_sec;
+
+                if t.translation.y <= 0. {
+                    t.translation.y = 0.01;
+                    ball_bounce.velocity =
+                        get_bounce_velocity(ball.dir.length(), ball_bounce.max_velocity);
+                    // puddles deaden the bounce
+                    if let Some(puddle) =
+                        get_puddle_at(puddle_q.iter(), ball_t.translation.truncate())
+                    {
+                        ball_bounce.velocity *= puddle.bounce_mult;
+                    }
+                    ball_bounce.count += 1;
+                    trace!("Bounce {}", ball_bounce.count);
+
+                    // eval serve on bounce
+                    let mut serve_bounce = false;
+                    if let BallStatus::Serve(region, fault_count, player_id) = *ball_status {
+                        if ball.region != region.get_inverse().unwrap() {
+                            // fault
+                            *ball_status = BallStatus::Fault(fault_count + 1, player_id);
+                            debug!("Bad serve {:?} => {:?}", region, ball.region);
+                        } else if ball.net_clipped {
+                            // let - replay the serve
+                            *ball_status = BallStatus::Used;
+                            ev_w_let.send(ServeLetEvt {
+                                ball_e,
+                                fault_count,
+                            });
+                            debug!("Let {:?} => {:?}", region, ball.region);
+                        } else {
+                            // good serve
+                            *ball_status = BallStatus::Rally(player_id);
+                            serve_bounce = true;
+                            debug!("Good serve {:?} => {:?}", region, ball.region);
+                        }
                     }
-                }
 
-                ev_w_bounce.send(BallBouncedEvt {
-                    ball_e,
-                    bounce_count: ball_bounce.count,
-                    side: if ball_t.translation.x < net.0 {
-                        -1.
-                    } else {
-                        1.
-                    },
-                });
-
-                spawn_bounce_track(
-                    &mut commands,
-                    &asset_server,
-                    &palette,
-                    ball_t.translation.truncate().extend(SHADOW_Z),
-                );
-                debug!("Bounced {} times", ball_bounce.count);
+                    let out = ball_bounce.count == 1
+                        && match *ball_status {
+                            BallStatus::Fault(..) => true,
+                            BallStatus::Rally(_) => ball.region.is_out_of_bounds(),
+                            _ => false,
+                        };
+
+                    ev_w_bounce.send(BallBouncedEvt {
+                        ball_e,
+                        bounce_count: ball_bounce.count,
+                        side: if ball_t.translation.x < net.0 {
+                            -1.
+                        } else {
+                            1.
+                        },
+                        out,
+                        serve_bounce,
+                    });
+
+                    spawn_bounce_track(
+                        &mut commands,
+                        &asset_server,
+                        &palette,
+                        &mut wear,
+                        ball_t.translation.truncate().extend(SHADOW_Z),
+                        out,
+                    );
+                    debug!("Bounced {} times", ball_bounce.count);
+                }
             }
         }
     }
 }
 
+// chalk coloured when in, so the call reads at a glance
+// also wears the court under the track
 fn spawn_bounce_track(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
     palette: &Res<Palette>,
+    wear: &mut CourtWear,
     pos: Vec3,
+    out: bool,
 ) {
-    let end_col = palette.get_color(&PaletteColor::Shadow);
+    wear_court(commands, asset_server, palette, wear, pos);
+
+    let end_col = if out {
+        OUT_CALL_COLOR
+    } else {
+        palette.get_color(&PaletteColor::CourtLines)
+    };
     let tween = Tween::new(
         EaseFunction::QuadraticOut,
         TweeningType::Once,
@@ -248,17 +615,114 @@ fn spawn_bounce_track(
             },
             ..Default::default()
         })
-        .insert(Animator::new(tween));
+        .insert(Animator::new(tween))
+        .insert(DespawnOnExit(GameState::Game));
+}
+
+fn wear_court(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    palette: &Res<Palette>,
+    wear: &mut CourtWear,
+    pos: Vec3,
+) {
+    let pos_2d = pos.truncate();
+    let nearest_i = wear
+        .decals
+        .iter()
+        .enumerate()
+        .map(|(i, mark)| (i, mark.pos.distance_squared(pos_2d)))
+        .filter(|(_, dist_sq)| *dist_sq <= WEAR_MERGE_DIST * WEAR_MERGE_DIST)
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(i, _)| i);
+
+    if let Some(mut mark) = nearest_i.and_then(|i| wear.decals.remove(i)) {
+        mark.hits += 1;
+        commands
+            .entity(mark.decal_e)
+            .insert(WearDecal { hits: mark.hits });
+        wear.decals.push_back(mark);
+        return;
+    }
+
+    // under the fading tracks
+    let decal_e = commands
+        .spawn_bundle(SpriteBundle {
+            texture: asset_server.load("art-ish/ball.png"),
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(1.0, 0.5) * BALL_SIZE),
+                color: get_wear_color(palette, 1),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(pos + Vec3::new(-3., -14., -0.1)),
+            ..Default::default()
+        })
+        .insert(WearDecal { hits: 1 })
+        .insert(Name::new("WearDecal"))
+        .insert(DespawnOnExit(GameState::Game))
+        .id();
+    wear.decals.push_back(WearMark {
+        decal_e,
+        pos: pos_2d,
+        hits: 1,
+    });
+
+    while wear.decals.len() > WEAR_MAX_DECALS {
+        if let Some(oldest) = wear.decals.pop_front() {
+            commands
+                .entity(oldest.decal_e)
+                .remove::<WearDecal>()
+                .insert(Animator::new(
+                    Tween::new(
+                        EaseFunction::QuadraticIn,
+                        TweeningType::Once,
+                        Duration::from_millis(WEAR_FADE_MS),
+                        SpriteColorLens {
+                            start: get_wear_color(palette, oldest.hits),
+                            end: Color::NONE,
+                        },
+                    )
+                    .with_completed_event(true, TweenDoneAction::DespawnRecursive.into()),
+                ));
+        }
+    }
+}
+
+// not PaletteColor, as that would reset the alpha
+fn color_wear_decals(
+    palette: Res<Palette>,
+    mut decal_q: Query<(&WearDecal, &mut Sprite, ChangeTrackers<WearDecal>)>,
+) {
+    for (decal, mut sprite, decal_tracker) in decal_q.iter_mut() {
+        if palette.is_changed() || decal_tracker.is_changed() {
+            sprite.color = get_wear_color(&palette, decal.hits);
+        }
+    }
+}
+
+fn reset_court_wear(mut wear: ResMut<CourtWear>) {
+    wear.decals.clear();
 }
 
 // nice2have: 'auto dash swing'?
-fn handle_collisions(
+pub fn handle_collisions(
     mut coll_er: EventReader<CollisionEvent>,
     mut ball_hit_ew: EventWriter<BallHitEvt>,
-    mut ball_q: Query<(&mut Ball, &mut BallStatus, &Children)>,
-    mut ball_bounce_q: Query<&mut BallBounce>,
+    mut ball_q: Query<(&mut Ball, &mut BallStatus, &Children, &GlobalTransform)>,
+    mut ball_bounce_q: Query<(&mut BallBounce, &Transform)>,
     player_aim_q: Query<&PlayerAim>,
-    mut player_q: Query<(&Player, &mut PlayerSwing, &GlobalTransform)>,
+    mut player_q: Query<(
+        &Player,
+        &mut PlayerSwing,
+        &GlobalTransform,
+        &PlayerFatigue,
+        &PlayerJump,
+        Option<&PlayerAssist>,
+    )>,
+    config: Res<GameConfig>,
+    court_set: Res<CourtSettings>,
+    mut momentum: ResMut<Momentum>,
+    mut rng: ResMut<GameRng>,
 ) {
     for ev in coll_er.iter() {
         if ev.is_started() {
@@ -267,56 +731,65 @@ fn handle_collisions(
             let ball_e;
             let other_e;
             let bounce_e;
+            let ball_pos;
             let (entity_1, entity_2) = ev.rigid_body_entities();
             if let Ok(b) = ball_q.get_mut(entity_1) {
                 ball = b.0;
                 status = b.1;
                 ball_e = entity_1;
                 bounce_e = b.2.iter().next().unwrap();
+                ball_pos = b.3.translation.truncate();
                 other_e = entity_2;
             } else if let Ok(b) = ball_q.get_mut(entity_2) {
                 ball = b.0;
                 status = b.1;
                 ball_e = entity_2;
                 bounce_e = b.2.iter().next().unwrap();
+                ball_pos = b.3.translation.truncate();
                 other_e = entity_1;
             } else {
                 continue;
             }
 
-            let mut ball_bounce = ball_bounce_q.get_mut(*bounce_e).unwrap();
+            // the point might've been resolved while a swing was already in progress
+            if !status.is_in_play() {
+                continue;
+            }
 
-            if let Ok((player, mut swing, _player_t)) = player_q.get_mut(other_e) {
+            let (mut ball_bounce, bounce_t) = ball_bounce_q.get_mut(*bounce_e).unwrap();
+
+            if let Ok((player, mut swing, _player_t, fatigue, jump, assist)) =
+                player_q.get_mut(other_e)
+            {
                 if let PlayerActionStatus::Active(ball_speed_multiplier) = swing.status {
+                    // whiffed under or over the ball
+                    if !is_ball_reachable(bounce_t.translation.y, jump.height) {
+                        trace!("Ball out of reach");
+                        continue;
+                    }
+
                     if !swing.timer.finished() {
                         swing.start_cooldown();
 
                         if let Ok(aim) = player_aim_q.get(player.aim_e) {
-                            let mut dir = aim.dir;
-
-                            let clamp_x = 1.;
-                            let clamp_y = 0.8;
-
-                            let player_sign = player.get_sign();
-                            if dir == Vec2::new(player_sign, 0.) {
-                                // player aiming into their own court - just aim straight
-                                dir = Vec2::new(-player_sign, 0.);
-                            } else if player.is_left() {
-                                dir = dir.clamp(
-                                    Vec2::new(clamp_x, -clamp_y),
-                                    Vec2::new(clamp_x, clamp_y),
-                                );
-                            } else {
-                                dir = dir.clamp(
-                                    Vec2::new(-clamp_x, -clamp_y),
-                                    Vec2::new(-clamp_x, clamp_y),
-                                );
-                            }
+                            // overcharged swings spray the shot
+                            let dir = apply_overcharge_jitter(
+                                get_shot_dir(player, aim.dir),
+                                swing.overcharge,
+                                &mut rng,
+                            );
 
-                            ball.dir = dir * ball_speed_multiplier;
+                            ball.dir = dir * ball_speed_multiplier * fatigue.swing_mult;
                             ball_bounce.velocity =
                                 get_bounce_velocity(dir.length(), ball_bounce.max_velocity);
 
+                            if jump.height > SMASH_MIN_HEIGHT {
+                                // fast and steep into the ground
+                                ball.dir *= config.player.smash_speed_mult;
+                                ball_bounce.velocity = -ball_bounce.max_velocity;
+                                debug!("Smash");
+                            }
+
                             let rot = Quat::from_rotation_arc_2d(Vec2::Y, dir)
                                 .to_euler(EulerRot::XYZ)
                                 .2
@@ -329,14 +802,42 @@ fn handle_collisions(
                                     *status = BallStatus::Rally(player.id);
                                     trace!("Vollied serve");
                                 }
+                                BallStatus::Drop => {
+                                    *status = BallStatus::Rally(player.id);
+                                }
+                                BallStatus::Serve(..) => {
+                                    ball.served = true;
+                                    if momentum.take_power_serve(player.id) {
+                                        ball.dir *= POWER_SERVE_SPEED_MULT;
+                                        ball_bounce.velocity *= POWER_SERVE_BOUNCE_MULT;
+                                        debug!("Power serve");
+                                    }
+                                }
                                 BallStatus::Rally(..) => {
                                     // set rally player on hit
                                     *status = BallStatus::Rally(player.id);
                                 }
                                 _ => {}
                             }
+
+                            if assist.map_or(false, |a| a.magnetize) {
+                                let shot = Shot {
+                                    pos: ball_pos,
+                                    height: bounce_t.translation.y,
+                                    dir: ball.dir,
+                                    bounce_velocity: ball_bounce.velocity,
+                                };
+                                ball.dir = get_magnetized_dir(
+                                    &shot,
+                                    &court_set,
+                                    config.player.magnet_assist_max_deg,
+                                    &config.ball,
+                                );
+                            }
                         }
 
+                        ball.hit_count += 1;
+                        ball.last_swing_mult = ball_speed_multiplier;
                         ball_hit_ew.send(BallHitEvt {
                             ball_e,
                             player_id: player.id,
@@ -349,14 +850,12 @@ fn handle_collisions(
 }
 
 fn handle_regions(
-    mut commands: Commands,
     mut coll_events: EventReader<CollisionEvent>,
     ball_q: Query<(Entity, &GlobalTransform), With<Ball>>,
     mut ball_mut_q: Query<&mut Ball>,
-    mut ball_bounce_q: Query<(&mut BallBounce, &Transform)>,
     region_q: Query<&CourtRegion>,
     court_set: Res<CourtSettings>,
-    entity_q: Query<Entity, Without<Ball>>,
+    net: Res<NetOffset>,
 ) {
     let all_events: Vec<CollisionEvent> = coll_events.iter().cloned().collect();
     for (ball_e, ball_t) in ball_q.iter() {
@@ -378,19 +877,25 @@ fn handle_regions(
                     trace!("[{}] Entered {:?}", i, r);
 
                     // entered region
-                    region = Some(r);
+                    region = Some(*r);
                 } else {
                     trace!("[{}] Exited {:?}", i, r);
 
                     // exited region
-                    if region.is_none()
-                        && *r != CourtRegion::OutOfBounds
-                        && (ball_t.translation.x < court_set.left
+                    if region.is_none() && *r != CourtRegion::OutOfBounds {
+                        if ball_t.translation.x < court_set.left
                             || ball_t.translation.x > court_set.right
                             || ball_t.translation.y < court_set.bottom
-                            || ball_t.translation.y > court_set.top)
-                    {
-                        region = Some(&CourtRegion::OutOfBounds);
+                            || ball_t.translation.y > court_set.top
+                        {
+                            region = Some(CourtRegion::OutOfBounds);
+                        } else if ball_mut_q.get(ball_e).map_or(false, |b| b.region == *r) {
+                            // a ball spawned where the regions meet (drop ball) never enters the one it ends up in
+                            region = Some(CourtRegion::get_court_quadrant(
+                                ball_t.translation.truncate(),
+                                net.0,
+                            ));
+                        }
                     }
                 }
             }
@@ -400,78 +905,245 @@ fn handle_regions(
             if let Ok(mut ball) = ball_mut_q.get_mut(ball_e) {
                 trace!("{:?} => {:?}", ball.region, r);
 
-                if (ball.region.is_left() && r.is_right())
-                    || (ball.region.is_right() && r.is_left())
-                {
-                    if let Ok((mut bounce, bounce_t)) =
-                        ball_bounce_q.get_mut(ball.bounce_e.unwrap())
-                    {
-                        bounce.count = 0;
-                        trace!("Crossed net");
-                        trace!("height over net {}", bounce_t.translation.y);
-
-                        if bounce_t.translation.y < 20. {
-                            debug!("hit net");
-                            let hit_vel_mult = 0.25;
-                            ball.dir *= Vec2::new(-hit_vel_mult, hit_vel_mult);
-                            bounce.velocity *= 0.5;
-
-                            if let Ok(e) = entity_q.get(ball.trail_e.unwrap()) {
-                                commands.entity(e).insert(FadeOutTrail {
-                                    stop_trail: true,
-                                    ..Default::default()
-                                });
-                            }
-                        }
-                    }
-                }
+                // crossing the net is handled by movement (see BallCrossedNetEvt), based on the net extents
+                ball.region = r;
+            }
+        }
+    }
+}
 
-                ball.region = *r;
+// the net might still be tugged after the point that spawned the drop ball
+fn keep_drop_ball_on_net(
+    mut ball_q: Query<(&mut Ball, &BallStatus, &mut Transform)>,
+    net: Res<NetOffset>,
+) {
+    for (mut ball, status, mut ball_t) in ball_q.iter_mut() {
+        if matches!(status, BallStatus::Drop) && ball.dir == Vec2::ZERO {
+            ball_t.translation.x = net.0;
+            ball.prev_pos.x = net.0;
+        }
+    }
+}
+
+// the serve spawns by the server, but never outside of the serving corner
+fn place_serve_ball(
+    mut ball_q: Query<(&mut Ball, &BallStatus, &mut Transform), Added<Ball>>,
+    player_q: Query<(&Player, &GlobalTransform)>,
+    court_set: Res<CourtSettings>,
+) {
+    for (mut ball, status, mut ball_t) in ball_q.iter_mut() {
+        if let BallStatus::Serve(region, _, player_id) = *status {
+            if let Some((_, player_t)) = player_q.iter().find(|(p, _)| p.id == player_id) {
+                let pos = get_serve_pos(region, player_t.translation.truncate(), &court_set);
+                ball_t.translation = pos.extend(ball_t.translation.z);
+                ball.prev_pos = ball_t.translation;
             }
         }
     }
 }
 
+// the closest spot to the server in the serving corner
+pub fn get_serve_pos(
+    serve_region: CourtRegion,
+    server_pos: Vec2,
+    court_set: &CourtSettings,
+) -> Vec2 {
+    let (min_x, max_x) = if serve_region.is_left() {
+        (-court_set.right, -court_set.right + SERVE_DEPTH)
+    } else {
+        (court_set.right - SERVE_DEPTH, court_set.right)
+    };
+    let (min_y, max_y) = if serve_region.is_bottom() {
+        (-SERVE_MAX_Y, -SERVE_MIN_Y)
+    } else {
+        (SERVE_MIN_Y, SERVE_MAX_Y)
+    };
+
+    Vec2::new(
+        server_pos.x.clamp(min_x, max_x),
+        server_pos.y.clamp(min_y, max_y),
+    )
+}
+
+// keeps balls in play in sync with hot reloaded tuning
+fn apply_ball_config(
+    config: Res<GameConfig>,
+    mut ball_q: Query<&mut Ball>,
+    mut bounce_q: Query<&mut BallBounce>,
+) {
+    if config.is_changed() {
+        for mut ball in ball_q.iter_mut() {
+            ball.max_speed = config.ball.max_speed;
+        }
+
+        for mut bounce in bounce_q.iter_mut() {
+            bounce.gravity = config.ball.gravity;
+            bounce.max_velocity = config.ball.max_bounce_velocity;
+        }
+    }
+}
+
+// marks the ball used as well, so a swing already in progress can't hit it while it scales out
+pub fn despawn_ball(
+    commands: &mut Commands,
+    ball_e: Entity,
+    status: &mut BallStatus,
+    ball_t: &Transform,
+    trail_e: Option<Entity>,
+) {
+    *status = BallStatus::Used;
+    // the colliders would outlive the scale out otherwise
+    commands.entity(ball_e).insert(CollisionLayers::none());
+    commands.entity(ball_e).insert(Animator::new(
+        Tween::new(
+            EaseFunction::QuadraticIn,
+            TweeningType::Once,
+            Duration::from_millis(450),
+            TransformScaleLens {
+                start: ball_t.scale,
+                end: Vec3::ZERO,
+            },
+        )
+        .with_completed_event(true, TweenDoneAction::DespawnRecursive.into()),
+    ));
+
+    if let Some(e) = trail_e {
+        commands.entity(e).insert(FadeOutTrail {
+            decrease_duration_by: 1.,
+            ..Default::default()
+        });
+    }
+}
+
 pub fn spawn_ball(
     commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
+    atlas: &SpriteAtlas,
     serve_region: CourtRegion,
     fault_count: u8,
     player_id: usize,
     court_set: &Res<CourtSettings>,
+    config: &GameConfig,
+    loadouts: &Loadouts,
+    rng: &mut GameRng,
 ) {
-    let bounce_e = commands
-        .spawn_bundle(SpriteBundle {
-            texture: asset_server.load("art-ish/ball.png"),
-            sprite: Sprite {
-                custom_size: Some(Vec2::ONE * BALL_SIZE),
-                ..Default::default()
-            },
-            transform: Transform::from_xyz(0., 0., 0.5),
+    // placed within the server's reach by place_serve_ball once it's spawned
+    let x = rng.gen_range((court_set.right - SERVE_DEPTH)..=court_set.right) as f32;
+    let x = if serve_region.is_left() { -x } else { x };
+    let y = rng.gen_range(SERVE_MIN_Y..=SERVE_MAX_Y);
+    let y = if serve_region.is_bottom() { -y } else { y };
+
+    spawn_ball_entity(
+        commands,
+        atlas,
+        Vec2::new(x, y),
+        serve_region,
+        BallStatus::Serve(serve_region, fault_count, player_id),
+        config,
+        loadouts,
+    );
+}
+
+// the ball waits on the net line in the middle of the court for whoever gets there first
+// a point starts either with a serve or a dropped ball
+pub fn spawn_point_ball(
+    commands: &mut Commands,
+    atlas: &SpriteAtlas,
+    rules: &MatchRules,
+    serve_region: CourtRegion,
+    fault_count: u8,
+    court_set: &Res<CourtSettings>,
+    config: &GameConfig,
+    loadouts: &Loadouts,
+    rng: &mut GameRng,
+    net_offset: f32,
+) {
+    match rules.point_start {
+        PointStart::Serve => spawn_ball(
+            commands,
+            atlas,
+            serve_region,
+            fault_count,
+            serve_region.get_player_id(),
+            court_set,
+            config,
+            loadouts,
+            rng,
+        ),
+        PointStart::DropBall => spawn_drop_ball(commands, atlas, net_offset, config, loadouts),
+    }
+}
+
+pub fn spawn_drop_ball(
+    commands: &mut Commands,
+    atlas: &SpriteAtlas,
+    net_offset: f32,
+    config: &GameConfig,
+    loadouts: &Loadouts,
+) {
+    spawn_ball_entity(
+        commands,
+        atlas,
+        Vec2::new(net_offset, 0.),
+        CourtRegion::OutOfBounds,
+        BallStatus::Drop,
+        config,
+        loadouts,
+    );
+}
+
+fn spawn_ball_entity(
+    commands: &mut Commands,
+    atlas: &SpriteAtlas,
+    pos: Vec2,
+    region: CourtRegion,
+    status: BallStatus,
+    config: &GameConfig,
+    loadouts: &Loadouts,
+) {
+    let loadout = loadouts.get_ball_loadout();
+    let ball_texture = loadout.ball_skin.get_texture();
+    let ball_sprite_e = commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: atlas.handle.clone(),
+            sprite: atlas.get_sprite(ball_texture),
+            transform: Transform::from_scale(atlas.get_scale(ball_texture, Vec2::ONE * BALL_SIZE)),
             ..Default::default()
         })
+        .insert(PaletteColor::Ball)
+        .id();
+
+    let bounce_e = commands
+        .spawn_bundle(TransformBundle::from_xyz(0., 0., 0.5))
         .insert(BallBounce {
-            gravity: -420.,
-            max_velocity: 200.,
+            gravity: config.ball.gravity,
+            max_velocity: config.ball.max_bounce_velocity,
             ..Default::default()
         })
-        .insert(PaletteColor::Ball)
+        .add_child(ball_sprite_e)
         .id();
 
+    let shadow_offset = Vec2::new(-3., -14.);
+    let shadow_scale = atlas
+        .get_scale("art-ish/ball.png", Vec2::new(1.0, 0.5) * BALL_SIZE)
+        .truncate();
     let shadow = commands
-        .spawn_bundle(SpriteBundle {
-            texture: asset_server.load("art-ish/ball.png"),
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(1.0, 0.5) * BALL_SIZE),
-                ..Default::default()
-            },
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: atlas.handle.clone(),
+            sprite: atlas.get_sprite("art-ish/ball.png"),
             transform: Transform {
-                translation: Vec3::new(-3., -14., -BALL_Z + SHADOW_Z),
+                translation: shadow_offset.extend(-BALL_Z + SHADOW_Z),
+                scale: shadow_scale.extend(1.),
                 ..Default::default()
             },
             ..Default::default()
         })
         .insert(PaletteColor::Shadow)
+        .insert(GroundShadow {
+            caster_e: bounce_e,
+            offset: shadow_offset,
+            scale: shadow_scale,
+            max_height: 80.,
+        })
         .id();
 
     let trail_e = commands
@@ -483,21 +1155,19 @@ pub fn spawn_ball(
         .insert(Trail {
             points: Vec::new(),
             transform_e: bounce_e,
-            duration_sec: 0.3,
-            max_width: 30.,
+            duration_sec: loadout.trail_shape.get_duration_sec(),
+            max_width: loadout.trail_shape.get_max_width(),
         })
+        .insert(loadout.trail_style)
+        .insert(PaletteColor::BallTrail)
         .insert(Name::new("BallTrail"))
+        .insert(DespawnOnExit(GameState::Game))
         .id();
 
-    let mut rng = rand::thread_rng();
-    let x = rng.gen_range((court_set.right / 2.)..=court_set.right) as f32;
-    let x = if serve_region.is_left() { -x } else { x };
-    let y = rng.gen_range(120..=280) as f32;
-    let y = if serve_region.is_bottom() { -y } else { y };
     commands
         .spawn_bundle(TransformBundle {
             transform: Transform {
-                translation: Vec3::new(x, y, BALL_Z),
+                translation: pos.extend(BALL_Z),
                 scale: Vec3::ZERO,
                 ..Default::default()
             },
@@ -506,17 +1176,20 @@ pub fn spawn_ball(
         .insert(GlobalTransform::default())
         .insert(Ball {
             size: BALL_SIZE,
-            max_speed: BALL_MAX_SPEED,
-            region: serve_region,
+            max_speed: config.ball.max_speed,
+            region,
+            // avoids a false net crossing when spawned on the net line
+            prev_pos: pos.extend(BALL_Z),
             bounce_e: Some(bounce_e),
             trail_e: Some(trail_e),
             ..Default::default()
         })
-        .insert(BallStatus::Serve(serve_region, fault_count, player_id))
+        .insert(status)
         .insert(RigidBody::KinematicPositionBased)
         .insert(CollisionShape::Sphere { radius: 15. })
-        .insert(CollisionLayers::all::<PhysLayer>())
+        .insert(PhysLayer::Ball.get_layers())
         .insert(Name::new("Ball"))
+        .insert(DespawnOnExit(GameState::Game))
         .add_child(bounce_e)
         .add_child(shadow)
         .insert(Animator::new(Delay::new(Duration::from_millis(500)).then(
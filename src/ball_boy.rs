@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_time::{ScaledTime, ScaledTimeDelta};
+use bevy_tweening::lens::TransformPositionLens;
+use bevy_tweening::*;
+
+use crate::{
+    animation::TweenDoneAction,
+    ball::{Ball, BallStatus},
+    level::CourtSettings,
+    palette::PaletteColor,
+    render::PLAYER_Z,
+    reset::DespawnOnExit,
+    GameState,
+};
+
+pub struct BallBoyPlugin;
+impl Plugin for BallBoyPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<BallBoySettings>()
+            .add_system(collect_dead_balls)
+            .add_system(despawn_dead_balls);
+    }
+}
+
+pub struct BallBoySettings {
+    pub show_critter: bool,
+    // failsafe for the despawn tween
+    pub collect_after_sec: f32,
+}
+
+impl Default for BallBoySettings {
+    fn default() -> Self {
+        Self {
+            show_critter: true,
+            collect_after_sec: 1.5,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct DeadBall {
+    timer: Timer,
+}
+
+fn collect_dead_balls(
+    mut commands: Commands,
+    ball_q: Query<(Entity, &Ball, &BallStatus, &GlobalTransform), Without<DeadBall>>,
+    entity_q: Query<Entity>,
+    settings: Res<BallBoySettings>,
+    court_set: Res<CourtSettings>,
+    asset_server: Res<AssetServer>,
+) {
+    for (ball_e, ball, status, ball_t) in ball_q.iter() {
+        if !matches!(status, BallStatus::Used) {
+            continue;
+        }
+
+        // the trail is not a child of the ball, so it has to be collected separately
+        for e in [Some(ball_e), ball.trail_e]
+            .iter()
+            .flatten()
+            .filter_map(|e| entity_q.get(*e).ok())
+        {
+            commands.entity(e).insert(DeadBall {
+                timer: Timer::from_seconds(settings.collect_after_sec, false),
+            });
+        }
+
+        if settings.show_critter {
+            spawn_critter(&mut commands, &asset_server, &court_set, ball_t.translation);
+        }
+    }
+}
+
+fn despawn_dead_balls(
+    mut commands: Commands,
+    mut dead_q: Query<(Entity, &mut DeadBall)>,
+    time: ScaledTime,
+) {
+    for (e, mut dead) in dead_q.iter_mut() {
+        dead.timer.tick(time.scaled_delta());
+
+        if dead.timer.just_finished() {
+            commands.entity(e).despawn_recursive();
+        }
+    }
+}
+
+fn spawn_critter(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    court_set: &Res<CourtSettings>,
+    ball_pos: Vec3,
+) {
+    // run in from the closer sideline
+    let start_y = if ball_pos.y < 0. {
+        court_set.bottom - 120.
+    } else {
+        court_set.top + 120.
+    };
+    let start = Vec3::new(ball_pos.x, start_y, PLAYER_Z);
+    let end = ball_pos.truncate().extend(PLAYER_Z);
+    let run_dur = Duration::from_millis(400);
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: asset_server.load("art-ish/player_body.png"),
+            transform: Transform {
+                translation: start,
+                scale: Vec3::new(0.35, 0.35, 1.),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(PaletteColor::CourtPost)
+        .insert(Name::new("BallBoy"))
+        .insert(DespawnOnExit(GameState::Game))
+        .insert(Animator::new(
+            Tween::new(
+                EaseFunction::QuadraticOut,
+                TweeningType::Once,
+                run_dur,
+                TransformPositionLens { start, end },
+            )
+            .then(
+                Tween::new(
+                    EaseFunction::QuadraticIn,
+                    TweeningType::Once,
+                    run_dur,
+                    TransformPositionLens {
+                        start: end,
+                        end: start,
+                    },
+                )
+                .with_completed_event(true, TweenDoneAction::DespawnRecursive.into()),
+            ),
+        ));
+}
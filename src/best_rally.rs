@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+
+use crate::{
+    ball::BallHitEvt,
+    celebration::Celebration,
+    replay::{Replay, ReplayPlayback, ReplayRecorder},
+    score::{PointReason, ScoreChangedEvt},
+    GameState,
+};
+
+// how much of the approach to the first hit gets kept
+const LEAD_SEC: f32 = 0.5;
+const WATCH_KEY: KeyCode = KeyCode::E;
+
+// keeps the replay of the longest rally of the match around for the results screen
+pub struct BestRallyPlugin;
+impl Plugin for BestRallyPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<BestRally>()
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(reset_best_rally))
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(track_best_rally)
+                    .with_system(watch_best_rally),
+            );
+    }
+}
+
+#[derive(Default)]
+pub struct BestRally {
+    pub hits: u32,
+    // frame times start at the clip start
+    pub replay: Option<Replay>,
+    current_hits: u32,
+    // replay recorder time of the first hit of the current rally
+    current_start_time: f32,
+}
+
+fn reset_best_rally(mut best: ResMut<BestRally>) {
+    *best = BestRally::default();
+}
+
+// counts hits the same way as the stats, so the card and the clip agree
+fn track_best_rally(
+    mut ev_r_hit: EventReader<BallHitEvt>,
+    mut ev_r_score: EventReader<ScoreChangedEvt>,
+    mut best: ResMut<BestRally>,
+    recorder: Res<ReplayRecorder>,
+) {
+    for _ in ev_r_hit.iter() {
+        if best.current_hits == 0 {
+            best.current_start_time = recorder.replay.frames.last().map_or(0., |f| f.time);
+        }
+        best.current_hits += 1;
+    }
+
+    // hits go first, a rally can end on the same frame as the last hit
+    for ev in ev_r_score.iter() {
+        // the rally has already ended
+        if ev.reason == PointReason::Overturned {
+            continue;
+        }
+
+        if best.current_hits > best.hits {
+            best.hits = best.current_hits;
+            best.replay = Some(get_clip(
+                &recorder.replay,
+                best.current_start_time - LEAD_SEC,
+            ));
+        }
+        best.current_hits = 0;
+    }
+}
+
+// from the start time up to the latest frame
+fn get_clip(replay: &Replay, start_time: f32) -> Replay {
+    let start_i = replay
+        .frames
+        .iter()
+        .position(|f| f.time >= start_time)
+        .unwrap_or(replay.frames.len());
+    let start_time = replay.frames.get(start_i).map_or(0., |f| f.time);
+
+    Replay {
+        frames: replay.frames[start_i..]
+            .iter()
+            .map(|f| {
+                let mut frame = f.clone();
+                frame.time -= start_time;
+                frame
+            })
+            .collect(),
+        // frame times aren't part of the checksums
+        checksums: replay.checksums.iter().skip(start_i).cloned().collect(),
+    }
+}
+
+// from the results screen, the match gets reset once the clip is over
+fn watch_best_rally(
+    keys: Res<Input<KeyCode>>,
+    celebration: Option<Res<Celebration>>,
+    best: Res<BestRally>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !keys.just_pressed(WATCH_KEY) || !celebration.map_or(false, |c| c.is_done()) {
+        return;
+    }
+
+    if let Some(replay) = &best.replay {
+        playback.replay = Some(replay.clone());
+        playback.slow_motion = true;
+        state.overwrite_replace(GameState::Replay).unwrap();
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    net::TcpListener,
+    sync::{
+        mpsc::{channel, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use bevy::prelude::*;
+use serde::Serialize;
+use tungstenite::{Message, WebSocket};
+
+use crate::{
+    ball::{Ball, BallHitEvt},
+    level::NetOffset,
+    score::{GameOverEvt, PlayerScore, PointOutcome, Score, ScoreChangedEvt},
+};
+
+const BROADCAST_ADDR: &str = "127.0.0.1:9001";
+
+// streams the match state as json to websocket clients, e.g. stream overlays
+pub struct BroadcastPlugin;
+impl Plugin for BroadcastPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        match start_server(BROADCAST_ADDR) {
+            Ok(server) => {
+                info!("Broadcasting match state on ws://{}", BROADCAST_ADDR);
+                app.insert_resource(server)
+                    .add_system(broadcast_state)
+                    .add_system(broadcast_events);
+            }
+            Err(e) => error!("Failed to start the broadcast server: {}", e),
+        }
+    }
+}
+
+pub struct BroadcastServer {
+    tx: Sender<String>,
+    // sent to new clients, so they don't have to wait for a change
+    last_state: Arc<Mutex<Option<String>>>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BroadcastMsg {
+    State {
+        left: BroadcastScore,
+        right: BroadcastScore,
+        set_history: Vec<(u8, u8)>,
+        net_offset: f32,
+        rally_hits: u32,
+    },
+    Point {
+        scoring_player_id: usize,
+        outcome: PointOutcome,
+    },
+    GameOver {
+        winner_id: usize,
+    },
+}
+
+#[derive(Serialize)]
+struct BroadcastScore {
+    points: u8,
+    games: u8,
+    sets: u8,
+}
+
+fn start_server(addr: &str) -> std::io::Result<BroadcastServer> {
+    let listener = TcpListener::bind(addr)?;
+    let clients = Arc::new(Mutex::new(Vec::<WebSocket<_>>::new()));
+    let last_state = Arc::new(Mutex::new(None::<String>));
+    let (tx, rx) = channel::<String>();
+
+    let accept_clients = clients.clone();
+    let accept_state = last_state.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Ok(mut ws) = tungstenite::accept(stream) {
+                if let Some(state) = accept_state.lock().unwrap().clone() {
+                    if ws.write_message(Message::Text(state)).is_err() {
+                        continue;
+                    }
+                }
+
+                accept_clients.lock().unwrap().push(ws);
+            }
+        }
+    });
+
+    thread::spawn(move || {
+        for msg in rx.iter() {
+            // drop disconnected clients
+            let mut clients = clients.lock().unwrap();
+            *clients = clients
+                .drain(..)
+                .filter_map(|mut ws| {
+                    ws.write_message(Message::Text(msg.clone()))
+                        .is_ok()
+                        .then(|| ws)
+                })
+                .collect();
+        }
+    });
+
+    Ok(BroadcastServer { tx, last_state })
+}
+
+impl BroadcastServer {
+    fn send(&self, msg: &BroadcastMsg) {
+        match serde_json::to_string(msg) {
+            Ok(json) => {
+                if let BroadcastMsg::State { .. } = msg {
+                    *self.last_state.lock().unwrap() = Some(json.clone());
+                }
+
+                self.tx.send(json).ok();
+            }
+            Err(e) => error!("Failed to serialize the broadcast: {}", e),
+        }
+    }
+}
+
+fn broadcast_state(
+    server: Res<BroadcastServer>,
+    score: Res<Score>,
+    net: Res<NetOffset>,
+    mut ev_r_hit: EventReader<BallHitEvt>,
+    new_ball_q: Query<Entity, Added<Ball>>,
+    mut hits: Local<u32>,
+) {
+    let mut rally_changed = false;
+
+    // a new ball means a new rally
+    if new_ball_q.iter().next().is_some() {
+        *hits = 0;
+        rally_changed = true;
+    }
+
+    for _ in ev_r_hit.iter() {
+        *hits += 1;
+        rally_changed = true;
+    }
+
+    if !rally_changed && !score.is_changed() && !net.is_changed() {
+        return;
+    }
+
+    server.send(&BroadcastMsg::State {
+        left: BroadcastScore::from(&score.left_player),
+        right: BroadcastScore::from(&score.right_player),
+        set_history: score.set_history.clone(),
+        net_offset: net.0,
+        rally_hits: *hits,
+    });
+}
+
+fn broadcast_events(
+    server: Res<BroadcastServer>,
+    mut ev_r_score: EventReader<ScoreChangedEvt>,
+    mut ev_r_game_over: EventReader<GameOverEvt>,
+) {
+    for ev in ev_r_score.iter() {
+        server.send(&BroadcastMsg::Point {
+            scoring_player_id: ev.scoring_player_id,
+            outcome: ev.outcome,
+        });
+    }
+
+    for ev in ev_r_game_over.iter() {
+        server.send(&BroadcastMsg::GameOver {
+            winner_id: ev.winner_id,
+        });
+    }
+}
+
+impl From<&PlayerScore> for BroadcastScore {
+    fn from(score: &PlayerScore) -> Self {
+        Self {
+            points: score.points,
+            games: score.games,
+            sets: score.sets,
+        }
+    }
+}
@@ -1,19 +1,239 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::reset::Persistent;
+use crate::{
+    ball::{Ball, BallHitEvt, BallStatus},
+    player::Player,
+    score::Score,
+    storage,
+    window::{WIN_HEIGHT, WIN_WIDTH},
+};
+
+const CAMERA_SETTINGS_STORAGE_KEY: &str = "settings/camera.ron";
 
 pub struct CameraPlugin;
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_startup_system(setup);
+        app.init_resource::<CameraFraming>()
+            .init_resource::<TargetCamScale>()
+            .insert_resource(CameraSettings::load())
+            .add_startup_system(setup)
+            .add_system(change_camera_mode)
+            .add_system(frame_camera);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CameraMode {
+    // frames the players and the ball all the time
+    Follow,
+    // only moves once the framed area drifts out of the deadzone
+    Deadzone,
+    // the whole court, no movement
+    Fixed,
+}
+
+impl CameraMode {
+    fn get_next(&self) -> Self {
+        match self {
+            CameraMode::Follow => CameraMode::Deadzone,
+            CameraMode::Deadzone => CameraMode::Fixed,
+            CameraMode::Fixed => CameraMode::Follow,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraSettings {
+    pub mode: CameraMode,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            mode: CameraMode::Follow,
+        }
+    }
+}
+
+impl CameraSettings {
+    fn load() -> Self {
+        storage::load(CAMERA_SETTINGS_STORAGE_KEY)
+            .ok()
+            .and_then(|bytes| ron::de::from_bytes(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let result = ron::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|data| {
+                storage::save(CAMERA_SETTINGS_STORAGE_KEY, data.as_bytes())
+                    .map_err(|e| e.to_string())
+            });
+
+        if let Err(e) = result {
+            error!("Failed to save the camera settings: {}", e);
+        }
+    }
+}
+
+pub struct CameraFraming {
+    pub margin: Vec2,
+    // 2 = zoomed in to half the base view
+    pub max_zoom: f32,
+    pub max_scale: f32,
+    pub speed: f32,
+    // half size of the box the framed center can move in without moving the camera
+    pub deadzone: Vec2,
+}
+
+impl Default for CameraFraming {
+    fn default() -> Self {
+        Self {
+            margin: Vec2::new(250., 200.),
+            max_zoom: 1.4,
+            max_scale: 1.,
+            speed: 2.5,
+            deadzone: Vec2::new(150., 100.),
+        }
+    }
+}
+
+// scale of the base view, grows with the court (see CourtSpec::get_view_scale)
+pub struct TargetCamScale(pub f32);
+
+impl Default for TargetCamScale {
+    fn default() -> Self {
+        Self(1.)
     }
 }
 
+#[derive(Component)]
+pub struct MainCamera;
+
+// the camera is moved by a sequence instead of following the players
+#[derive(Component)]
+pub struct ScriptedCamera;
+
 fn setup(mut commands: Commands) {
     commands
         .spawn_bundle(OrthographicCameraBundle::new_2d())
-        .insert(Persistent);
-    commands
-        .spawn_bundle(UiCameraBundle::default())
-        .insert(Persistent);
+        .insert(MainCamera);
+    commands.spawn_bundle(UiCameraBundle::default());
+}
+
+// same as the other settings - only before the 1st point of a match
+fn change_camera_mode(
+    keys: Res<Input<KeyCode>>,
+    score: Res<Score>,
+    mut settings: ResMut<CameraSettings>,
+) {
+    if score.is_match_start() && keys.just_pressed(KeyCode::V) {
+        settings.mode = settings.mode.get_next();
+        settings.save();
+    }
+}
+
+// frames both players and the ball
+fn frame_camera(
+    mut cam_q: Query<
+        (&mut Transform, &mut OrthographicProjection),
+        (With<MainCamera>, Without<ScriptedCamera>),
+    >,
+    player_q: Query<&GlobalTransform, With<Player>>,
+    ball_q: Query<(Entity, &BallStatus, &GlobalTransform), With<Ball>>,
+    mut ev_r_hit: EventReader<BallHitEvt>,
+    framing: Res<CameraFraming>,
+    settings: Res<CameraSettings>,
+    base_scale: Res<TargetCamScale>,
+    time: Res<Time>,
+    mut last_hit_ball: Local<Option<Entity>>,
+) {
+    if let Some(ev) = ev_r_hit.iter().last() {
+        *last_hit_ball = Some(ev.ball_e);
+    }
+
+    let (target_pos, target_scale) = if settings.mode == CameraMode::Fixed {
+        (Vec2::ZERO, base_scale.0)
+    } else {
+        let ball_pos = get_focus_ball_pos(&ball_q, *last_hit_ball);
+        let focus = player_q
+            .iter()
+            .map(|t| t.translation.truncate())
+            .chain(ball_pos);
+        match get_focus_framing(focus, &framing, base_scale.0) {
+            Some(target) => target,
+            None => return,
+        }
+    };
+
+    for (mut cam_t, mut projection) in cam_q.iter_mut() {
+        let cam_pos = cam_t.translation.truncate();
+        let target_pos = if settings.mode == CameraMode::Deadzone {
+            // just enough to get the framed center back into the deadzone
+            let offset = target_pos - cam_pos;
+            cam_pos + offset - offset.clamp(-framing.deadzone, framing.deadzone)
+        } else {
+            target_pos
+        };
+
+        let t = (framing.speed * time.delta_seconds()).min(1.);
+        cam_t.translation = cam_pos.lerp(target_pos, t).extend(cam_t.translation.z);
+        projection.scale += (target_scale - projection.scale) * t;
+    }
+}
+
+// the old ball can still be scaling out when the next one spawns
+// prefers the live ball (the last hit one if there's more), then the last hit one
+// without either the players get framed on their own
+fn get_focus_ball_pos(
+    ball_q: &Query<(Entity, &BallStatus, &GlobalTransform), With<Ball>>,
+    last_hit_ball: Option<Entity>,
+) -> Option<Vec2> {
+    let mut live = ball_q.iter().filter(|(_, status, _)| status.is_in_play());
+    let first_live = live.next();
+    let focus = match (first_live, live.next()) {
+        (Some(ball), None) => Some(ball),
+        (Some(ball), Some(_)) => last_hit_ball
+            .and_then(|e| ball_q.get(e).ok())
+            .filter(|(_, status, _)| status.is_in_play())
+            .or(Some(ball)),
+        (None, _) => last_hit_ball.and_then(|e| ball_q.get(e).ok()),
+    };
+
+    focus.map(|(_, _, t)| t.translation.truncate())
+}
+
+// the position and scale framing all the focused positions
+fn get_focus_framing(
+    focus: impl Iterator<Item = Vec2>,
+    framing: &CameraFraming,
+    base_scale: f32,
+) -> Option<(Vec2, f32)> {
+    let mut min = Vec2::splat(f32::MAX);
+    let mut max = Vec2::splat(f32::MIN);
+
+    for pos in focus {
+        min = min.min(pos);
+        max = max.max(pos);
+    }
+
+    if min.x > max.x {
+        return None;
+    }
+
+    let min = min - framing.margin;
+    let max = max + framing.margin;
+    let size = max - min;
+    let target_scale = (size.x / WIN_WIDTH).max(size.y / WIN_HEIGHT).clamp(
+        base_scale / framing.max_zoom,
+        framing.max_scale * base_scale,
+    );
+
+    // don't let the view wander past the base view
+    let max_offset = (Vec2::new(WIN_WIDTH, WIN_HEIGHT) / 2. * (base_scale - target_scale)).abs();
+    let target_pos = ((min + max) / 2.).clamp(-max_offset, max_offset);
+
+    Some((target_pos, target_scale))
 }
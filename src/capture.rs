@@ -0,0 +1,447 @@
+use std::{
+    collections::VecDeque,
+    num::NonZeroU32,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    core_pipeline::{draw_2d_graph, node, Transparent2d},
+    prelude::*,
+    render::{
+        camera::Camera,
+        render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext, SlotValue},
+        render_phase::RenderPhase,
+        render_resource::{
+            Buffer, BufferDescriptor, BufferUsages, Extent3d, ImageCopyBuffer, ImageDataLayout,
+            MapMode, Texture, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::{BevyDefault, TextureCache},
+        view::{ExtractedView, ViewTarget, VisibleEntities},
+        RenderApp, RenderStage,
+    },
+    tasks::AsyncComputeTaskPool,
+};
+use image::{
+    codecs::{gif::GifEncoder, png::PngEncoder},
+    ColorType, Delay, Frame, RgbaImage,
+};
+
+use crate::{
+    camera::MainCamera,
+    storage,
+    timer::{GameClock, GameTimer},
+};
+
+const CAPTURE_PASS_DRIVER: &str = "capture_pass_driver";
+const CAPTURE_COPY: &str = "capture_copy";
+const CAPTURE_DIR: &str = "captures";
+const CAPTURE_KEY: KeyCode = KeyCode::Insert;
+// the last ~5s, small and choppy to keep the ring buffer and the readback stalls cheap
+const GIF_FRAME_SEC: f32 = 0.1;
+const GIF_FRAME_COUNT: usize = 50;
+const GIF_SCALE: f32 = 0.4;
+// wgpu's COPY_BYTES_PER_ROW_ALIGNMENT
+const COPY_ROW_ALIGNMENT: u32 = 256;
+
+// png screenshots and a gif of the last few seconds, saved by the same hotkey
+// the court gets rendered again to an offscreen target and read back to the cpu,
+// the HUD is left out as bevy_ui draws in a pass of its own
+// the readback blocks until the gpu is done, so it's a feature of its own and native only
+pub struct CapturePlugin;
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        let captured = CapturedFrames::default();
+
+        app.init_resource::<CaptureRequest>()
+            .init_resource::<GifFrames>()
+            .insert_resource(captured.clone())
+            .add_system_to_stage(CoreStage::First, clear_capture_request)
+            .add_system(request_gif_frame)
+            .add_system(request_screenshot)
+            .add_system(save_captures);
+
+        let render_app = app.sub_app_mut(RenderApp);
+        render_app
+            .insert_resource(captured)
+            .init_resource::<CaptureTarget>()
+            .add_system_to_stage(RenderStage::Extract, extract_capture_view)
+            .add_system_to_stage(RenderStage::Prepare, prepare_capture_target)
+            .add_system_to_stage(RenderStage::Cleanup, read_capture);
+        let mut graph = render_app.world.get_resource_mut::<RenderGraph>().unwrap();
+        graph.add_node(CAPTURE_PASS_DRIVER, CapturePassDriver);
+        graph.add_node(CAPTURE_COPY, CaptureCopyNode);
+        graph
+            .add_node_edge(node::MAIN_PASS_DEPENDENCIES, CAPTURE_PASS_DRIVER)
+            .unwrap();
+        // sub graphs run right after their node, so the copy sees the finished pass
+        graph
+            .add_node_edge(CAPTURE_PASS_DRIVER, CAPTURE_COPY)
+            .unwrap();
+    }
+}
+
+// ordered by priority, only one capture gets rendered per frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CaptureKind {
+    GifFrame,
+    Screenshot,
+    // supersampled photo mode shot
+    Photo,
+}
+
+// the capture to render this frame and its scale of the window size
+#[derive(Default)]
+pub struct CaptureRequest(Option<(CaptureKind, f32)>);
+
+impl CaptureRequest {
+    // a skipped gif frame just holds the previous one a bit longer
+    pub fn request(&mut self, kind: CaptureKind, scale: f32) {
+        if self.0.map_or(true, |(current, _)| kind > current) {
+            self.0 = Some((kind, scale));
+        }
+    }
+}
+
+// filled by the render world once a capture is read back
+#[derive(Default, Clone)]
+struct CapturedFrames(Arc<Mutex<Vec<CapturedFrame>>>);
+
+struct CapturedFrame {
+    kind: CaptureKind,
+    image: RgbaImage,
+}
+
+// the ring buffer the gif is made from
+struct GifFrames {
+    frames: VecDeque<RgbaImage>,
+    timer: GameTimer,
+}
+
+impl Default for GifFrames {
+    fn default() -> Self {
+        Self {
+            frames: VecDeque::with_capacity(GIF_FRAME_COUNT + 1),
+            timer: GameTimer::unscaled(GIF_FRAME_SEC),
+        }
+    }
+}
+
+#[derive(Component)]
+struct CaptureView {
+    kind: CaptureKind,
+}
+
+struct PendingCapture {
+    view_e: Entity,
+    kind: CaptureKind,
+    width: u32,
+    height: u32,
+    texture: Texture,
+    buffer: Buffer,
+}
+
+#[derive(Default)]
+struct CaptureTarget(Option<PendingCapture>);
+
+fn clear_capture_request(mut request: ResMut<CaptureRequest>) {
+    request.0 = None;
+}
+
+fn request_gif_frame(
+    mut gif: ResMut<GifFrames>,
+    mut request: ResMut<CaptureRequest>,
+    clock: GameClock,
+) {
+    if !gif.timer.tick(&clock).finished() {
+        return;
+    }
+    gif.timer.reset();
+
+    request.request(CaptureKind::GifFrame, GIF_SCALE);
+}
+
+fn request_screenshot(keys: Res<Input<KeyCode>>, mut request: ResMut<CaptureRequest>) {
+    if keys.just_pressed(CAPTURE_KEY) {
+        request.request(CaptureKind::Screenshot, 1.);
+    }
+}
+
+fn save_captures(
+    captured: Res<CapturedFrames>,
+    mut gif: ResMut<GifFrames>,
+    task_pool: Res<AsyncComputeTaskPool>,
+) {
+    let frames: Vec<_> = captured.0.lock().unwrap().drain(..).collect();
+
+    for frame in frames {
+        match frame.kind {
+            CaptureKind::GifFrame => {
+                gif.frames.push_back(frame.image);
+                if gif.frames.len() > GIF_FRAME_COUNT {
+                    gif.frames.pop_front();
+                }
+            }
+            CaptureKind::Screenshot => {
+                save_png(&frame.image, "screenshot");
+
+                // encoding takes a while, the ring buffer keeps going meanwhile
+                let gif_frames: Vec<_> = gif.frames.iter().cloned().collect();
+                task_pool
+                    .spawn(async move {
+                        save_gif(gif_frames);
+                    })
+                    .detach();
+            }
+            CaptureKind::Photo => save_png(&frame.image, "photo"),
+        }
+    }
+}
+
+fn get_capture_key(name: &str, ext: &str) -> String {
+    let prefix = format!("{}/{}_", CAPTURE_DIR, name);
+    let number = storage::get_next_number(&storage::list(CAPTURE_DIR), &prefix, ext);
+    format!("{}{:05}.{}", prefix, number, ext)
+}
+
+fn save_png(image: &RgbaImage, name: &str) {
+    let key = get_capture_key(name, "png");
+    let mut data = Vec::new();
+    let encoded = PngEncoder::new(&mut data).encode(
+        image.as_raw(),
+        image.width(),
+        image.height(),
+        ColorType::Rgba8,
+    );
+    let result = encoded
+        .map_err(|e| e.to_string())
+        .and_then(|_| storage::save(&key, &data).map_err(|e| e.to_string()));
+
+    match result {
+        Ok(_) => info!("Capture saved to {}", key),
+        Err(e) => warn!("Failed to save the capture: {}", e),
+    }
+}
+
+fn save_gif(frames: Vec<RgbaImage>) {
+    if frames.is_empty() {
+        return;
+    }
+
+    let key = get_capture_key("clip", "gif");
+    let delay = Delay::from_numer_denom_ms((GIF_FRAME_SEC * 1000.) as u32, 1);
+    let mut data = Vec::new();
+    // the encoder only writes the gif trailer once it's dropped
+    let encoded = GifEncoder::new(&mut data).encode_frames(
+        frames
+            .into_iter()
+            .map(|f| Frame::from_parts(f, 0, 0, delay)),
+    );
+    let result = encoded
+        .map_err(|e| e.to_string())
+        .and_then(|_| storage::save(&key, &data).map_err(|e| e.to_string()));
+
+    match result {
+        Ok(_) => info!("Capture saved to {}", key),
+        Err(e) => warn!("Failed to save the gif capture: {}", e),
+    }
+}
+
+// rows of the copied texture have to be aligned
+fn get_padded_bytes_per_row(width: u32) -> u32 {
+    let bytes_per_row = width * 4;
+    (bytes_per_row + COPY_ROW_ALIGNMENT - 1) / COPY_ROW_ALIGNMENT * COPY_ROW_ALIGNMENT
+}
+
+// the same view as the main camera, only scaled to the capture size
+fn extract_capture_view(
+    mut commands: Commands,
+    request: Res<CaptureRequest>,
+    windows: Res<Windows>,
+    cam_q: Query<(&Camera, &GlobalTransform, &VisibleEntities), With<MainCamera>>,
+) {
+    let (kind, scale) = match request.0 {
+        Some(request) => request,
+        None => return,
+    };
+
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+
+    if let Ok((camera, cam_t, visible_entities)) = cam_q.get_single() {
+        commands.spawn_bundle((
+            CaptureView { kind },
+            ExtractedView {
+                projection: camera.projection_matrix,
+                transform: *cam_t,
+                width: ((window.physical_width() as f32 * scale) as u32).max(1),
+                height: ((window.physical_height() as f32 * scale) as u32).max(1),
+                near: camera.near,
+                far: camera.far,
+            },
+            visible_entities.clone(),
+            RenderPhase::<Transparent2d>::default(),
+        ));
+    }
+}
+
+fn prepare_capture_target(
+    mut commands: Commands,
+    mut target: ResMut<CaptureTarget>,
+    view_q: Query<(Entity, &CaptureView, &ExtractedView)>,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    msaa: Res<Msaa>,
+) {
+    let (view_e, capture_view, view) = match view_q.get_single() {
+        Ok(view) => view,
+        Err(_) => return,
+    };
+
+    let size = Extent3d {
+        width: view.width,
+        height: view.height,
+        depth_or_array_layers: 1,
+    };
+    // the pipelines are specialized for the window format
+    let get_descriptor =
+        |label: &'static str, sample_count: u32, usage: TextureUsages| TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count: 1,
+            sample_count,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::bevy_default(),
+            usage,
+        };
+
+    let texture = texture_cache.get(
+        &render_device,
+        get_descriptor(
+            "capture_texture",
+            1,
+            TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+        ),
+    );
+    // the pass is multisampled like the main one and resolves to the copied texture
+    let sampled_target = (msaa.samples > 1).then(|| {
+        texture_cache
+            .get(
+                &render_device,
+                get_descriptor(
+                    "capture_sampled_texture",
+                    msaa.samples,
+                    TextureUsages::RENDER_ATTACHMENT,
+                ),
+            )
+            .default_view
+    });
+    let buffer = render_device.create_buffer(&BufferDescriptor {
+        label: Some("capture_buffer"),
+        size: (get_padded_bytes_per_row(view.width) * view.height) as u64,
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    commands.entity(view_e).insert(ViewTarget {
+        view: texture.default_view,
+        sampled_target,
+    });
+    target.0 = Some(PendingCapture {
+        view_e,
+        kind: capture_view.kind,
+        width: view.width,
+        height: view.height,
+        texture: texture.texture,
+        buffer,
+    });
+}
+
+// waits for the gpu, the frame is submitted by now
+fn read_capture(
+    mut target: ResMut<CaptureTarget>,
+    render_device: Res<RenderDevice>,
+    captured: Res<CapturedFrames>,
+) {
+    let pending = match target.0.take() {
+        Some(pending) => pending,
+        None => return,
+    };
+
+    let slice = pending.buffer.slice(..);
+    render_device.map_buffer(&slice, MapMode::Read);
+    let bytes_per_row = pending.width as usize * 4;
+    let mut data = Vec::with_capacity(bytes_per_row * pending.height as usize);
+    for row in slice
+        .get_mapped_range()
+        .chunks(get_padded_bytes_per_row(pending.width) as usize)
+    {
+        data.extend_from_slice(&row[..bytes_per_row]);
+    }
+    pending.buffer.unmap();
+
+    if TextureFormat::bevy_default() == TextureFormat::Bgra8UnormSrgb {
+        for pixel in data.chunks_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    if let Some(image) = RgbaImage::from_raw(pending.width, pending.height, data) {
+        captured.0.lock().unwrap().push(CapturedFrame {
+            kind: pending.kind,
+            image,
+        });
+    }
+}
+
+// the 2d graph only drives the primary camera, this runs it for the capture view as well
+struct CapturePassDriver;
+impl Node for CapturePassDriver {
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        _render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let target = world.get_resource::<CaptureTarget>().unwrap();
+        if let Some(pending) = &target.0 {
+            graph.run_sub_graph(draw_2d_graph::NAME, vec![SlotValue::Entity(pending.view_e)])?;
+        }
+
+        Ok(())
+    }
+}
+
+struct CaptureCopyNode;
+impl Node for CaptureCopyNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let target = world.get_resource::<CaptureTarget>().unwrap();
+        if let Some(pending) = &target.0 {
+            render_context.command_encoder.copy_texture_to_buffer(
+                pending.texture.as_image_copy(),
+                ImageCopyBuffer {
+                    buffer: &pending.buffer,
+                    layout: ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: NonZeroU32::new(get_padded_bytes_per_row(pending.width)),
+                        rows_per_image: None,
+                    },
+                },
+                Extent3d {
+                    width: pending.width,
+                    height: pending.height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        Ok(())
+    }
+}
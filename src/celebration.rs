@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+
+use crate::{
+    camera::{MainCamera, ScriptedCamera},
+    player::Player,
+    score::GameOverEvt,
+    GameState,
+};
+
+const CELEBRATION_SEC: f32 = 4.;
+const ZOOM_SCALE: f32 = 0.7;
+// slower than the challenge zoom, it's a victory lap
+const ZOOM_SPEED: f32 = 0.8;
+
+// the camera slowly closes in on the winner while the jingle plays
+// confetti and the net sliding over the loser's side are handled by effects and level
+// the stats card comes up once it's over
+pub struct CelebrationPlugin;
+impl Plugin for CelebrationPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .with_system(start_celebration)
+                .with_system(play_celebration),
+        )
+        .add_system_set(SystemSet::on_exit(GameState::Game).with_system(finish_celebration));
+    }
+}
+
+pub struct Celebration {
+    winner_id: usize,
+    elapsed_sec: f32,
+}
+
+impl Celebration {
+    pub fn is_done(&self) -> bool {
+        self.elapsed_sec >= CELEBRATION_SEC
+    }
+}
+
+fn start_celebration(
+    mut commands: Commands,
+    mut ev_r_game_over: EventReader<GameOverEvt>,
+    cam_q: Query<Entity, With<MainCamera>>,
+    #[cfg(feature = "audio")] audio: Res<Audio>,
+    #[cfg(feature = "audio")] asset_server: Res<AssetServer>,
+) {
+    if let Some(ev) = ev_r_game_over.iter().last() {
+        commands.insert_resource(Celebration {
+            winner_id: ev.winner_id,
+            elapsed_sec: 0.,
+        });
+
+        for cam_e in cam_q.iter() {
+            commands.entity(cam_e).insert(ScriptedCamera);
+        }
+
+        #[cfg(feature = "audio")]
+        audio.play(asset_server.load("sfx/victory_jingle.wav"));
+    }
+}
+
+fn play_celebration(
+    mut commands: Commands,
+    celebration: Option<ResMut<Celebration>>,
+    player_q: Query<(&Player, &GlobalTransform)>,
+    mut cam_q: Query<(Entity, &mut Transform, &mut OrthographicProjection), With<MainCamera>>,
+    time: Res<Time>,
+) {
+    let mut celebration = match celebration {
+        Some(celebration) => celebration,
+        None => return,
+    };
+
+    if celebration.is_done() {
+        return;
+    }
+
+    celebration.elapsed_sec += time.delta_seconds();
+
+    // the regular framing takes over again for the stats card
+    if celebration.is_done() {
+        for (cam_e, ..) in cam_q.iter() {
+            commands.entity(cam_e).remove::<ScriptedCamera>();
+        }
+        return;
+    }
+
+    let winner_pos = match player_q.iter().find(|(p, _)| p.id == celebration.winner_id) {
+        Some((_, player_t)) => player_t.translation.truncate(),
+        None => return,
+    };
+
+    let zoom_t = (ZOOM_SPEED * time.delta_seconds()).min(1.);
+    for (_, mut cam_t, mut projection) in cam_q.iter_mut() {
+        let target = winner_pos.extend(cam_t.translation.z);
+        cam_t.translation = cam_t.translation.lerp(target, zoom_t);
+        projection.scale += (ZOOM_SCALE - projection.scale) * zoom_t;
+    }
+}
+
+// also runs on reset mid celebration
+fn finish_celebration(mut commands: Commands, cam_q: Query<Entity, With<MainCamera>>) {
+    commands.remove_resource::<Celebration>();
+
+    for cam_e in cam_q.iter() {
+        commands.entity(cam_e).remove::<ScriptedCamera>();
+    }
+}
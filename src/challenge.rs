@@ -0,0 +1,566 @@
+use bevy::prelude::*;
+
+use crate::{
+    asset::SpriteAtlas,
+    ball::{despawn_ball, spawn_point_ball, Ball, BallHitEvt, BallStatus, BALL_SIZE},
+    camera::{MainCamera, ScriptedCamera},
+    config::GameConfig,
+    cosmetic::Loadouts,
+    hud_layout::HudAnchor,
+    input_binding::{InputAction, PlayerInput},
+    level::{CourtRegion, CourtSettings, CourtSpec, NetOffset, ServingRegion},
+    locale::Locale,
+    palette::PaletteColor,
+    player::{get_next_serve_region, is_left_player_id},
+    prompt::ButtonPrompt,
+    render::{BALL_Z, COURT_LINE_Z, SHADOW_Z},
+    replay::ReplayRecorder,
+    reset::DespawnOnExit,
+    rng::GameRng,
+    score::{
+        add_point_to_score, GameOverEvt, MatchRules, PointKind, PointOutcome, PointReason,
+        PointValue, Score, ScoreChangedEvt,
+    },
+    ui_text::{get_text_style, ScaledText, TextSettings},
+    GameState,
+};
+
+const CHALLENGES_PER_MATCH: u8 = 2;
+// how much of the shot before the landing gets replayed
+const REPLAY_LEAD_SEC: f32 = 0.6;
+const REPLAY_SPEED: f32 = 0.25;
+const VERDICT_SEC: f32 = 1.5;
+const ZOOM_SCALE: f32 = 0.45;
+const ZOOM_SPEED: f32 = 4.;
+
+// the player who lost a point to a line call can challenge it before the next point starts
+// the landing is replayed in slow motion on top of the paused game
+pub struct ChallengePlugin;
+impl Plugin for ChallengePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<Challenges>()
+            .add_event::<LineCallEvt>()
+            .add_event::<CallOverturnedEvt>()
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(reset_challenges))
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(track_line_call)
+                    .with_system(handle_challenge_input)
+                    .with_system(overturn_call),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Challenge).with_system(setup))
+            .add_system_set(SystemSet::on_update(GameState::Challenge).with_system(play_challenge))
+            .add_system_set(SystemSet::on_exit(GameState::Challenge).with_system(finish_challenge))
+            .add_system(update_prompt);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineCall {
+    // landed outside of the court
+    Out,
+    // the 2nd serve missed the given service box
+    Fault(CourtRegion),
+}
+
+impl LineCall {
+    // the area the ball had to land in as (min, max), measured to the middle of the lines
+    fn get_bounds(&self, court_set: &CourtSettings, net_x: f32) -> (Vec2, Vec2) {
+        match self {
+            LineCall::Out => (
+                Vec2::new(court_set.left, court_set.bottom),
+                Vec2::new(court_set.right, court_set.top),
+            ),
+            LineCall::Fault(region) => {
+                let (min_x, max_x) = if region.is_left() {
+                    (court_set.left, net_x)
+                } else {
+                    (net_x, court_set.right)
+                };
+                let (min_y, max_y) = if region.is_top() {
+                    (0., court_set.top)
+                } else {
+                    (court_set.bottom, 0.)
+                };
+
+                (Vec2::new(min_x, min_y), Vec2::new(max_x, max_y))
+            }
+        }
+    }
+}
+
+// sent once a point is lost to a line call
+#[derive(Clone)]
+pub struct LineCallEvt {
+    pub player_id: usize,
+    pub call: LineCall,
+    pub landing: Vec2,
+    pub net_x: f32,
+    // to replay the point with the opposite call
+    pub score_before: Score,
+    pub serving_region: CourtRegion,
+}
+
+struct CallOverturnedEvt(LineCallEvt);
+
+pub struct Challenges {
+    // per player
+    pub remaining: [u8; 2],
+    pending: Option<PendingCall>,
+}
+
+impl Default for Challenges {
+    fn default() -> Self {
+        Self {
+            remaining: [CHALLENGES_PER_MATCH; 2],
+            pending: None,
+        }
+    }
+}
+
+struct PendingCall {
+    call: LineCallEvt,
+    // replay recorder time of the landing
+    time: f32,
+}
+
+struct ChallengeReplay {
+    call: LineCallEvt,
+    // (time since the replay start, ball position and bounce height)
+    frames: Vec<(f32, Vec3)>,
+    is_in: bool,
+    elapsed_sec: f32,
+    verdict_shown: bool,
+    done: bool,
+}
+
+#[derive(Component)]
+struct ChallengePrompt;
+
+#[derive(Component)]
+struct ChallengeBall;
+
+#[derive(Component)]
+struct LandingMark;
+
+#[derive(Component)]
+struct VerdictText;
+
+fn reset_challenges(mut challenges: ResMut<Challenges>) {
+    *challenges = Challenges::default();
+}
+
+// a call can only be challenged until the next point starts
+fn track_line_call(
+    mut ev_r_line_call: EventReader<LineCallEvt>,
+    mut ev_r_hit: EventReader<BallHitEvt>,
+    mut ev_r_game_over: EventReader<GameOverEvt>,
+    mut challenges: ResMut<Challenges>,
+    recorder: Res<ReplayRecorder>,
+) {
+    let point_started = ev_r_hit.iter().count() > 0;
+    let game_over = ev_r_game_over.iter().count() > 0;
+    if (point_started || game_over) && challenges.pending.is_some() {
+        challenges.pending = None;
+    }
+
+    for ev in ev_r_line_call.iter() {
+        if challenges.remaining[ev.player_id - 1] > 0 {
+            challenges.pending = Some(PendingCall {
+                call: ev.clone(),
+                time: recorder.replay.frames.last().map_or(0., |f| f.time),
+            });
+        }
+    }
+}
+
+fn handle_challenge_input(
+    mut input: ResMut<PlayerInput>,
+    challenges: Res<Challenges>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if let Some(pending) = &challenges.pending {
+        let id = pending.call.player_id;
+        // pushed on top of the paused game, so anything leaving it for good has to replace the stack
+        // a state change queued the same frame, e.g. a reset, wins over the challenge
+        if input.just_pressed(id, InputAction::Challenge)
+            && state.push(GameState::Challenge).is_ok()
+        {
+            input.use_button_action(id, InputAction::Challenge);
+        }
+    }
+}
+
+fn update_prompt(
+    mut commands: Commands,
+    challenges: Res<Challenges>,
+    prompt_q: Query<Entity, With<ChallengePrompt>>,
+    asset_server: Res<AssetServer>,
+    text_settings: Res<TextSettings>,
+) {
+    if !challenges.is_changed() {
+        return;
+    }
+
+    for e in prompt_q.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+
+    if let Some(pending) = &challenges.pending {
+        let player_id = pending.call.player_id;
+
+        commands
+            .spawn_bundle(TextBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        bottom: Val::Px(40.),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                text: Text::with_section(
+                    "",
+                    get_text_style(&asset_server, &text_settings, 32.),
+                    TextAlignment::default(),
+                ),
+                ..Default::default()
+            })
+            .insert(ButtonPrompt {
+                player_id,
+                action: InputAction::Challenge,
+                template_key: "challenge-prompt",
+            })
+            .insert(PaletteColor::Text)
+            .insert(ScaledText(32.))
+            .insert(HudAnchor {
+                player_id,
+                edge_margin: Val::Px(40.),
+            })
+            .insert(ChallengePrompt)
+            .insert(Name::new("ChallengePrompt"))
+            .insert(DespawnOnExit(GameState::Game));
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut challenges: ResMut<Challenges>,
+    recorder: Res<ReplayRecorder>,
+    court_set: Res<CourtSettings>,
+    spec: Res<CourtSpec>,
+    asset_server: Res<AssetServer>,
+    text_settings: Res<TextSettings>,
+    cam_q: Query<Entity, With<MainCamera>>,
+) {
+    let pending = match challenges.pending.take() {
+        Some(pending) => pending,
+        None => return,
+    };
+    let landing = pending.call.landing;
+
+    // the ball closest to the landing, in case there's more of them
+    let start_time = pending.time - REPLAY_LEAD_SEC;
+    let frames: Vec<(f32, Vec3)> = recorder
+        .replay
+        .frames
+        .iter()
+        .filter(|f| f.time >= start_time && f.time <= pending.time)
+        .filter_map(|f| {
+            f.balls
+                .iter()
+                .min_by(|a, b| {
+                    a.truncate()
+                        .distance_squared(landing)
+                        .partial_cmp(&b.truncate().distance_squared(landing))
+                        .unwrap()
+                })
+                .map(|pos| (f.time - start_time, *pos))
+        })
+        .collect();
+
+    // touching the line is in
+    let (min, max) = pending.call.call.get_bounds(&court_set, pending.call.net_x);
+    let margin = spec.line_thickness / 2. + BALL_SIZE / 2.;
+    let is_in = landing.cmpge(min - margin).all() && landing.cmple(max + margin).all();
+
+    let ball_pos = frames.first().map_or(landing.extend(0.), |(_, pos)| *pos);
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: asset_server.load("art-ish/ball.png"),
+            sprite: Sprite {
+                custom_size: Some(Vec2::ONE * BALL_SIZE),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(ball_pos.x, ball_pos.y + ball_pos.z, BALL_Z),
+            ..Default::default()
+        })
+        .insert(PaletteColor::Ball)
+        .insert(ChallengeBall)
+        .insert(Name::new("ChallengeBall"))
+        .insert(DespawnOnExit(GameState::Challenge));
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: asset_server.load("art-ish/ball.png"),
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(1.0, 0.5) * BALL_SIZE),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(landing.extend(SHADOW_Z)),
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(PaletteColor::Shadow)
+        .insert(LandingMark)
+        .insert(Name::new("ChallengeLandingMark"))
+        .insert(DespawnOnExit(GameState::Challenge));
+
+    // highlight the line closest to the landing
+    let center = (min + max) / 2.;
+    let size = max - min;
+    let thickness = spec.line_thickness;
+    let edges = [
+        (
+            (landing.x - min.x).abs(),
+            Vec2::new(min.x, center.y),
+            Vec2::new(thickness, size.y),
+        ),
+        (
+            (landing.x - max.x).abs(),
+            Vec2::new(max.x, center.y),
+            Vec2::new(thickness, size.y),
+        ),
+        (
+            (landing.y - min.y).abs(),
+            Vec2::new(center.x, min.y),
+            Vec2::new(size.x, thickness),
+        ),
+        (
+            (landing.y - max.y).abs(),
+            Vec2::new(center.x, max.y),
+            Vec2::new(size.x, thickness),
+        ),
+    ];
+    if let Some((_, pos, size)) = edges
+        .iter()
+        .min_by(|(a, ..), (b, ..)| a.partial_cmp(b).unwrap())
+    {
+        commands
+            .spawn_bundle(SpriteBundle {
+                transform: Transform::from_translation(pos.extend(COURT_LINE_Z + 0.5)),
+                sprite: Sprite {
+                    custom_size: Some(*size),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(PaletteColor::Ball)
+            .insert(Name::new("ChallengeLine"))
+            .insert(DespawnOnExit(GameState::Challenge));
+    }
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::Center,
+                position_type: PositionType::Relative,
+                margin: Rect {
+                    top: Val::Auto,
+                    bottom: Val::Px(60.0),
+                    right: Val::Auto,
+                    left: Val::Auto,
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                get_text_style(&asset_server, &text_settings, 60.),
+                TextAlignment {
+                    horizontal: HorizontalAlign::Center,
+                    ..Default::default()
+                },
+            ),
+            ..Default::default()
+        })
+        .insert(PaletteColor::Text)
+        .insert(ScaledText(60.))
+        .insert(VerdictText)
+        .insert(Name::new("ChallengeVerdict"))
+        .insert(DespawnOnExit(GameState::Challenge));
+
+    for cam_e in cam_q.iter() {
+        commands.entity(cam_e).insert(ScriptedCamera);
+    }
+
+    commands.insert_resource(ChallengeReplay {
+        call: pending.call,
+        frames,
+        is_in,
+        elapsed_sec: 0.,
+        verdict_shown: false,
+        done: false,
+    });
+}
+
+fn play_challenge(
+    replay: Option<ResMut<ChallengeReplay>>,
+    mut challenges: ResMut<Challenges>,
+    mut state: ResMut<State<GameState>>,
+    mut ball_q: Query<&mut Transform, (With<ChallengeBall>, Without<MainCamera>)>,
+    mut mark_q: Query<&mut Visibility, With<LandingMark>>,
+    mut verdict_q: Query<&mut Text, With<VerdictText>>,
+    mut cam_q: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
+    locale: Res<Locale>,
+    time: Res<Time>,
+) {
+    let mut replay = match replay {
+        Some(replay) => replay,
+        None => {
+            state.pop().unwrap();
+            return;
+        }
+    };
+    replay.elapsed_sec += time.delta_seconds();
+
+    let zoom_t = (ZOOM_SPEED * time.delta_seconds()).min(1.);
+    for (mut cam_t, mut projection) in cam_q.iter_mut() {
+        let target = replay.call.landing.extend(cam_t.translation.z);
+        cam_t.translation = cam_t.translation.lerp(target, zoom_t);
+        projection.scale += (ZOOM_SCALE - projection.scale) * zoom_t;
+    }
+
+    let replay_sec = replay.elapsed_sec * REPLAY_SPEED;
+    if let Some((_, pos)) = replay.frames.iter().rev().find(|(t, _)| *t <= replay_sec) {
+        for mut ball_t in ball_q.iter_mut() {
+            ball_t.translation = Vec3::new(pos.x, pos.y + pos.z, BALL_Z);
+        }
+    }
+
+    let landing_sec = replay.frames.last().map_or(0., |(t, _)| *t) / REPLAY_SPEED;
+    if !replay.verdict_shown && replay.elapsed_sec >= landing_sec {
+        replay.verdict_shown = true;
+
+        // only a failed challenge is used up
+        let remaining = &mut challenges.remaining[replay.call.player_id - 1];
+        if !replay.is_in {
+            *remaining = remaining.saturating_sub(1);
+        }
+
+        let verdict_key = if replay.is_in {
+            "challenge-in"
+        } else {
+            "challenge-out"
+        };
+        let value = format!(
+            "{}\n{}",
+            locale.get(verdict_key),
+            locale.format("challenges-left", &[&remaining.to_string()])
+        );
+        for mut text in verdict_q.iter_mut() {
+            text.sections[0].value = value.clone();
+        }
+
+        for mut visibility in mark_q.iter_mut() {
+            visibility.is_visible = true;
+        }
+    } else if replay.verdict_shown && replay.elapsed_sec >= landing_sec + VERDICT_SEC {
+        replay.done = true;
+        state.pop().unwrap();
+    }
+}
+
+// also runs on reset, so the call only gets overturned for a finished replay
+fn finish_challenge(
+    mut commands: Commands,
+    replay: Option<Res<ChallengeReplay>>,
+    cam_q: Query<Entity, With<MainCamera>>,
+    mut ev_w_overturned: EventWriter<CallOverturnedEvt>,
+) {
+    if let Some(replay) = replay {
+        if replay.done && replay.is_in {
+            ev_w_overturned.send(CallOverturnedEvt(replay.call.clone()));
+        }
+    }
+    commands.remove_resource::<ChallengeReplay>();
+
+    for cam_e in cam_q.iter() {
+        commands.entity(cam_e).remove::<ScriptedCamera>();
+    }
+}
+
+// the point goes to the challenger instead and the next point is set up again
+fn overturn_call(
+    mut commands: Commands,
+    mut ev_r_overturned: EventReader<CallOverturnedEvt>,
+    mut ball_q: Query<(Entity, &Ball, &mut BallStatus, &Transform)>,
+    entity_q: Query<Entity>,
+    mut score: ResMut<Score>,
+    mut serving_region: ResMut<ServingRegion>,
+    rules: Res<MatchRules>,
+    point_value: Res<PointValue>,
+    atlas: Res<SpriteAtlas>,
+    court_set: Res<CourtSettings>,
+    config: Res<GameConfig>,
+    loadouts: Res<Loadouts>,
+    net_offset: Res<NetOffset>,
+    mut rng: ResMut<GameRng>,
+    mut ev_w_score_changed: EventWriter<ScoreChangedEvt>,
+    mut ev_w_game_over: EventWriter<GameOverEvt>,
+) {
+    for CallOverturnedEvt(call) in ev_r_overturned.iter() {
+        *score = call.score_before.clone();
+        let scoring_left = is_left_player_id(call.player_id);
+        let mut outcome = PointOutcome::Point;
+
+        // extra points don't carry over into the next game
+        for _ in 0..point_value.0.max(1) {
+            outcome = add_point_to_score(&mut score, &rules, scoring_left);
+            if outcome.is_game_won() {
+                break;
+            }
+        }
+        ev_w_score_changed.send(ScoreChangedEvt {
+            scoring_player_id: call.player_id,
+            outcome,
+            reason: PointReason::Overturned,
+            kind: PointKind::Regular,
+        });
+        debug!("Player {} has won the challenge", call.player_id);
+
+        for (ball_e, ball, mut status, ball_t) in ball_q.iter_mut() {
+            if let BallStatus::Used = *status {
+                continue;
+            }
+
+            despawn_ball(
+                &mut commands,
+                ball_e,
+                &mut status,
+                ball_t,
+                ball.trail_e.and_then(|e| entity_q.get(e).ok()),
+            );
+        }
+
+        if outcome == PointOutcome::Match {
+            if let Some(winner_id) = score.get_winner_id(&rules) {
+                ev_w_game_over.send(GameOverEvt { winner_id });
+            }
+            continue;
+        }
+
+        serving_region.0 = get_next_serve_region(call.serving_region, &score, &rules, outcome);
+        spawn_point_ball(
+            &mut commands,
+            &atlas,
+            &rules,
+            serving_region.0,
+            0,
+            &court_set,
+            &config,
+            &loadouts,
+            &mut rng,
+            net_offset.0,
+        );
+    }
+}
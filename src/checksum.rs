@@ -0,0 +1,149 @@
+use std::cmp::Ordering;
+
+use bevy::prelude::*;
+
+use crate::{
+    replay::ReplayFrame,
+    score::{PlayerScore, Score},
+};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// fnv-1a, unlike the std hasher it's stable across builds and platforms
+// which matters once checksums get compared over the network
+pub struct StateHasher(u64);
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        Self(FNV_OFFSET)
+    }
+}
+
+impl StateHasher {
+    pub fn write_u8(&mut self, value: u8) {
+        self.0 ^= value as u64;
+        self.0 = self.0.wrapping_mul(FNV_PRIME);
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        // -0 and 0 are the same position
+        let value = if value == 0. { 0. } else { value };
+        for b in value.to_bits().to_le_bytes() {
+            self.write_u8(b);
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+// the score isn't part of replays, so only re-simulations can check it
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StateChecksum {
+    pub transforms: u64,
+    pub score: Option<u64>,
+}
+
+impl StateChecksum {
+    pub fn new(frame: &ReplayFrame, score: Option<&Score>) -> Self {
+        Self {
+            transforms: hash_frame(frame),
+            score: score.map(hash_score),
+        }
+    }
+
+    fn matches(&self, other: &StateChecksum) -> bool {
+        let score_matches = match (self.score, other.score) {
+            (Some(score), Some(other_score)) => score == other_score,
+            _ => true,
+        };
+
+        self.transforms == other.transforms && score_matches
+    }
+}
+
+// frame time is left out, it depends on the frame rate
+// entities are sorted, so the query order doesn't matter
+pub fn hash_frame(frame: &ReplayFrame) -> u64 {
+    let mut hasher = StateHasher::default();
+
+    let mut players = frame.players.clone();
+    players.sort_by_key(|(id, _)| *id);
+    for (id, pos) in players.iter() {
+        hasher.write_u8(*id);
+        hasher.write_f32(pos.x);
+        hasher.write_f32(pos.y);
+    }
+
+    let mut balls = frame.balls.clone();
+    balls.sort_by(|b1, b2| {
+        b1.x.partial_cmp(&b2.x)
+            .unwrap_or(Ordering::Equal)
+            .then(b1.y.partial_cmp(&b2.y).unwrap_or(Ordering::Equal))
+    });
+    hasher.write_u8(balls.len() as u8);
+    for pos in balls.iter() {
+        hasher.write_f32(pos.x);
+        hasher.write_f32(pos.y);
+        hasher.write_f32(pos.z);
+    }
+
+    hasher.finish()
+}
+
+pub fn hash_score(score: &Score) -> u64 {
+    let mut hasher = StateHasher::default();
+
+    for PlayerScore {
+        points,
+        games,
+        sets,
+    } in [&score.left_player, &score.right_player]
+    {
+        hasher.write_u8(*points);
+        hasher.write_u8(*games);
+        hasher.write_u8(*sets);
+    }
+    hasher.write_f32(score.net_bonus);
+
+    hasher.finish()
+}
+
+// compares a replayed or re-simulated match against the recorded checksums
+#[derive(Default)]
+pub struct SyncCheck {
+    expected: Vec<StateChecksum>,
+    diverged_at: Option<usize>,
+}
+
+impl SyncCheck {
+    pub fn start(&mut self, expected: Vec<StateChecksum>) {
+        self.expected = expected;
+        self.diverged_at = None;
+    }
+
+    // only the first divergent frame is logged, everything after it is out of sync anyway
+    pub fn check(&mut self, frame: usize, actual: StateChecksum) -> bool {
+        if self.diverged_at.is_some() {
+            return false;
+        }
+
+        match self.expected.get(frame) {
+            Some(expected) if !expected.matches(&actual) => {
+                warn!(
+                    "State diverged at frame {}: expected {:?}, got {:?}",
+                    frame, expected, actual
+                );
+                self.diverged_at = Some(frame);
+                false
+            }
+            _ => true,
+        }
+    }
+
+    pub fn diverged_at(&self) -> Option<usize> {
+        self.diverged_at
+    }
+}
@@ -0,0 +1,153 @@
+use std::{fs, io};
+
+use bevy::prelude::*;
+
+#[cfg(feature = "ai")]
+use crate::ai_player_controller::AiDifficulty;
+#[cfg(feature = "input_recording")]
+use crate::input_recording::{InputPlayback, InputRecording};
+use crate::{
+    mode::GameMode,
+    palette::PaletteTheme,
+    replay::{Replay, ReplayPlayback},
+    rng::GameRng,
+};
+
+pub const USAGE: &str = "usage: tug_of_ball [options]
+  --mode <match|wall|volley|target>
+  --ai <easy|normal|hard>
+  --palette <grass|clay|rain>
+  --seed <number>
+  --size <width>x<height>
+  --headless
+  --replay <file>
+  --input-recording <file>
+  --help";
+
+// launch configuration for testers and scripts, so they don't have to click through the game
+// options override the env vars (e.g. the seed)
+#[derive(Default)]
+pub struct LaunchArgs {
+    pub mode: Option<GameMode>,
+    #[cfg(feature = "ai")]
+    pub ai_difficulty: Option<AiDifficulty>,
+    pub palette: Option<PaletteTheme>,
+    pub seed: Option<u64>,
+    pub window_size: Option<(f32, f32)>,
+    // runs without a window, driven by the schedule runner
+    pub headless: bool,
+    // path to a replay file played instead of a match
+    pub replay: Option<String>,
+    // path to an input recording re-simulated with its seed, needs the input_recording feature
+    #[cfg(feature = "input_recording")]
+    pub input_recording: Option<String>,
+    pub help: bool,
+}
+
+impl LaunchArgs {
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut launch_args = Self::default();
+        let mut args = args;
+
+        while let Some(arg) = args.next() {
+            let mut get_value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing a value for {}", arg))
+            };
+
+            match arg.as_str() {
+                "--mode" => {
+                    let value = get_value()?;
+                    launch_args.mode = Some(
+                        GameMode::parse(&value)
+                            .ok_or_else(|| format!("unknown mode '{}'", value))?,
+                    );
+                }
+                #[cfg(feature = "ai")]
+                "--ai" => {
+                    let value = get_value()?;
+                    launch_args.ai_difficulty = Some(
+                        AiDifficulty::parse(&value)
+                            .ok_or_else(|| format!("unknown ai difficulty '{}'", value))?,
+                    );
+                }
+                "--palette" => {
+                    let value = get_value()?;
+                    launch_args.palette = Some(
+                        PaletteTheme::parse(&value)
+                            .ok_or_else(|| format!("unknown palette '{}'", value))?,
+                    );
+                }
+                "--seed" => {
+                    let value = get_value()?;
+                    launch_args.seed = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("invalid seed '{}'", value))?,
+                    );
+                }
+                "--size" => {
+                    let value = get_value()?;
+                    launch_args.window_size = Some(
+                        parse_size(&value)
+                            .ok_or_else(|| format!("invalid window size '{}'", value))?,
+                    );
+                }
+                "--headless" => launch_args.headless = true,
+                "--replay" => launch_args.replay = Some(get_value()?),
+                #[cfg(feature = "input_recording")]
+                "--input-recording" => launch_args.input_recording = Some(get_value()?),
+                "--help" | "-h" => launch_args.help = true,
+                _ => return Err(format!("unknown option '{}'", arg)),
+            }
+        }
+
+        Ok(launch_args)
+    }
+
+    // has to run before the game plugins, so their init_resource calls keep these
+    pub fn insert_resources(&self, app: &mut App) -> io::Result<()> {
+        if let Some(mode) = self.mode {
+            app.insert_resource(mode);
+        }
+
+        #[cfg(feature = "ai")]
+        if let Some(difficulty) = self.ai_difficulty {
+            app.insert_resource(difficulty);
+        }
+
+        if let Some(palette) = self.palette {
+            app.insert_resource(palette.get_palette());
+        }
+
+        if let Some(seed) = self.seed {
+            app.insert_resource(GameRng::new(seed));
+        }
+
+        if let Some(path) = &self.replay {
+            let replay = Replay::read(&mut fs::read(path)?.as_slice())?;
+            app.insert_resource(ReplayPlayback::new(replay));
+        }
+
+        // the recorded seed wins over --seed
+        #[cfg(feature = "input_recording")]
+        if let Some(path) = &self.input_recording {
+            let recording = InputRecording::read(&mut fs::read(path)?.as_slice())?;
+            app.insert_resource(GameRng::new(recording.seed))
+                .insert_resource(InputPlayback::new(recording));
+        }
+
+        Ok(())
+    }
+}
+
+// e.g. 1280x720
+fn parse_size(value: &str) -> Option<(f32, f32)> {
+    let (width, height) = value.split_once('x')?;
+    let size = (width.parse().ok()?, height.parse().ok()?);
+    (size.0 > 0. && size.1 > 0.).then(|| size)
+}
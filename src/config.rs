@@ -0,0 +1,146 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::ball::BALL_MAX_SPEED;
+
+pub struct ConfigPlugin;
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_asset::<GameConfig>()
+            .init_asset_loader::<GameConfigLoader>()
+            .init_resource::<GameConfig>()
+            .add_startup_system(setup)
+            .add_system(on_config_loaded);
+    }
+}
+
+// tuning - see assets/game.tuning.ron
+#[derive(Deserialize, TypeUuid, Clone)]
+#[uuid = "4f6c8a3e-0b1d-4f7a-9a53-2d6c1e8b7f10"]
+pub struct GameConfig {
+    pub player: PlayerConfig,
+    pub ball: BallConfig,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct PlayerConfig {
+    pub speed: f32,
+    pub charging_speed: f32,
+    pub time_to_max_speed: f32,
+    pub dash_speed: f32,
+    pub dash_duration_sec: f32,
+    pub dash_cooldown_sec: f32,
+    pub swing_duration_sec: f32,
+    pub swing_cooldown_sec: f32,
+    // shorter than the cooldown, but the player stumbles and slows down
+    pub swing_whiff_recovery_sec: f32,
+    pub swing_radius: f32,
+    pub aim_ring_rotation_deg: f32,
+    pub jump_velocity: f32,
+    pub jump_gravity: f32,
+    pub jump_cooldown_sec: f32,
+    pub smash_speed_mult: f32,
+    pub body_radius: f32,
+    // players push each other out instead of passing through
+    pub body_blocking: bool,
+    // how far the magnet assist can bend a shot
+    pub magnet_assist_max_deg: f32,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct BallConfig {
+    pub max_speed: f32,
+    pub gravity: f32,
+    pub max_bounce_velocity: f32,
+}
+
+// used until the asset is loaded
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            player: PlayerConfig {
+                speed: 550.,
+                charging_speed: 125.,
+                time_to_max_speed: 0.11,
+                dash_speed: 2200.,
+                dash_duration_sec: 0.085,
+                dash_cooldown_sec: 0.5,
+                swing_duration_sec: 0.35,
+                swing_cooldown_sec: 0.35,
+                swing_whiff_recovery_sec: 0.25,
+                swing_radius: 100.,
+                aim_ring_rotation_deg: 50.,
+                jump_velocity: 520.,
+                jump_gravity: -1600.,
+                jump_cooldown_sec: 0.3,
+                smash_speed_mult: 1.4,
+                body_radius: 35.,
+                body_blocking: true,
+                magnet_assist_max_deg: 8.,
+            },
+            ball: BallConfig {
+                max_speed: BALL_MAX_SPEED,
+                gravity: -420.,
+                max_bounce_velocity: 200.,
+            },
+        }
+    }
+}
+
+struct GameConfigHandle(Handle<GameConfig>);
+
+#[derive(Default)]
+pub struct GameConfigLoader;
+
+impl AssetLoader for GameConfigLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let config: GameConfig = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(config));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Err(e) = asset_server.watch_for_changes() {
+        warn!("Tuning hot reload is not available: {:?}", e);
+    }
+
+    commands.insert_resource(GameConfigHandle(asset_server.load("game.tuning.ron")));
+}
+
+fn on_config_loaded(
+    mut ev_asset: EventReader<AssetEvent<GameConfig>>,
+    assets: Res<Assets<GameConfig>>,
+    handle: Res<GameConfigHandle>,
+    mut config: ResMut<GameConfig>,
+) {
+    for ev in ev_asset.iter() {
+        match ev {
+            AssetEvent::Created { handle: h } | AssetEvent::Modified { handle: h }
+                if *h == handle.0 =>
+            {
+                if let Some(loaded) = assets.get(h) {
+                    *config = loaded.clone();
+                    info!("Tuning loaded");
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use bevy_time::{ScaledTime, ScaledTimeDelta};
+
+use crate::{
+    palette::{Palette, PaletteColor},
+    player::{PlayerDash, PlayerSwing},
+    player_action::PlayerActionStatus,
+    timer::GameTimer,
+    GameState,
+};
+
+// just outside the player circle
+const ARC_RADIUS: f32 = 52.;
+const ARC_WIDTH: f32 = 4.;
+// of a full arc, the dash one is below the player and the swing one above
+const ARC_SWEEP_RAD: f32 = PI * 0.6;
+const ARC_SEGMENTS: usize = 16;
+const FADE_SPEED: f32 = 4.;
+
+pub struct CooldownIndicatorPlugin;
+impl Plugin for CooldownIndicatorPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system_set(SystemSet::on_update(GameState::Game).with_system(update_cooldown_arcs));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CooldownAction {
+    Dash,
+    Swing,
+}
+
+impl CooldownAction {
+    fn get_mid_angle(&self) -> f32 {
+        match self {
+            CooldownAction::Dash => -PI / 2.,
+            CooldownAction::Swing => PI / 2.,
+        }
+    }
+}
+
+// an arc around the player filling up until the action is ready again
+#[derive(Component)]
+pub struct CooldownArc {
+    pub action: CooldownAction,
+    alpha: f32,
+}
+
+// children of the player
+pub fn spawn_cooldown_arcs(b: &mut ChildBuilder) {
+    for action in [CooldownAction::Dash, CooldownAction::Swing] {
+        b.spawn_bundle(GeometryBuilder::build_as(
+            &PathBuilder::new().build().0,
+            DrawMode::Stroke(StrokeMode::new(Color::NONE, ARC_WIDTH)),
+            Transform::from_xyz(0., 0., -0.15),
+        ))
+        .insert(CooldownArc { action, alpha: 0. })
+        .insert(PaletteColor::PlayerCharge)
+        .insert(Name::new("player_cooldown_arc"));
+    }
+}
+
+// 0 right after the action, 1 once it can be used again
+fn get_cooldown_progress<T: Default>(status: &PlayerActionStatus<T>, timer: &GameTimer) -> f32 {
+    match status {
+        PlayerActionStatus::Ready | PlayerActionStatus::Charging(_) => 1.,
+        PlayerActionStatus::Active(_) => 0.,
+        PlayerActionStatus::Cooldown => timer.get_fraction(),
+    }
+}
+
+// grows outwards from the middle of the sweep
+fn build_arc(action: CooldownAction, progress: f32) -> Path {
+    let mut path_builder = PathBuilder::new();
+    let sweep = ARC_SWEEP_RAD * progress;
+    let start = action.get_mid_angle() - sweep / 2.;
+
+    for i in 0..=ARC_SEGMENTS {
+        let angle = start + sweep * i as f32 / ARC_SEGMENTS as f32;
+        let pos = Vec2::new(angle.cos(), angle.sin()) * ARC_RADIUS;
+
+        if i == 0 {
+            path_builder.move_to(pos);
+        } else {
+            path_builder.line_to(pos);
+        }
+    }
+
+    path_builder.build()
+}
+
+// the color is set every frame as the alpha fades, so palette changes get picked up as well
+fn update_cooldown_arcs(
+    mut arc_q: Query<(
+        &mut CooldownArc,
+        &Parent,
+        &PaletteColor,
+        &mut Path,
+        &mut DrawMode,
+    )>,
+    player_q: Query<(&PlayerDash, &PlayerSwing)>,
+    palette: Res<Palette>,
+    time: ScaledTime,
+) {
+    for (mut arc, parent, col, mut path, mut draw_mode) in arc_q.iter_mut() {
+        if let Ok((dash, swing)) = player_q.get(parent.0) {
+            let progress = match arc.action {
+                CooldownAction::Dash => get_cooldown_progress(&dash.status, &dash.timer),
+                CooldownAction::Swing => get_cooldown_progress(&swing.status, &swing.timer),
+            };
+            let ready = progress >= 1.;
+
+            let target_alpha = if ready { 0. } else { 1. };
+            let step = FADE_SPEED * time.scaled_delta_seconds();
+            let alpha = if arc.alpha < target_alpha {
+                (arc.alpha + step).min(target_alpha)
+            } else {
+                (arc.alpha - step).max(target_alpha)
+            };
+
+            // nothing to redraw once it's faded out
+            if alpha == 0. && arc.alpha == 0. {
+                continue;
+            }
+            arc.alpha = alpha;
+
+            *path = build_arc(arc.action, progress);
+            let mut color = palette.get_color(col);
+            color.set_a(color.a() * alpha);
+            *draw_mode = DrawMode::Stroke(StrokeMode::new(color, ARC_WIDTH));
+        }
+    }
+}
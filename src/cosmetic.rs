@@ -0,0 +1,486 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    locale::Locale,
+    palette::PaletteColor,
+    profile::{Profile, ProfileStats, Profiles},
+    score::Score,
+    trail::TrailStyle,
+    ui_text::{get_text_style, ScaledText, TextSettings},
+    GameState,
+};
+
+// cosmetic pick keys, indexed by player
+const SLOT_KEYS: [KeyCode; 2] = [KeyCode::Key7, KeyCode::Key8];
+const ITEM_KEYS: [KeyCode; 2] = [KeyCode::Key9, KeyCode::Key0];
+
+// earned cosmetics, everything not listed is available from the start
+const UNLOCKABLES: [(Cosmetic, Requirement); 7] = [
+    (
+        Cosmetic::PlayerSkin(PlayerSkin::Team),
+        Requirement::MatchesWon(1),
+    ),
+    (
+        Cosmetic::PlayerSkin(PlayerSkin::Charge),
+        Requirement::Rating(1100.),
+    ),
+    (
+        Cosmetic::BallSkin(BallSkin::Ring),
+        Requirement::PointsWon(100),
+    ),
+    (
+        Cosmetic::TrailShape(TrailShape::Thin),
+        Requirement::MatchesWon(3),
+    ),
+    (
+        Cosmetic::TrailShape(TrailShape::Comet),
+        Requirement::WinStreak(3),
+    ),
+    (
+        Cosmetic::Confetti(ConfettiStyle::Classic),
+        Requirement::MatchesWon(1),
+    ),
+    (
+        Cosmetic::Confetti(ConfettiStyle::Streamers),
+        Requirement::MatchesWon(10),
+    ),
+];
+
+// unlocked by the profile stats, picked before the match
+pub struct CosmeticPlugin;
+impl Plugin for CosmeticPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<Loadouts>()
+            .init_resource::<CosmeticPicker>()
+            .add_startup_system(setup)
+            .add_system(pick_cosmetics)
+            .add_system(sync_loadouts)
+            .add_system(update_cosmetics_ui);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum PlayerSkin {
+    Classic,
+    // colored by the side of the court
+    Team,
+    Charge,
+}
+
+impl Default for PlayerSkin {
+    fn default() -> Self {
+        PlayerSkin::Classic
+    }
+}
+
+impl PlayerSkin {
+    pub fn get_body_texture(&self) -> &'static str {
+        "art-ish/player_body.png"
+    }
+
+    pub fn get_body_color(&self, is_left: bool) -> PaletteColor {
+        match self {
+            PlayerSkin::Classic => PaletteColor::Player,
+            PlayerSkin::Team if is_left => PaletteColor::PlayerLeft,
+            PlayerSkin::Team => PaletteColor::PlayerRight,
+            PlayerSkin::Charge => PaletteColor::PlayerCharge,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum BallSkin {
+    Classic,
+    Ring,
+}
+
+impl Default for BallSkin {
+    fn default() -> Self {
+        BallSkin::Classic
+    }
+}
+
+impl BallSkin {
+    pub fn get_texture(&self) -> &'static str {
+        match self {
+            BallSkin::Classic => "art-ish/ball.png",
+            BallSkin::Ring => "art-ish/player_circle.png",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum TrailShape {
+    Classic,
+    Thin,
+    Comet,
+}
+
+impl Default for TrailShape {
+    fn default() -> Self {
+        TrailShape::Classic
+    }
+}
+
+impl TrailShape {
+    pub fn get_duration_sec(&self) -> f32 {
+        match self {
+            TrailShape::Classic | TrailShape::Thin => 0.3,
+            TrailShape::Comet => 0.6,
+        }
+    }
+
+    pub fn get_max_width(&self) -> f32 {
+        match self {
+            TrailShape::Classic => 30.,
+            TrailShape::Thin => 12.,
+            TrailShape::Comet => 40.,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ConfettiStyle {
+    None,
+    Classic,
+    Streamers,
+}
+
+impl Default for ConfettiStyle {
+    fn default() -> Self {
+        ConfettiStyle::None
+    }
+}
+
+impl ConfettiStyle {
+    pub fn get_piece_size(&self) -> Option<Vec2> {
+        match self {
+            ConfettiStyle::None => None,
+            ConfettiStyle::Classic => Some(Vec2::new(10., 6.)),
+            ConfettiStyle::Streamers => Some(Vec2::new(4., 22.)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Cosmetic {
+    PlayerSkin(PlayerSkin),
+    BallSkin(BallSkin),
+    TrailShape(TrailShape),
+    TrailStyle(TrailStyle),
+    Confetti(ConfettiStyle),
+}
+
+impl Cosmetic {
+    fn get_name_key(&self) -> &'static str {
+        match self {
+            Cosmetic::PlayerSkin(PlayerSkin::Classic)
+            | Cosmetic::BallSkin(BallSkin::Classic)
+            | Cosmetic::TrailShape(TrailShape::Classic)
+            | Cosmetic::Confetti(ConfettiStyle::Classic) => "cosmetic-classic",
+            Cosmetic::PlayerSkin(PlayerSkin::Team) => "cosmetic-team",
+            Cosmetic::PlayerSkin(PlayerSkin::Charge) => "cosmetic-charge",
+            Cosmetic::BallSkin(BallSkin::Ring) => "cosmetic-ring",
+            Cosmetic::TrailShape(TrailShape::Thin) => "cosmetic-thin",
+            Cosmetic::TrailShape(TrailShape::Comet) => "cosmetic-comet",
+            Cosmetic::TrailStyle(TrailStyle::Ribbon) => "cosmetic-ribbon",
+            Cosmetic::TrailStyle(TrailStyle::Dotted) => "cosmetic-dotted",
+            Cosmetic::TrailStyle(TrailStyle::Comet) => "cosmetic-comet-particles",
+            Cosmetic::TrailStyle(TrailStyle::None) => "cosmetic-none",
+            Cosmetic::Confetti(ConfettiStyle::None) => "cosmetic-none",
+            Cosmetic::Confetti(ConfettiStyle::Streamers) => "cosmetic-streamers",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum CosmeticSlot {
+    PlayerSkin,
+    BallSkin,
+    TrailShape,
+    TrailStyle,
+    Confetti,
+}
+
+impl Default for CosmeticSlot {
+    fn default() -> Self {
+        CosmeticSlot::PlayerSkin
+    }
+}
+
+impl CosmeticSlot {
+    fn get_next(&self) -> Self {
+        match self {
+            CosmeticSlot::PlayerSkin => CosmeticSlot::BallSkin,
+            CosmeticSlot::BallSkin => CosmeticSlot::TrailShape,
+            CosmeticSlot::TrailShape => CosmeticSlot::TrailStyle,
+            CosmeticSlot::TrailStyle => CosmeticSlot::Confetti,
+            CosmeticSlot::Confetti => CosmeticSlot::PlayerSkin,
+        }
+    }
+
+    fn get_name_key(&self) -> &'static str {
+        match self {
+            CosmeticSlot::PlayerSkin => "cosmetic-slot-skin",
+            CosmeticSlot::BallSkin => "cosmetic-slot-ball",
+            CosmeticSlot::TrailShape => "cosmetic-slot-trail",
+            CosmeticSlot::TrailStyle => "cosmetic-slot-trail-style",
+            CosmeticSlot::Confetti => "cosmetic-slot-confetti",
+        }
+    }
+
+    fn get_items(&self) -> Vec<Cosmetic> {
+        match self {
+            CosmeticSlot::PlayerSkin => [PlayerSkin::Classic, PlayerSkin::Team, PlayerSkin::Charge]
+                .into_iter()
+                .map(Cosmetic::PlayerSkin)
+                .collect(),
+            CosmeticSlot::BallSkin => [BallSkin::Classic, BallSkin::Ring]
+                .into_iter()
+                .map(Cosmetic::BallSkin)
+                .collect(),
+            CosmeticSlot::TrailShape => [TrailShape::Classic, TrailShape::Thin, TrailShape::Comet]
+                .into_iter()
+                .map(Cosmetic::TrailShape)
+                .collect(),
+            CosmeticSlot::TrailStyle => [
+                TrailStyle::Ribbon,
+                TrailStyle::Dotted,
+                TrailStyle::Comet,
+                TrailStyle::None,
+            ]
+            .into_iter()
+            .map(Cosmetic::TrailStyle)
+            .collect(),
+            CosmeticSlot::Confetti => [
+                ConfettiStyle::None,
+                ConfettiStyle::Classic,
+                ConfettiStyle::Streamers,
+            ]
+            .into_iter()
+            .map(Cosmetic::Confetti)
+            .collect(),
+        }
+    }
+}
+
+enum Requirement {
+    MatchesWon(u32),
+    PointsWon(u32),
+    Rating(f32),
+    // the last n matches won
+    WinStreak(usize),
+}
+
+impl Requirement {
+    fn is_met(&self, stats: &ProfileStats) -> bool {
+        match self {
+            Requirement::MatchesWon(count) => stats.matches_won >= *count,
+            Requirement::PointsWon(count) => stats.points_won >= *count,
+            Requirement::Rating(rating) => stats.rating >= *rating,
+            Requirement::WinStreak(count) => {
+                stats.recent_form.len() >= *count
+                    && stats.recent_form.iter().rev().take(*count).all(|won| *won)
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Debug)]
+pub struct Loadout {
+    pub player_skin: PlayerSkin,
+    pub ball_skin: BallSkin,
+    pub trail_shape: TrailShape,
+    // profiles saved before the trail styles get the ribbon
+    #[serde(default)]
+    pub trail_style: TrailStyle,
+    pub confetti: ConfettiStyle,
+}
+
+impl Loadout {
+    fn get(&self, slot: CosmeticSlot) -> Cosmetic {
+        match slot {
+            CosmeticSlot::PlayerSkin => Cosmetic::PlayerSkin(self.player_skin),
+            CosmeticSlot::BallSkin => Cosmetic::BallSkin(self.ball_skin),
+            CosmeticSlot::TrailShape => Cosmetic::TrailShape(self.trail_shape),
+            CosmeticSlot::TrailStyle => Cosmetic::TrailStyle(self.trail_style),
+            CosmeticSlot::Confetti => Cosmetic::Confetti(self.confetti),
+        }
+    }
+
+    fn set(&mut self, cosmetic: Cosmetic) {
+        match cosmetic {
+            Cosmetic::PlayerSkin(skin) => self.player_skin = skin,
+            Cosmetic::BallSkin(skin) => self.ball_skin = skin,
+            Cosmetic::TrailShape(shape) => self.trail_shape = shape,
+            Cosmetic::TrailStyle(style) => self.trail_style = style,
+            Cosmetic::Confetti(style) => self.confetti = style,
+        }
+    }
+}
+
+// indexed by player, follows the selected profiles
+#[derive(Default)]
+pub struct Loadouts(pub [Loadout; 2]);
+
+impl Loadouts {
+    // the left player's profile picks the ball, same as the palette
+    pub fn get_ball_loadout(&self) -> &Loadout {
+        &self.0[0]
+    }
+}
+
+// the slot each player is picking, indexed by player
+#[derive(Default)]
+struct CosmeticPicker([CosmeticSlot; 2]);
+
+#[derive(Component)]
+struct CosmeticsText;
+
+pub fn is_unlocked(profile: &Profile, cosmetic: Cosmetic) -> bool {
+    !UNLOCKABLES.iter().any(|(c, _)| *c == cosmetic) || profile.unlocked.contains(&cosmetic)
+}
+
+// returns the newly unlocked cosmetics
+pub fn unlock_earned(profile: &mut Profile) -> Vec<Cosmetic> {
+    let earned: Vec<Cosmetic> = UNLOCKABLES
+        .iter()
+        .filter(|(c, req)| !profile.unlocked.contains(c) && req.is_met(&profile.stats))
+        .map(|(c, _)| *c)
+        .collect();
+    profile.unlocked.extend(earned.iter().cloned());
+    earned
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, text_settings: Res<TextSettings>) {
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(130.),
+                    right: Val::Px(15.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                get_text_style(&asset_server, &text_settings, 22.),
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(PaletteColor::Text)
+        .insert(ScaledText(22.))
+        .insert(CosmeticsText)
+        .insert(Name::new("CosmeticsText"));
+}
+
+// only profiles keep cosmetics
+fn pick_cosmetics(
+    keys: Res<Input<KeyCode>>,
+    score: Res<Score>,
+    mut picker: ResMut<CosmeticPicker>,
+    mut profiles: ResMut<Profiles>,
+) {
+    if !score.is_match_start() {
+        return;
+    }
+
+    for i in 0..2 {
+        if keys.just_pressed(SLOT_KEYS[i]) {
+            picker.0[i] = picker.0[i].get_next();
+        }
+
+        if keys.just_pressed(ITEM_KEYS[i]) {
+            if let Some(profile) = profiles.get_selected_mut(i + 1) {
+                let slot = picker.0[i];
+                let items: Vec<Cosmetic> = slot
+                    .get_items()
+                    .into_iter()
+                    .filter(|c| is_unlocked(profile, *c))
+                    .collect();
+                let current = profile.loadout.get(slot);
+                let next_i = items
+                    .iter()
+                    .position(|c| *c == current)
+                    .map_or(0, |i| (i + 1) % items.len());
+                profile.loadout.set(items[next_i]);
+                profile.save();
+                debug!("Player {} picked {:?}", i + 1, items[next_i]);
+            }
+        }
+    }
+}
+
+// the players and the ball are respawned with the new looks
+fn sync_loadouts(
+    profiles: Res<Profiles>,
+    score: Res<Score>,
+    mut loadouts: ResMut<Loadouts>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !profiles.is_changed() {
+        return;
+    }
+
+    let selected = [1, 2].map(|id| {
+        profiles
+            .get_selected(id)
+            .map_or_else(Loadout::default, |p| p.loadout)
+    });
+
+    if loadouts.0 != selected {
+        loadouts.0 = selected;
+
+        if score.is_match_start() && *state.current() == GameState::Game {
+            state.overwrite_replace(GameState::Reset).unwrap();
+        }
+    }
+}
+
+fn update_cosmetics_ui(
+    profiles: Res<Profiles>,
+    picker: Res<CosmeticPicker>,
+    score: Res<Score>,
+    locale: Res<Locale>,
+    mut text_q: Query<&mut Text, With<CosmeticsText>>,
+) {
+    if !profiles.is_changed() && !picker.is_changed() && !score.is_changed() && !locale.is_changed()
+    {
+        return;
+    }
+
+    let value = if score.is_match_start() {
+        (0..2)
+            .filter_map(|i| {
+                profiles.get_selected(i + 1).map(|profile| {
+                    let slot = picker.0[i];
+                    let items = slot.get_items();
+                    let unlocked = items.iter().filter(|c| is_unlocked(profile, **c)).count();
+                    format!(
+                        "{:?}/{:?} {} | {}: {} ({}/{})",
+                        SLOT_KEYS[i],
+                        ITEM_KEYS[i],
+                        profile.name,
+                        locale.get(slot.get_name_key()),
+                        locale.get(profile.loadout.get(slot).get_name_key()),
+                        unlocked,
+                        items.len()
+                    )
+                })
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        String::new()
+    };
+
+    for mut text in text_q.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+
+use crate::{
+    level::{CourtSettings, NetOffset},
+    mode::GameMode,
+    palette::{Palette, PaletteColor},
+    player::{Player, PlayerSwing},
+    render::COURT_LINE_Z,
+    reset::DespawnOnExit,
+    GameSetupPhase, GameState,
+};
+
+// charges below this don't give anything away
+const CHARGE_THRESHOLD: f32 = 0.6;
+// charges above this tend to land in the back half
+const DEEP_CHARGE: f32 = 0.85;
+const MAX_ALPHA: f32 = 0.15;
+// portion of the band width the zone starts at, grows to the full band while charging
+const START_WIDTH: f32 = 0.5;
+const GROWTH_PER_SEC: f32 = 1.5;
+
+// hints the landing depth band (shallow/deep) of the opponent's charged swing on the receiving side
+pub struct DangerZonePlugin;
+impl Plugin for DangerZonePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Game).with_system(setup.after(GameSetupPhase::Level)),
+        )
+        .add_system_set(SystemSet::on_update(GameState::Game).with_system(update_danger_zones));
+    }
+}
+
+#[derive(Component)]
+struct DangerZone {
+    // the charging player
+    player_id: usize,
+    width: f32,
+}
+
+fn setup(mut commands: Commands, mode: Res<GameMode>) {
+    if !mode.has_opponent() {
+        return;
+    }
+
+    for player_id in 1..=2 {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::NONE,
+                    ..Default::default()
+                },
+                visibility: Visibility { is_visible: false },
+                ..Default::default()
+            })
+            .insert(DangerZone {
+                player_id,
+                width: START_WIDTH,
+            })
+            .insert(Name::new("DangerZone"))
+            .insert(DespawnOnExit(GameState::Game));
+    }
+}
+
+fn update_danger_zones(
+    mut zone_q: Query<(
+        &mut DangerZone,
+        &mut Sprite,
+        &mut Transform,
+        &mut Visibility,
+    )>,
+    player_q: Query<(&Player, &PlayerSwing)>,
+    court: Res<CourtSettings>,
+    net: Res<NetOffset>,
+    palette: Res<Palette>,
+    time: Res<Time>,
+) {
+    for (mut zone, mut sprite, mut zone_t, mut visibility) in zone_q.iter_mut() {
+        let charger = player_q
+            .iter()
+            .find(|(p, _)| p.id == zone.player_id)
+            .and_then(|(p, swing)| swing.get_charge_multiplier().map(|charge| (p, charge)))
+            .filter(|(_, charge)| *charge >= CHARGE_THRESHOLD);
+
+        let (player, charge) = match charger {
+            Some(charger) => charger,
+            None => {
+                if visibility.is_visible {
+                    visibility.is_visible = false;
+                    zone.width = START_WIDTH;
+                }
+                continue;
+            }
+        };
+
+        // the receiving half, split into the shallow and the deep band
+        let baseline_x = if player.is_left() {
+            court.right
+        } else {
+            court.left
+        };
+        let mid_x = (net.0 + baseline_x) / 2.;
+        let (from_x, to_x) = if charge >= DEEP_CHARGE {
+            (mid_x, baseline_x)
+        } else {
+            (net.0, mid_x)
+        };
+
+        zone.width = (zone.width + GROWTH_PER_SEC * time.delta_seconds()).min(1.);
+        let band_size = Vec2::new((to_x - from_x).abs(), court.top - court.bottom);
+        sprite.custom_size = Some(band_size * Vec2::new(zone.width, 1.));
+        zone_t.translation = Vec3::new(
+            (from_x + to_x) / 2.,
+            (court.top + court.bottom) / 2.,
+            // above the region highlights, under the lines
+            COURT_LINE_Z - 0.25,
+        );
+
+        let charge_t = (charge - CHARGE_THRESHOLD) / (1. - CHARGE_THRESHOLD);
+        let mut color = palette.get_color(&PaletteColor::PlayerCharge);
+        color.set_a(MAX_ALPHA * (0.5 + charge_t * 0.5));
+        sprite.color = color;
+        visibility.is_visible = true;
+    }
+}
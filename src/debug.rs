@@ -1,11 +1,17 @@
 use crate::{
     ai_player_controller::AiPlayerInputs,
-    ball::{Ball, BallBounce},
-    level::CourtRegion,
-    player::{Player, PlayerDash, PlayerMovement, PlayerSwing},
+    animation::{GroundShadow, TransformRotation},
+    ball::{Ball, BallBounce, BallStatus},
+    fatigue::PlayerFatigue,
+    level::{CourtRegion, CourtSettings, NetOffset},
+    player::{Player, PlayerAim, PlayerDash, PlayerJump, PlayerMovement, PlayerSwing},
+    player_animation::{AgentAnimationData, PlayerAnimation},
+    score::{PlayerScore, Score},
 };
 use bevy::prelude::*;
-use bevy_inspector_egui::{RegisterInspectable, WorldInspectorPlugin};
+use bevy_inspector_egui::{
+    widgets::InspectorQuery, InspectorPlugin, RegisterInspectable, WorldInspectorPlugin,
+};
 use bevy_prototype_lyon::prelude::Path;
 use bevy_time::ScaledTime;
 
@@ -13,12 +19,28 @@ pub struct DebugPlugin;
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_plugin(WorldInspectorPlugin::new())
+            // entity pickers
+            .add_plugin(InspectorPlugin::<InspectorQuery<Entity, With<Player>>>::new())
+            .add_plugin(InspectorPlugin::<InspectorQuery<Entity, With<Ball>>>::new())
+            // resource editors
+            .add_plugin(InspectorPlugin::<NetOffset>::new())
+            .add_plugin(InspectorPlugin::<Score>::new())
+            .add_plugin(InspectorPlugin::<CourtSettings>::new())
             .register_inspectable::<Player>()
             .register_inspectable::<PlayerMovement>()
             .register_inspectable::<PlayerDash>()
             .register_inspectable::<PlayerSwing>()
+            .register_inspectable::<PlayerJump>()
+            .register_inspectable::<PlayerAim>()
+            .register_inspectable::<PlayerScore>()
+            .register_inspectable::<PlayerFatigue>()
+            .register_inspectable::<PlayerAnimation>()
+            .register_inspectable::<AgentAnimationData>()
+            .register_inspectable::<TransformRotation>()
+            .register_inspectable::<GroundShadow>()
             .register_inspectable::<Ball>()
             .register_inspectable::<BallBounce>()
+            .register_inspectable::<BallStatus>()
             .register_inspectable::<CourtRegion>()
             .register_inspectable::<AiPlayerInputs>()
             .add_startup_system(test_setup)
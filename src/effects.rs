@@ -0,0 +1,265 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_time::{ScaledTime, ScaledTimeDelta};
+use bevy_tweening::lens::{SpriteColorLens, TransformScaleLens};
+use bevy_tweening::*;
+use rand::*;
+
+use crate::{
+    animation::TweenDoneAction,
+    ball::{
+        Ball, BallBounce, BallBouncedEvt, BallCrossedNetEvt, ShotRisk, BALL_SIZE, OUT_CALL_COLOR,
+    },
+    cosmetic::Loadouts,
+    level::CourtSettings,
+    mode::GameMode,
+    palette::{Palette, PaletteColor},
+    player::Player,
+    render::{BALL_Z, SHADOW_Z},
+    reset::DespawnOnExit,
+    rng::GameRng,
+    score::GameOverEvt,
+    GameState,
+};
+
+// squash at full speed, slower bounces squash proportionally less
+const BOUNCE_SQUASH: f32 = 0.4;
+const BOUNCE_SQUASH_MS: u64 = 180;
+const DUST_RING_SIZE: f32 = BALL_SIZE * 2.;
+const DUST_RING_MS: u64 = 350;
+// dust of slow bounces isn't worth showing
+const DUST_MIN_SPEED: f32 = 0.3;
+const CONFETTI_COUNT: usize = 60;
+const CONFETTI_SEC: f32 = 2.5;
+const CONFETTI_GRAVITY: f32 = -900.;
+const CONFETTI_COLORS: [PaletteColor; 4] = [
+    PaletteColor::PlayerLeft,
+    PaletteColor::PlayerRight,
+    PaletteColor::Ball,
+    PaletteColor::PlayerCharge,
+];
+
+// cosmetic reactions to gameplay events
+pub struct EffectsPlugin;
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .with_system(squash_bounce)
+                .with_system(puff_net)
+                .with_system(spawn_victory_confetti)
+                .with_system(move_confetti),
+        );
+    }
+}
+
+// bursts out of the winner, in the style picked by their loadout
+#[derive(Component)]
+struct ConfettiPiece {
+    velocity: Vec2,
+    spin: f32,
+    remaining_sec: f32,
+}
+
+fn squash_bounce(
+    mut commands: Commands,
+    mut ev_r_bounce: EventReader<BallBouncedEvt>,
+    ball_q: Query<(&Ball, &GlobalTransform)>,
+    bounce_q: Query<&Transform, With<BallBounce>>,
+    asset_server: Res<AssetServer>,
+    palette: Res<Palette>,
+    #[cfg(feature = "audio")] audio: Res<Audio>,
+) {
+    for ev in ev_r_bounce.iter() {
+        let (ball, ball_t) = match ball_q.get(ev.ball_e) {
+            Ok(ball) => ball,
+            Err(_) => continue,
+        };
+        let speed = ball.dir.length().min(1.);
+
+        if let Some(bounce_e) = ball.bounce_e {
+            if let Ok(bounce_t) = bounce_q.get(bounce_e) {
+                // recovers to the current scale, as modifiers can resize the ball
+                let squash = BOUNCE_SQUASH * speed;
+                commands.entity(bounce_e).insert(Animator::new(Tween::new(
+                    EaseFunction::BackOut,
+                    TweeningType::Once,
+                    Duration::from_millis(BOUNCE_SQUASH_MS),
+                    TransformScaleLens {
+                        start: bounce_t.scale * Vec3::new(1. + squash * 0.5, 1. - squash, 1.),
+                        end: bounce_t.scale,
+                    },
+                )));
+            }
+        }
+
+        // an out call always kicks up dust and buzzes, so it can't be missed
+        if ev.out {
+            spawn_dust_ring(
+                &mut commands,
+                &asset_server,
+                OUT_CALL_COLOR,
+                ball_t.translation.truncate().extend(SHADOW_Z),
+                1.,
+            );
+            #[cfg(feature = "audio")]
+            audio.play(asset_server.load("sfx/out_buzzer.wav"));
+        } else if speed >= DUST_MIN_SPEED {
+            spawn_dust_ring(
+                &mut commands,
+                &asset_server,
+                palette.get_color(&PaletteColor::CourtLines),
+                ball_t.translation.truncate().extend(SHADOW_Z),
+                speed,
+            );
+        }
+    }
+}
+
+// the net shakes off some dust when the ball catches it
+fn puff_net(
+    mut commands: Commands,
+    mut ev_r_crossed_net: EventReader<BallCrossedNetEvt>,
+    asset_server: Res<AssetServer>,
+    palette: Res<Palette>,
+    court_set: Res<CourtSettings>,
+    mode: Res<GameMode>,
+) {
+    if mode.has_wall() {
+        return;
+    }
+
+    for ev in ev_r_crossed_net.iter() {
+        let strength = match ev.get_shot_risk(court_set.net_extent) {
+            Some(ShotRisk::Net) => 1.,
+            Some(ShotRisk::Clip) => 0.5,
+            _ => continue,
+        };
+        spawn_dust_ring(
+            &mut commands,
+            &asset_server,
+            palette.get_color(&PaletteColor::Shadow),
+            ev.pos.extend(SHADOW_Z),
+            ev.speed.min(1.) * strength,
+        );
+    }
+}
+
+fn spawn_dust_ring(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    color: Color,
+    pos: Vec3,
+    speed: f32,
+) {
+    let mut start_col = color;
+    start_col.set_a(start_col.a() * speed);
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: asset_server.load("art-ish/player_circle.png"),
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(1.0, 0.5) * DUST_RING_SIZE),
+                color: start_col,
+                ..Default::default()
+            },
+            transform: Transform {
+                translation: pos,
+                scale: Vec3::new(0.3, 0.3, 1.),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(Animator::new(Tween::new(
+            EaseFunction::QuadraticOut,
+            TweeningType::Once,
+            Duration::from_millis(DUST_RING_MS),
+            TransformScaleLens {
+                start: Vec3::new(0.3, 0.3, 1.),
+                end: Vec3::ONE,
+            },
+        )))
+        .insert(Animator::new(
+            Tween::new(
+                EaseFunction::QuadraticIn,
+                TweeningType::Once,
+                Duration::from_millis(DUST_RING_MS),
+                SpriteColorLens {
+                    start: start_col,
+                    end: Color::NONE,
+                },
+            )
+            .with_completed_event(true, TweenDoneAction::DespawnRecursive.into()),
+        ))
+        .insert(Name::new("DustRing"))
+        .insert(DespawnOnExit(GameState::Game));
+}
+
+fn spawn_victory_confetti(
+    mut commands: Commands,
+    mut ev_r_game_over: EventReader<GameOverEvt>,
+    player_q: Query<(&Player, &GlobalTransform)>,
+    loadouts: Res<Loadouts>,
+    palette: Res<Palette>,
+    mut rng: ResMut<GameRng>,
+) {
+    for ev in ev_r_game_over.iter() {
+        let size = match loadouts.0[ev.winner_id - 1].confetti.get_piece_size() {
+            Some(size) => size,
+            None => continue,
+        };
+
+        let pos = match player_q.iter().find(|(p, _)| p.id == ev.winner_id) {
+            Some((_, player_t)) => player_t.translation.truncate(),
+            None => continue,
+        };
+
+        for i in 0..CONFETTI_COUNT {
+            let angle = rng.gen_range(-60f32..60.).to_radians();
+            let speed = rng.gen_range(350. ..750.);
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(size),
+                        color: palette.get_color(&CONFETTI_COLORS[i % CONFETTI_COLORS.len()]),
+                        ..Default::default()
+                    },
+                    transform: Transform {
+                        translation: pos.extend(BALL_Z + 1.),
+                        rotation: Quat::from_rotation_z(rng.gen_range(0. ..std::f32::consts::TAU)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(ConfettiPiece {
+                    velocity: Vec2::new(angle.sin(), angle.cos()) * speed,
+                    spin: rng.gen_range(-10. ..10.),
+                    remaining_sec: CONFETTI_SEC * rng.gen_range(0.6..1.),
+                })
+                .insert(Name::new("Confetti"))
+                .insert(DespawnOnExit(GameState::Game));
+        }
+    }
+}
+
+fn move_confetti(
+    mut commands: Commands,
+    mut confetti_q: Query<(Entity, &mut ConfettiPiece, &mut Transform)>,
+    time: ScaledTime,
+) {
+    let dt = time.scaled_delta_seconds();
+    for (confetti_e, mut piece, mut piece_t) in confetti_q.iter_mut() {
+        piece.remaining_sec -= dt;
+        if piece.remaining_sec <= 0. {
+            commands.entity(confetti_e).despawn_recursive();
+            continue;
+        }
+
+        // flutters down, so the fall slows to a drift
+        piece.velocity.y += CONFETTI_GRAVITY * dt;
+        piece.velocity *= 1. - 2. * dt;
+        piece_t.translation += (piece.velocity * dt).extend(0.);
+        piece_t.rotate(Quat::from_rotation_z(piece.spin * dt));
+    }
+}
@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+#[cfg(feature = "inspector")]
+use bevy_inspector_egui::Inspectable;
+use bevy_time::{ScaledTime, ScaledTimeDelta};
+
+use crate::{
+    ball::BallStatus,
+    input_binding::{InputAction, PlayerInput},
+    player::{Player, PlayerDash},
+    player_action::PlayerActionStatus,
+    score::Score,
+    GameState,
+};
+
+pub struct FatiguePlugin;
+impl Plugin for FatiguePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<FatigueSettings>().add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .with_system(toggle_fatigue)
+                .with_system(add_dash_fatigue)
+                .with_system(add_rally_fatigue)
+                .with_system(recover_fatigue)
+                .with_system(update_fatigue_indicator),
+        );
+    }
+}
+
+pub struct FatigueSettings {
+    // optional, toggled before the match
+    pub enabled: bool,
+    pub dash_cost: f32,
+    pub rally_cost_per_sec: f32,
+    pub max_speed_penalty: f32,
+    pub max_swing_penalty: f32,
+}
+
+impl Default for FatigueSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dash_cost: 0.04,
+            rally_cost_per_sec: 0.005,
+            max_speed_penalty: 0.2,
+            max_swing_penalty: 0.15,
+        }
+    }
+}
+
+#[derive(Default, Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
+pub struct PlayerFatigue {
+    // 0 = fresh, 1 = exhausted
+    pub value: f32,
+    pub speed_mult: f32,
+    pub swing_mult: f32,
+    was_dashing: bool,
+}
+
+impl PlayerFatigue {
+    pub fn new() -> Self {
+        Self {
+            speed_mult: 1.,
+            swing_mult: 1.,
+            ..Default::default()
+        }
+    }
+
+    fn add(&mut self, amount: f32, settings: &FatigueSettings) {
+        self.value = (self.value + amount).clamp(0., 1.);
+        self.speed_mult = 1. - self.value * settings.max_speed_penalty;
+        self.swing_mult = 1. - self.value * settings.max_swing_penalty;
+    }
+}
+
+#[derive(Component)]
+pub struct FatigueIndicator;
+
+fn toggle_fatigue(
+    mut input: ResMut<PlayerInput>,
+    score: Res<Score>,
+    mut settings: ResMut<FatigueSettings>,
+) {
+    if !score.is_match_start() {
+        return;
+    }
+
+    if let Some(id) = (1..=4).find(|id| input.just_pressed(*id, InputAction::ToggleFatigue)) {
+        input.use_button_action(id, InputAction::ToggleFatigue);
+        settings.enabled = !settings.enabled;
+        debug!("Fatigue set to {}", settings.enabled);
+    }
+}
+
+fn add_dash_fatigue(
+    mut q: Query<(&PlayerDash, &mut PlayerFatigue)>,
+    settings: Res<FatigueSettings>,
+) {
+    for (dash, mut fatigue) in q.iter_mut() {
+        let dashing = matches!(dash.status, PlayerActionStatus::Active(_));
+
+        if dashing && !fatigue.was_dashing && settings.enabled {
+            fatigue.add(settings.dash_cost, &settings);
+        }
+
+        fatigue.was_dashing = dashing;
+    }
+}
+
+fn add_rally_fatigue(
+    mut q: Query<&mut PlayerFatigue>,
+    ball_q: Query<&BallStatus>,
+    settings: Res<FatigueSettings>,
+    time: ScaledTime,
+) {
+    if !settings.enabled || !ball_q.iter().any(|s| matches!(s, BallStatus::Rally(..))) {
+        return;
+    }
+
+    for mut fatigue in q.iter_mut() {
+        fatigue.add(
+            settings.rally_cost_per_sec * time.scaled_delta_seconds(),
+            &settings,
+        );
+    }
+}
+
+// players get their breath back between games
+fn recover_fatigue(
+    mut q: Query<&mut PlayerFatigue>,
+    score: Res<Score>,
+    settings: Res<FatigueSettings>,
+    mut games: Local<u8>,
+) {
+    if score.is_changed() {
+        let total_games = score.left_player.games + score.right_player.games;
+
+        if total_games != *games {
+            *games = total_games;
+
+            for mut fatigue in q.iter_mut() {
+                fatigue.value = 0.;
+                fatigue.add(0., &settings);
+            }
+        }
+    }
+}
+
+fn update_fatigue_indicator(
+    mut q: Query<(&Parent, &mut Transform), With<FatigueIndicator>>,
+    fatigue_q: Query<&PlayerFatigue, With<Player>>,
+) {
+    for (parent, mut t) in q.iter_mut() {
+        if let Ok(fatigue) = fatigue_q.get(parent.0) {
+            t.scale.x = fatigue.value;
+        }
+    }
+}
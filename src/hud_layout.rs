@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+use crate::{player::is_left_player_id, score::Score};
+
+const MIRROR_KEY: KeyCode = KeyCode::M;
+
+// places the per-player hud on the screen side of the player, or the other one when mirrored
+// for players sitting on the opposite side of a shared screen than their court half
+// the stamina bars and other indicators attached to the players stay with them
+pub struct HudLayoutPlugin;
+impl Plugin for HudLayoutPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<HudLayout>()
+            .add_system(toggle_mirrored_hud)
+            .add_system(place_hud_anchors);
+    }
+}
+
+#[derive(Default)]
+pub struct HudLayout {
+    pub mirrored: bool,
+}
+
+impl HudLayout {
+    pub fn is_on_left(&self, player_id: usize) -> bool {
+        is_left_player_id(player_id) != self.mirrored
+    }
+
+    // per-player values in the screen order, left player's first unless mirrored
+    pub fn order<T>(&self, left_player: T, right_player: T) -> (T, T) {
+        if self.mirrored {
+            (right_player, left_player)
+        } else {
+            (left_player, right_player)
+        }
+    }
+}
+
+// an absolutely positioned node kept at the given distance from the player's screen edge
+#[derive(Component)]
+pub struct HudAnchor {
+    pub player_id: usize,
+    pub edge_margin: Val,
+}
+
+// same as the other settings - only before the 1st point of a match
+fn toggle_mirrored_hud(
+    keys: Res<Input<KeyCode>>,
+    score: Res<Score>,
+    mut layout: ResMut<HudLayout>,
+) {
+    if score.is_match_start() && keys.just_pressed(MIRROR_KEY) {
+        layout.mirrored = !layout.mirrored;
+        debug!("Mirrored hud: {}", layout.mirrored);
+    }
+}
+
+fn place_hud_anchors(
+    layout: Res<HudLayout>,
+    mut anchor_q: Query<(&HudAnchor, &mut Style, ChangeTrackers<HudAnchor>)>,
+) {
+    for (anchor, mut style, anchor_tracker) in anchor_q.iter_mut() {
+        if layout.is_changed() || anchor_tracker.is_added() {
+            let (left, right) = if layout.is_on_left(anchor.player_id) {
+                (anchor.edge_margin, Val::Undefined)
+            } else {
+                (Val::Undefined, anchor.edge_margin)
+            };
+            style.position.left = left;
+            style.position.right = right;
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 use bevy_extensions::panic_on_error;
 use bevy_input::*;
+use serde::{Deserialize, Serialize};
+
+use crate::prompt::ButtonPrompts;
 
 pub struct InputBindingPlugin;
 impl Plugin for InputBindingPlugin {
@@ -13,9 +16,19 @@ impl Plugin for InputBindingPlugin {
 pub enum InputAction {
     Swing,
     Dash,
+    Jump,
     LockPosition,
     ChangePalette,
     Reset,
+    SaveReplay,
+    PlayReplay,
+    Challenge,
+    ShowStats,
+    TogglePointStart,
+    ToggleFatigue,
+    CycleAimSpeed,
+    CycleAimSmoothing,
+    ToggleInstantAim,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -28,93 +41,168 @@ pub enum InputAxis {
 
 pub type PlayerInput = ActionInput<InputAction, InputAxis>;
 
+// picked per profile, the bindings stay the same
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ControlPreset {
+    // aims with the aim stick, falling back to the movement
+    Standard,
+    // aims with the movement only, for playing with one hand or sharing a pad
+    OneStick,
+}
+
+impl Default for ControlPreset {
+    fn default() -> Self {
+        ControlPreset::Standard
+    }
+}
+
+impl ControlPreset {
+    pub fn get_next(&self) -> Self {
+        match self {
+            ControlPreset::Standard => ControlPreset::OneStick,
+            ControlPreset::OneStick => ControlPreset::Standard,
+        }
+    }
+
+    pub fn get_name_key(&self) -> &'static str {
+        match self {
+            ControlPreset::Standard => "controls-standard",
+            ControlPreset::OneStick => "controls-one-stick",
+        }
+    }
+}
+
+// the pad has no spare button and Start alone is too easy to hit, so the reset is a combo
+// the ActionMap only binds single buttons, so the combo is read straight from the pads
+pub const RESET_PAD_COMBO: [GamepadButtonType; 2] =
+    [GamepadButtonType::Select, GamepadButtonType::Start];
+
+pub fn is_pad_combo_held(
+    gamepads: &Gamepads,
+    buttons: &Input<GamepadButton>,
+    combo: &[GamepadButtonType],
+) -> bool {
+    gamepads
+        .iter()
+        .any(|g| combo.iter().all(|b| buttons.pressed(GamepadButton(*g, *b))))
+}
+
 fn setup_bindings(
     mut map: ResMut<ActionMap<InputAction, InputAxis>>,
     mut gamepad_map: ResMut<GamepadMap>,
+    mut prompts: ResMut<ButtonPrompts>,
 ) -> Result<(), BindingError> {
     let deadzone = 0.15;
 
-    for id in 1..=2 {
-        map.bind_button_action(id, InputAction::Dash, GamepadButtonType::RightTrigger)?
-            .bind_button_action(id, InputAction::Dash, GamepadButtonType::RightTrigger2)?
-            .bind_button_action(id, InputAction::Swing, GamepadButtonType::South)?
-            .bind_button_action(id, InputAction::Swing, GamepadButtonType::West)?
-            .bind_button_action(id, InputAction::Swing, GamepadButtonType::East)?
-            .bind_button_action(id, InputAction::Swing, GamepadButtonType::North)?
-            .bind_button_action(id, InputAction::Swing, GamepadButtonType::LeftTrigger2)?
-            .bind_button_action(id, InputAction::ChangePalette, GamepadButtonType::Select)?
-            .bind_button_action(id, InputAction::Reset, GamepadButtonType::Start)?
-            .bind_button_action(
-                id,
-                InputAction::LockPosition,
-                GamepadButtonType::LeftTrigger,
-            )?
-            .bind_axis_with_deadzone(
-                id,
-                InputAxis::MoveX,
-                AxisBinding::GamepadAxis(GamepadAxisType::LeftStickX),
-                deadzone,
-            )
-            .bind_axis_with_deadzone(
-                id,
-                InputAxis::MoveX,
-                AxisBinding::GamepadAxis(GamepadAxisType::DPadX),
-                deadzone,
-            )
-            .bind_axis_with_deadzone(
-                id,
-                InputAxis::MoveY,
-                AxisBinding::GamepadAxis(GamepadAxisType::LeftStickY),
-                deadzone,
-            )
-            .bind_axis_with_deadzone(
-                id,
-                InputAxis::MoveY,
-                AxisBinding::GamepadAxis(GamepadAxisType::DPadY),
-                deadzone,
-            )
-            .bind_axis_with_deadzone(
-                id,
-                InputAxis::AimX,
-                AxisBinding::GamepadAxis(GamepadAxisType::RightStickX),
-                deadzone,
-            )
-            .bind_axis_with_deadzone(
-                id,
-                InputAxis::AimY,
-                AxisBinding::GamepadAxis(GamepadAxisType::RightStickY),
-                deadzone,
-            );
+    let gamepad_buttons = [
+        (InputAction::Dash, GamepadButtonType::RightTrigger),
+        (InputAction::Dash, GamepadButtonType::RightTrigger2),
+        (InputAction::Swing, GamepadButtonType::South),
+        (InputAction::Swing, GamepadButtonType::West),
+        (InputAction::Swing, GamepadButtonType::East),
+        (InputAction::Jump, GamepadButtonType::North),
+        (InputAction::Swing, GamepadButtonType::LeftTrigger2),
+        (InputAction::ChangePalette, GamepadButtonType::Select),
+        (InputAction::LockPosition, GamepadButtonType::LeftTrigger),
+        (InputAction::Challenge, GamepadButtonType::RightThumb),
+        (InputAction::ShowStats, GamepadButtonType::LeftThumb),
+    ];
 
-        gamepad_map.map_gamepad(id - 1, id);
-    }
+    for id in 1..=2 {
+        for (action, button) in gamepad_buttons.iter() {
+            map.bind_button_action(id, *action, *button)?;
+            prompts.add_gamepad_button(id, *action, *button);
+        }
 
-    map.bind_button_action(1, InputAction::Dash, KeyCode::Space)?
-        .bind_button_action(1, InputAction::Swing, KeyCode::J)?
-        .bind_button_action(1, InputAction::Reset, KeyCode::Escape)?
-        .bind_axis(
-            1,
+        map.bind_axis_with_deadzone(
+            id,
             InputAxis::MoveX,
-            AxisBinding::Buttons(KeyCode::A.into(), KeyCode::D.into()),
+            AxisBinding::GamepadAxis(GamepadAxisType::LeftStickX),
+            deadzone,
         )
-        .bind_axis(
-            1,
-            InputAxis::MoveY,
-            AxisBinding::Buttons(KeyCode::S.into(), KeyCode::W.into()),
-        );
-
-    map.bind_button_action(2, InputAction::Dash, KeyCode::Numpad0)?
-        .bind_button_action(2, InputAction::Swing, KeyCode::NumpadAdd)?
-        .bind_button_action(2, InputAction::ChangePalette, KeyCode::P)?
-        .bind_axis(
-            2,
+        .bind_axis_with_deadzone(
+            id,
             InputAxis::MoveX,
-            AxisBinding::Buttons(KeyCode::Left.into(), KeyCode::Right.into()),
+            AxisBinding::GamepadAxis(GamepadAxisType::DPadX),
+            deadzone,
         )
-        .bind_axis(
-            2,
+        .bind_axis_with_deadzone(
+            id,
             InputAxis::MoveY,
-            AxisBinding::Buttons(KeyCode::Down.into(), KeyCode::Up.into()),
+            AxisBinding::GamepadAxis(GamepadAxisType::LeftStickY),
+            deadzone,
+        )
+        .bind_axis_with_deadzone(
+            id,
+            InputAxis::MoveY,
+            AxisBinding::GamepadAxis(GamepadAxisType::DPadY),
+            deadzone,
+        )
+        .bind_axis_with_deadzone(
+            id,
+            InputAxis::AimX,
+            AxisBinding::GamepadAxis(GamepadAxisType::RightStickX),
+            deadzone,
+        )
+        .bind_axis_with_deadzone(
+            id,
+            InputAxis::AimY,
+            AxisBinding::GamepadAxis(GamepadAxisType::RightStickY),
+            deadzone,
         );
+
+        prompts.add_gamepad_combo(id, InputAction::Reset, &RESET_PAD_COMBO);
+        gamepad_map.map_gamepad(id - 1, id);
+    }
+
+    let keys = [
+        (1, InputAction::Dash, KeyCode::Space),
+        (1, InputAction::Swing, KeyCode::J),
+        (1, InputAction::Jump, KeyCode::K),
+        (1, InputAction::Reset, KeyCode::R),
+        (1, InputAction::SaveReplay, KeyCode::F5),
+        (1, InputAction::PlayReplay, KeyCode::F9),
+        (1, InputAction::Challenge, KeyCode::Q),
+        (1, InputAction::TogglePointStart, KeyCode::F11),
+        (1, InputAction::ToggleFatigue, KeyCode::F12),
+        (1, InputAction::CycleAimSpeed, KeyCode::G),
+        (1, InputAction::CycleAimSmoothing, KeyCode::Y),
+        (1, InputAction::ToggleInstantAim, KeyCode::I),
+        (2, InputAction::Dash, KeyCode::Numpad0),
+        (2, InputAction::Swing, KeyCode::NumpadAdd),
+        (2, InputAction::Jump, KeyCode::NumpadEnter),
+        (2, InputAction::ChangePalette, KeyCode::P),
+        (2, InputAction::Challenge, KeyCode::NumpadSubtract),
+        (2, InputAction::CycleAimSpeed, KeyCode::H),
+        (2, InputAction::CycleAimSmoothing, KeyCode::U),
+        (2, InputAction::ToggleInstantAim, KeyCode::O),
+    ];
+
+    for (id, action, key) in keys.iter() {
+        map.bind_button_action(*id, *action, *key)?;
+        prompts.add_key(*id, *action, *key);
+    }
+
+    map.bind_axis(
+        1,
+        InputAxis::MoveX,
+        AxisBinding::Buttons(KeyCode::A.into(), KeyCode::D.into()),
+    )
+    .bind_axis(
+        1,
+        InputAxis::MoveY,
+        AxisBinding::Buttons(KeyCode::S.into(), KeyCode::W.into()),
+    );
+
+    map.bind_axis(
+        2,
+        InputAxis::MoveX,
+        AxisBinding::Buttons(KeyCode::Left.into(), KeyCode::Right.into()),
+    )
+    .bind_axis(
+        2,
+        InputAxis::MoveY,
+        AxisBinding::Buttons(KeyCode::Down.into(), KeyCode::Up.into()),
+    );
     Ok(())
 }
@@ -0,0 +1,473 @@
+use std::{
+    io::{self, Read, Write},
+    time::Duration,
+};
+
+use bevy::{core::CoreSystem, input::InputSystem, prelude::*, utils::Instant};
+
+use crate::{
+    ball::Ball,
+    checksum::{StateChecksum, SyncCheck},
+    player::Player,
+    replay::{get_frame, read_f32, read_u32, read_u64, read_u8, write_f32s},
+    rng::GameRng,
+    score::{GameOverEvt, Score},
+    storage, GameState,
+};
+
+const RECORDING_DIR: &str = "input_recordings";
+const RECORDING_EXT: &str = "tobi";
+const RECORDING_MAGIC: &[u8; 4] = b"TOBI";
+const RECORDING_VERSION: u8 = 4;
+const SAVE_KEY: KeyCode = KeyCode::F8;
+// delta, keys, both gamepads' buttons and axes and the checksum
+const FRAME_BYTES: usize = 4 + 8 + 2 * (2 + RECORDED_AXES.len() * 4) + 8 + 8;
+
+// every key the game reads, recorded as a u64 bitmask - that fits 64 keys, widen it before adding more
+// the order is part of the file format, so new keys go at the end (and bump the version)
+const RECORDED_KEYS: [KeyCode; 64] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F9,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Space,
+    KeyCode::Return,
+    KeyCode::Escape,
+    KeyCode::Back,
+    KeyCode::Delete,
+    KeyCode::Tab,
+    KeyCode::Minus,
+    KeyCode::Equals,
+    KeyCode::Numpad0,
+    KeyCode::NumpadAdd,
+    KeyCode::NumpadSubtract,
+    KeyCode::NumpadEnter,
+    KeyCode::F11,
+    KeyCode::LShift,
+    KeyCode::RShift,
+    KeyCode::F12,
+];
+
+const RECORDED_BUTTONS: [GamepadButtonType; 12] = [
+    GamepadButtonType::South,
+    GamepadButtonType::East,
+    GamepadButtonType::West,
+    GamepadButtonType::North,
+    GamepadButtonType::LeftTrigger,
+    GamepadButtonType::RightTrigger,
+    GamepadButtonType::LeftTrigger2,
+    GamepadButtonType::RightTrigger2,
+    GamepadButtonType::Select,
+    GamepadButtonType::Start,
+    GamepadButtonType::LeftThumb,
+    GamepadButtonType::RightThumb,
+];
+
+const RECORDED_AXES: [GamepadAxisType; 6] = [
+    GamepadAxisType::LeftStickX,
+    GamepadAxisType::LeftStickY,
+    GamepadAxisType::RightStickX,
+    GamepadAxisType::RightStickY,
+    GamepadAxisType::DPadX,
+    GamepadAxisType::DPadY,
+];
+
+// records the raw keyboard and gamepad input of a match along with the rng seed
+// played back with --input-recording, it re-simulates the match to reproduce bug reports
+// the frame times are recorded too and playback holds every frame until its recorded time is up,
+// bevy 0.6 can't set the clock directly, so a frame slower than the recorded one still drifts
+// and the state checksums tell where a re-simulation drifted from the recorded match
+pub struct InputRecordingPlugin;
+impl Plugin for InputRecordingPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<InputRecorder>()
+            .init_resource::<InputPlayback>()
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(start_input_recording))
+            // both the Time and the ScaledTime deltas come from the time system's update
+            .add_system_to_stage(CoreStage::First, pace_playback.before(CoreSystem::Time))
+            // overrides the device input before the game reads it
+            .add_system_to_stage(CoreStage::PreUpdate, record_inputs.after(InputSystem))
+            .add_system_to_stage(CoreStage::PreUpdate, play_inputs.after(InputSystem))
+            .add_system(save_input_recording);
+    }
+}
+
+// the 2 gamepads mapped to the players
+#[derive(Default, Clone)]
+pub struct GamepadFrame {
+    // bitmask of the RECORDED_BUTTONS
+    pub buttons: u16,
+    pub axes: [f32; RECORDED_AXES.len()],
+}
+
+#[derive(Default, Clone)]
+pub struct InputFrame {
+    pub delta_seconds: f32,
+    // bitmask of the RECORDED_KEYS
+    pub keys: u64,
+    pub gamepads: [GamepadFrame; 2],
+    // state at the start of the frame, before the input gets applied
+    pub checksum: StateChecksum,
+}
+
+#[derive(Default, Clone)]
+pub struct InputRecording {
+    pub seed: u64,
+    pub frames: Vec<InputFrame>,
+}
+
+#[derive(Default)]
+pub struct InputRecorder {
+    pub recording: InputRecording,
+    active: bool,
+}
+
+#[derive(Default)]
+pub struct InputPlayback {
+    pub recording: Option<InputRecording>,
+    frame: usize,
+    started: bool,
+    sync_check: SyncCheck,
+}
+
+impl InputPlayback {
+    pub fn new(recording: InputRecording) -> Self {
+        Self {
+            recording: Some(recording),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.recording.is_some()
+    }
+}
+
+impl InputFrame {
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.delta_seconds.to_le_bytes())?;
+        w.write_all(&self.keys.to_le_bytes())?;
+        for gamepad in self.gamepads.iter() {
+            w.write_all(&gamepad.buttons.to_le_bytes())?;
+            write_f32s(w, &gamepad.axes)?;
+        }
+        w.write_all(&self.checksum.transforms.to_le_bytes())?;
+        w.write_all(&self.checksum.score.unwrap_or_default().to_le_bytes())?;
+
+        Ok(())
+    }
+
+    pub fn read<R: Read>(r: &mut R) -> io::Result<Self> {
+        let delta_seconds = read_f32(r)?;
+        let keys = read_u64(r)?;
+        let mut gamepads: [GamepadFrame; 2] = Default::default();
+        for gamepad in gamepads.iter_mut() {
+            let mut buttons = [0u8; 2];
+            r.read_exact(&mut buttons)?;
+            let mut axes = [0.; RECORDED_AXES.len()];
+            for axis in axes.iter_mut() {
+                *axis = read_f32(r)?;
+            }
+            *gamepad = GamepadFrame {
+                buttons: u16::from_le_bytes(buttons),
+                axes,
+            };
+        }
+        let checksum = StateChecksum {
+            transforms: read_u64(r)?,
+            score: Some(read_u64(r)?),
+        };
+
+        Ok(Self {
+            delta_seconds,
+            keys,
+            gamepads,
+            checksum,
+        })
+    }
+}
+
+impl InputRecording {
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(RECORDING_MAGIC)?;
+        w.write_all(&[RECORDING_VERSION])?;
+        w.write_all(&self.seed.to_le_bytes())?;
+        w.write_all(&(self.frames.len() as u32).to_le_bytes())?;
+
+        for frame in self.frames.iter() {
+            frame.write(w)?;
+        }
+
+        Ok(())
+    }
+
+    // reads from the whole file, so the frame count can be checked against the bytes left
+    pub fn read(r: &mut &[u8]) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != RECORDING_MAGIC || read_u8(r)? != RECORDING_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a supported input recording file",
+            ));
+        }
+
+        let seed = read_u64(r)?;
+        let len = read_u32(r)? as usize;
+        // a broken or crafted file could ask for any length
+        let mut frames = Vec::with_capacity(len.min(r.len() / FRAME_BYTES));
+
+        for _ in 0..len {
+            frames.push(InputFrame::read(r)?);
+        }
+
+        Ok(Self { seed, frames })
+    }
+
+    pub fn save(&self) -> io::Result<String> {
+        let prefix = format!("{}/input_", RECORDING_DIR);
+        let number =
+            storage::get_next_number(&storage::list(RECORDING_DIR), &prefix, RECORDING_EXT);
+        let key = format!("{}{:05}.{}", prefix, number, RECORDING_EXT);
+        let mut data = Vec::new();
+        self.write(&mut data)?;
+        storage::save(&key, &data)?;
+        Ok(key)
+    }
+}
+
+// the rng is reseeded on entering the game as well, so the recording starts from the same state
+fn start_input_recording(
+    mut recorder: ResMut<InputRecorder>,
+    mut playback: ResMut<InputPlayback>,
+    rng: Res<GameRng>,
+) {
+    // a recording only plays once, the resets it contains come from its own input
+    if playback.is_playing() {
+        if !playback.started {
+            playback.started = true;
+            playback.frame = 0;
+            let checksums = playback.recording.as_ref().map_or(Vec::new(), |r| {
+                r.frames.iter().map(|f| f.checksum).collect()
+            });
+            playback.sync_check.start(checksums);
+        }
+        return;
+    }
+
+    recorder.recording = InputRecording {
+        seed: rng.get_seed(),
+        frames: Vec::new(),
+    };
+    recorder.active = true;
+}
+
+// frame time is left out of the checksums
+fn get_checksum(
+    score: &Score,
+    player_q: &Query<(&Player, &GlobalTransform)>,
+    ball_q: &Query<(&Ball, &GlobalTransform)>,
+    bounce_t_q: &Query<&Transform>,
+) -> StateChecksum {
+    StateChecksum::new(&get_frame(0., player_q, ball_q, bounce_t_q), Some(score))
+}
+
+fn record_inputs(
+    mut recorder: ResMut<InputRecorder>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    time: Res<Time>,
+    score: Res<Score>,
+    player_q: Query<(&Player, &GlobalTransform)>,
+    ball_q: Query<(&Ball, &GlobalTransform)>,
+    bounce_t_q: Query<&Transform>,
+) {
+    if !recorder.active {
+        return;
+    }
+
+    let frame = InputFrame {
+        delta_seconds: time.delta_seconds(),
+        keys: RECORDED_KEYS
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| keys.pressed(**key))
+            .fold(0, |mask, (i, _)| mask | (1 << i)),
+        gamepads: [0, 1].map(|id| {
+            let gamepad = Gamepad(id);
+            GamepadFrame {
+                buttons: RECORDED_BUTTONS
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, button)| buttons.pressed(GamepadButton(gamepad, **button)))
+                    .fold(0, |mask, (i, _)| mask | (1 << i)),
+                axes: RECORDED_AXES
+                    .map(|axis| axes.get(GamepadAxis(gamepad, axis)).unwrap_or_default()),
+            }
+        }),
+        checksum: get_checksum(&score, &player_q, &ball_q, &bounce_t_q),
+    };
+
+    recorder.recording.frames.push(frame);
+}
+
+// waits out the recorded frame time, so the clock update right after measures the recorded delta
+fn pace_playback(playback: Res<InputPlayback>, time: Res<Time>) {
+    if !playback.started {
+        return;
+    }
+
+    let frame = playback
+        .recording
+        .as_ref()
+        .and_then(|r| r.frames.get(playback.frame));
+
+    if let (Some(frame), Some(last_update)) = (frame, time.last_update()) {
+        let frame_end = last_update + Duration::from_secs_f32(frame.delta_seconds);
+        while Instant::now() < frame_end {
+            std::hint::spin_loop();
+        }
+    }
+}
+
+// the recorded input replaces whatever the devices sent, so touching them doesn't break the playback
+fn play_inputs(
+    mut playback: ResMut<InputPlayback>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut buttons: ResMut<Input<GamepadButton>>,
+    mut axes: ResMut<Axis<GamepadAxis>>,
+    time: Res<Time>,
+    score: Res<Score>,
+    player_q: Query<(&Player, &GlobalTransform)>,
+    ball_q: Query<(&Ball, &GlobalTransform)>,
+    bounce_t_q: Query<&Transform>,
+) {
+    if !playback.started {
+        return;
+    }
+
+    let frame_i = playback.frame;
+    let frame = match playback
+        .recording
+        .as_ref()
+        .and_then(|r| r.frames.get(frame_i))
+    {
+        Some(frame) => frame.clone(),
+        None => {
+            match playback.sync_check.diverged_at() {
+                Some(diverged_at) => {
+                    info!("Input playback done, diverged at frame {}", diverged_at)
+                }
+                None => info!("Input playback done, the match was reproduced"),
+            }
+            for key in RECORDED_KEYS.iter() {
+                keys.release(*key);
+            }
+            *playback = InputPlayback::default();
+            return;
+        }
+    };
+
+    let checksum = get_checksum(&score, &player_q, &ball_q, &bounce_t_q);
+    playback.sync_check.check(frame_i, checksum);
+
+    if (time.delta_seconds() - frame.delta_seconds).abs() > f32::EPSILON {
+        trace!(
+            "Input playback frame {} took {}s instead of {}s",
+            frame_i,
+            time.delta_seconds(),
+            frame.delta_seconds
+        );
+    }
+
+    for (i, key) in RECORDED_KEYS.iter().enumerate() {
+        set_pressed(&mut keys, *key, frame.keys & (1 << i) != 0);
+    }
+
+    for (id, gamepad_frame) in frame.gamepads.iter().enumerate() {
+        let gamepad = Gamepad(id);
+        for (i, button) in RECORDED_BUTTONS.iter().enumerate() {
+            set_pressed(
+                &mut buttons,
+                GamepadButton(gamepad, *button),
+                gamepad_frame.buttons & (1 << i) != 0,
+            );
+        }
+        for (axis, value) in RECORDED_AXES.iter().zip(gamepad_frame.axes.iter()) {
+            axes.set(GamepadAxis(gamepad, *axis), *value);
+        }
+    }
+
+    playback.frame += 1;
+}
+
+fn set_pressed<T: Copy + Eq + std::hash::Hash>(input: &mut Input<T>, button: T, pressed: bool) {
+    if pressed && !input.pressed(button) {
+        input.press(button);
+    } else if !pressed && input.pressed(button) {
+        input.release(button);
+    }
+}
+
+// at the end of a match or whenever something looks off, the file then goes with the bug report
+fn save_input_recording(
+    keys: Res<Input<KeyCode>>,
+    mut ev_r_game_over: EventReader<GameOverEvt>,
+    recorder: Res<InputRecorder>,
+    playback: Res<InputPlayback>,
+) {
+    let game_over = ev_r_game_over.iter().count() > 0;
+    if !recorder.active || playback.is_playing() || !(game_over || keys.just_pressed(SAVE_KEY)) {
+        return;
+    }
+
+    match recorder.recording.save() {
+        Ok(key) => info!("Input recording saved to {}", key),
+        Err(e) => warn!("Failed to save the input recording: {}", e),
+    }
+}
@@ -0,0 +1,226 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_tweening::{lens::*, *};
+
+use crate::{
+    camera::{MainCamera, ScriptedCamera},
+    input_binding::{InputAction, PlayerInput},
+    level::{Net, NetOffset},
+    locale::Locale,
+    mode::GameMode,
+    palette::PaletteColor,
+    profile::Profiles,
+    reset::DespawnOnExit,
+    ui_text::{get_text_style, ScaledText, TextSettings},
+    GameSetupPhase, GameState,
+};
+
+const CAMERA_SWEEP_X: f32 = 450.;
+const CAMERA_SWEEP_SEC: f32 = 1.2;
+const NET_INTRO_OFFSET: f32 = 160.;
+const NET_DROP_DELAY_MS: u64 = 700;
+const NET_DROP_MS: u64 = 500;
+// the first landing of the bounce ease
+const NET_THUNK_SEC: f32 = 0.9;
+const COUNTDOWN_START_SEC: f32 = 1.5;
+const COUNTDOWN_STEP_SEC: f32 = 0.5;
+const COUNTDOWN_FROM: u32 = 3;
+
+// camera sweep, player names sliding in, the net dropping to the center and a countdown
+// the game state stays underneath, so the match is already set up
+pub struct IntroPlugin;
+impl Plugin for IntroPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Game)
+                .with_system(start_intro.after(GameSetupPhase::Player)),
+        )
+        .add_system_set(SystemSet::on_enter(GameState::PreMatch).with_system(setup))
+        .add_system_set(SystemSet::on_update(GameState::PreMatch).with_system(play_intro))
+        .add_system_set(SystemSet::on_exit(GameState::PreMatch).with_system(finish_intro));
+    }
+}
+
+#[derive(Default)]
+struct IntroSequence {
+    elapsed_sec: f32,
+    thunk_played: bool,
+}
+
+#[derive(Component)]
+struct CountdownText;
+
+fn start_intro(mode: Res<GameMode>, mut state: ResMut<State<GameState>>) {
+    if *mode == GameMode::Match {
+        state.push(GameState::PreMatch).unwrap();
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    text_settings: Res<TextSettings>,
+    locale: Res<Locale>,
+    profiles: Res<Profiles>,
+    mut cam_q: Query<(Entity, &mut Transform), With<MainCamera>>,
+    mut net_q: Query<(Entity, &mut Transform), (With<Net>, Without<MainCamera>)>,
+) {
+    commands.insert_resource(IntroSequence::default());
+
+    for (cam_e, mut cam_t) in cam_q.iter_mut() {
+        cam_t.translation.x = -CAMERA_SWEEP_X;
+        commands.entity(cam_e).insert(ScriptedCamera);
+    }
+
+    if let Ok((net_e, mut net_t)) = net_q.get_single_mut() {
+        let center = net_t.translation;
+        net_t.translation.x = center.x + NET_INTRO_OFFSET;
+        commands.entity(net_e).insert(Animator::new(
+            Delay::new(Duration::from_millis(NET_DROP_DELAY_MS)).then(Tween::new(
+                EaseFunction::BounceOut,
+                TweeningType::Once,
+                Duration::from_millis(NET_DROP_MS),
+                TransformPositionLens {
+                    start: net_t.translation,
+                    end: center,
+                },
+            )),
+        ));
+    }
+
+    // player names slide in from their sides
+    for (player_id, col, is_left) in [
+        (1, PaletteColor::PlayerLeft, true),
+        (2, PaletteColor::PlayerRight, false),
+    ] {
+        let get_rect = |x: f32| {
+            let x = Val::Px(x);
+            Rect {
+                top: Val::Px(120.),
+                left: if is_left { x } else { Val::Undefined },
+                right: if is_left { Val::Undefined } else { x },
+                ..Default::default()
+            }
+        };
+
+        commands
+            .spawn_bundle(TextBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: get_rect(-400.),
+                    ..Default::default()
+                },
+                text: Text::with_section(
+                    profiles.get_player_name(player_id, &locale),
+                    get_text_style(&asset_server, &text_settings, 70.),
+                    TextAlignment::default(),
+                ),
+                ..Default::default()
+            })
+            .insert(col)
+            .insert(ScaledText(70.))
+            .insert(Animator::new(Tween::new(
+                EaseFunction::BackOut,
+                TweeningType::Once,
+                Duration::from_millis(600),
+                UiPositionLens {
+                    start: get_rect(-400.),
+                    end: get_rect(80.),
+                },
+            )))
+            .insert(Name::new("IntroPlayerName"))
+            .insert(DespawnOnExit(GameState::PreMatch));
+    }
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::Center,
+                margin: Rect::all(Val::Auto),
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                get_text_style(&asset_server, &text_settings, 140.),
+                TextAlignment {
+                    horizontal: HorizontalAlign::Center,
+                    ..Default::default()
+                },
+            ),
+            ..Default::default()
+        })
+        .insert(PaletteColor::Text)
+        .insert(ScaledText(140.))
+        .insert(CountdownText)
+        .insert(Name::new("IntroCountdown"))
+        .insert(DespawnOnExit(GameState::PreMatch));
+}
+
+fn play_intro(
+    mut intro: ResMut<IntroSequence>,
+    mut state: ResMut<State<GameState>>,
+    mut input: ResMut<PlayerInput>,
+    mut cam_q: Query<&mut Transform, With<MainCamera>>,
+    mut countdown_q: Query<&mut Text, With<CountdownText>>,
+    #[cfg(feature = "audio")] audio: Res<Audio>,
+    #[cfg(feature = "audio")] asset_server: Res<AssetServer>,
+    time: Res<Time>,
+) {
+    intro.elapsed_sec += time.delta_seconds();
+
+    // any swing skips the intro
+    for id in 1..=2 {
+        if input.just_pressed(id, InputAction::Swing) {
+            input.use_button_action(id, InputAction::Swing);
+            state.pop().unwrap();
+            return;
+        }
+    }
+
+    let sweep_t = (intro.elapsed_sec / CAMERA_SWEEP_SEC).clamp(0., 1.);
+    let sweep_t = sweep_t * sweep_t * (3. - 2. * sweep_t);
+    for mut cam_t in cam_q.iter_mut() {
+        cam_t.translation.x = -CAMERA_SWEEP_X * (1. - sweep_t);
+    }
+
+    if !intro.thunk_played && intro.elapsed_sec >= NET_THUNK_SEC {
+        intro.thunk_played = true;
+        #[cfg(feature = "audio")]
+        audio.play(asset_server.load("sfx/net_thunk.wav"));
+    }
+
+    if intro.elapsed_sec >= COUNTDOWN_START_SEC {
+        let step = ((intro.elapsed_sec - COUNTDOWN_START_SEC) / COUNTDOWN_STEP_SEC) as u32;
+        if step >= COUNTDOWN_FROM {
+            state.pop().unwrap();
+            return;
+        }
+
+        let value = (COUNTDOWN_FROM - step).to_string();
+        for mut text in countdown_q.iter_mut() {
+            if text.sections[0].value != value {
+                text.sections[0].value = value.clone();
+            }
+        }
+    }
+}
+
+// also runs when skipped, so the scripted parts have to be snapped to their end
+fn finish_intro(
+    mut commands: Commands,
+    cam_q: Query<Entity, With<MainCamera>>,
+    mut net_q: Query<(Entity, &mut Transform), With<Net>>,
+    net: Res<NetOffset>,
+) {
+    commands.remove_resource::<IntroSequence>();
+
+    for cam_e in cam_q.iter() {
+        commands.entity(cam_e).remove::<ScriptedCamera>();
+    }
+
+    if let Ok((net_e, mut net_t)) = net_q.get_single_mut() {
+        commands.entity(net_e).remove::<Animator<Transform>>();
+        net_t.translation.x = net.0;
+    }
+}
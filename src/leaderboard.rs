@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use crate::{
+    locale::Locale,
+    palette::PaletteColor,
+    profile::Profiles,
+    score::Score,
+    ui_text::{get_text_style, ScaledText, TextSettings},
+};
+
+const ELO_K: f32 = 32.;
+const LEADERBOARD_LEN: usize = 10;
+
+pub struct LeaderboardPlugin;
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup)
+            .add_system(toggle_leaderboard)
+            .add_system(update_leaderboard_ui);
+    }
+}
+
+#[derive(Component)]
+struct LeaderboardText;
+
+pub fn get_rating_change(rating: f32, other_rating: f32, won: bool) -> f32 {
+    let expected = 1. / (1. + 10f32.powf((other_rating - rating) / 400.));
+    let result = if won { 1. } else { 0. };
+    ELO_K * (result - expected)
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, text_settings: Res<TextSettings>) {
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::Center,
+                margin: Rect::all(Val::Auto),
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                get_text_style(&asset_server, &text_settings, 32.),
+                TextAlignment {
+                    horizontal: HorizontalAlign::Center,
+                    ..Default::default()
+                },
+            ),
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(PaletteColor::Text)
+        .insert(ScaledText(32.))
+        .insert(LeaderboardText)
+        .insert(Name::new("LeaderboardText"));
+}
+
+// only between matches, so it doesn't cover the court
+fn toggle_leaderboard(
+    keys: Res<Input<KeyCode>>,
+    score: Res<Score>,
+    mut text_q: Query<&mut Visibility, With<LeaderboardText>>,
+) {
+    for mut visibility in text_q.iter_mut() {
+        if !score.is_match_start() {
+            if visibility.is_visible {
+                visibility.is_visible = false;
+            }
+        } else if keys.just_pressed(KeyCode::B) {
+            visibility.is_visible = !visibility.is_visible;
+        }
+    }
+}
+
+fn update_leaderboard_ui(
+    profiles: Res<Profiles>,
+    locale: Res<Locale>,
+    mut text_q: Query<&mut Text, With<LeaderboardText>>,
+) {
+    if !profiles.is_changed() && !locale.is_changed() {
+        return;
+    }
+
+    let mut lines = vec![locale.get("leaderboard")];
+    lines.extend(
+        profiles
+            .get_ranked()
+            .iter()
+            .take(LEADERBOARD_LEN)
+            .enumerate()
+            .map(|(i, profile)| {
+                let form: String = profile
+                    .stats
+                    .recent_form
+                    .iter()
+                    .map(|won| locale.get(if *won { "form-win" } else { "form-loss" }))
+                    .collect();
+                format!(
+                    "{}. {}  {:.0}  {}",
+                    i + 1,
+                    profile.name,
+                    profile.stats.rating,
+                    form
+                )
+            }),
+    );
+
+    for mut text in text_q.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}
@@ -1,37 +1,95 @@
 use crate::{
+    asset::SpriteAtlas,
+    camera::TargetCamScale,
     extra::TransformBundle,
-    palette::PaletteColor,
+    palette::{Palette, PaletteColor},
     physics::PhysLayer,
     render::{COURT_LINE_Z, COURT_Z, NET_Z, SHADOW_Z},
-    reset::Persistent,
-    score::Score,
-    GameState, WIN_HEIGHT, WIN_WIDTH,
+    reset::DespawnOnExit,
+    rng::GameRng,
+    score::{MatchRules, Score},
+    GameSetupPhase, GameState, WIN_HEIGHT, WIN_WIDTH,
 };
 use bevy::{
     math::Vec2,
     prelude::*,
     sprite::{Sprite, SpriteBundle},
 };
+#[cfg(feature = "inspector")]
 use bevy_inspector_egui::Inspectable;
 use bevy_prototype_lyon::prelude::*;
 use bevy_tweening::{lens::TransformPositionLens, Animator, EaseFunction, Tween, TweeningType};
 use heron::*;
 use rand::*;
-use std::{ops::RangeInclusive, time::Duration};
+use std::{collections::VecDeque, ops::RangeInclusive, time::Duration};
+
+const NET_OFFSET_LABEL: &str = "net_offset";
+// how far the net gets tugged by a game lead
+const NET_OFFSET_PER_GAME: f32 = 50.;
+// portion of the loser's half covered by the net after the match
+const MATCH_WON_NET_SIDE: f32 = 0.85;
+const REGION_HIGHLIGHT_ALPHA: f32 = 0.2;
+// tint of the court strip captured by the net, at the set threshold
+const CAPTURE_TINT_MAX_ALPHA: f32 = 0.12;
 
 pub struct LevelPlugin;
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.insert_resource(NetOffset(0.))
-            .add_startup_system(setup)
+            .init_resource::<CourtSpec>()
+            .init_resource::<CourtSettings>()
+            .init_resource::<BuiltCourtSpec>()
+            .init_resource::<HighlightedRegion>()
+            .init_resource::<NetTugTween>()
+            // rebuilt for every game, as the court dimensions depend on the mode
+            .add_system_set(
+                SystemSet::on_enter(GameState::Game)
+                    .with_system(setup.label(GameSetupPhase::Level)),
+            )
+            // spectators only get the court, the match itself is streamed
+            .add_system_set(SystemSet::on_enter(GameState::Spectate).with_system(setup))
             .add_system(draw_court)
-            .add_system_set(SystemSet::on_update(GameState::Game).with_system(handle_net_offset));
+            .add_system(highlight_region)
+            .add_system(tint_captured_court)
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(rebuild_court.after(NET_OFFSET_LABEL))
+                    .with_system(handle_net_offset.label(NET_OFFSET_LABEL))
+                    .with_system(step_net_tug.after(NET_OFFSET_LABEL)),
+            );
     }
 }
 
 #[derive(Component)]
 pub struct Net;
 
+// offsets the net still has to travel to, played one after another
+#[derive(Component, Default)]
+struct NetTug {
+    targets: VecDeque<f32>,
+    step_sec_left: f32,
+}
+
+// how the net travels to a new offset
+pub struct NetTugTween {
+    pub ease: EaseFunction,
+    pub step_ms: u64,
+    // longer moves are split into steps of at most this
+    pub max_step: f32,
+}
+
+impl Default for NetTugTween {
+    fn default() -> Self {
+        Self {
+            ease: EaseFunction::QuadraticInOut,
+            step_ms: 400,
+            max_step: NET_OFFSET_PER_GAME * 2.,
+        }
+    }
+}
+
+#[derive(Default)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct NetOffset(pub f32);
 
 #[derive(Component)]
@@ -42,6 +100,88 @@ pub struct InitialRegion(pub CourtRegion);
 
 pub struct ServingRegion(pub CourtRegion);
 
+// tinted region, e.g. the serve target
+#[derive(Default)]
+pub struct HighlightedRegion(pub Option<CourtRegion>);
+
+#[derive(Component)]
+struct RegionHighlight;
+
+// strip between the center and the net, in the color of the side that tugged it
+#[derive(Component)]
+struct CaptureTint;
+
+// court geometry, the level is rebuilt whenever it changes
+#[derive(Clone, PartialEq)]
+pub struct CourtSpec {
+    // baseline to baseline
+    pub width: f32,
+    pub height: f32,
+    pub line_thickness: f32,
+    pub corner_radius: f32,
+    // sidelines move apart for every extra player on a side (doubles and up), 0 keeps the court as is
+    pub players_per_side: usize,
+    pub height_per_extra_player: f32,
+}
+
+impl Default for CourtSpec {
+    fn default() -> Self {
+        Self {
+            width: WIN_WIDTH - 600.,
+            height: WIN_HEIGHT - 250.,
+            line_thickness: 12.,
+            corner_radius: 20.,
+            players_per_side: 1,
+            height_per_extra_player: 0.35,
+        }
+    }
+}
+
+impl CourtSpec {
+    // the height scaled by the player count
+    pub fn get_height(&self) -> f32 {
+        let extra_players = self.players_per_side.saturating_sub(1) as f32;
+        self.height * (1. + extra_players * self.height_per_extra_player)
+    }
+
+    // how much the base view has to grow to fit a court bigger than the default one
+    pub fn get_view_scale(&self) -> f32 {
+        let default = CourtSpec::default();
+        (self.width / default.width)
+            .max(self.get_height() / default.height)
+            .max(1.)
+    }
+
+    pub fn get_settings(&self) -> CourtSettings {
+        let x = self.width / 2.;
+        let y = self.get_height() / 2.;
+        let half_thickness = self.line_thickness / 2.;
+
+        CourtSettings {
+            left: -x,
+            right: x,
+            top: y,
+            bottom: -y,
+            // regions reach half way into the lines
+            base_region_size: Vec3::new((x + half_thickness) / 2., (y + half_thickness) / 2., 0.),
+            region_x: (x + half_thickness) / 2.,
+            region_y: (y + half_thickness) / 2.,
+            net_extent: y,
+            corner_radius: self.corner_radius,
+        }
+    }
+}
+
+#[derive(Default)]
+struct BuiltCourtSpec(Option<CourtSpec>);
+
+// marks everything that makes up the level, so it can be rebuilt
+#[derive(Component)]
+pub struct CourtPart;
+
+// derived from the CourtSpec
+#[derive(Default)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct CourtSettings {
     // nice2have: replace by proper bounds
     pub left: f32,
@@ -50,11 +190,15 @@ pub struct CourtSettings {
     pub bottom: f32,
     pub base_region_size: Vec3,
     pub region_x: f32,
+    pub region_y: f32,
+    // net spans from -net_extent to net_extent, shots passing outside of it go around the posts
+    pub net_extent: f32,
+    pub corner_radius: f32,
 }
 
-#[derive(Default, Component, Inspectable, Clone, Copy, Debug, PartialEq)]
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub enum CourtRegion {
-    #[default]
     OutOfBounds,
     TopLeft,
     TopRight,
@@ -62,6 +206,12 @@ pub enum CourtRegion {
     BottomRight,
 }
 
+impl Default for CourtRegion {
+    fn default() -> Self {
+        CourtRegion::OutOfBounds
+    }
+}
+
 #[allow(dead_code)]
 impl CourtRegion {
     pub fn is_left(&self) -> bool {
@@ -102,20 +252,39 @@ impl CourtRegion {
         }
     }
 
-    pub fn get_random() -> Self {
-        Self::get_random_from_range(0..=3)
+    // deuce side is the server's right hand side when facing the net
+    pub fn get_serve_region(player_id: usize, deuce_side: bool) -> Self {
+        match (player_id == 1, deuce_side) {
+            (true, true) => CourtRegion::BottomLeft,
+            (true, false) => CourtRegion::TopLeft,
+            (false, true) => CourtRegion::TopRight,
+            (false, false) => CourtRegion::BottomRight,
+        }
+    }
+
+    // quadrant split by the net and the horizontal line
+    pub fn get_court_quadrant(pos: Vec2, net_x: f32) -> Self {
+        match (pos.x < net_x, pos.y < 0.) {
+            (true, true) => CourtRegion::BottomLeft,
+            (true, false) => CourtRegion::TopLeft,
+            (false, true) => CourtRegion::BottomRight,
+            (false, false) => CourtRegion::TopRight,
+        }
     }
 
-    pub fn get_random_left() -> Self {
-        Self::get_random_from_range(0..=1)
+    pub fn get_random(rng: &mut GameRng) -> Self {
+        Self::get_random_from_range(0..=3, rng)
     }
 
-    pub fn get_random_right() -> Self {
-        Self::get_random_from_range(2..=3)
+    pub fn get_random_left(rng: &mut GameRng) -> Self {
+        Self::get_random_from_range(0..=1, rng)
     }
 
-    pub fn get_random_from_range(range: RangeInclusive<usize>) -> Self {
-        let mut rng = rand::thread_rng();
+    pub fn get_random_right(rng: &mut GameRng) -> Self {
+        Self::get_random_from_range(2..=3, rng)
+    }
+
+    pub fn get_random_from_range(range: RangeInclusive<usize>, rng: &mut GameRng) -> Self {
         [
             CourtRegion::TopLeft,
             CourtRegion::BottomLeft,
@@ -125,24 +294,73 @@ impl CourtRegion {
     }
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let x = WIN_WIDTH / 2. - 300.;
-    let height = WIN_HEIGHT - 250.;
-    let y = height / 2.;
-    let thickness = 12.;
-    let width = x * 2. + thickness;
-    let region_x = x / 2. + thickness / 4.;
-    let region_y = y / 2. + thickness / 4.;
-    let region_size = Vec3::new(width / 4., height / 4. + thickness / 4., 0.);
-
-    let settings = CourtSettings {
-        left: -x,
-        right: x,
-        top: y,
-        bottom: -y,
-        base_region_size: region_size,
-        region_x,
-    };
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    atlas: Res<SpriteAtlas>,
+    spec: Res<CourtSpec>,
+    mut offset: ResMut<NetOffset>,
+    mut settings: ResMut<CourtSettings>,
+    mut built_spec: ResMut<BuiltCourtSpec>,
+) {
+    // every game starts with a centered net
+    offset.0 = 0.;
+    build_court(
+        &mut commands,
+        &asset_server,
+        &atlas,
+        &spec,
+        offset.0,
+        &mut settings,
+    );
+    built_spec.0 = Some(spec.clone());
+}
+
+// regenerates the level when the spec changes during a game
+fn rebuild_court(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    atlas: Res<SpriteAtlas>,
+    spec: Res<CourtSpec>,
+    offset: Res<NetOffset>,
+    mut settings: ResMut<CourtSettings>,
+    mut built_spec: ResMut<BuiltCourtSpec>,
+    part_q: Query<Entity, With<CourtPart>>,
+) {
+    if built_spec.0.as_ref() == Some(&*spec) {
+        return;
+    }
+
+    trace!("rebuilding court");
+    for e in part_q.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+
+    build_court(
+        &mut commands,
+        &asset_server,
+        &atlas,
+        &spec,
+        offset.0,
+        &mut settings,
+    );
+    built_spec.0 = Some(spec.clone());
+}
+
+fn build_court(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    atlas: &SpriteAtlas,
+    spec: &CourtSpec,
+    net_offset: f32,
+    settings: &mut CourtSettings,
+) {
+    *settings = spec.get_settings();
+    commands.insert_resource(TargetCamScale(spec.get_view_scale()));
+    let x = spec.width / 2.;
+    let y = spec.get_height() / 2.;
+    let thickness = spec.line_thickness;
+    let width = spec.width + thickness;
 
     let lines = [
         // horizonal split
@@ -161,25 +379,17 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
             })
             .insert(PaletteColor::CourtLines)
             .insert(Name::new("LevelLine"))
-            .insert(Persistent);
+            .insert(CourtPart)
+            .insert(DespawnOnExit(GameState::Game));
     }
 
-    let colliders = [
-        (-region_x, region_y, CourtRegion::TopLeft),
-        (-region_x, -region_y, CourtRegion::BottomLeft),
-        (region_x, region_y, CourtRegion::TopRight),
-        (region_x, -region_y, CourtRegion::BottomRight),
-    ];
-
-    for (x, y, region) in colliders.iter() {
-        spawn_region(&mut commands, *region, *x, *y, region_size);
-    }
+    spawn_regions(commands, settings, net_offset);
 
     // net
-    let net_size = Vec2::new(thickness * 0.8, height);
+    let net_size = Vec2::new(thickness * 0.8, spec.get_height());
     commands
         .spawn_bundle(SpriteBundle {
-            transform: Transform::from_xyz(0., 5., NET_Z),
+            transform: Transform::from_xyz(net_offset, 5., NET_Z),
             sprite: Sprite {
                 custom_size: Some(net_size),
                 ..Default::default()
@@ -188,19 +398,19 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         })
         .insert(PaletteColor::CourtLines)
         .insert(Net)
+        .insert(NetTug::default())
         .insert(Name::new("Net"))
-        .insert(Persistent)
+        .insert(CourtPart)
+        .insert(DespawnOnExit(GameState::Game))
         .with_children(|b| {
             // shadow
-            b.spawn_bundle(SpriteBundle {
-                texture: asset_server.load("art-ish/net_post.png"),
-                sprite: Sprite {
-                    custom_size: Some(net_size),
-                    ..Default::default()
-                },
+            b.spawn_bundle(SpriteSheetBundle {
+                texture_atlas: atlas.handle.clone(),
+                sprite: atlas.get_sprite("art-ish/net_post.png"),
                 transform: Transform {
                     translation: Vec3::new(-7., -3., -NET_Z + SHADOW_Z),
-                    scale: Vec3::new(1., 0.97, 1.),
+                    scale: atlas.get_scale("art-ish/net_post.png", net_size)
+                        * Vec3::new(1., 0.97, 1.),
                     ..Default::default()
                 },
                 ..Default::default()
@@ -211,26 +421,22 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
             let post_offset = 11.;
             for (y, z_offset) in [(y + post_offset, -0.1), (-y + post_offset, 0.1)].iter() {
                 let z = NET_Z + z_offset;
-                b.spawn_bundle(SpriteBundle {
-                    texture: asset_server.load("art-ish/net_post.png"),
+                b.spawn_bundle(SpriteSheetBundle {
+                    texture_atlas: atlas.handle.clone(),
+                    sprite: atlas.get_sprite("art-ish/net_post.png"),
                     transform: Transform::from_xyz(0., *y, z),
-                    sprite: Sprite {
-                        ..Default::default()
-                    },
                     ..Default::default()
                 })
                 .insert(PaletteColor::CourtPost)
                 .with_children(|b| {
-                    b.spawn_bundle(SpriteBundle {
-                        texture: asset_server.load("art-ish/net_post.png"),
+                    b.spawn_bundle(SpriteSheetBundle {
+                        texture_atlas: atlas.handle.clone(),
+                        sprite: atlas.get_sprite("art-ish/net_post.png"),
                         transform: Transform {
                             scale: Vec3::new(1.0, 0.5, 1.),
                             translation: Vec3::new(-3., -17., -z + SHADOW_Z),
                             ..Default::default()
                         },
-                        sprite: Sprite {
-                            ..Default::default()
-                        },
                         ..Default::default()
                     })
                     .insert(PaletteColor::Shadow);
@@ -245,7 +451,24 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
             Transform::from_xyz(0., 0., COURT_Z),
         ))
         .insert(Court)
-        .insert(Persistent);
+        .insert(CourtPart)
+        .insert(DespawnOnExit(GameState::Game));
+
+    // under the region highlights, follows the net
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform: Transform::from_xyz(0., 0., COURT_Z + (COURT_LINE_Z - COURT_Z) / 4.),
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(0., spec.get_height())),
+                ..Default::default()
+            },
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(CaptureTint)
+        .insert(Name::new("CaptureTint"))
+        .insert(CourtPart)
+        .insert(DespawnOnExit(GameState::Game));
 
     // dashed tug lines
     let dash_line_x = x / 2.;
@@ -260,7 +483,8 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                 ..Default::default()
             })
             .insert(PaletteColor::CourtPost)
-            .insert(Persistent);
+            .insert(CourtPart)
+            .insert(DespawnOnExit(GameState::Game));
     }
 
     // cheeky bg - maybe just set for camera?
@@ -273,17 +497,16 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
             ..Default::default()
         })
         .insert(PaletteColor::Background)
-        .insert(Persistent);
-
-    commands.insert_resource(settings);
+        .insert(CourtPart)
+        .insert(DespawnOnExit(GameState::Game));
 }
 
-fn draw_court(mut court_q: Query<&mut Path, With<Court>>, court: Res<CourtSettings>) {
-    if court.is_added() || court.is_changed() {
-        for mut path in court_q.iter_mut() {
+fn draw_court(mut court_q: Query<(&mut Path, ChangeTrackers<Court>)>, court: Res<CourtSettings>) {
+    for (mut path, court_tracker) in court_q.iter_mut() {
+        if court.is_changed() || court_tracker.is_added() {
             trace!("drawing court");
             let mut path_builder = PathBuilder::new();
-            let radius = 20.;
+            let radius = court.corner_radius;
             let top_l = Vec2::new(court.left, court.top);
             let top_r = Vec2::new(court.right, court.top);
             let btm_l = Vec2::new(court.left, court.bottom);
@@ -312,55 +535,228 @@ fn spawn_region(commands: &mut Commands, region: CourtRegion, x: f32, y: f32, re
             half_extends: region_size,
             border_radius: None,
         })
-        .insert(CollisionLayers::all::<PhysLayer>())
+        .insert(PhysLayer::Region.get_layers())
         .insert(region)
         .insert(Name::new("Region"))
-        .insert(Persistent);
+        .insert(CourtPart)
+        .insert(DespawnOnExit(GameState::Game))
+        .with_children(|b| {
+            // under the lines
+            b.spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(region_size.truncate() * 2.),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0., 0., (COURT_LINE_Z - COURT_Z) / 2.),
+                visibility: Visibility { is_visible: false },
+                ..Default::default()
+            })
+            .insert(RegionHighlight);
+        });
+}
+
+// center and half size
+// the net offset grows the losing side's regions
+pub fn get_region_bounds(
+    region: CourtRegion,
+    settings: &CourtSettings,
+    net_offset: f32,
+) -> (Vec2, Vec2) {
+    let side_mult = if region.is_left() { -1. } else { 1. };
+    let x = settings.region_x * side_mult + net_offset / 2.;
+    let y = if region.is_top() {
+        settings.region_y
+    } else {
+        -settings.region_y
+    };
+    let mut half_size = settings.base_region_size.truncate();
+    half_size.x -= (net_offset / 2.) * side_mult;
+    (Vec2::new(x, y), half_size)
+}
+
+// the net and the horizontal line split the court into quadrants
+fn spawn_regions(commands: &mut Commands, settings: &CourtSettings, net_offset: f32) {
+    let regions = [
+        CourtRegion::TopLeft,
+        CourtRegion::BottomLeft,
+        CourtRegion::TopRight,
+        CourtRegion::BottomRight,
+    ];
+
+    for region in regions.iter() {
+        let (pos, half_size) = get_region_bounds(*region, settings, net_offset);
+        spawn_region(commands, *region, pos.x, pos.y, half_size.extend(0.));
+    }
+}
+
+// regions get respawned with the net offset, so this just runs every frame
+fn highlight_region(
+    highlighted: Res<HighlightedRegion>,
+    palette: Res<Palette>,
+    region_q: Query<&CourtRegion>,
+    mut highlight_q: Query<(&Parent, &mut Visibility, &mut Sprite), With<RegionHighlight>>,
+) {
+    let mut color = palette.get_color(&PaletteColor::PlayerAim);
+    color.set_a(REGION_HIGHLIGHT_ALPHA);
+
+    for (parent, mut visibility, mut sprite) in highlight_q.iter_mut() {
+        let is_visible = region_q
+            .get(parent.0)
+            .map_or(false, |region| highlighted.0 == Some(*region));
+        if visibility.is_visible != is_visible {
+            visibility.is_visible = is_visible;
+        }
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
+
+// follows the net transform instead of the offset, so the strip grows with the tug tween
+fn tint_captured_court(
+    palette: Res<Palette>,
+    rules: Res<MatchRules>,
+    net_q: Query<&Transform, (With<Net>, Without<CaptureTint>)>,
+    mut tint_q: Query<(&mut Transform, &mut Visibility, &mut Sprite), With<CaptureTint>>,
+) {
+    let net_x = match net_q.get_single() {
+        Ok(net_t) => net_t.translation.x,
+        Err(_) => return,
+    };
+
+    for (mut tint_t, mut visibility, mut sprite) in tint_q.iter_mut() {
+        let is_visible = net_x.abs() > f32::EPSILON;
+        if visibility.is_visible != is_visible {
+            visibility.is_visible = is_visible;
+        }
+        if !is_visible {
+            continue;
+        }
+
+        // the net moves away from the side that's winning
+        let mut color = palette.get_color(if net_x > 0. {
+            &PaletteColor::PlayerLeft
+        } else {
+            &PaletteColor::PlayerRight
+        });
+        let threshold = rules.games_per_set as f32 * NET_OFFSET_PER_GAME;
+        color.set_a((net_x.abs() / threshold).min(1.) * CAPTURE_TINT_MAX_ALPHA);
+        sprite.color = color;
+
+        tint_t.translation.x = net_x / 2.;
+        if let Some(size) = sprite.custom_size.as_mut() {
+            size.x = net_x.abs();
+        }
+    }
 }
 
 fn handle_net_offset(
     mut commands: Commands,
     score: Res<Score>,
     mut offset: ResMut<NetOffset>,
-    net_q: Query<(Entity, &Transform), With<Net>>,
-    mut region_q: Query<(Entity, &CourtRegion, &mut Transform, &mut CollisionShape), Without<Net>>,
+    mut net_q: Query<&mut NetTug>,
+    region_q: Query<Entity, With<CourtRegion>>,
     settings: Res<CourtSettings>,
+    rules: Res<MatchRules>,
+    spec: Res<CourtSpec>,
+    built_spec: Res<BuiltCourtSpec>,
+    mut sets_played: Local<usize>,
 ) {
     if score.is_changed() {
-        let offset_mult = -50.;
+        let offset_mult = -NET_OFFSET_PER_GAME;
         offset.0 = (score.right_player.games as f32 - score.left_player.games as f32) * offset_mult;
 
+        if score.is_tiebreak(&rules) {
+            // games are level, so tug by the tiebreak points instead, but only gently
+            offset.0 = (score.right_player.points as f32 - score.left_player.points as f32)
+                * offset_mult
+                * rules.tiebreak_net_offset_mult;
+        }
+
         if cfg!(feature = "debug") {
             offset.0 =
                 (score.right_player.points as f32 - score.left_player.points as f32) * offset_mult;
         }
 
-        // tween net
-        if let Ok((net_e, net_t)) = net_q.get_single() {
-            commands.entity(net_e).insert(Animator::new(Tween::new(
-                EaseFunction::QuadraticInOut,
-                TweeningType::Once,
-                Duration::from_millis(400),
-                TransformPositionLens {
-                    start: net_t.translation,
-                    end: Vec3::new(offset.0, net_t.translation.y, net_t.translation.z),
-                },
-            )));
+        // aces and full charge winners tug a bit further until the game is won
+        offset.0 += score.net_bonus * NET_OFFSET_PER_GAME;
+
+        // the games reset once a set is won, so the winning tug gets shown before the net returns
+        // several points can land in one frame, so the net never travels past the set threshold
+        let threshold = rules.games_per_set as f32 * NET_OFFSET_PER_GAME;
+        if let Ok(mut tug) = net_q.get_single_mut() {
+            if score.set_history.len() > *sets_played {
+                if let Some((left_games, right_games)) = score.set_history.last() {
+                    tug.targets.push_back(if right_games > left_games {
+                        -threshold
+                    } else {
+                        threshold
+                    });
+                }
+            }
+
+            // the net slides over most of the loser's side once the match is over
+            match score.get_winner_id(&rules) {
+                None => tug.targets.push_back(offset.0.clamp(-threshold, threshold)),
+                Some(winner_id) => {
+                    let side = if winner_id == 1 { 1. } else { -1. };
+                    tug.targets
+                        .push_back(side * settings.right * MATCH_WON_NET_SIDE);
+                }
+            }
         }
+        *sets_played = score.set_history.len();
 
-        // resize regions
-        for (region_e, region, region_t, _region_coll_shape) in region_q.iter_mut() {
-            let x = if region.is_left() {
-                -settings.region_x + offset.0 / 2.
-            } else {
-                settings.region_x + offset.0 / 2.
-            };
-            let side_mult = if region.is_left() { 1. } else { -1. };
-            let mut extends = settings.base_region_size;
-            extends.x += (offset.0 / 2.) * side_mult;
-            spawn_region(&mut commands, *region, x, region_t.translation.y, extends);
+        // a rebuild of the whole court spawns the regions with the new offset instead
+        if built_spec.0.as_ref() != Some(&*spec) {
+            return;
+        }
 
+        // resize regions
+        for region_e in region_q.iter() {
             commands.entity(region_e).despawn_recursive();
         }
+        spawn_regions(&mut commands, &settings, offset.0);
+    }
+}
+
+// moves the net through the queued offsets with a capped travel per step
+fn step_net_tug(
+    mut commands: Commands,
+    mut net_q: Query<(Entity, &mut NetTug, &Transform)>,
+    tween: Res<NetTugTween>,
+    time: Res<Time>,
+) {
+    for (net_e, mut tug, net_t) in net_q.iter_mut() {
+        if tug.step_sec_left > 0. {
+            tug.step_sec_left -= time.delta_seconds();
+            continue;
+        }
+
+        let target = match tug.targets.pop_front() {
+            Some(target) => target,
+            None => continue,
+        };
+
+        let travel = target - net_t.translation.x;
+        if travel.abs() < f32::EPSILON {
+            continue;
+        }
+
+        let step_end = net_t.translation.x + travel.clamp(-tween.max_step, tween.max_step);
+        if (target - step_end).abs() > f32::EPSILON {
+            tug.targets.push_front(target);
+        }
+
+        tug.step_sec_left = tween.step_ms as f32 / 1000.;
+        commands.entity(net_e).insert(Animator::new(Tween::new(
+            tween.ease,
+            TweeningType::Once,
+            Duration::from_millis(tween.step_ms),
+            TransformPositionLens {
+                start: net_t.translation,
+                end: Vec3::new(step_end, net_t.translation.y, net_t.translation.z),
+            },
+        )));
     }
 }
@@ -0,0 +1,257 @@
+#![allow(
+    clippy::type_complexity,
+    clippy::too_many_arguments,
+    // manual enum Default impls keep older stable toolchains working
+    clippy::derivable_impls
+)]
+
+use aim_settings::AimSettingsPlugin;
+use animation::AnimationPlugin;
+use asset::AssetPlugin;
+use assist::AssistPlugin;
+use ball::BallPlugin;
+use ball_boy::BallBoyPlugin;
+use best_rally::BestRallyPlugin;
+use bevy::{app::PluginGroupBuilder, prelude::*};
+use camera::CameraPlugin;
+use celebration::CelebrationPlugin;
+use challenge::ChallengePlugin;
+use config::ConfigPlugin;
+use cooldown_indicator::CooldownIndicatorPlugin;
+use cosmetic::CosmeticPlugin;
+use danger_zone::DangerZonePlugin;
+use effects::EffectsPlugin;
+use fatigue::FatiguePlugin;
+use hud_layout::HudLayoutPlugin;
+use input_binding::InputBindingPlugin;
+use intro::IntroPlugin;
+use leaderboard::LeaderboardPlugin;
+use level::LevelPlugin;
+use locale::LocalePlugin;
+use minimap::MinimapPlugin;
+use mode::ModePlugin;
+use modifier::ModifierPlugin;
+use momentum::MomentumPlugin;
+use palette::PalettePlugin;
+use photo::PhotoModePlugin;
+use player::PlayerPlugin;
+use player_action::PlayerActionPlugin;
+use player_animation::PlayerAnimationPlugin;
+use player_controller::PlayerControllerPlugin;
+use profile::ProfilePlugin;
+use prompt::PromptPlugin;
+use replay::ReplayPlugin;
+use reset::ResetPlugin;
+use rng::RngPlugin;
+use score::ScorePlugin;
+use shot_clock::ShotClockPlugin;
+use spectate::{SpectateMode, SpectatePlugin};
+use sprite_sheet::SpriteSheetPlugin;
+use stats::StatsPlugin;
+use stats_export::StatsExportPlugin;
+use target::TargetPlugin;
+use touch::TouchPlugin;
+use trail::TrailPlugin;
+use ui_text::UiTextPlugin;
+use wall::WallPlugin;
+use weather::WeatherPlugin;
+
+// todo: namespace modules (e.g. player)
+#[cfg(feature = "ai")]
+pub mod ai_config;
+#[cfg(feature = "ai")]
+pub mod ai_player_controller;
+pub mod aim_settings;
+pub mod animation;
+pub mod asset;
+pub mod assist;
+pub mod ball;
+pub mod ball_boy;
+pub mod best_rally;
+#[cfg(feature = "broadcast")]
+pub mod broadcast;
+pub mod camera;
+#[cfg(feature = "capture")]
+pub mod capture;
+pub mod celebration;
+pub mod challenge;
+pub mod checksum;
+pub mod cli;
+pub mod config;
+pub mod cooldown_indicator;
+pub mod cosmetic;
+pub mod danger_zone;
+#[cfg(feature = "inspector")]
+pub mod debug;
+pub mod effects;
+pub mod extra;
+pub mod fatigue;
+pub mod hud_layout;
+pub mod input_binding;
+#[cfg(feature = "input_recording")]
+pub mod input_recording;
+pub mod intro;
+pub mod leaderboard;
+pub mod level;
+pub mod locale;
+pub mod minimap;
+pub mod mode;
+pub mod modifier;
+pub mod momentum;
+pub mod palette;
+pub mod photo;
+pub mod physics;
+pub mod player;
+pub mod player_action;
+pub mod player_animation;
+pub mod player_controller;
+pub mod profile;
+pub mod prompt;
+pub mod render;
+pub mod replay;
+pub mod reset;
+pub mod rng;
+pub mod score;
+pub mod shot_clock;
+pub mod spectate;
+pub mod sprite_sheet;
+pub mod stats;
+pub mod stats_export;
+#[cfg(feature = "stats_window")]
+pub mod stats_window;
+#[cfg(feature = "steam")]
+pub mod steam;
+pub mod storage;
+pub mod target;
+pub mod timer;
+pub mod touch;
+pub mod trail;
+pub mod trajectory;
+pub mod ui_text;
+pub mod wall;
+pub mod weather;
+pub mod window;
+
+pub const NAME: &str = "Tag of Ball";
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum GameState {
+    // building the sprite atlas, the game starts once it's done
+    Loading,
+    Game,
+    // match intro, pushed on top of the game
+    PreMatch,
+    Reset,
+    Replay,
+    // slow motion replay of a challenged call, pushed on top of the game
+    Challenge,
+    // watching a match hosted elsewhere
+    Spectate,
+}
+
+#[derive(SystemLabel, Debug, Clone, Eq, PartialEq, Hash)]
+pub enum GameSetupPhase {
+    Level,
+    Ball,
+    Player,
+}
+
+// all the gameplay plugins
+// expects the bevy, 3rd party (heron, tweening, big-brain with the ai feature, lyon) and game crate (time, input) plugins to be added
+// optional plugins are gated by the cargo features, see Cargo.toml
+pub struct GamePluginGroup;
+impl PluginGroup for GamePluginGroup {
+    fn build(&mut self, group: &mut PluginGroupBuilder) {
+        // restores the cloud saves, so it goes before everything loading them
+        #[cfg(feature = "steam")]
+        group.add(steam::SteamPlugin);
+
+        group
+            .add(GameStatePlugin)
+            .add(AimSettingsPlugin)
+            .add(AnimationPlugin)
+            .add(AssetPlugin)
+            .add(AssistPlugin)
+            .add(BallPlugin)
+            .add(BallBoyPlugin)
+            .add(BestRallyPlugin)
+            .add(CameraPlugin)
+            .add(CelebrationPlugin)
+            .add(ChallengePlugin)
+            .add(ConfigPlugin)
+            .add(CooldownIndicatorPlugin)
+            .add(CosmeticPlugin)
+            .add(DangerZonePlugin)
+            .add(EffectsPlugin)
+            .add(FatiguePlugin)
+            .add(HudLayoutPlugin)
+            .add(InputBindingPlugin)
+            .add(IntroPlugin)
+            .add(LeaderboardPlugin)
+            .add(LevelPlugin)
+            .add(LocalePlugin)
+            .add(MinimapPlugin)
+            .add(ModePlugin)
+            .add(ModifierPlugin)
+            .add(MomentumPlugin)
+            .add(PalettePlugin)
+            .add(PhotoModePlugin)
+            .add(PlayerPlugin)
+            .add(PlayerControllerPlugin)
+            .add(PlayerActionPlugin)
+            .add(PlayerAnimationPlugin)
+            .add(ProfilePlugin)
+            .add(PromptPlugin)
+            .add(ReplayPlugin)
+            .add(ResetPlugin)
+            .add(RngPlugin)
+            .add(ScorePlugin)
+            .add(ShotClockPlugin)
+            .add(SpectatePlugin)
+            .add(SpriteSheetPlugin)
+            .add(StatsPlugin)
+            .add(StatsExportPlugin)
+            .add(TargetPlugin)
+            .add(TouchPlugin)
+            .add(TrailPlugin)
+            .add(UiTextPlugin)
+            .add(WallPlugin)
+            .add(WeatherPlugin);
+
+        #[cfg(feature = "ai")]
+        group
+            .add(ai_config::AiConfigPlugin)
+            .add(ai_player_controller::AiPlayerControllerPlugin);
+
+        #[cfg(feature = "broadcast")]
+        group.add(broadcast::BroadcastPlugin);
+
+        #[cfg(feature = "capture")]
+        group.add(capture::CapturePlugin);
+
+        #[cfg(feature = "input_recording")]
+        group.add(input_recording::InputRecordingPlugin);
+
+        #[cfg(feature = "stats_window")]
+        group.add(stats_window::StatsWindowPlugin);
+    }
+}
+
+pub struct GameStatePlugin;
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_state(GameState::Loading);
+    }
+}
+
+// the state entered once loading is done
+pub fn get_initial_state(has_replay: bool) -> GameState {
+    if has_replay {
+        return GameState::Replay;
+    }
+
+    match spectate::get_spectate_mode() {
+        Some(SpectateMode::View(_)) => GameState::Spectate,
+        _ => GameState::Game,
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{score::Score, storage};
+
+// codes of the assets/lang/{code}.lang.ron files
+pub const LANGUAGES: [&str; 2] = ["en", "cs"];
+const LANGUAGE_STORAGE_KEY: &str = "settings/language";
+
+pub struct LocalePlugin;
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_asset::<LocaleStrings>()
+            .init_asset_loader::<LocaleStringsLoader>()
+            .init_resource::<Locale>()
+            .add_startup_system(setup)
+            .add_system(on_locale_loaded)
+            .add_system(cycle_language);
+    }
+}
+
+#[derive(Deserialize, TypeUuid, Clone)]
+#[uuid = "a3d5e7c1-6b2f-4e89-8c14-5f0b9d2a7e36"]
+#[serde(transparent)]
+pub struct LocaleStrings(pub HashMap<String, String>);
+
+#[derive(Default)]
+pub struct Locale {
+    lang_i: usize,
+    strings: HashMap<String, String>,
+}
+
+impl Locale {
+    // falls back to the key, so missing strings stand out
+    pub fn get(&self, key: &str) -> String {
+        self.strings
+            .get(key)
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    // each '{}' gets replaced by the next arg
+    pub fn format(&self, key: &str, args: &[&str]) -> String {
+        args.iter()
+            .fold(self.get(key), |value, arg| value.replacen("{}", arg, 1))
+    }
+
+    pub fn get_language(&self) -> &'static str {
+        LANGUAGES[self.lang_i]
+    }
+}
+
+// all languages are loaded upfront, so switching is instant
+struct LocaleHandles(Vec<Handle<LocaleStrings>>);
+
+#[derive(Default)]
+pub struct LocaleStringsLoader;
+
+impl AssetLoader for LocaleStringsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let strings: LocaleStrings = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(strings));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lang.ron"]
+    }
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut locale: ResMut<Locale>) {
+    if let Ok(lang) = storage::load(LANGUAGE_STORAGE_KEY) {
+        if let Some(i) = LANGUAGES.iter().position(|l| l.as_bytes() == lang) {
+            locale.lang_i = i;
+        }
+    }
+
+    commands.insert_resource(LocaleHandles(
+        LANGUAGES
+            .iter()
+            .map(|lang| asset_server.load(&format!("lang/{}.lang.ron", lang)))
+            .collect(),
+    ));
+}
+
+fn on_locale_loaded(
+    mut ev_asset: EventReader<AssetEvent<LocaleStrings>>,
+    assets: Res<Assets<LocaleStrings>>,
+    handles: Res<LocaleHandles>,
+    mut locale: ResMut<Locale>,
+) {
+    for ev in ev_asset.iter() {
+        match ev {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle }
+                if *handle == handles.0[locale.lang_i] =>
+            {
+                if let Some(strings) = assets.get(handle) {
+                    locale.strings = strings.0.clone();
+                    info!("Locale {} loaded", locale.get_language());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn cycle_language(
+    keys: Res<Input<KeyCode>>,
+    score: Res<Score>,
+    assets: Res<Assets<LocaleStrings>>,
+    handles: Res<LocaleHandles>,
+    mut locale: ResMut<Locale>,
+) {
+    if score.is_match_start() && keys.just_pressed(KeyCode::L) {
+        locale.lang_i = (locale.lang_i + 1) % LANGUAGES.len();
+        // not loaded yet - on_locale_loaded picks it up
+        locale.strings = assets
+            .get(&handles.0[locale.lang_i])
+            .map(|s| s.0.clone())
+            .unwrap_or_default();
+
+        if let Err(e) = storage::save(LANGUAGE_STORAGE_KEY, locale.get_language().as_bytes()) {
+            error!("Failed to save the language: {}", e);
+        }
+    }
+}
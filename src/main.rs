@@ -1,72 +1,47 @@
-#![cfg_attr(feature = "release", windows_subsystem = "windows")]
-#![feature(derive_default_enum)]
-#![feature(if_let_guard)]
-#![feature(drain_filter)]
-#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+#![cfg_attr(
+    all(feature = "release", target_os = "windows"),
+    windows_subsystem = "windows"
+)]
 
-use ai_player_controller::AiPlayerControllerPlugin;
-use animation::AnimationPlugin;
-use asset::AssetPlugin;
-use ball::BallPlugin;
+use std::{process, time::Duration};
+
+use bevy::app::ScheduleRunnerPlugin;
 use bevy::prelude::*;
+#[cfg(feature = "windowed")]
+use bevy::winit::WinitPlugin;
 use bevy_input::ActionInputPlugin;
 use bevy_prototype_lyon::plugin::ShapePlugin;
 use bevy_time::TimePlugin;
 use bevy_tweening::TweeningPlugin;
+#[cfg(feature = "ai")]
 use big_brain::BigBrainPlugin;
-use camera::CameraPlugin;
-use debug::DebugPlugin;
 use heron::*;
-use input_binding::{InputAction, InputAxis, InputBindingPlugin};
-use level::{CourtRegion, InitialRegion, LevelPlugin};
-use palette::PalettePlugin;
-use player::PlayerPlugin;
-use player_action::PlayerActionPlugin;
-use player_animation::PlayerAnimationPlugin;
-use player_controller::PlayerControllerPlugin;
-use reset::ResetPlugin;
-use score::ScorePlugin;
-use trail::TrailPlugin;
-use window::{WIN_HEIGHT, WIN_WIDTH};
-
-// todo: namespace modules (e.g. player)
-mod ai_player_controller;
-mod animation;
-mod asset;
-mod ball;
-mod camera;
-mod debug;
-mod extra;
-mod input_binding;
-mod level;
-mod palette;
-mod physics;
-mod player;
-mod player_action;
-mod player_animation;
-mod player_controller;
-mod render;
-mod reset;
-mod score;
-mod trail;
-mod window;
-
-const NAME: &str = "Tag of Ball";
-
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-enum GameState {
-    Game,
-    Reset,
-}
-
-#[derive(SystemLabel, Debug, Clone, Eq, PartialEq, Hash)]
-enum GameSetupPhase {
-    Ball,
-    Player,
-}
+#[cfg(feature = "debug")]
+use tug_of_ball::debug::DebugPlugin;
+use tug_of_ball::{
+    cli::{LaunchArgs, USAGE},
+    input_binding::{InputAction, InputAxis},
+    level::{CourtRegion, InitialRegion},
+    window::{WIN_HEIGHT, WIN_WIDTH},
+    GamePluginGroup, NAME,
+};
 
 fn main() {
-    // let mut region = CourtRegion::get_random();
+    let args = match LaunchArgs::from_env() {
+        Ok(args) if args.help => {
+            println!("{}", USAGE);
+            return;
+        }
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    let (width, height) = args.window_size.unwrap_or((WIN_WIDTH, WIN_HEIGHT));
+    let headless = args.headless || cfg!(not(feature = "windowed"));
+
+    // let mut region = CourtRegion::get_random(&mut GameRng::default());
     let mut region = CourtRegion::BottomLeft;
     let mut scale_factor_override = None;
 
@@ -80,49 +55,59 @@ fn main() {
         // resources needed before default plugins to take effect
         .insert_resource(WindowDescriptor {
             title: NAME.to_string(),
-            width: WIN_WIDTH,
-            height: WIN_HEIGHT,
+            width,
+            height,
             resizable: false,
             scale_factor_override,
+            #[cfg(target_arch = "wasm32")]
+            canvas: Some("#bevy".to_string()),
             ..Default::default()
         })
         .insert_resource(ClearColor(Color::WHITE))
         // game resources
-        .insert_resource(InitialRegion(region))
-        // bevy plugins
-        .add_plugins(DefaultPlugins)
-        // 3rd party crates
-        .add_plugin(PhysicsPlugin::default())
-        .add_plugin(TweeningPlugin)
-        .add_plugin(BigBrainPlugin)
-        // game crates
-        .add_plugin(TimePlugin)
+        .insert_resource(InitialRegion(region));
+
+    if let Err(e) = args.insert_resources(&mut app) {
+        eprintln!("Failed to load the launch file: {}", e);
+        process::exit(1);
+    }
+
+    // bevy plugins
+    #[cfg(feature = "windowed")]
+    if headless {
+        app.add_plugins_with(DefaultPlugins, |group| group.disable::<WinitPlugin>());
+    } else {
+        app.add_plugins(DefaultPlugins);
+    }
+    #[cfg(not(feature = "windowed"))]
+    app.add_plugins(DefaultPlugins);
+
+    // 3rd party crates
+    app.add_plugin(PhysicsPlugin::default())
+        .add_plugin(TweeningPlugin);
+
+    // the ai plugin adds systems to the big-brain stages
+    #[cfg(feature = "ai")]
+    app.add_plugin(BigBrainPlugin);
+
+    // game crates
+    app.add_plugin(TimePlugin)
         .add_plugin(ActionInputPlugin::<InputAction, InputAxis>::default())
         // game plugins
-        .add_plugin(AiPlayerControllerPlugin)
-        .add_plugin(AnimationPlugin)
-        .add_plugin(AssetPlugin)
-        .add_plugin(BallPlugin)
-        .add_plugin(CameraPlugin)
-        .add_plugin(InputBindingPlugin)
-        .add_plugin(LevelPlugin)
-        .add_plugin(PalettePlugin)
-        .add_plugin(PlayerPlugin)
-        .add_plugin(PlayerControllerPlugin)
-        .add_plugin(PlayerActionPlugin)
-        .add_plugin(PlayerAnimationPlugin)
-        .add_plugin(ResetPlugin)
-        .add_plugin(ScorePlugin)
-        .add_plugin(TrailPlugin)
-        // initial state
-        .add_state(GameState::Game);
+        .add_plugins(GamePluginGroup);
 
-    if cfg!(feature = "debug") {
-        app.add_plugin(DebugPlugin);
-    } else {
-        // heron 2d-debug adds lyon plugin as well, which would cause a panic
-        app.add_plugin(ShapePlugin);
+    // without a window the app has to be driven by the schedule runner
+    if headless {
+        app.add_plugin(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+            1. / 60.,
+        )));
     }
 
+    #[cfg(feature = "debug")]
+    app.add_plugin(DebugPlugin);
+    // heron 2d-debug adds lyon plugin as well, which would cause a panic
+    #[cfg(not(feature = "debug"))]
+    app.add_plugin(ShapePlugin);
+
     app.run();
 }
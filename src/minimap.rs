@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+
+use crate::{
+    ball::Ball,
+    level::{CourtSettings, Net},
+    palette::PaletteColor,
+    player::Player,
+};
+
+const MINIMAP_SIZE: Vec2 = Vec2::new(220., 130.);
+const DOT_SIZE: f32 = 10.;
+const LINE_THICKNESS: f32 = 2.;
+
+pub struct MinimapPlugin;
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup)
+            .add_system(spawn_dots)
+            .add_system(update_dots)
+            .add_system(update_net);
+    }
+}
+
+#[derive(Component)]
+struct MinimapRoot;
+
+#[derive(Component)]
+struct MinimapNet;
+
+#[derive(Component)]
+struct MinimapDot {
+    target_e: Entity,
+}
+
+fn setup(mut commands: Commands) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(MINIMAP_SIZE.x), Val::Px(MINIMAP_SIZE.y)),
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.),
+                    right: Val::Px(10.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(PaletteColor::Court)
+        .insert(MinimapRoot)
+        .insert(Name::new("Minimap"))
+        .with_children(|b| {
+            // horizontal region split
+            b.spawn_bundle(get_line_bundle(
+                Size::new(Val::Percent(100.), Val::Px(LINE_THICKNESS)),
+                Rect {
+                    left: Val::Px(0.),
+                    bottom: Val::Percent(50.),
+                    ..Default::default()
+                },
+            ))
+            .insert(PaletteColor::CourtLines);
+
+            // net
+            b.spawn_bundle(get_line_bundle(
+                Size::new(Val::Px(LINE_THICKNESS), Val::Percent(100.)),
+                Rect {
+                    left: Val::Percent(50.),
+                    bottom: Val::Px(0.),
+                    ..Default::default()
+                },
+            ))
+            .insert(PaletteColor::CourtLines)
+            .insert(MinimapNet);
+        });
+}
+
+fn get_line_bundle(size: Size<Val>, position: Rect<Val>) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size,
+            position_type: PositionType::Absolute,
+            position,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn spawn_dots(
+    mut commands: Commands,
+    root_q: Query<Entity, With<MinimapRoot>>,
+    player_q: Query<Entity, Added<Player>>,
+    ball_q: Query<Entity, Added<Ball>>,
+) {
+    if let Ok(root_e) = root_q.get_single() {
+        let new_dots = player_q
+            .iter()
+            .map(|e| (e, PaletteColor::Player, 1.))
+            .chain(ball_q.iter().map(|e| (e, PaletteColor::Ball, 0.7)));
+
+        for (target_e, color, size_mult) in new_dots {
+            let size = DOT_SIZE * size_mult;
+            let dot_e = commands
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(size), Val::Px(size)),
+                        position_type: PositionType::Absolute,
+                        margin: Rect {
+                            left: Val::Px(-size / 2.),
+                            bottom: Val::Px(-size / 2.),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(color)
+                .insert(MinimapDot { target_e })
+                .id();
+            commands.entity(root_e).add_child(dot_e);
+        }
+    }
+}
+
+fn update_dots(
+    mut commands: Commands,
+    mut dot_q: Query<(Entity, &MinimapDot, &mut Style)>,
+    transform_q: Query<&GlobalTransform>,
+    court: Option<Res<CourtSettings>>,
+) {
+    let court = match court {
+        Some(court) => court,
+        None => return,
+    };
+
+    for (dot_e, dot, mut style) in dot_q.iter_mut() {
+        if let Ok(t) = transform_q.get(dot.target_e) {
+            let pos = get_minimap_percent(t.translation.truncate(), &court);
+            style.position.left = Val::Percent(pos.x);
+            style.position.bottom = Val::Percent(pos.y);
+        } else {
+            commands.entity(dot_e).despawn_recursive();
+        }
+    }
+}
+
+fn update_net(
+    mut net_ui_q: Query<&mut Style, With<MinimapNet>>,
+    net_q: Query<&GlobalTransform, With<Net>>,
+    court: Option<Res<CourtSettings>>,
+) {
+    if let (Some(court), Ok(net_t)) = (court, net_q.get_single()) {
+        for mut style in net_ui_q.iter_mut() {
+            let pos = get_minimap_percent(net_t.translation.truncate(), &court);
+            style.position.left = Val::Percent(pos.x);
+        }
+    }
+}
+
+fn get_minimap_percent(pos: Vec2, court: &CourtSettings) -> Vec2 {
+    let size = Vec2::new(court.right - court.left, court.top - court.bottom);
+    ((pos - Vec2::new(court.left, court.bottom)) / size * 100.).clamp(Vec2::ZERO, Vec2::splat(100.))
+}
@@ -0,0 +1,292 @@
+use bevy::prelude::*;
+
+use crate::{
+    assist::Assists,
+    fatigue::FatigueSettings,
+    hud_layout::HudLayout,
+    input_binding::{InputAction, PlayerInput},
+    level::CourtSpec,
+    locale::Locale,
+    palette::PaletteColor,
+    score::{MatchRules, PointStart, Score},
+    ui_text::{get_text_style, ScaledText, TextSettings},
+    GameSetupPhase, GameState,
+};
+
+const SHOT_CLOCK_SEC: f32 = 3.;
+// in games of net tug
+const ACE_NET_BONUS: f32 = 0.5;
+const FULL_CHARGE_NET_BONUS: f32 = 0.25;
+
+pub struct ModePlugin;
+impl Plugin for ModePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<GameMode>()
+            .add_startup_system(setup)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Game)
+                    .with_system(apply_mode_rules.before(GameSetupPhase::Level)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(cycle_mode)
+                    .with_system(toggle_point_start)
+                    .with_system(toggle_shot_clock)
+                    .with_system(toggle_net_bonus)
+                    .with_system(toggle_no_ad),
+            )
+            .add_system(update_mode_ui);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    Match,
+    WallPractice,
+    Volley,
+    TargetPractice,
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        GameMode::Match
+    }
+}
+
+impl GameMode {
+    pub fn get_name_key(&self) -> &'static str {
+        match self {
+            GameMode::Match => "mode-match",
+            GameMode::WallPractice => "mode-wall-practice",
+            GameMode::Volley => "mode-volley",
+            GameMode::TargetPractice => "mode-target-practice",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "match" => Some(GameMode::Match),
+            "wall" => Some(GameMode::WallPractice),
+            "volley" => Some(GameMode::Volley),
+            "target" => Some(GameMode::TargetPractice),
+            _ => None,
+        }
+    }
+
+    pub fn get_court_length_mult(&self) -> f32 {
+        match self {
+            GameMode::Volley => 0.65,
+            _ => 1.,
+        }
+    }
+
+    pub fn get_bounce_limit(&self) -> usize {
+        match self {
+            GameMode::Volley => 0,
+            _ => 1,
+        }
+    }
+
+    // practice modes replace the opponent and keep their own score
+    pub fn has_opponent(&self) -> bool {
+        !matches!(self, GameMode::WallPractice | GameMode::TargetPractice)
+    }
+
+    pub fn has_wall(&self) -> bool {
+        *self == GameMode::WallPractice
+    }
+
+    fn get_next(&self) -> Self {
+        match self {
+            GameMode::Match => GameMode::WallPractice,
+            GameMode::WallPractice => GameMode::Volley,
+            GameMode::Volley => GameMode::TargetPractice,
+            GameMode::TargetPractice => GameMode::Match,
+        }
+    }
+}
+
+#[derive(Component)]
+struct ModeText;
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, text_settings: Res<TextSettings>) {
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(10.),
+                    right: Val::Px(15.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                get_text_style(&asset_server, &text_settings, 22.0),
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(PaletteColor::Text)
+        .insert(ScaledText(22.0))
+        .insert(ModeText)
+        .insert(Name::new("ModeText"));
+}
+
+fn apply_mode_rules(
+    mode: Res<GameMode>,
+    mut rules: ResMut<MatchRules>,
+    mut spec: ResMut<CourtSpec>,
+) {
+    rules.bounce_limit = mode.get_bounce_limit();
+
+    let width = CourtSpec::default().width * mode.get_court_length_mult();
+    if spec.width != width {
+        spec.width = width;
+    }
+}
+
+// the mode can only be switched before the 1st point of a match
+fn cycle_mode(
+    keys: Res<Input<KeyCode>>,
+    score: Res<Score>,
+    mut mode: ResMut<GameMode>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if score.is_match_start() && keys.just_pressed(KeyCode::Tab) {
+        *mode = mode.get_next();
+        debug!("Switched to {:?}", *mode);
+        state.overwrite_replace(GameState::Reset).unwrap();
+    }
+}
+
+fn toggle_point_start(
+    mut input: ResMut<PlayerInput>,
+    score: Res<Score>,
+    mut rules: ResMut<MatchRules>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !score.is_match_start() {
+        return;
+    }
+
+    if let Some(id) = (1..=4).find(|id| input.just_pressed(*id, InputAction::TogglePointStart)) {
+        input.use_button_action(id, InputAction::TogglePointStart);
+        rules.point_start = match rules.point_start {
+            PointStart::Serve => PointStart::DropBall,
+            PointStart::DropBall => PointStart::Serve,
+        };
+        debug!("Points start with {:?}", rules.point_start);
+        // respawn the 1st ball
+        state.overwrite_replace(GameState::Reset).unwrap();
+    }
+}
+
+fn toggle_shot_clock(keys: Res<Input<KeyCode>>, score: Res<Score>, mut rules: ResMut<MatchRules>) {
+    if score.is_match_start() && keys.just_pressed(KeyCode::C) {
+        rules.shot_clock_sec = match rules.shot_clock_sec {
+            Some(_) => None,
+            None => Some(SHOT_CLOCK_SEC),
+        };
+        debug!("Shot clock set to {:?}", rules.shot_clock_sec);
+    }
+}
+
+fn toggle_net_bonus(keys: Res<Input<KeyCode>>, score: Res<Score>, mut rules: ResMut<MatchRules>) {
+    if score.is_match_start() && keys.just_pressed(KeyCode::T) {
+        let enable = rules.ace_net_bonus.is_none();
+        rules.ace_net_bonus = enable.then(|| ACE_NET_BONUS);
+        rules.full_charge_net_bonus = enable.then(|| FULL_CHARGE_NET_BONUS);
+        debug!("Net bonus set to {:?}", rules.ace_net_bonus);
+    }
+}
+
+fn toggle_no_ad(keys: Res<Input<KeyCode>>, score: Res<Score>, mut rules: ResMut<MatchRules>) {
+    if score.is_match_start() && keys.just_pressed(KeyCode::Z) {
+        rules.no_ad = !rules.no_ad;
+        debug!("No-ad scoring set to {}", rules.no_ad);
+    }
+}
+
+fn update_mode_ui(
+    mode: Res<GameMode>,
+    score: Res<Score>,
+    rules: Res<MatchRules>,
+    assists: Res<Assists>,
+    fatigue: Res<FatigueSettings>,
+    locale: Res<Locale>,
+    text_settings: Res<TextSettings>,
+    layout: Res<HudLayout>,
+    mut text_q: Query<&mut Text, With<ModeText>>,
+) {
+    if mode.is_changed()
+        || score.is_changed()
+        || rules.is_changed()
+        || assists.is_changed()
+        || fatigue.is_changed()
+        || locale.is_changed()
+        || text_settings.is_changed()
+        || layout.is_changed()
+    {
+        for mut text in text_q.iter_mut() {
+            text.sections[0].value = if score.is_match_start() {
+                format!(
+                    "-/+ {}\nF {}\nL {}\nF11 {}\nC {}\nT {}\nZ {}\n3/4 {}\n5/6 {}\nF6/F7 {}\nF12 {}\nM {}\nTab {}\nF9 {}",
+                    locale.format(
+                        "text-scale",
+                        &[&format!("{:.0}", text_settings.scale * 100.)]
+                    ),
+                    locale.get(text_settings.font.get_name_key()),
+                    locale.get("language"),
+                    locale.get(rules.point_start.get_name_key()),
+                    match rules.shot_clock_sec {
+                        Some(sec) => locale.format("shot-clock-on", &[&format!("{:.0}", sec)]),
+                        None => locale.get("shot-clock-off"),
+                    },
+                    locale.get(if rules.ace_net_bonus.is_some() {
+                        "net-bonus-on"
+                    } else {
+                        "net-bonus-off"
+                    }),
+                    locale.get(if rules.no_ad { "no-ad-on" } else { "no-ad-off" }),
+                    locale.format(
+                        "serve-assist",
+                        &[
+                            &get_on_off(&locale, assists.0[0].serve_aim),
+                            &get_on_off(&locale, assists.0[1].serve_aim)
+                        ]
+                    ),
+                    locale.format(
+                        "magnet-assist-toggle",
+                        &[
+                            &get_on_off(&locale, assists.0[0].magnetize),
+                            &get_on_off(&locale, assists.0[1].magnetize)
+                        ]
+                    ),
+                    locale.format(
+                        "two-bounce-assist-toggle",
+                        &[
+                            &get_on_off(&locale, assists.0[0].two_bounce),
+                            &get_on_off(&locale, assists.0[1].two_bounce)
+                        ]
+                    ),
+                    locale.format("fatigue-toggle", &[&get_on_off(&locale, fatigue.enabled)]),
+                    locale.get(if layout.mirrored {
+                        "hud-mirrored"
+                    } else {
+                        "hud-regular"
+                    }),
+                    locale.get(mode.get_name_key()),
+                    locale.get("replay-watch")
+                )
+            } else {
+                String::new()
+            };
+        }
+    }
+}
+
+fn get_on_off(locale: &Locale, on: bool) -> String {
+    locale.get(if on { "modifier-on" } else { "modifier-off" })
+}
@@ -0,0 +1,255 @@
+use std::collections::HashSet;
+
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+use heron::*;
+
+use crate::{
+    ball::{Ball, BallHitEvt},
+    level::CourtSpec,
+    locale::Locale,
+    palette::PaletteColor,
+    score::{PointValue, Score},
+    ui_text::{get_text_style, ScaledText, TextSettings},
+    GameState,
+};
+
+// keys toggling the registered modifiers in order
+const TOGGLE_KEYS: [KeyCode; 4] = [KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4];
+
+pub struct ModifierPlugin;
+impl Plugin for ModifierPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<ModifierRegistry>()
+            .init_resource::<ActiveModifiers>()
+            .add_startup_system(setup)
+            .add_system(toggle_modifiers)
+            .add_system(update_modifiers_ui)
+            .add_modifier(
+                ModifierInfo {
+                    id: LONG_RALLY_ID,
+                    name_key: "modifier-long-rally",
+                },
+                SystemSet::new().with_system(double_long_rally_points),
+            )
+            .add_modifier(
+                ModifierInfo {
+                    id: SHRINKING_COURT_ID,
+                    name_key: "modifier-shrinking-court",
+                },
+                SystemSet::new().with_system(shrink_court),
+            )
+            .add_modifier(
+                ModifierInfo {
+                    id: GROWING_BALL_ID,
+                    name_key: "modifier-growing-ball",
+                },
+                SystemSet::new().with_system(grow_ball),
+            );
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ModifierInfo {
+    pub id: &'static str,
+    pub name_key: &'static str,
+}
+
+#[derive(Default)]
+pub struct ModifierRegistry {
+    pub modifiers: Vec<ModifierInfo>,
+}
+
+// the mutators picked for the current match
+#[derive(Default)]
+pub struct ActiveModifiers(pub HashSet<&'static str>);
+
+impl ActiveModifiers {
+    pub fn is_active(&self, id: &str) -> bool {
+        self.0.contains(id)
+    }
+}
+
+pub trait ModifierAppExt {
+    // the systems only run during a game while the modifier is active
+    fn add_modifier(&mut self, info: ModifierInfo, systems: SystemSet) -> &mut Self;
+}
+
+impl ModifierAppExt for App {
+    fn add_modifier(&mut self, info: ModifierInfo, systems: SystemSet) -> &mut Self {
+        let id = info.id;
+        self.world
+            .get_resource_or_insert_with(ModifierRegistry::default)
+            .modifiers
+            .push(info);
+
+        self.add_system_set(systems.with_run_criteria(
+            move |active: Res<ActiveModifiers>, state: Res<State<GameState>>| {
+                if active.is_active(id) && *state.current() == GameState::Game {
+                    ShouldRun::Yes
+                } else {
+                    ShouldRun::No
+                }
+            },
+        ))
+    }
+}
+
+#[derive(Component)]
+struct ModifiersText;
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, text_settings: Res<TextSettings>) {
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(10.),
+                    left: Val::Px(15.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                get_text_style(&asset_server, &text_settings, 22.0),
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(PaletteColor::Text)
+        .insert(ScaledText(22.0))
+        .insert(ModifiersText)
+        .insert(Name::new("ModifiersText"));
+}
+
+// modifiers can only be picked before the 1st point of a match
+fn toggle_modifiers(
+    keys: Res<Input<KeyCode>>,
+    registry: Res<ModifierRegistry>,
+    score: Res<Score>,
+    mut active: ResMut<ActiveModifiers>,
+    mut point_value: ResMut<PointValue>,
+) {
+    if !score.is_match_start() {
+        return;
+    }
+
+    for (modifier, key) in registry.modifiers.iter().zip(TOGGLE_KEYS.iter()) {
+        if keys.just_pressed(*key) {
+            if !active.0.remove(modifier.id) {
+                active.0.insert(modifier.id);
+            }
+
+            *point_value = PointValue::default();
+            debug!(
+                "Modifier {} active: {}",
+                modifier.id,
+                active.is_active(modifier.id)
+            );
+        }
+    }
+}
+
+fn update_modifiers_ui(
+    registry: Res<ModifierRegistry>,
+    active: Res<ActiveModifiers>,
+    score: Res<Score>,
+    locale: Res<Locale>,
+    mut text_q: Query<&mut Text, With<ModifiersText>>,
+) {
+    if !active.is_changed() && !score.is_changed() && !locale.is_changed() {
+        return;
+    }
+
+    let match_start = score.is_match_start();
+    let lines: Vec<String> = registry
+        .modifiers
+        .iter()
+        .zip(TOGGLE_KEYS.iter())
+        .filter(|(m, _)| match_start || active.is_active(m.id))
+        .map(|(m, key)| {
+            if match_start {
+                let state = if active.is_active(m.id) {
+                    "modifier-on"
+                } else {
+                    "modifier-off"
+                };
+                format!(
+                    "{:?} {} [{}]",
+                    key,
+                    locale.get(m.name_key),
+                    locale.get(state)
+                )
+            } else {
+                locale.get(m.name_key)
+            }
+        })
+        .collect();
+
+    for mut text in text_q.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+pub const LONG_RALLY_ID: &str = "long_rally_double_points";
+const LONG_RALLY_HITS: u32 = 8;
+
+fn double_long_rally_points(
+    mut ev_r_hit: EventReader<BallHitEvt>,
+    new_ball_q: Query<Entity, Added<Ball>>,
+    mut point_value: ResMut<PointValue>,
+    mut hits: Local<u32>,
+) {
+    // a new ball means a new rally
+    if new_ball_q.iter().next().is_some() {
+        *hits = 0;
+        *point_value = PointValue::default();
+    }
+
+    for _ in ev_r_hit.iter() {
+        *hits += 1;
+
+        if *hits == LONG_RALLY_HITS {
+            point_value.0 = 2;
+            debug!("Long rally - the point is worth double");
+        }
+    }
+}
+
+pub const SHRINKING_COURT_ID: &str = "shrinking_court";
+const SHRINK_PER_GAME: f32 = 0.04;
+const MIN_COURT_SCALE: f32 = 0.6;
+
+// shrinks the court vertically, so the net and the serve sides stay put
+fn shrink_court(score: Res<Score>, mut spec: ResMut<CourtSpec>) {
+    let scale = (1. - score.games_played() as f32 * SHRINK_PER_GAME).max(MIN_COURT_SCALE);
+    let height = CourtSpec::default().height * scale;
+
+    if (spec.height - height).abs() > f32::EPSILON {
+        spec.height = height;
+    }
+}
+
+pub const GROWING_BALL_ID: &str = "growing_ball";
+const GROWTH_PER_HIT: f32 = 1.1;
+const MAX_BALL_GROWTH: f32 = 2.;
+
+fn grow_ball(
+    mut ev_r_hit: EventReader<BallHitEvt>,
+    mut ball_q: Query<(&Ball, &mut CollisionShape)>,
+    mut transform_q: Query<&mut Transform>,
+) {
+    for ev in ev_r_hit.iter() {
+        if let Ok((ball, mut coll_shape)) = ball_q.get_mut(ev.ball_e) {
+            if let Ok(mut bounce_t) = transform_q.get_mut(ball.bounce_e.unwrap()) {
+                let growth = (bounce_t.scale.x * GROWTH_PER_HIT).min(MAX_BALL_GROWTH);
+                let mult = growth / bounce_t.scale.x;
+                bounce_t.scale = Vec3::new(growth, growth, 1.);
+
+                if let CollisionShape::Sphere { radius } = coll_shape.as_mut() {
+                    *radius *= mult;
+                }
+            }
+        }
+    }
+}
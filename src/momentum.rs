@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_tweening::{lens::TransformScaleLens, Animator, EaseFunction, Tween, TweeningType};
+
+use crate::{
+    palette::PaletteColor,
+    player::Player,
+    score::{PointOutcome, ScoreChangedEvt},
+    GameState,
+};
+
+// points in a row needed for a power serve
+const POWER_SERVE_STREAK: u8 = 3;
+pub const POWER_SERVE_SPEED_MULT: f32 = 1.35;
+// lower arc
+pub const POWER_SERVE_BOUNCE_MULT: f32 = 0.6;
+
+pub struct MomentumPlugin;
+impl Plugin for MomentumPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<Momentum>()
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(reset_momentum))
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(update_momentum)
+                    .with_system(update_power_serve_charge),
+            );
+    }
+}
+
+#[derive(Default)]
+pub struct Momentum {
+    pub streak_player_id: Option<usize>,
+    pub streak: u8,
+    // granted, but not served yet
+    pub power_serve_player_id: Option<usize>,
+}
+
+impl Momentum {
+    // true if the player had a power serve, which is used up by this
+    pub fn take_power_serve(&mut self, player_id: usize) -> bool {
+        if self.power_serve_player_id == Some(player_id) {
+            self.power_serve_player_id = None;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Component)]
+struct PowerServeCharge;
+
+fn reset_momentum(mut momentum: ResMut<Momentum>) {
+    *momentum = Momentum::default();
+}
+
+fn update_momentum(
+    mut ev_r_score: EventReader<ScoreChangedEvt>,
+    mut momentum: ResMut<Momentum>,
+    #[cfg(feature = "audio")] audio: Res<Audio>,
+    #[cfg(feature = "audio")] asset_server: Res<AssetServer>,
+) {
+    for ev in ev_r_score.iter() {
+        if momentum.streak_player_id == Some(ev.scoring_player_id) {
+            momentum.streak += 1;
+        } else {
+            momentum.streak_player_id = Some(ev.scoring_player_id);
+            momentum.streak = 1;
+        }
+
+        // no point in granting it once the match is over
+        if momentum.streak >= POWER_SERVE_STREAK && ev.outcome != PointOutcome::Match {
+            momentum.streak = 0;
+            momentum.power_serve_player_id = Some(ev.scoring_player_id);
+            #[cfg(feature = "audio")]
+            audio.play(asset_server.load("sfx/power_serve.wav"));
+            debug!("Player {} earned a power serve", ev.scoring_player_id);
+        }
+    }
+}
+
+// pulses around the player until the power serve is hit
+fn update_power_serve_charge(
+    mut commands: Commands,
+    momentum: Res<Momentum>,
+    player_q: Query<(Entity, &Player)>,
+    charge_q: Query<(Entity, &Parent), With<PowerServeCharge>>,
+    asset_server: Res<AssetServer>,
+) {
+    if !momentum.is_changed() {
+        return;
+    }
+
+    let holder_e = player_q
+        .iter()
+        .find(|(_, p)| momentum.power_serve_player_id == Some(p.id))
+        .map(|(e, _)| e);
+
+    let mut has_charge = false;
+    for (charge_e, parent) in charge_q.iter() {
+        if Some(parent.0) == holder_e {
+            has_charge = true;
+        } else {
+            commands.entity(charge_e).despawn_recursive();
+        }
+    }
+
+    if let (Some(holder_e), false) = (holder_e, has_charge) {
+        let charge_e = commands
+            .spawn_bundle(SpriteBundle {
+                texture: asset_server.load("art-ish/player_circle.png"),
+                transform: Transform {
+                    translation: Vec3::new(0., 0., -0.15),
+                    scale: Vec3::new(0.6, 0.6, 1.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(PaletteColor::PlayerCharge)
+            .insert(PowerServeCharge)
+            .insert(Name::new("PowerServeCharge"))
+            .insert(Animator::new(Tween::new(
+                EaseFunction::SineInOut,
+                TweeningType::PingPong,
+                Duration::from_millis(450),
+                TransformScaleLens {
+                    start: Vec3::new(0.6, 0.6, 1.),
+                    end: Vec3::new(1.15, 1.15, 1.),
+                },
+            )))
+            .id();
+        commands.entity(holder_e).add_child(charge_e);
+    }
+}
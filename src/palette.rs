@@ -4,30 +4,37 @@ use bevy_tweening::{
     lens::{SpriteColorLens, TextColorLens},
     Animator, EaseFunction, Tween, TweeningType,
 };
-use rand::random;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    asset::AtlasSpriteColorLens,
     input_binding::{InputAction, PlayerInput},
     level::Court,
+    rng::GameRng,
     trail::Trail,
 };
 
 const COURT_STROKE_WIDTH: f32 = 10.;
+const PALETTE_TWEEN_MS: u64 = 1000;
+// tweened colors can be off by float rounding
+const COLOR_EPSILON: f32 = 0.005;
 
 pub struct PalettePlugin;
 impl Plugin for PalettePlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_system(on_palette_changed)
             .add_system(on_sprite_added)
+            .add_system(on_atlas_sprite_added)
             .add_system(on_text_added)
-            .add_system(on_trail_added)
+            .add_system(on_ui_color_added)
+            .add_system(on_ui_palette_changed)
+            .add_system(on_palette_color_changed)
+            .add_system(reconcile_palette_colors)
+            .add_system(update_trail_color)
             .add_system(on_court_added)
             .add_system(handle_palette_input)
-            .insert_resource(if random::<bool>() {
-                CLAY_PALETTE
-            } else {
-                GRASS_PALETTE
-            });
+            .init_resource::<Palette>();
     }
 }
 
@@ -55,6 +62,18 @@ impl From<RgbColor> for Color {
     }
 }
 
+// picked with the seeded rng, so a seed also reproduces the look of a match
+impl FromWorld for Palette {
+    fn from_world(world: &mut World) -> Self {
+        let mut rng = world.get_resource_or_insert_with(GameRng::default);
+        match rng.gen_range(0..3) {
+            0 => CLAY_PALETTE,
+            1 => GRASS_PALETTE,
+            _ => RAIN_PALETTE,
+        }
+    }
+}
+
 pub struct Palette {
     background: RgbColor,
     court: RgbColor,
@@ -66,11 +85,25 @@ pub struct Palette {
     player_aim: RgbColor,
     player_face: RgbColor,
     player_charge: RgbColor,
+    // accents telling the players apart
+    player_left: RgbColor,
+    player_right: RgbColor,
     score_text: RgbColor,
     shadow: RgbColor,
+    puddle: RgbColor,
 }
 
 impl Palette {
+    pub fn get_theme(&self) -> PaletteTheme {
+        if self.background == GRASS_PALETTE.background {
+            PaletteTheme::Grass
+        } else if self.background == RAIN_PALETTE.background {
+            PaletteTheme::Rain
+        } else {
+            PaletteTheme::Clay
+        }
+    }
+
     pub fn get_color(&self, col: &PaletteColor) -> Color {
         match col {
             PaletteColor::Background => self.background.into(),
@@ -83,8 +116,46 @@ impl Palette {
             PaletteColor::PlayerAim => self.player_aim.into(),
             PaletteColor::PlayerFace => self.player_face.into(),
             PaletteColor::PlayerCharge => self.player_charge.into(),
+            PaletteColor::PlayerLeft => self.player_left.into(),
+            PaletteColor::PlayerRight => self.player_right.into(),
             PaletteColor::Text => self.score_text.into(),
             PaletteColor::Shadow => self.shadow.into(),
+            PaletteColor::Puddle => self.puddle.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaletteTheme {
+    Grass,
+    Clay,
+    // the court gets slick puddles
+    Rain,
+}
+
+impl PaletteTheme {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "grass" => Some(PaletteTheme::Grass),
+            "clay" => Some(PaletteTheme::Clay),
+            "rain" => Some(PaletteTheme::Rain),
+            _ => None,
+        }
+    }
+
+    pub fn get_palette(&self) -> Palette {
+        match self {
+            PaletteTheme::Grass => GRASS_PALETTE,
+            PaletteTheme::Clay => CLAY_PALETTE,
+            PaletteTheme::Rain => RAIN_PALETTE,
+        }
+    }
+
+    fn get_next(&self) -> Self {
+        match self {
+            PaletteTheme::Grass => PaletteTheme::Clay,
+            PaletteTheme::Clay => PaletteTheme::Rain,
+            PaletteTheme::Rain => PaletteTheme::Grass,
         }
     }
 }
@@ -102,8 +173,11 @@ pub const GRASS_PALETTE: Palette = Palette {
     player_aim: RgbColor::new(251, 247, 243),
     player_face: RgbColor::new(32, 40, 61),
     player_charge: RgbColor::new(109, 141, 138),
+    player_left: RgbColor::new(229, 139, 112),
+    player_right: RgbColor::new(122, 173, 201),
     score_text: RgbColor::new(251, 247, 243),
     shadow: RgbColor::new_with_alpha(0, 8, 24, 80),
+    puddle: RgbColor::new_with_alpha(32, 40, 61, 110),
 };
 
 // based on
@@ -119,8 +193,30 @@ pub const CLAY_PALETTE: Palette = Palette {
     player_aim: RgbColor::new(246, 237, 205),
     player_face: RgbColor::new(101, 80, 87),
     player_charge: RgbColor::new(203, 129, 117),
+    player_left: RgbColor::new(240, 207, 142),
+    player_right: RgbColor::new(109, 141, 138),
     score_text: RgbColor::new(246, 237, 205),
     shadow: RgbColor::new_with_alpha(22, 12, 0, 80),
+    puddle: RgbColor::new_with_alpha(101, 80, 87, 110),
+};
+
+// an overcast take on the grass palette
+pub const RAIN_PALETTE: Palette = Palette {
+    background: RgbColor::new(38, 43, 68),
+    court: RgbColor::new(84, 104, 118),
+    court_lines: RgbColor::new(234, 240, 246),
+    court_pickets: RgbColor::new(139, 155, 180),
+    ball: RgbColor::new(238, 214, 120),
+    ball_trail: RgbColor::new(234, 240, 246),
+    player: RgbColor::new(234, 240, 246),
+    player_aim: RgbColor::new(234, 240, 246),
+    player_face: RgbColor::new(38, 43, 68),
+    player_charge: RgbColor::new(139, 155, 180),
+    player_left: RgbColor::new(228, 166, 114),
+    player_right: RgbColor::new(115, 190, 211),
+    score_text: RgbColor::new(234, 240, 246),
+    shadow: RgbColor::new_with_alpha(8, 10, 28, 90),
+    puddle: RgbColor::new_with_alpha(58, 72, 104, 170),
 };
 
 #[derive(Component, Clone, Copy)]
@@ -135,32 +231,51 @@ pub enum PaletteColor {
     PlayerAim,
     PlayerFace,
     PlayerCharge,
+    PlayerLeft,
+    PlayerRight,
     Text,
     Shadow,
+    Puddle,
 }
 
 fn on_palette_changed(
     mut commands: Commands,
     palette: Res<Palette>,
-    sprite_q: Query<(Entity, &PaletteColor, Option<&Sprite>, Option<&Text>)>,
+    sprite_q: Query<(
+        Entity,
+        &PaletteColor,
+        Option<&Sprite>,
+        Option<&TextureAtlasSprite>,
+        Option<&Text>,
+    )>,
 ) {
     if palette.is_changed() {
-        for (e, col, sprite, text) in sprite_q.iter() {
+        for (e, col, sprite, atlas_sprite, text) in sprite_q.iter() {
             if let Some(sprite) = sprite {
                 commands.entity(e).insert(Animator::new(Tween::new(
                     EaseFunction::QuadraticInOut,
                     TweeningType::Once,
-                    std::time::Duration::from_millis(1000),
+                    std::time::Duration::from_millis(PALETTE_TWEEN_MS),
                     SpriteColorLens {
                         start: sprite.color,
                         end: palette.get_color(col),
                     },
                 )));
+            } else if let Some(sprite) = atlas_sprite {
+                commands.entity(e).insert(Animator::new(Tween::new(
+                    EaseFunction::QuadraticInOut,
+                    TweeningType::Once,
+                    std::time::Duration::from_millis(PALETTE_TWEEN_MS),
+                    AtlasSpriteColorLens {
+                        start: sprite.color,
+                        end: palette.get_color(col),
+                    },
+                )));
             } else if let Some(text) = text {
                 commands.entity(e).insert(Animator::new(Tween::new(
                     EaseFunction::QuadraticInOut,
                     TweeningType::Once,
-                    std::time::Duration::from_millis(1000),
+                    std::time::Duration::from_millis(PALETTE_TWEEN_MS),
                     TextColorLens {
                         start: text.sections[0].style.color,
                         end: palette.get_color(col),
@@ -181,15 +296,148 @@ fn on_sprite_added(
     }
 }
 
+fn on_atlas_sprite_added(
+    palette: Res<Palette>,
+    mut q: Query<(&PaletteColor, &mut TextureAtlasSprite), Added<TextureAtlasSprite>>,
+) {
+    for (col, mut sprite) in q.iter_mut() {
+        sprite.color = palette.get_color(col);
+    }
+}
+
 fn on_text_added(palette: Res<Palette>, mut q: Query<(&PaletteColor, &mut Text), Added<Text>>) {
     for (col, mut text) in q.iter_mut() {
         text.sections[0].style.color = palette.get_color(col);
     }
 }
 
-fn on_trail_added(palette: Res<Palette>, mut q: Query<&mut DrawMode, Added<Trail>>) {
-    for mut draw_mode in q.iter_mut() {
-        *draw_mode = DrawMode::Fill(FillMode::color(palette.get_color(&PaletteColor::BallTrail)));
+fn on_ui_color_added(
+    palette: Res<Palette>,
+    mut q: Query<(&PaletteColor, &mut UiColor), Added<UiColor>>,
+) {
+    for (col, mut ui_color) in q.iter_mut() {
+        ui_color.0 = palette.get_color(col);
+    }
+}
+
+// covers a PaletteColor added (or swapped) after the sprite, text or ui color
+fn on_palette_color_changed(
+    palette: Res<Palette>,
+    mut q: Query<
+        (
+            &PaletteColor,
+            Option<&mut Sprite>,
+            Option<&mut TextureAtlasSprite>,
+            Option<&mut Text>,
+            Option<&mut UiColor>,
+        ),
+        Changed<PaletteColor>,
+    >,
+) {
+    for (col, sprite, atlas_sprite, text, ui_color) in q.iter_mut() {
+        let color = palette.get_color(col);
+        if let Some(mut sprite) = sprite {
+            sprite.color = color;
+        }
+        if let Some(mut sprite) = atlas_sprite {
+            sprite.color = color;
+        }
+        if let Some(mut text) = text {
+            text.sections[0].style.color = color;
+        }
+        if let Some(mut ui_color) = ui_color {
+            ui_color.0 = color;
+        }
+    }
+}
+
+// once the palette tween is over, anything the tween missed or started from a stale color gets fixed
+// only the rgb is compared, so fades and other alpha animations are left alone
+fn reconcile_palette_colors(
+    palette: Res<Palette>,
+    time: Res<Time>,
+    mut settle_timer: Local<Option<Timer>>,
+    mut q: Query<(
+        &PaletteColor,
+        Option<&mut Sprite>,
+        Option<&mut TextureAtlasSprite>,
+        Option<&mut Text>,
+    )>,
+) {
+    if palette.is_changed() {
+        *settle_timer = Some(Timer::new(
+            std::time::Duration::from_millis(PALETTE_TWEEN_MS),
+            false,
+        ));
+        return;
+    }
+
+    let settled = match settle_timer.as_mut() {
+        Some(timer) => timer.tick(time.delta()).finished(),
+        None => false,
+    };
+    if !settled {
+        return;
+    }
+    *settle_timer = None;
+
+    let mut fixed = 0;
+    for (col, sprite, atlas_sprite, text) in q.iter_mut() {
+        let target = palette.get_color(col);
+        if let Some(mut sprite) = sprite {
+            if let Some(color) = get_reconciled_color(sprite.color, target) {
+                sprite.color = color;
+                fixed += 1;
+            }
+        }
+        if let Some(mut sprite) = atlas_sprite {
+            if let Some(color) = get_reconciled_color(sprite.color, target) {
+                sprite.color = color;
+                fixed += 1;
+            }
+        }
+        if let Some(mut text) = text {
+            if let Some(color) = get_reconciled_color(text.sections[0].style.color, target) {
+                text.sections[0].style.color = color;
+                fixed += 1;
+            }
+        }
+    }
+
+    if fixed > 0 {
+        debug!("Reconciled {} palette colors", fixed);
+    }
+}
+
+// the target rgb with the current alpha, if the rgb differs
+fn get_reconciled_color(current: Color, target: Color) -> Option<Color> {
+    let [r, g, b, a] = current.as_rgba_f32();
+    let [target_r, target_g, target_b, _] = target.as_rgba_f32();
+    let matches = [(r, target_r), (g, target_g), (b, target_b)]
+        .iter()
+        .all(|(c, t)| (c - t).abs() < COLOR_EPSILON);
+
+    (!matches).then(|| Color::rgba(target_r, target_g, target_b, a))
+}
+
+// there's no UiColor lens, so just swap the colors
+fn on_ui_palette_changed(palette: Res<Palette>, mut q: Query<(&PaletteColor, &mut UiColor)>) {
+    if palette.is_changed() {
+        for (col, mut ui_color) in q.iter_mut() {
+            ui_color.0 = palette.get_color(col);
+        }
+    }
+}
+
+// trails are lyon shapes, so they're recolored through the DrawMode
+fn update_trail_color(
+    palette: Res<Palette>,
+    mut q: Query<(&PaletteColor, &mut DrawMode, ChangeTrackers<PaletteColor>), With<Trail>>,
+) {
+    for (col, mut draw_mode, col_tracker) in q.iter_mut() {
+        if palette.is_changed() || col_tracker.is_changed() {
+            *draw_mode = DrawMode::Fill(FillMode::color(palette.get_color(col)));
+        }
     }
 }
 
@@ -208,12 +456,7 @@ fn on_court_added(palette: Res<Palette>, mut q: Query<&mut DrawMode, With<Court>
 fn handle_palette_input(mut palette: ResMut<Palette>, input: Res<PlayerInput>) {
     for id in 0..=4 {
         if input.just_pressed(id, InputAction::ChangePalette) {
-            let is_grass = palette.background == GRASS_PALETTE.background;
-            *palette = if is_grass {
-                CLAY_PALETTE
-            } else {
-                GRASS_PALETTE
-            };
+            *palette = palette.get_theme().get_next().get_palette();
 
             break;
         }
@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+
+#[cfg(feature = "capture")]
+use crate::capture::{CaptureKind, CaptureRequest};
+
+const PHOTO_KEY: KeyCode = KeyCode::F10;
+const SCALE_KEY: KeyCode = KeyCode::PageUp;
+// supersampling of the shot, cycled by the scale key
+const SCALES: [f32; 3] = [2., 3., 4.];
+
+// hides the whole HUD for a clean view of the court
+// with the capture feature, entering it also saves a supersampled png of the court
+pub struct PhotoModePlugin;
+impl Plugin for PhotoModePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<PhotoMode>()
+            .add_system(toggle_photo_mode)
+            .add_system(cycle_scale)
+            .add_system_to_stage(CoreStage::PostUpdate, hide_hud);
+    }
+}
+
+#[derive(Default)]
+pub struct PhotoMode {
+    pub active: bool,
+    // index into SCALES, kept for the next photo
+    scale_i: usize,
+    // shown again once the photo mode is over, the rest stays hidden
+    hidden: Vec<Entity>,
+}
+
+impl PhotoMode {
+    pub fn get_scale(&self) -> f32 {
+        SCALES[self.scale_i]
+    }
+}
+
+fn toggle_photo_mode(
+    keys: Res<Input<KeyCode>>,
+    mut photo: ResMut<PhotoMode>,
+    mut visibility_q: Query<&mut Visibility, With<Node>>,
+    #[cfg(feature = "capture")] mut capture: ResMut<CaptureRequest>,
+) {
+    if !keys.just_pressed(PHOTO_KEY) {
+        return;
+    }
+
+    photo.active = !photo.active;
+    debug!("Photo mode: {}", photo.active);
+
+    // the offscreen render leaves out the HUD, so the shot can be taken right away
+    #[cfg(feature = "capture")]
+    if photo.active {
+        capture.request(CaptureKind::Photo, photo.get_scale());
+    }
+
+    if !photo.active {
+        for e in photo.hidden.drain(..) {
+            if let Ok(mut visibility) = visibility_q.get_mut(e) {
+                visibility.is_visible = true;
+            }
+        }
+    }
+}
+
+// picked before entering the photo mode, which takes the shot
+fn cycle_scale(keys: Res<Input<KeyCode>>, mut photo: ResMut<PhotoMode>) {
+    if keys.just_pressed(SCALE_KEY) {
+        photo.scale_i = (photo.scale_i + 1) % SCALES.len();
+        debug!("Photo scale: {}x", photo.get_scale());
+    }
+}
+
+// every frame, the HUD systems keep showing their nodes (and spawning new ones)
+fn hide_hud(
+    mut photo: ResMut<PhotoMode>,
+    mut visibility_q: Query<(Entity, &mut Visibility), With<Node>>,
+) {
+    if !photo.active {
+        return;
+    }
+
+    for (e, mut visibility) in visibility_q.iter_mut() {
+        if visibility.is_visible {
+            visibility.is_visible = false;
+            if !photo.hidden.contains(&e) {
+                photo.hidden.push(e);
+            }
+        }
+    }
+}
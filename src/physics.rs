@@ -1,7 +1,35 @@
-use heron::PhysicsLayer;
+use heron::{CollisionLayers, PhysicsLayer};
 
-#[derive(PhysicsLayer)]
+#[derive(PhysicsLayer, Clone, Copy)]
 #[allow(dead_code)]
 pub enum PhysLayer {
-    All,
+    Ball,
+    // only active during the swing
+    SwingHitbox,
+    Region,
+    Net,
+    Obstacle,
+    // players blocking each other
+    PlayerBody,
+}
+
+impl PhysLayer {
+    // the layer as a group and the layers it interacts with
+    pub fn get_layers(&self) -> CollisionLayers {
+        let masks: &[PhysLayer] = match self {
+            PhysLayer::Ball => &[
+                PhysLayer::SwingHitbox,
+                PhysLayer::Region,
+                PhysLayer::Net,
+                PhysLayer::Obstacle,
+            ],
+            PhysLayer::SwingHitbox | PhysLayer::Region | PhysLayer::Net => &[PhysLayer::Ball],
+            PhysLayer::Obstacle => &[PhysLayer::Ball, PhysLayer::PlayerBody],
+            PhysLayer::PlayerBody => &[PhysLayer::PlayerBody, PhysLayer::Obstacle],
+        };
+
+        CollisionLayers::none()
+            .with_group(*self)
+            .with_masks(masks.iter().copied())
+    }
 }
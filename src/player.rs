@@ -1,17 +1,37 @@
 use crate::{
-    ai_player_controller::AiPlayer,
-    animation::{inverse_lerp, TransformRotation, TweenDoneAction},
-    ball::{spawn_ball, Ball, BallBouncedEvt, BallStatus},
+    aim_settings::PlayerAimSettings,
+    animation::{inverse_lerp, GroundShadow, TransformRotation},
+    asset::SpriteAtlas,
+    assist::PlayerAssist,
+    ball::{
+        despawn_ball, spawn_ball, spawn_point_ball, Ball, BallBouncedEvt, BallHitEvt, BallStatus,
+        ServeLetEvt,
+    },
+    camera::TargetCamScale,
+    challenge::{LineCall, LineCallEvt},
+    config::{GameConfig, PlayerConfig},
+    cooldown_indicator::spawn_cooldown_arcs,
+    cosmetic::Loadouts,
     extra::TransformBundle,
+    fatigue::{FatigueIndicator, PlayerFatigue},
     impl_player_action_timer,
     level::{CourtRegion, CourtSettings, InitialRegion, Net, NetOffset, ServingRegion},
+    mode::GameMode,
     palette::PaletteColor,
     physics::PhysLayer,
     player_action::{ActionTimer, PlayerActionStatus},
     player_animation::{AgentAnimationData, PlayerAnimation},
     render::{PLAYER_Z, SHADOW_Z},
-    score::{add_point_to_score, PlayerScore, Score},
-    trail::FadeOutTrail,
+    reset::DespawnOnExit,
+    rng::GameRng,
+    score::{
+        add_point_to_score, GameOverEvt, MatchRules, PlayerScore, PointKind, PointOutcome,
+        PointReason, PointValue, Score, ScoreChangedEvt,
+    },
+    shot_clock::ShotClockExpiredEvt,
+    sprite_sheet::SpriteSheetPlayer,
+    timer::GameTimer,
+    weather::{get_puddle_at, Puddle},
     GameSetupPhase, GameState, WIN_HEIGHT, WIN_WIDTH,
 };
 use bevy::{
@@ -19,37 +39,70 @@ use bevy::{
     math::Vec2,
     prelude::*,
     sprite::{collide_aabb::collide, Sprite, SpriteBundle},
+    transform::TransformSystem,
+    utils::HashSet,
 };
 use bevy_extensions::Vec2Conversion;
+#[cfg(feature = "inspector")]
 use bevy_inspector_egui::Inspectable;
 use bevy_time::{ScaledTime, ScaledTimeDelta};
-use bevy_tweening::lens::TransformScaleLens;
 use bevy_tweening::*;
 use heron::*;
-use interpolation::EaseFunction;
-use std::time::Duration;
+use rand::Rng;
 
-pub const AIM_RING_ROTATION_DEG: f32 = 50.;
 // todo: get rid of this by fixing the animation system order and sue an enum label for that
 pub const SWING_LABEL: &str = "swing";
+pub const AIM_LABEL: &str = "aim";
+const MOVE_LABEL: &str = "move";
+// portion of the overlap resolved per second
+const BODY_PUSH_OUT_SPEED: f32 = 12.;
+// how quickly a sliding player can change direction
+const SLIDE_TURN_SPEED: f32 = 10.;
+// movement speed while stumbling after a whiff
+const WHIFF_SPEED_MULT: f32 = 0.35;
+// the swing charges up to full speed, holds it through the perfect window and then gets wild
+const MIN_SWING_MULT: f32 = 0.4;
+const PERFECT_CHARGE_START_SEC: f32 = 0.5;
+const PERFECT_CHARGE_END_SEC: f32 = 0.75;
+// overcharging past the perfect window for this long gives the max aim jitter
+const OVERCHARGE_MAX_SEC: f32 = 0.75;
+const OVERCHARGE_MAX_JITTER_DEG: f32 = 20.;
 
 pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_system_set(
-            SystemSet::on_enter(GameState::Game).with_system(setup.label(GameSetupPhase::Player)),
+            SystemSet::on_enter(GameState::Game).with_system(
+                setup
+                    .label(GameSetupPhase::Player)
+                    .after(GameSetupPhase::Level),
+            ),
         )
         .add_system_set(
             SystemSet::on_update(GameState::Game)
-                .with_system(move_player.before(SWING_LABEL))
-                .with_system(aim)
+                .with_system(move_player.label(MOVE_LABEL).before(SWING_LABEL))
+                .with_system(push_out_bodies.after(MOVE_LABEL))
+                .with_system(aim.label(AIM_LABEL))
                 .with_system(swing)
-                .with_system(on_ball_bounced),
+                .with_system(on_ball_bounced)
+                .with_system(jump)
+                .with_system(apply_player_config)
+                .with_system(on_serve_let),
+        )
+        // overrides the body root animation
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            apply_jump_height.before(TransformSystem::TransformPropagate),
         );
     }
 }
 
-#[derive(Component, Inspectable)]
+// controlled by the ai controller (when built with the ai feature)
+#[derive(Debug, Clone, Component)]
+pub struct AiPlayer;
+
+#[derive(Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct Player {
     pub id: usize,
     pub aim_e: Entity,
@@ -58,6 +111,15 @@ pub struct Player {
 }
 
 impl Player {
+    pub fn new(id: usize, aim_e: Entity, aim_charge_e: Entity) -> Self {
+        Self {
+            id,
+            side: if is_left_player_id(id) { -1. } else { 1. },
+            aim_e,
+            aim_charge_e,
+        }
+    }
+
     pub fn is_left(&self) -> bool {
         is_left_player_id(self.id)
     }
@@ -76,7 +138,8 @@ pub fn is_left_player_id(id: usize) -> bool {
     id == 1
 }
 
-#[derive(Default, Component, Inspectable)]
+#[derive(Default, Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct PlayerMovement {
     speed: f32,
     charging_speed: f32,
@@ -86,11 +149,12 @@ pub struct PlayerMovement {
     last_non_zero_raw_dir: Vec2,
 }
 
-#[derive(Default, Component, Inspectable)]
+#[derive(Default, Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct PlayerDash {
     pub status: PlayerActionStatus<Vec2>,
-    #[inspectable(ignore)]
-    pub timer: Timer,
+    #[cfg_attr(feature = "inspector", inspectable(ignore))]
+    pub timer: GameTimer,
     pub duration_sec: f32,
     cooldown_sec: f32,
     speed: f32,
@@ -98,32 +162,112 @@ pub struct PlayerDash {
 
 impl_player_action_timer!(PlayerDash, Vec2);
 
-#[derive(Default, Component, Inspectable)]
+#[derive(Default, Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct PlayerAim {
     pub raw_dir: Vec2,
     pub dir: Vec2,
+    // raw_dir eased by the aim smoothing setting
+    smoothed_raw_dir: Vec2,
 }
 
-#[derive(Component, Inspectable)]
+#[derive(Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct SwingRangeSprite;
 
-#[derive(Default, Component, Inspectable)]
+// collider used for blocking other players
+#[derive(Component)]
+pub struct PlayerBody;
+
+#[derive(Default, Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct PlayerSwing {
     pub status: PlayerActionStatus<f32>,
     pub duration_sec: f32,
     pub cooldown_sec: f32,
-    #[inspectable(ignore)]
-    pub timer: Timer,
+    // recovery after a swing that didn't hit anything
+    pub whiff_recovery_sec: f32,
+    #[cfg_attr(feature = "inspector", inspectable(ignore))]
+    pub timer: GameTimer,
+    // of the last released swing, 0 within the perfect window, 1 at the max aim jitter
+    pub overcharge: f32,
+    whiffed: bool,
 }
 
 impl PlayerSwing {
     pub fn start_cooldown(&mut self) {
         self.status = PlayerActionStatus::Cooldown;
-        self.timer = Timer::from_seconds(self.cooldown_sec, false);
+        self.timer.restart(self.cooldown_sec);
+        self.whiffed = false;
+    }
+
+    pub fn is_whiffing(&self) -> bool {
+        self.whiffed && matches!(self.status, PlayerActionStatus::Cooldown)
+    }
+
+    // the speed multiplier the swing would get if released now
+    pub fn get_charge_multiplier(&self) -> Option<f32> {
+        match self.status {
+            PlayerActionStatus::Charging(duration) => Some(get_swing_multiplier_clamped(duration)),
+            _ => None,
+        }
     }
 }
 
-impl_player_action_timer!(PlayerSwing, f32);
+impl ActionTimer<f32> for PlayerSwing {
+    fn get_cooldown_sec(&self) -> f32 {
+        self.cooldown_sec
+    }
+
+    fn get_timer_mut(&mut self) -> &mut GameTimer {
+        &mut self.timer
+    }
+
+    fn get_action_status_mut(&mut self) -> &mut PlayerActionStatus<f32> {
+        &mut self.status
+    }
+
+    // whiffs get their own recovery
+    fn on_active_finished(&mut self) {
+        self.status = PlayerActionStatus::Cooldown;
+        self.timer.restart(self.whiff_recovery_sec);
+        self.whiffed = true;
+    }
+}
+
+// vertical model mirroring BallBounce
+#[derive(Default, Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
+pub struct PlayerJump {
+    // active while airborne
+    pub status: PlayerActionStatus<f32>,
+    #[cfg_attr(feature = "inspector", inspectable(ignore))]
+    pub timer: GameTimer,
+    pub cooldown_sec: f32,
+    pub height: f32,
+    velocity: f32,
+    gravity: f32,
+    jump_velocity: f32,
+}
+
+impl PlayerJump {
+    pub fn start(&mut self) {
+        self.velocity = self.jump_velocity;
+        self.status = PlayerActionStatus::Active(self.jump_velocity);
+        let airtime_sec = self.get_airtime_sec();
+        self.timer.restart(airtime_sec);
+    }
+
+    pub fn is_airborne(&self) -> bool {
+        self.height > 0.
+    }
+
+    fn get_airtime_sec(&self) -> f32 {
+        2. * self.jump_velocity / -self.gravity
+    }
+}
+
+impl_player_action_timer!(PlayerJump, f32);
 
 #[derive(Bundle)]
 pub struct PlayerBundle {
@@ -131,49 +275,78 @@ pub struct PlayerBundle {
     movement: PlayerMovement,
     dash: PlayerDash,
     swing: PlayerSwing,
+    jump: PlayerJump,
     score: PlayerScore,
+    fatigue: PlayerFatigue,
 }
 
 // todo: just remove the bundle and insert the components directly?
 impl PlayerBundle {
-    fn new(id: usize, initial_dir: Vec2, aim_e: Entity, aim_charge_e: Entity) -> Self {
+    fn new(id: usize, aim_e: Entity, aim_charge_e: Entity, config: &PlayerConfig) -> Self {
         Self {
-            player: Player {
-                id,
-                side: -initial_dir.x.signum(),
-                aim_e,
-                aim_charge_e,
-            },
+            player: Player::new(id, aim_e, aim_charge_e),
             movement: PlayerMovement {
-                speed: 550.,
-                charging_speed: 125.,
-                time_to_max_speed: 0.11,
+                speed: config.speed,
+                charging_speed: config.charging_speed,
+                time_to_max_speed: config.time_to_max_speed,
                 ..Default::default()
             },
             dash: PlayerDash {
-                speed: 2200.,
-                duration_sec: 0.085,
-                cooldown_sec: 0.5,
+                speed: config.dash_speed,
+                duration_sec: config.dash_duration_sec,
+                cooldown_sec: config.dash_cooldown_sec,
                 ..Default::default()
             },
             swing: PlayerSwing {
-                duration_sec: 0.35,
-                cooldown_sec: 0.35,
+                duration_sec: config.swing_duration_sec,
+                cooldown_sec: config.swing_cooldown_sec,
+                whiff_recovery_sec: config.swing_whiff_recovery_sec,
+                ..Default::default()
+            },
+            jump: PlayerJump {
+                cooldown_sec: config.jump_cooldown_sec,
+                gravity: config.jump_gravity,
+                jump_velocity: config.jump_velocity,
                 ..Default::default()
             },
             score: PlayerScore {
                 ..Default::default()
             },
+            fatigue: PlayerFatigue::new(),
         }
     }
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>, region: Res<InitialRegion>) {
-    if cfg!(feature = "debug") {
-        spawn_player(1, &mut commands, &asset_server, &region);
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    atlas: Res<SpriteAtlas>,
+    region: Res<InitialRegion>,
+    config: Res<GameConfig>,
+    mode: Res<GameMode>,
+    loadouts: Res<Loadouts>,
+) {
+    if cfg!(feature = "debug") || !mode.has_opponent() {
+        spawn_player(
+            1,
+            &mut commands,
+            &asset_server,
+            &atlas,
+            &region,
+            &config,
+            &loadouts,
+        );
     } else {
         for id in 1..=2 {
-            spawn_player(id, &mut commands, &asset_server, &region);
+            spawn_player(
+                id,
+                &mut commands,
+                &asset_server,
+                &atlas,
+                &region,
+                &config,
+                &loadouts,
+            );
         }
     }
 }
@@ -182,7 +355,10 @@ pub fn spawn_player<'a, 'b, 'c>(
     id: usize,
     commands: &'c mut Commands<'a, 'b>,
     asset_server: &Res<AssetServer>,
+    atlas: &SpriteAtlas,
     region: &Res<InitialRegion>,
+    config: &GameConfig,
+    loadouts: &Loadouts,
 ) -> EntityCommands<'a, 'b, 'c> {
     let x = WIN_WIDTH / 4.;
     let x = if id == 1 { -x } else { x };
@@ -194,17 +370,19 @@ pub fn spawn_player<'a, 'b, 'c>(
     }
 
     let initial_dir = if is_left { Vec2::X } else { -Vec2::X };
+    let skin = loadouts.0[id - 1].player_skin;
+    let body_sprite = atlas.get_sprite(skin.get_body_texture());
 
     let mut body_e = None;
     let mut body_root_e = None;
 
     // face
     let face_e = commands
-        .spawn_bundle(SpriteBundle {
-            texture: asset_server.load("art-ish/face_happy.png"),
-            sprite: Sprite {
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: atlas.handle.clone(),
+            sprite: TextureAtlasSprite {
                 flip_x: !is_left,
-                ..Default::default()
+                ..atlas.get_sprite("art-ish/face_happy.png")
             },
             ..Default::default()
         })
@@ -225,6 +403,7 @@ pub fn spawn_player<'a, 'b, 'c>(
         .insert(PlayerAim {
             dir: initial_dir,
             raw_dir: Vec2::ZERO,
+            smoothed_raw_dir: Vec2::ZERO,
         })
         .with_children(|b| {
             // aim arrow
@@ -251,19 +430,23 @@ pub fn spawn_player<'a, 'b, 'c>(
         .id();
 
     let mut p = commands.spawn_bundle(TransformBundle::from_xyz(x, player_y, PLAYER_Z));
-    p.insert_bundle(PlayerBundle::new(id, initial_dir, aim_e, aim_charge_e))
+    let bundle = PlayerBundle::new(id, aim_e, aim_charge_e, &config.player);
+    p.insert_bundle(bundle)
         .insert(RigidBody::KinematicPositionBased)
-        .insert(CollisionShape::Sphere { radius: 100. })
+        .insert(CollisionShape::Sphere {
+            radius: config.player.swing_radius,
+        })
         .insert(CollisionLayers::none())
         .insert(Name::new("Player"))
+        .insert(DespawnOnExit(GameState::Game))
         .add_child(aim_e)
         .add_child(aim_charge_e)
         .with_children(|b| {
             // circle
             let rotation_speed = if is_left {
-                -AIM_RING_ROTATION_DEG
+                -config.player.aim_ring_rotation_deg
             } else {
-                AIM_RING_ROTATION_DEG
+                config.player.aim_ring_rotation_deg
             };
             b.spawn_bundle(SpriteBundle {
                 texture: asset_server.load("art-ish/player_circle.png"),
@@ -274,6 +457,34 @@ pub fn spawn_player<'a, 'b, 'c>(
             .insert(SwingRangeSprite)
             .insert(TransformRotation::new(rotation_speed.to_radians()));
 
+            // fatigue bar
+            b.spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(60., 6.)),
+                    ..Default::default()
+                },
+                transform: Transform {
+                    translation: Vec3::new(0., -60., -0.2),
+                    scale: Vec3::new(0., 1., 1.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(PaletteColor::PlayerCharge)
+            .insert(FatigueIndicator)
+            .insert(Name::new("player_fatigue"));
+
+            spawn_cooldown_arcs(b);
+
+            // body collider
+            b.spawn_bundle(TransformBundle::default())
+                .insert(CollisionShape::Sphere {
+                    radius: config.player.body_radius,
+                })
+                .insert(get_body_layers(config.player.body_blocking))
+                .insert(PlayerBody)
+                .insert(Name::new("player_body_collider"));
+
             // body root
             body_root_e = Some(
                 b.spawn_bundle(TransformBundle::from_xyz(0., 0., 0.))
@@ -282,40 +493,52 @@ pub fn spawn_player<'a, 'b, 'c>(
                     .with_children(|b| {
                         // body
                         body_e = Some(
-                            b.spawn_bundle(SpriteBundle {
-                                texture: asset_server.load("art-ish/player_body.png"),
+                            b.spawn_bundle(SpriteSheetBundle {
+                                texture_atlas: atlas.handle.clone(),
+                                sprite: body_sprite.clone(),
                                 ..Default::default()
                             })
-                            .insert(PaletteColor::Player)
+                            .insert(SpriteSheetPlayer::new(
+                                atlas.handle.clone(),
+                                body_sprite.index,
+                            ))
+                            .insert(skin.get_body_color(is_left))
                             .insert(Animator::<Transform>::default())
                             .insert(Name::new("player_body"))
-                            .with_children(|b| {
-                                // shadow
-                                b.spawn_bundle(SpriteBundle {
-                                    texture: asset_server.load("art-ish/player_body.png"),
-                                    transform: Transform {
-                                        scale: Vec3::new(1.0, 0.5, 1.),
-                                        translation: Vec3::new(-5., -30., -PLAYER_Z + SHADOW_Z),
-                                        ..Default::default()
-                                    },
-                                    ..Default::default()
-                                })
-                                .insert(PaletteColor::Shadow)
-                                .insert(Name::new("player_shadow"));
-                            })
                             .id(),
                         );
                     })
                     .insert(Animator::<Transform>::default())
                     .id(),
             );
+
+            // shadow - stays on the ground while the body root hops
+            let shadow_offset = Vec2::new(-5., -30.);
+            let shadow_scale = Vec2::new(1.0, 0.5);
+            b.spawn_bundle(SpriteSheetBundle {
+                texture_atlas: atlas.handle.clone(),
+                sprite: atlas.get_sprite(skin.get_body_texture()),
+                transform: Transform {
+                    scale: shadow_scale.extend(1.),
+                    translation: shadow_offset.extend(-PLAYER_Z + SHADOW_Z),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(PaletteColor::Shadow)
+            .insert(GroundShadow {
+                caster_e: body_root_e.unwrap(),
+                offset: shadow_offset,
+                scale: shadow_scale,
+                max_height: 120.,
+            })
+            .insert(Name::new("player_shadow"));
         })
-        .insert(AgentAnimationData {
-            animation: PlayerAnimation::Idle,
+        .insert(AgentAnimationData::new(
             face_e,
-            body_e: body_e.unwrap(),
-            body_root_e: body_root_e.unwrap(),
-        });
+            body_e.unwrap(),
+            body_root_e.unwrap(),
+        ));
     p
 }
 
@@ -328,25 +551,63 @@ fn move_player(
         &mut Transform,
         &PlayerSwing,
         &mut AgentAnimationData,
+        &PlayerFatigue,
+        &PlayerJump,
     )>,
     net_q: Query<&GlobalTransform, With<Net>>,
+    ball_q: Query<(&Ball, &BallStatus)>,
+    puddle_q: Query<(&Puddle, &GlobalTransform)>,
     time: ScaledTime,
     net_offset: Res<NetOffset>,
+    court_set: Res<CourtSettings>,
+    cam_scale: Res<TargetCamScale>,
 ) {
-    for (player, mut player_movement, player_dash, mut player_t, player_swing, mut p_anim) in
-        query.iter_mut()
+    // the server is held behind the baseline until the serve is hit
+    let serve = ball_q.iter().find_map(|(ball, status)| match status {
+        BallStatus::Serve(region, _, player_id) if ball.dir == Vec2::ZERO => {
+            Some((*player_id, *region))
+        }
+        _ => None,
+    });
+
+    for (
+        player,
+        mut player_movement,
+        player_dash,
+        mut player_t,
+        player_swing,
+        mut p_anim,
+        fatigue,
+        player_jump,
+    ) in query.iter_mut()
     {
+        // the jump animation plays until landing
+        let airborne = player_jump.is_airborne();
+
         let charging = matches!(player_swing.status, PlayerActionStatus::Charging(_));
         let speed = if charging {
             player_movement.charging_speed
         } else {
             player_movement.speed
         };
-        let dir = if player_movement.raw_dir != Vec2::ZERO {
+        let mut speed = speed * fatigue.speed_mult;
+        if player_swing.is_whiffing() {
+            speed *= WHIFF_SPEED_MULT;
+        }
+        let mut dir = if player_movement.raw_dir != Vec2::ZERO {
             player_movement.raw_dir
         } else {
             player_movement.last_non_zero_raw_dir
         };
+
+        // puddles are slick - the momentum carries and turning takes a while
+        let traction = get_puddle_at(puddle_q.iter(), player_t.translation.truncate())
+            .map_or(1., |puddle| puddle.traction);
+        if traction < 1. && player_movement.last_non_zero_raw_dir != Vec2::ZERO {
+            let turn_t = (traction * SLIDE_TURN_SPEED * time.scaled_delta_seconds()).min(1.);
+            dir = player_movement.last_non_zero_raw_dir.lerp(dir, turn_t);
+        }
+
         let mut move_by = (dir * speed).to_vec3();
         let mut dashing = false;
 
@@ -364,7 +625,7 @@ fn move_player(
             } else {
                 time.scaled_delta_seconds()
             };
-            player_movement.easing_time += ease_time_delta;
+            player_movement.easing_time += ease_time_delta * traction;
             player_movement.easing_time = player_movement
                 .easing_time
                 .clamp(0., player_movement.time_to_max_speed);
@@ -383,11 +644,29 @@ fn move_player(
         // nice2have: get/store properly
         let player_size = Vec2::splat(80.);
         let is_left = player.is_left();
+
+        if let Some((server_id, serve_region)) = serve {
+            if server_id == player.id {
+                let player_half_w = player_size.x / 2.;
+                final_pos.x = if is_left {
+                    final_pos.x.min(court_set.left - player_half_w)
+                } else {
+                    final_pos.x.max(court_set.right + player_half_w)
+                };
+                final_pos.y = if serve_region.is_bottom() {
+                    final_pos.y.min(0.)
+                } else {
+                    final_pos.y.max(0.)
+                };
+            }
+        }
         // nice2have: get (from resource or component)
+        // the base view grows with the court
+        let area_size = Vec2::new(WIN_WIDTH, WIN_HEIGHT) * cam_scale.0;
         let player_area_size = if is_left {
-            Vec2::new(WIN_WIDTH / 2. + net_offset.0, WIN_HEIGHT)
+            Vec2::new(area_size.x / 2. + net_offset.0, area_size.y)
         } else {
-            Vec2::new(WIN_WIDTH / 2. - net_offset.0, WIN_HEIGHT)
+            Vec2::new(area_size.x / 2. - net_offset.0, area_size.y)
         };
         let pos_offset = Vec3::new(player_area_size.x / 2., 0., 0.);
         let player_area_pos = if is_left {
@@ -415,28 +694,30 @@ fn move_player(
                 }
             }
 
-            if p_anim.animation != PlayerAnimation::Idle {
-                p_anim.animation = PlayerAnimation::Idle;
+            if !airborne {
+                p_anim.request(PlayerAnimation::Idle);
             }
 
             trace!("{}: {:?}", if is_left { "LeftP" } else { "RightP" }, coll);
         } else {
-            if (final_pos - player_t.translation).length().abs() > 0.1 {
-                if !dashing {
-                    if charging && p_anim.animation != PlayerAnimation::Walking {
-                        p_anim.animation = PlayerAnimation::Walking;
-                    } else if !charging && p_anim.animation != PlayerAnimation::Running {
-                        p_anim.animation = PlayerAnimation::Running;
+            if !airborne {
+                if (final_pos - player_t.translation).length().abs() > 0.1 {
+                    if !dashing {
+                        p_anim.request(if charging {
+                            PlayerAnimation::Walking
+                        } else {
+                            PlayerAnimation::Running
+                        });
                     }
+                } else {
+                    p_anim.request(PlayerAnimation::Idle);
                 }
-            } else if p_anim.animation != PlayerAnimation::Idle {
-                p_anim.animation = PlayerAnimation::Idle;
             }
 
             player_t.translation = final_pos;
 
             if player_movement.raw_dir != Vec2::ZERO {
-                player_movement.last_non_zero_raw_dir = player_movement.raw_dir;
+                player_movement.last_non_zero_raw_dir = dir;
             }
         }
     }
@@ -444,14 +725,28 @@ fn move_player(
 
 // todo: clamp angle based on Y distance from center?
 fn aim(
-    player_q: Query<(&Player, &AgentAnimationData)>,
+    player_q: Query<(&Player, &AgentAnimationData, Option<&PlayerAimSettings>)>,
     mut aim_q: Query<(&mut PlayerAim, &mut Transform, &Parent)>,
     mut transform_q: Query<&mut Transform, Without<PlayerAim>>,
     time: ScaledTime,
 ) {
     for (mut aim, mut aim_t, aim_parent) in aim_q.iter_mut() {
-        if let Ok((p, p_anim)) = player_q.get(aim_parent.0) {
-            let mut dir = aim.raw_dir.normalize_or_zero();
+        if let Ok((p, p_anim, settings)) = player_q.get(aim_parent.0) {
+            let settings = settings.cloned().unwrap_or_default();
+            let dt = time.scaled_delta_seconds();
+
+            // frame rate independent easing, 0 smoothing follows the raw dir right away
+            let raw_dir = aim.raw_dir;
+            aim.smoothed_raw_dir = aim
+                .smoothed_raw_dir
+                .lerp(raw_dir, 1. - settings.smoothing.powf(dt * 60.));
+            let input_dir = if settings.instant {
+                raw_dir
+            } else {
+                aim.smoothed_raw_dir
+            };
+
+            let mut dir = input_dir.normalize_or_zero();
 
             if dir == Vec2::ZERO {
                 continue;
@@ -472,8 +767,8 @@ fn aim(
 
             // nice2have: extract this to extensions & for now just move to extra
             let target_rotation = Quat::from_axis_angle(-Vec3::Z, dir.angle_between(Vec2::Y));
-            let limit = 260f32.to_radians() * time.scaled_delta_seconds() * aim.raw_dir.length();
-            if target_rotation.angle_between(aim_t.rotation) <= limit {
+            let limit = settings.rotation_speed_deg.to_radians() * dt * input_dir.length();
+            if settings.instant || target_rotation.angle_between(aim_t.rotation) <= limit {
                 aim_t.rotation = Quat::from_axis_angle(-Vec3::Z, dir.angle_between(Vec2::Y));
             } else {
                 let rotate_by = if target_rotation.to_euler(EulerRot::XYZ).2
@@ -500,127 +795,494 @@ fn aim(
 
 fn swing(
     mut query: Query<(
+        &Player,
         &PlayerSwing,
         ChangeTrackers<PlayerSwing>,
         &mut CollisionLayers,
         &mut AgentAnimationData,
+        &PlayerJump,
     )>,
+    mut ev_r_hit: EventReader<BallHitEvt>,
 ) {
-    for (player_swing, player_swing_tracker, mut coll_layers, mut anim) in query.iter_mut() {
+    let hitter_ids: Vec<usize> = ev_r_hit.iter().map(|ev| ev.player_id).collect();
+
+    for (player, player_swing, player_swing_tracker, mut coll_layers, mut anim, jump) in
+        query.iter_mut()
+    {
+        // the shot only plays once the swing connects
+        if hitter_ids.contains(&player.id) {
+            anim.request(if jump.is_airborne() {
+                PlayerAnimation::Smashing
+            } else {
+                PlayerAnimation::Shooting
+            });
+        }
+
         if player_swing_tracker.is_changed() {
             match player_swing.status {
+                PlayerActionStatus::Cooldown if player_swing.is_whiffing() => {
+                    *coll_layers = CollisionLayers::none();
+                    anim.request(PlayerAnimation::Stumbling);
+                }
                 PlayerActionStatus::Ready
                 | PlayerActionStatus::Cooldown
                 | PlayerActionStatus::Charging(_) => {
                     *coll_layers = CollisionLayers::none();
                 }
                 PlayerActionStatus::Active(_) => {
-                    *coll_layers = CollisionLayers::all::<PhysLayer>();
-
-                    // 2fix: animation should fire only after collision or the timer runs out
-                    anim.animation = PlayerAnimation::Shooting;
+                    *coll_layers = PhysLayer::SwingHitbox.get_layers();
                 }
             }
         }
     }
 }
 
+fn jump(mut query: Query<(&mut PlayerJump, &mut AgentAnimationData)>, time: ScaledTime) {
+    for (mut jump, mut anim) in query.iter_mut() {
+        if !jump.is_airborne() && jump.velocity <= 0. {
+            continue;
+        }
+
+        jump.velocity += jump.gravity * time.scaled_delta_seconds();
+        jump.height = (jump.height + jump.velocity * time.scaled_delta_seconds()).max(0.);
+
+        if jump.height == 0. {
+            // landed
+            jump.velocity = 0.;
+        } else {
+            anim.request(PlayerAnimation::Jumping);
+        }
+    }
+}
+
+// grounded players' body root belongs to the animations, so it's only set back to the ground once on landing
+fn apply_jump_height(
+    player_q: Query<(Entity, &PlayerJump, &AgentAnimationData)>,
+    mut transform_q: Query<&mut Transform>,
+    mut airborne: Local<HashSet<Entity>>,
+) {
+    for (player_e, jump, anim) in player_q.iter() {
+        let height = if jump.is_airborne() {
+            airborne.insert(player_e);
+            jump.height
+        } else if airborne.remove(&player_e) {
+            0.
+        } else {
+            continue;
+        };
+
+        if let Ok(mut body_root_t) = transform_q.get_mut(anim.body_root_e) {
+            body_root_t.translation.y = height;
+        }
+    }
+}
+
+fn get_body_layers(blocking: bool) -> CollisionLayers {
+    if blocking {
+        PhysLayer::PlayerBody.get_layers()
+    } else {
+        CollisionLayers::none()
+    }
+}
+
+// soft push-out, so the players can't stack on top of each other
+fn push_out_bodies(
+    mut coll_er: EventReader<CollisionEvent>,
+    body_q: Query<&Parent, With<PlayerBody>>,
+    mut player_q: Query<&mut Transform, With<Player>>,
+    config: Res<GameConfig>,
+    time: ScaledTime,
+    mut contacts: Local<Vec<(Entity, Entity)>>,
+) {
+    for ev in coll_er.iter() {
+        let (e1, e2) = ev.collision_shape_entities();
+        if body_q.get(e1).is_err() || body_q.get(e2).is_err() {
+            continue;
+        }
+
+        if ev.is_started() {
+            contacts.push((e1, e2));
+        } else {
+            contacts.retain(|c| *c != (e1, e2) && *c != (e2, e1));
+        }
+    }
+
+    if !config.player.body_blocking {
+        contacts.clear();
+        return;
+    }
+
+    let push_t = (BODY_PUSH_OUT_SPEED * time.scaled_delta_seconds()).min(1.);
+    contacts.retain(|(e1, e2)| {
+        let (player_e1, player_e2) = match (body_q.get(*e1), body_q.get(*e2)) {
+            (Ok(p1), Ok(p2)) => (p1.0, p2.0),
+            // despawned
+            _ => return false,
+        };
+        let (pos1, pos2) = match (player_q.get(player_e1), player_q.get(player_e2)) {
+            (Ok(t1), Ok(t2)) => (t1.translation.truncate(), t2.translation.truncate()),
+            _ => return false,
+        };
+
+        let overlap = config.player.body_radius * 2. - pos1.distance(pos2);
+        if overlap > 0. {
+            let mut dir = (pos1 - pos2).normalize_or_zero();
+            if dir == Vec2::ZERO {
+                dir = Vec2::Y;
+            }
+
+            let push_by = (dir * overlap * push_t / 2.).extend(0.);
+            if let Ok(mut t) = player_q.get_mut(player_e1) {
+                t.translation += push_by;
+            }
+            if let Ok(mut t) = player_q.get_mut(player_e2) {
+                t.translation -= push_by;
+            }
+        }
+
+        true
+    });
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChargeZone {
+    Weak,
+    Perfect,
+    Overcharged,
+}
+
+impl ChargeZone {
+    pub fn from_duration(duration: f32) -> Self {
+        if duration < PERFECT_CHARGE_START_SEC {
+            ChargeZone::Weak
+        } else if duration <= PERFECT_CHARGE_END_SEC {
+            ChargeZone::Perfect
+        } else {
+            ChargeZone::Overcharged
+        }
+    }
+}
+
 pub fn get_swing_multiplier_clamped(duration: f32) -> f32 {
-    get_swing_multiplier(duration).clamp(0.4, 1.)
+    get_swing_multiplier(duration).max(MIN_SWING_MULT)
 }
 
+// overcharged swings keep the full speed, they lose accuracy instead
 pub fn get_swing_multiplier(duration: f32) -> f32 {
-    ((duration * 1.8).sin().abs() * 1.15).min(1.)
+    (duration / PERFECT_CHARGE_START_SEC).min(1.)
+}
+
+pub fn get_overcharge(duration: f32) -> f32 {
+    ((duration - PERFECT_CHARGE_END_SEC) / OVERCHARGE_MAX_SEC).clamp(0., 1.)
+}
+
+// turns the shot by a random angle of up to the max jitter scaled by the overcharge
+pub fn apply_overcharge_jitter(dir: Vec2, overcharge: f32, rng: &mut GameRng) -> Vec2 {
+    if overcharge <= 0. {
+        return dir;
+    }
+
+    let max_rad = (OVERCHARGE_MAX_JITTER_DEG * overcharge).to_radians();
+    let angle = rng.gen_range(-max_rad..=max_rad);
+    (Quat::from_rotation_z(angle) * dir.extend(0.)).truncate()
+}
+
+// the point a bounce of a rally ball decides, if any
+// the serve has to land in the receiver's court, so that bounce doesn't count against the limit
+// (volley mode has no bounces and would give away every good serve otherwise)
+pub fn get_rally_bounce_reason(
+    out_of_bounds: bool,
+    bounce_count: usize,
+    bounce_limit: usize,
+    serve_bounce: bool,
+) -> Option<PointReason> {
+    if out_of_bounds && bounce_count == 1 {
+        Some(PointReason::Out)
+    } else if !serve_bounce && bounce_count > bounce_limit {
+        Some(PointReason::TooManyBounces)
+    } else {
+        None
+    }
 }
 
 fn on_ball_bounced(
     mut commands: Commands,
     mut ev_r_ball_bounced: EventReader<BallBouncedEvt>,
-    player_q: Query<&Player>,
+    mut ev_r_shot_clock: EventReader<ShotClockExpiredEvt>,
+    player_q: Query<(&Player, Option<&PlayerAssist>)>,
     mut ball_q: Query<(&Ball, &mut BallStatus, &Transform)>,
-    asset_server: Res<AssetServer>,
+    atlas: Res<SpriteAtlas>,
     mut serving_region: ResMut<ServingRegion>,
     entity_q: Query<Entity>,
     mut score: ResMut<Score>,
+    rules: Res<MatchRules>,
+    point_value: Res<PointValue>,
     court_set: Res<CourtSettings>,
+    config: Res<GameConfig>,
+    loadouts: Res<Loadouts>,
+    mut rng: ResMut<GameRng>,
+    mode: Res<GameMode>,
+    mut ev_w_game_over: EventWriter<GameOverEvt>,
+    mut ev_w_score_changed: EventWriter<ScoreChangedEvt>,
+    mut ev_w_line_call: EventWriter<LineCallEvt>,
+    net_offset: Res<NetOffset>,
 ) {
+    // practice modes keep their own score
+    if !mode.has_opponent() {
+        return;
+    }
+
+    // (ball, losing player, fault count, reason, challengeable call)
+    let mut resolutions = Vec::new();
+
     for ev in ev_r_ball_bounced.iter() {
-        if let Ok((ball, mut status, ball_t)) = ball_q.get_mut(ev.ball_e) {
+        if let Ok((ball, status, _)) = ball_q.get(ev.ball_e) {
             let ball_res = match *status {
                 BallStatus::Fault(count, player_id) => {
                     // nice2have: limit might come from an upgrade
                     let limit = 1;
                     let losing_player = if count > limit { Some(player_id) } else { None };
                     let fault_count = if count > limit { 0 } else { count };
-                    Some((losing_player, fault_count, "double fault"))
+                    let line_call = losing_player
+                        .and(serving_region.0.get_inverse())
+                        .map(LineCall::Fault);
+                    Some((
+                        losing_player,
+                        fault_count,
+                        PointReason::DoubleFault,
+                        line_call,
+                    ))
                 }
                 BallStatus::Rally(player_id) => {
                     // nice2have: limit might come from an upgrade
-                    let bounce_limit = 1;
+                    // the receiving side's assist can allow an extra bounce
+                    let receiver = player_q.iter().find(|(p, _)| p.side == ev.side);
+                    let bounce_limit = receiver
+                        .and_then(|(_, assist)| assist)
+                        .map_or(rules.bounce_limit, |a| {
+                            a.get_bounce_limit(rules.bounce_limit)
+                        });
 
-                    // out of bounds
-                    if ball.region.is_out_of_bounds() && ev.bounce_count == 1 {
-                        Some((Some(player_id), 0, "shooting out of bounds"))
-                    } else if ev.bounce_count > bounce_limit {
-                        let player = player_q.iter().find(|p| p.side == ev.side).unwrap();
-
-                        Some((Some(player.id), 0, "too many bounces"))
-                    } else {
-                        None
+                    match get_rally_bounce_reason(
+                        ball.region.is_out_of_bounds(),
+                        ev.bounce_count,
+                        bounce_limit,
+                        ev.serve_bounce,
+                    ) {
+                        Some(PointReason::Out) => {
+                            Some((Some(player_id), 0, PointReason::Out, Some(LineCall::Out)))
+                        }
+                        Some(reason) => {
+                            receiver.map(|(player, _)| (Some(player.id), 0, reason, None))
+                        }
+                        None => None,
                     }
                 }
-                BallStatus::Serve(..) | BallStatus::Used => None,
+                BallStatus::Serve(..) | BallStatus::Drop | BallStatus::Used => None,
             };
 
-            if let Some((losing_player, fault_count, reason)) = ball_res {
-                let mut swap_serve = false;
-
-                if let Some(losing_player) = losing_player {
-                    swap_serve = add_point_to_score(&mut score, !is_left_player_id(losing_player));
-                    debug!(
-                        "Player {} has lost a point to {}! (bounce_count: {})",
-                        losing_player, reason, ev.bounce_count
-                    );
-                }
+            if let Some((losing_player, fault_count, reason, line_call)) = ball_res {
+                resolutions.push((ev.ball_e, losing_player, fault_count, reason, line_call));
+            }
+        }
+    }
 
-                *status = BallStatus::Used;
-                commands.entity(ev.ball_e).insert(Animator::new(
-                    Tween::new(
-                        EaseFunction::QuadraticIn,
-                        TweeningType::Once,
-                        Duration::from_millis(450),
-                        TransformScaleLens {
-                            start: ball_t.scale,
-                            end: Vec3::ZERO,
-                        },
-                    )
-                    .with_completed_event(true, TweenDoneAction::DespawnRecursive.into()),
+    for ev in ev_r_shot_clock.iter() {
+        if let Ok((_, BallStatus::Rally(hitter_id), _)) = ball_q.get(ev.ball_e) {
+            if *hitter_id != ev.player_id {
+                resolutions.push((
+                    ev.ball_e,
+                    Some(ev.player_id),
+                    0,
+                    PointReason::ShotClock,
+                    None,
                 ));
+            }
+        }
+    }
 
-                if let Ok(e) = entity_q.get(ball.trail_e.unwrap()) {
-                    commands.entity(e).insert(FadeOutTrail {
-                        decrease_duration_by: 1.,
-                        ..Default::default()
-                    });
+    for (ball_e, losing_player, fault_count, reason, line_call) in resolutions {
+        if let Ok((ball, mut status, ball_t)) = ball_q.get_mut(ball_e) {
+            // the same ball might've been resolved already
+            if let BallStatus::Used = *status {
+                continue;
+            }
+
+            let mut outcome = PointOutcome::Point;
+            let score_before = score.clone();
+
+            if let Some(losing_player) = losing_player {
+                let scoring_left = !is_left_player_id(losing_player);
+
+                // extra points don't carry over into the next game
+                for _ in 0..point_value.0.max(1) {
+                    outcome = add_point_to_score(&mut score, &rules, scoring_left);
+                    if outcome.is_game_won() {
+                        break;
+                    }
+                }
+                let kind = PointKind::classify(reason, ball);
+                if !outcome.is_game_won() {
+                    let bonus = rules.get_net_bonus(kind);
+                    score.net_bonus += if scoring_left { bonus } else { -bonus };
                 }
+                ev_w_score_changed.send(ScoreChangedEvt {
+                    scoring_player_id: if scoring_left { 1 } else { 2 },
+                    outcome,
+                    reason,
+                    kind,
+                });
+                debug!("Player {} has lost a point to {:?}!", losing_player, reason);
+            }
+
+            despawn_ball(
+                &mut commands,
+                ball_e,
+                &mut status,
+                ball_t,
+                entity_q.get(ball.trail_e.unwrap()).ok(),
+            );
 
-                if swap_serve {
-                    serving_region.0 = if serving_region.0.is_left() {
-                        CourtRegion::get_random_right()
-                    } else {
-                        CourtRegion::get_random_left()
-                    };
+            if outcome == PointOutcome::Match {
+                if let Some(winner_id) = score.get_winner_id(&rules) {
+                    ev_w_game_over.send(GameOverEvt { winner_id });
                 }
 
-                spawn_ball(
-                    &mut commands,
-                    &asset_server,
-                    serving_region.0,
-                    fault_count,
-                    serving_region.0.get_player_id(),
-                    &court_set,
-                );
+                // no more serves until the match is reset
+                continue;
             }
+
+            if let (Some(losing_player), Some(call)) = (losing_player, line_call) {
+                ev_w_line_call.send(LineCallEvt {
+                    player_id: losing_player,
+                    call,
+                    landing: ball_t.translation.truncate(),
+                    net_x: net_offset.0,
+                    score_before,
+                    serving_region: serving_region.0,
+                });
+            }
+
+            if losing_player.is_some() {
+                serving_region.0 = get_next_serve_region(serving_region.0, &score, &rules, outcome);
+            }
+
+            spawn_point_ball(
+                &mut commands,
+                &atlas,
+                &rules,
+                serving_region.0,
+                fault_count,
+                &court_set,
+                &config,
+                &loadouts,
+                &mut rng,
+                net_offset.0,
+            );
+        }
+    }
+}
+
+pub fn get_next_serve_region(
+    serving_region: CourtRegion,
+    score: &Score,
+    rules: &MatchRules,
+    outcome: PointOutcome,
+) -> CourtRegion {
+    let mut server_id = serving_region.get_player_id();
+    if score.is_serve_swap(rules, outcome) {
+        server_id = if is_left_player_id(server_id) { 2 } else { 1 };
+    }
+    CourtRegion::get_serve_region(server_id, score.is_deuce_side())
+}
+
+fn on_serve_let(
+    mut commands: Commands,
+    mut ev_r_let: EventReader<ServeLetEvt>,
+    mut ball_q: Query<(&Ball, &mut BallStatus, &Transform)>,
+    atlas: Res<SpriteAtlas>,
+    serving_region: Res<ServingRegion>,
+    entity_q: Query<Entity>,
+    court_set: Res<CourtSettings>,
+    config: Res<GameConfig>,
+    loadouts: Res<Loadouts>,
+    mut rng: ResMut<GameRng>,
+) {
+    for ev in ev_r_let.iter() {
+        if let Ok((ball, mut status, ball_t)) = ball_q.get_mut(ev.ball_e) {
+            despawn_ball(
+                &mut commands,
+                ev.ball_e,
+                &mut status,
+                ball_t,
+                entity_q.get(ball.trail_e.unwrap()).ok(),
+            );
+
+            spawn_ball(
+                &mut commands,
+                &atlas,
+                serving_region.0,
+                ev.fault_count,
+                serving_region.0.get_player_id(),
+                &court_set,
+                &config,
+                &loadouts,
+                &mut rng,
+            );
+        }
+    }
+}
+
+// keeps spawned players in sync with hot reloaded tuning
+fn apply_player_config(
+    config: Res<GameConfig>,
+    mut player_q: Query<
+        (
+            &Player,
+            &mut PlayerMovement,
+            &mut PlayerDash,
+            &mut PlayerSwing,
+            &mut PlayerJump,
+            &mut CollisionShape,
+        ),
+        Without<PlayerBody>,
+    >,
+    mut ring_q: Query<(&Parent, &mut TransformRotation), With<SwingRangeSprite>>,
+    mut body_q: Query<(&mut CollisionShape, &mut CollisionLayers), With<PlayerBody>>,
+) {
+    if !config.is_changed() {
+        return;
+    }
+
+    let c = &config.player;
+    for (_, mut movement, mut dash, mut swing, mut jump, mut coll_shape) in player_q.iter_mut() {
+        movement.speed = c.speed;
+        movement.charging_speed = c.charging_speed;
+        movement.time_to_max_speed = c.time_to_max_speed;
+        dash.speed = c.dash_speed;
+        dash.duration_sec = c.dash_duration_sec;
+        dash.cooldown_sec = c.dash_cooldown_sec;
+        swing.duration_sec = c.swing_duration_sec;
+        swing.cooldown_sec = c.swing_cooldown_sec;
+        swing.whiff_recovery_sec = c.swing_whiff_recovery_sec;
+        jump.cooldown_sec = c.jump_cooldown_sec;
+        jump.gravity = c.jump_gravity;
+        jump.jump_velocity = c.jump_velocity;
+        *coll_shape = CollisionShape::Sphere {
+            radius: c.swing_radius,
+        };
+    }
+
+    for (mut coll_shape, mut coll_layers) in body_q.iter_mut() {
+        *coll_shape = CollisionShape::Sphere {
+            radius: c.body_radius,
+        };
+        *coll_layers = get_body_layers(c.body_blocking);
+    }
+
+    for (parent, mut rot) in ring_q.iter_mut() {
+        if let Ok((player, ..)) = player_q.get(parent.0) {
+            rot.rotation_max_rad = player.get_sign() * c.aim_ring_rotation_deg.to_radians();
         }
     }
 }
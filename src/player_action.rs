@@ -1,11 +1,10 @@
-use std::time::Duration;
-
 use bevy::prelude::*;
+#[cfg(feature = "inspector")]
 use bevy_inspector_egui::Inspectable;
-use bevy_time::{ScaledTime, ScaledTimeDelta};
 
 use crate::{
-    player::{PlayerDash, PlayerSwing},
+    player::{PlayerDash, PlayerJump, PlayerSwing},
+    timer::{GameClock, GameTimer},
     GameState,
 };
 
@@ -15,12 +14,14 @@ impl Plugin for PlayerActionPlugin {
         app.add_system_set(
             SystemSet::on_update(GameState::Game)
                 .with_system(handle_action_cooldown::<PlayerDash, Vec2>)
+                .with_system(handle_action_cooldown::<PlayerJump, f32>)
                 .with_system(handle_action_cooldown::<PlayerSwing, f32>),
         );
     }
 }
 
-#[derive(Inspectable, Clone, Copy)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub enum PlayerActionStatus<TActiveData: Default> {
     Ready,
     Charging(f32),
@@ -35,13 +36,13 @@ impl<TActiveData: Default> Default for PlayerActionStatus<TActiveData> {
 }
 
 pub trait ActionTimer<TActiveData: Default> {
-    fn get_timer_mut(&mut self) -> &mut Timer;
+    fn get_timer_mut(&mut self) -> &mut GameTimer;
 
     fn get_action_status_mut(&mut self) -> &mut PlayerActionStatus<TActiveData>;
 
     fn get_cooldown_sec(&self) -> f32;
 
-    fn handle_action_timer(&mut self, scaled_delta_time: Duration) {
+    fn handle_action_timer(&mut self, clock: &GameClock) {
         let cooldown_sec = self.get_cooldown_sec();
         let status = self.get_action_status_mut();
         let is_cooldown = matches!(status, PlayerActionStatus::Cooldown);
@@ -49,26 +50,46 @@ pub trait ActionTimer<TActiveData: Default> {
 
         if is_cooldown || is_active {
             let t = self.get_timer_mut();
-            t.tick(scaled_delta_time);
-
-            if t.just_finished() {
-                *t = Timer::from_seconds(cooldown_sec, false);
-                *self.get_action_status_mut() = if is_cooldown {
-                    PlayerActionStatus::Ready
+            if t.tick(clock).just_finished() {
+                if is_cooldown {
+                    t.restart(cooldown_sec);
+                    *self.get_action_status_mut() = PlayerActionStatus::Ready;
                 } else {
-                    PlayerActionStatus::Cooldown
-                };
+                    self.on_active_finished();
+                }
             }
         }
     }
+
+    // the action ran out without being interrupted
+    fn on_active_finished(&mut self) {
+        let cooldown_sec = self.get_cooldown_sec();
+        self.get_timer_mut().restart(cooldown_sec);
+        *self.get_action_status_mut() = PlayerActionStatus::Cooldown;
+    }
+}
+
+// an active action still has the whole cooldown ahead of it
+pub fn get_time_until_ready<TActiveData: Default>(
+    status: &PlayerActionStatus<TActiveData>,
+    timer: &GameTimer,
+    cooldown_sec: f32,
+) -> f32 {
+    let timer_left_sec = timer.get_remaining_sec();
+
+    match status {
+        PlayerActionStatus::Ready | PlayerActionStatus::Charging(_) => 0.,
+        PlayerActionStatus::Active(_) => timer_left_sec + cooldown_sec,
+        PlayerActionStatus::Cooldown => timer_left_sec,
+    }
 }
 
 fn handle_action_cooldown<T: ActionTimer<TActiveData> + Component, TActiveData: Default>(
     mut query: Query<&mut T>,
-    time: ScaledTime,
+    clock: GameClock,
 ) {
     for mut activity in query.iter_mut() {
-        activity.handle_action_timer(time.scaled_delta());
+        activity.handle_action_timer(&clock);
     }
 }
 
@@ -80,7 +101,7 @@ macro_rules! impl_player_action_timer {
                 self.cooldown_sec
             }
 
-            fn get_timer_mut(&mut self) -> &mut Timer {
+            fn get_timer_mut(&mut self) -> &mut $crate::timer::GameTimer {
                 &mut self.timer
             }
 
@@ -1,162 +1,368 @@
-use crate::player::{get_swing_multiplier, Player, PlayerSwing};
+use crate::player::{
+    get_overcharge, get_swing_multiplier, get_swing_multiplier_clamped, ChargeZone, Player,
+    PlayerSwing,
+};
 use crate::GameState;
 use crate::{
     animation::TransformRotation,
-    player::{PlayerDash, SwingRangeSprite, SWING_LABEL},
+    ball::{get_bounce_velocity, get_shot_dir, get_shot_risk, Ball, BallBounce, ShotRisk},
+    config::GameConfig,
+    fatigue::PlayerFatigue,
+    level::NetOffset,
+    palette::{Palette, PaletteColor},
+    player::PlayerAim,
+    player::{PlayerDash, SwingRangeSprite},
     player_action::PlayerActionStatus,
+    trajectory::{get_height_at_x, Shot},
 };
 use bevy::{math::Vec2, prelude::*};
+#[cfg(feature = "inspector")]
 use bevy_inspector_egui::Inspectable;
 use bevy_time::{ScaledTime, ScaledTimeDelta};
 use bevy_tweening::lens::{TransformPositionLens, TransformRotationLens, TransformScaleLens};
 use bevy_tweening::*;
 use interpolation::EaseFunction;
+use serde::Deserialize;
 use std::time::Duration;
 
+const SWING_RANGE_OCCLUDING_ALPHA: f32 = 0.3;
+const SWING_RANGE_FADE_SPEED: f32 = 4.;
+const STUMBLE_MS: u64 = 300;
+const STUMBLE_TILT_DEG: f32 = 18.;
+const PERFECT_CHARGE_UI_SCALE: f32 = 1.1;
+
 pub struct PlayerAnimationPlugin;
 impl Plugin for PlayerAnimationPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_system(animate.after(SWING_LABEL))
-            .add_system(unblock_animation)
+        app.add_event::<AnimationStartedEvt>()
+            .add_event::<AnimationDoneEvt>()
+            .add_system(animate)
             .add_system_set(
                 SystemSet::on_update(GameState::Game)
                     .with_system(animate_dash_state_ui)
-                    .with_system(animate_swing_charge_ui),
+                    .with_system(animate_swing_charge_ui)
+                    .with_system(animate_shot_risk_ui)
+                    .with_system(fade_occluding_swing_range),
             );
     }
 }
 
-#[derive(Default, Component, Inspectable, PartialEq, Debug)]
+#[derive(Component, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub enum PlayerAnimation {
-    #[default]
     Idle,
     Walking,
     Running,
     Dashing,
+    Jumping,
     Celebrating,
     Shooting,
+    Smashing,
+    // after a whiff
+    Stumbling,
+}
+
+impl Default for PlayerAnimation {
+    fn default() -> Self {
+        PlayerAnimation::Idle
+    }
+}
+
+// what can cut an animation short before its lock runs out
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AnimationInterrupt {
+    Any,
+    HigherPriority,
+    Never,
+}
+
+impl PlayerAnimation {
+    pub fn get_priority(&self) -> u8 {
+        match self {
+            PlayerAnimation::Idle | PlayerAnimation::Walking | PlayerAnimation::Running => 0,
+            PlayerAnimation::Jumping | PlayerAnimation::Celebrating => 1,
+            PlayerAnimation::Dashing => 2,
+            PlayerAnimation::Shooting | PlayerAnimation::Smashing => 3,
+            PlayerAnimation::Stumbling => 4,
+        }
+    }
+
+    pub fn get_interrupt(&self) -> AnimationInterrupt {
+        match self {
+            PlayerAnimation::Idle
+            | PlayerAnimation::Walking
+            | PlayerAnimation::Running
+            | PlayerAnimation::Jumping
+            | PlayerAnimation::Celebrating => AnimationInterrupt::Any,
+            // a whiff still cuts the swing short
+            PlayerAnimation::Dashing | PlayerAnimation::Shooting | PlayerAnimation::Smashing => {
+                AnimationInterrupt::HigherPriority
+            }
+            PlayerAnimation::Stumbling => AnimationInterrupt::Never,
+        }
+    }
 }
 
-#[derive(Component, Inspectable)]
+// animations are requested and the highest priority request of the frame gets a go at the transition
+#[derive(Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct AgentAnimationData {
-    pub animation: PlayerAnimation,
     pub face_e: Entity,
     pub body_e: Entity,
     pub body_root_e: Entity,
+    animation: PlayerAnimation,
+    requested: Option<PlayerAnimation>,
+    // the time left until the animation can be interrupted regardless of its policy
+    lock_sec: f32,
+    // the initial animation has to play even though it's not a change
+    started: bool,
+}
+
+impl AgentAnimationData {
+    pub fn new(face_e: Entity, body_e: Entity, body_root_e: Entity) -> Self {
+        Self {
+            face_e,
+            body_e,
+            body_root_e,
+            animation: PlayerAnimation::Idle,
+            requested: Some(PlayerAnimation::Idle),
+            lock_sec: 0.,
+            started: false,
+        }
+    }
+
+    pub fn get_animation(&self) -> PlayerAnimation {
+        self.animation
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.lock_sec > 0.
+    }
+
+    pub fn request(&mut self, animation: PlayerAnimation) {
+        if self
+            .requested
+            .map_or(true, |r| animation.get_priority() >= r.get_priority())
+        {
+            self.requested = Some(animation);
+        }
+    }
+
+    fn can_transition_to(&self, next: PlayerAnimation) -> bool {
+        if !self.started {
+            return true;
+        }
+
+        if next == self.animation {
+            return false;
+        }
+
+        if !self.is_locked() {
+            return true;
+        }
+
+        match self.animation.get_interrupt() {
+            AnimationInterrupt::Any => true,
+            AnimationInterrupt::HigherPriority => {
+                next.get_priority() > self.animation.get_priority()
+            }
+            AnimationInterrupt::Never => false,
+        }
+    }
+}
+
+pub struct AnimationStartedEvt {
+    pub agent_e: Entity,
+    pub animation: PlayerAnimation,
 }
 
-#[derive(Component, Inspectable)]
-pub struct AgentAnimationBlock(pub f32);
+// only sent for locked animations, once they've played out or got interrupted
+pub struct AnimationDoneEvt {
+    pub agent_e: Entity,
+    pub animation: PlayerAnimation,
+    pub interrupted: bool,
+}
 
 fn animate(
-    mut commands: Commands,
-    player_anim_q: Query<(
+    mut anim_q: Query<(
         Entity,
-        &AgentAnimationData,
-        Option<&AgentAnimationBlock>,
-        ChangeTrackers<AgentAnimationData>,
+        &mut AgentAnimationData,
+        Option<&PlayerFatigue>,
+        Option<&Player>,
     )>,
     mut animator_q: Query<(&mut Animator<Transform>, &Transform)>,
+    mut ev_w_started: EventWriter<AnimationStartedEvt>,
+    mut ev_w_done: EventWriter<AnimationDoneEvt>,
+    time: ScaledTime,
 ) {
-    for (anim_e, anim, block, anim_tracker) in player_anim_q.iter() {
-        if anim_tracker.is_changed() || anim_tracker.is_added() {
-            if block.is_some() {
-                continue;
+    for (anim_e, mut anim, fatigue, player) in anim_q.iter_mut() {
+        if anim.is_locked() {
+            anim.lock_sec -= time.scaled_delta_seconds();
+            if !anim.is_locked() {
+                ev_w_done.send(AnimationDoneEvt {
+                    agent_e: anim_e,
+                    animation: anim.animation,
+                    interrupted: false,
+                });
             }
+        }
 
-            let mut stop_anim_entities: Vec<Entity> = Vec::new();
-            let mut body_root_tween = None;
+        // requests don't carry over, the requesters keep asking while it makes sense
+        let next = match anim.requested.take() {
+            Some(next) if anim.can_transition_to(next) => next,
+            _ => continue,
+        };
 
-            debug!("anim change to {:?}", anim.animation);
-            match anim.animation {
-                PlayerAnimation::Shooting => {
-                    stop_anim_entities.push(anim.face_e);
-                    stop_anim_entities.push(anim.body_root_e);
+        if anim.is_locked() {
+            ev_w_done.send(AnimationDoneEvt {
+                agent_e: anim_e,
+                animation: anim.animation,
+                interrupted: true,
+            });
+        }
 
-                    if let Ok((mut animator, t)) = animator_q.get_mut(anim.body_e) {
-                        let (tween, dur) = get_body_scale_tween(t, 1.8, 300);
-                        animator.set_tweenable(tween);
-                        animator.rewind();
-                        animator.state = AnimatorState::Playing;
+        debug!("anim change to {:?}", next);
+        anim.animation = next;
+        anim.started = true;
+        anim.lock_sec = play_animation(&anim, fatigue, player, &mut animator_q);
+        ev_w_started.send(AnimationStartedEvt {
+            agent_e: anim_e,
+            animation: next,
+        });
+    }
+}
 
-                        commands.entity(anim_e).insert(AgentAnimationBlock(dur));
-                    }
-                }
-                PlayerAnimation::Dashing => {
-                    stop_anim_entities.push(anim.face_e);
-                    stop_anim_entities.push(anim.body_root_e);
+// returns the time the animation is locked for
+fn play_animation(
+    anim: &AgentAnimationData,
+    fatigue: Option<&PlayerFatigue>,
+    player: Option<&Player>,
+    animator_q: &mut Query<(&mut Animator<Transform>, &Transform)>,
+) -> f32 {
+    let mut stop_anim_entities: Vec<Entity> = Vec::new();
+    let mut body_root_tween = None;
+    let mut lock_sec = 0.;
 
-                    if let Ok((mut animator, t)) = animator_q.get_mut(anim.body_e) {
-                        let (tween, dur) = get_body_scale_tween(t, 1.3, 220);
-                        animator.set_tweenable(tween);
-                        animator.rewind();
-                        animator.state = AnimatorState::Playing;
+    match anim.animation {
+        PlayerAnimation::Shooting => {
+            stop_anim_entities.push(anim.face_e);
+            stop_anim_entities.push(anim.body_root_e);
 
-                        commands.entity(anim_e).insert(AgentAnimationBlock(dur));
-                    }
-                }
-                PlayerAnimation::Idle => {
-                    stop_anim_entities.push(anim.body_root_e);
+            if let Ok((mut animator, t)) = animator_q.get_mut(anim.body_e) {
+                let (tween, dur) = get_body_scale_tween(t, 1.8, 300);
+                animator.set_tweenable(tween);
+                animator.rewind();
+                animator.state = AnimatorState::Playing;
+                lock_sec = dur;
+            }
+        }
+        PlayerAnimation::Smashing => {
+            stop_anim_entities.push(anim.face_e);
+            stop_anim_entities.push(anim.body_root_e);
 
-                    if let Ok((mut animator, t)) = animator_q.get_mut(anim.face_e) {
-                        animator.set_tweenable(get_idle_face_tween(t.translation.z));
-                        animator.rewind();
-                        animator.state = AnimatorState::Playing;
-                    }
+            if let Ok((mut animator, t)) = animator_q.get_mut(anim.body_e) {
+                let (tween, dur) = get_body_scale_tween(t, 2.2, 250);
+                animator.set_tweenable(tween);
+                animator.rewind();
+                animator.state = AnimatorState::Playing;
+                lock_sec = dur;
+            }
+        }
+        PlayerAnimation::Stumbling => {
+            stop_anim_entities.push(anim.face_e);
 
-                    if let Ok((mut animator, t)) = animator_q.get_mut(anim.body_e) {
-                        animator.set_tweenable(get_idle_body_tween(t.translation.z));
-                        animator.rewind();
-                        animator.state = AnimatorState::Playing;
-                    }
-                }
-                PlayerAnimation::Walking => {
-                    stop_anim_entities.push(anim.face_e);
-                    stop_anim_entities.push(anim.body_e);
-                    body_root_tween = Some(get_move_tween(400, 4., 3.));
-                }
-                PlayerAnimation::Running => {
-                    stop_anim_entities.push(anim.face_e);
-                    stop_anim_entities.push(anim.body_e);
-                    body_root_tween = Some(get_move_tween(300, 5., 8.));
-                }
-                PlayerAnimation::Celebrating => {
-                    stop_anim_entities.push(anim.face_e);
-                    stop_anim_entities.push(anim.body_e);
-                    body_root_tween = Some(get_move_tween(500, 20., 12.));
-                }
+            if let Ok((mut animator, t)) = animator_q.get_mut(anim.body_e) {
+                let (tween, _) = get_body_scale_tween(t, 0.8, STUMBLE_MS);
+                animator.set_tweenable(tween);
+                animator.rewind();
+                animator.state = AnimatorState::Playing;
             }
 
-            for e in stop_anim_entities.iter() {
-                if let Ok((mut animator, t)) = animator_q.get_mut(*e) {
-                    animator.set_tweenable(get_reset_trans_tween(t, 250));
-                    animator.rewind();
-                    animator.state = AnimatorState::Playing;
-                }
+            // lean towards the net
+            let tilt = player.map_or(1., |p| p.get_sign()) * STUMBLE_TILT_DEG;
+            if let Ok((mut animator, t)) = animator_q.get_mut(anim.body_root_e) {
+                animator.set_tweenable(get_stumble_tween(t, tilt, STUMBLE_MS));
+                animator.rewind();
+                animator.state = AnimatorState::Playing;
             }
 
-            if let Some(move_tween) = body_root_tween {
-                if let Ok((mut animator, _t)) = animator_q.get_mut(anim.body_root_e) {
-                    animator.set_tweenable(move_tween);
-                    animator.state = AnimatorState::Playing;
-                }
+            lock_sec = STUMBLE_MS as f32 / 1000.;
+        }
+        PlayerAnimation::Jumping => {
+            // the body root height is driven by the jump
+            stop_anim_entities.push(anim.face_e);
+            stop_anim_entities.push(anim.body_root_e);
+
+            if let Ok((mut animator, t)) = animator_q.get_mut(anim.body_e) {
+                let (tween, _) = get_body_scale_tween(t, 1.15, 400);
+                animator.set_tweenable(tween);
+                animator.rewind();
+                animator.state = AnimatorState::Playing;
+            }
+        }
+        PlayerAnimation::Dashing => {
+            stop_anim_entities.push(anim.face_e);
+            stop_anim_entities.push(anim.body_root_e);
+
+            if let Ok((mut animator, t)) = animator_q.get_mut(anim.body_e) {
+                let (tween, dur) = get_body_scale_tween(t, 1.3, 220);
+                animator.set_tweenable(tween);
+                animator.rewind();
+                animator.state = AnimatorState::Playing;
+                lock_sec = dur;
+            }
+        }
+        PlayerAnimation::Idle => {
+            stop_anim_entities.push(anim.body_root_e);
+
+            // tired players breathe slower
+            let idle_ms = 400 + fatigue.map_or(0, |f| (f.value * 250.) as u64);
+
+            if let Ok((mut animator, t)) = animator_q.get_mut(anim.face_e) {
+                animator.set_tweenable(get_idle_face_tween(t.translation.z, idle_ms));
+                animator.rewind();
+                animator.state = AnimatorState::Playing;
+            }
+
+            if let Ok((mut animator, t)) = animator_q.get_mut(anim.body_e) {
+                animator.set_tweenable(get_idle_body_tween(t.translation.z, idle_ms));
+                animator.rewind();
+                animator.state = AnimatorState::Playing;
             }
         }
+        PlayerAnimation::Walking => {
+            stop_anim_entities.push(anim.face_e);
+            stop_anim_entities.push(anim.body_e);
+            body_root_tween = Some(get_move_tween(400, 4., 3.));
+        }
+        PlayerAnimation::Running => {
+            stop_anim_entities.push(anim.face_e);
+            stop_anim_entities.push(anim.body_e);
+            body_root_tween = Some(get_move_tween(300, 5., 8.));
+        }
+        PlayerAnimation::Celebrating => {
+            stop_anim_entities.push(anim.face_e);
+            stop_anim_entities.push(anim.body_e);
+            body_root_tween = Some(get_move_tween(500, 20., 12.));
+        }
     }
-}
 
-fn unblock_animation(
-    mut commands: Commands,
-    mut block_q: Query<(Entity, &mut AgentAnimationBlock)>,
-    time: ScaledTime,
-) {
-    for (e, mut block) in block_q.iter_mut() {
-        block.0 -= time.scaled_delta_seconds();
+    for e in stop_anim_entities.iter() {
+        if let Ok((mut animator, t)) = animator_q.get_mut(*e) {
+            animator.set_tweenable(get_reset_trans_tween(t, 250));
+            animator.rewind();
+            animator.state = AnimatorState::Playing;
+        }
+    }
 
-        if block.0 < 0. {
-            commands.entity(e).remove::<AgentAnimationBlock>();
+    if let Some(move_tween) = body_root_tween {
+        if let Ok((mut animator, _t)) = animator_q.get_mut(anim.body_root_e) {
+            animator.set_tweenable(move_tween);
+            animator.state = AnimatorState::Playing;
         }
     }
+
+    lock_sec
 }
 
 fn get_move_tween(walk_cycle_ms: u64, pos_y: f32, rot: f32) -> Tracks<Transform> {
@@ -219,11 +425,11 @@ fn get_reset_tween<T, L: Lens<T> + Send + Sync + 'static>(duration_ms: u64, lens
     )
 }
 
-fn get_idle_face_tween(z: f32) -> Tween<Transform> {
+fn get_idle_face_tween(z: f32, duration_ms: u64) -> Tween<Transform> {
     Tween::new(
         EaseFunction::QuadraticInOut,
         TweeningType::PingPong,
-        Duration::from_millis(400),
+        Duration::from_millis(duration_ms),
         TransformPositionLens {
             start: Vec3::ZERO,
             end: Vec3::new(0., -4., z),
@@ -231,11 +437,11 @@ fn get_idle_face_tween(z: f32) -> Tween<Transform> {
     )
 }
 
-fn get_idle_body_tween(z: f32) -> Tracks<Transform> {
+fn get_idle_body_tween(z: f32, duration_ms: u64) -> Tracks<Transform> {
     let body_idle_size_tween = Tween::new(
         EaseFunction::QuadraticInOut,
         TweeningType::PingPong,
-        Duration::from_millis(400),
+        Duration::from_millis(duration_ms),
         TransformScaleLens {
             start: Vec3::ONE,
             end: Vec3::new(1.075, 0.925, 1.),
@@ -244,7 +450,7 @@ fn get_idle_body_tween(z: f32) -> Tracks<Transform> {
     let body_idle_pos_tween = Tween::new(
         EaseFunction::QuadraticInOut,
         TweeningType::PingPong,
-        Duration::from_millis(400),
+        Duration::from_millis(duration_ms),
         TransformPositionLens {
             start: Vec2::ZERO.extend(z),
             end: Vec3::new(0., -4., z),
@@ -277,6 +483,28 @@ fn get_body_scale_tween(transform: &Transform, scale: f32, dur: u64) -> (Sequenc
     (t, 0.5)
 }
 
+fn get_stumble_tween(transform: &Transform, tilt_deg: f32, dur: u64) -> Sequence<Transform> {
+    let end = Quat::from_rotation_z(tilt_deg.to_radians());
+    Tween::new(
+        EaseFunction::QuadraticOut,
+        TweeningType::Once,
+        Duration::from_millis(dur / 3),
+        TransformRotationLens {
+            start: transform.rotation,
+            end,
+        },
+    )
+    .then(Tween::new(
+        EaseFunction::QuadraticInOut,
+        TweeningType::Once,
+        Duration::from_millis(dur * 2 / 3),
+        TransformRotationLens {
+            start: end,
+            end: Quat::IDENTITY,
+        },
+    ))
+}
+
 fn animate_dash_state_ui(
     mut q: Query<(&Parent, &mut TransformRotation), With<SwingRangeSprite>>,
     dash_q: Query<&PlayerDash>,
@@ -292,7 +520,7 @@ fn animate_dash_state_ui(
                     }
                 }
                 PlayerActionStatus::Active(..) => {
-                    let mult = 1. - dash.timer.percent();
+                    let mult = dash.timer.get_fraction_remaining();
                     rot.rotation_rad = rot.rotation_max_rad * mult * rot.rotation_max_rad.signum();
                 }
                 PlayerActionStatus::Cooldown => rot.rotation_rad = 0.,
@@ -309,7 +537,15 @@ fn animate_swing_charge_ui(
     for (player, player_swing) in player_q.iter() {
         if let Ok(mut t) = aim_charge_q.get_mut(player.aim_charge_e) {
             if let PlayerActionStatus::Charging(dur) = player_swing.status {
-                let scale = get_swing_multiplier(dur);
+                // grows through the weak zone, stands out in the perfect one and wobbles once overcharged
+                let scale = match ChargeZone::from_duration(dur) {
+                    ChargeZone::Weak => get_swing_multiplier(dur),
+                    ChargeZone::Perfect => PERFECT_CHARGE_UI_SCALE,
+                    ChargeZone::Overcharged => {
+                        let overcharge = get_overcharge(dur);
+                        1. - 0.15 * overcharge + (dur * 40.).sin() * 0.06 * overcharge
+                    }
+                };
                 t.scale = Vec2::splat(scale).extend(1.);
             } else if !matches!(player_swing.status, PlayerActionStatus::Active(_)) {
                 t.scale =
@@ -319,3 +555,118 @@ fn animate_swing_charge_ui(
         }
     }
 }
+
+// tints the swing charge by how likely the shot is to end up in the net
+fn animate_shot_risk_ui(
+    player_q: Query<(&Player, &PlayerSwing, &PlayerFatigue, &GlobalTransform)>,
+    aim_q: Query<&PlayerAim>,
+    ball_q: Query<(&Ball, &GlobalTransform)>,
+    bounce_q: Query<&Transform, With<BallBounce>>,
+    mut sprite_q: Query<&mut Sprite>,
+    net: Res<NetOffset>,
+    config: Res<GameConfig>,
+    palette: Res<Palette>,
+) {
+    let charge_col = palette.get_color(&PaletteColor::PlayerCharge);
+    let risk_col = Color::rgb(0.9, 0.25, 0.2);
+
+    for (player, swing, fatigue, player_t) in player_q.iter() {
+        let mut sprite = match sprite_q.get_mut(player.aim_charge_e) {
+            Ok(sprite) => sprite,
+            Err(_) => continue,
+        };
+
+        let dur = if let PlayerActionStatus::Charging(dur) = swing.status {
+            dur
+        } else {
+            if sprite.color != charge_col {
+                sprite.color = charge_col;
+            }
+            continue;
+        };
+
+        let aim = match aim_q.get(player.aim_e) {
+            Ok(aim) => aim,
+            Err(_) => continue,
+        };
+
+        // the closest ball is the one about to be hit
+        let player_pos = player_t.translation.truncate();
+        let (pos, height) = ball_q
+            .iter()
+            .map(|(ball, t)| {
+                let height = ball
+                    .bounce_e
+                    .and_then(|bounce_e| bounce_q.get(bounce_e).ok())
+                    .map_or(0., |bounce_t| bounce_t.translation.y);
+                (t.translation.truncate(), height)
+            })
+            .min_by(|(a, _), (b, _)| {
+                a.distance_squared(player_pos)
+                    .partial_cmp(&b.distance_squared(player_pos))
+                    .unwrap()
+            })
+            .unwrap_or((player_pos, 0.));
+
+        let dir = get_shot_dir(player, aim.dir);
+        let shot = Shot {
+            pos,
+            height,
+            dir: dir * get_swing_multiplier_clamped(dur) * fatigue.swing_mult,
+            bounce_velocity: get_bounce_velocity(dir.length(), config.ball.max_bounce_velocity),
+        };
+
+        // falling short is as bad as netting it
+        let risk = get_height_at_x(&shot, net.0, &config.ball)
+            .map_or(ShotRisk::Net, |h| get_shot_risk(h, shot.dir.length()));
+        let risk_mult = match risk {
+            ShotRisk::Clear => 0.,
+            ShotRisk::Clip => 0.5,
+            ShotRisk::Net => 1.,
+        };
+
+        sprite.color = Vec4::from(charge_col)
+            .lerp(Vec4::from(risk_col), risk_mult)
+            .into();
+    }
+}
+
+// the ring is big, so it fades out when the ball or the other player is underneath it
+fn fade_occluding_swing_range(
+    mut ring_q: Query<(&Parent, &mut Sprite), With<SwingRangeSprite>>,
+    player_q: Query<(Entity, &GlobalTransform), With<Player>>,
+    ball_q: Query<&GlobalTransform, With<Ball>>,
+    config: Res<GameConfig>,
+    time: ScaledTime,
+) {
+    for (parent, mut sprite) in ring_q.iter_mut() {
+        let pos = match player_q.get(parent.0) {
+            Ok((_, t)) => t.translation.truncate(),
+            Err(_) => continue,
+        };
+
+        let radius = config.player.swing_radius;
+        let occluding = ball_q
+            .iter()
+            .any(|t| t.translation.truncate().distance(pos) < radius)
+            || player_q.iter().any(|(e, t)| {
+                e != parent.0
+                    && t.translation.truncate().distance(pos) < radius + config.player.body_radius
+            });
+
+        let target_alpha = if occluding {
+            SWING_RANGE_OCCLUDING_ALPHA
+        } else {
+            1.
+        };
+        let alpha = sprite.color.a();
+        let step = SWING_RANGE_FADE_SPEED * time.scaled_delta_seconds();
+        if alpha != target_alpha {
+            sprite.color.set_a(if alpha < target_alpha {
+                (alpha + step).min(target_alpha)
+            } else {
+                (alpha - step).max(target_alpha)
+            });
+        }
+    }
+}
@@ -1,11 +1,12 @@
 use crate::{
-    ai_player_controller::AiPlayer,
-    input_binding::{InputAction, InputAxis, PlayerInput},
+    input_binding::{ControlPreset, InputAction, InputAxis, PlayerInput},
     player::{
-        get_swing_multiplier_clamped, Player, PlayerAim, PlayerDash, PlayerMovement, PlayerSwing,
-        SWING_LABEL,
+        get_overcharge, get_swing_multiplier_clamped, AiPlayer, Player, PlayerAim, PlayerDash,
+        PlayerJump, PlayerMovement, PlayerSwing, SWING_LABEL,
     },
     player_action::PlayerActionStatus,
+    profile::Profiles,
+    touch::{TouchControls, TouchSwing},
     GameState,
 };
 use bevy::prelude::*;
@@ -23,21 +24,28 @@ impl Plugin for PlayerControllerPlugin {
 
 fn process_player_input(
     input: Res<PlayerInput>,
+    touch: Res<TouchControls>,
+    profiles: Res<Profiles>,
     mut q: Query<
         (
             &Player,
             &mut PlayerMovement,
             &mut PlayerDash,
             &mut PlayerSwing,
+            &mut PlayerJump,
         ),
         Without<AiPlayer>,
     >,
     mut aim_q: Query<&mut PlayerAim>,
 ) {
-    for (player, mut player_movement, mut player_dash, mut player_swing) in q.iter_mut() {
+    for (player, mut player_movement, mut player_dash, mut player_swing, mut player_jump) in
+        q.iter_mut()
+    {
         // movement
         player_movement.raw_dir = if input.held(player.id, InputAction::LockPosition) {
             Vec2::ZERO
+        } else if let Some(dir) = touch.get_move_dir(player.id) {
+            dir
         } else {
             input.get_xy_axes_raw(player.id, &InputAxis::MoveX, &InputAxis::MoveY)
         };
@@ -45,12 +53,19 @@ fn process_player_input(
         // aim
         if let Ok(mut player_aim) = aim_q.get_mut(player.aim_e) {
             // start with aim dir
-            player_aim.raw_dir =
-                input.get_xy_axes_raw(player.id, &InputAxis::AimX, &InputAxis::AimY);
+            player_aim.raw_dir = touch.get_aim_dir(player.id).unwrap_or_else(|| {
+                match profiles.get_controls(player.id) {
+                    ControlPreset::Standard => {
+                        input.get_xy_axes_raw(player.id, &InputAxis::AimX, &InputAxis::AimY)
+                    }
+                    ControlPreset::OneStick => Vec2::ZERO,
+                }
+            });
             if player_aim.raw_dir == Vec2::ZERO {
                 // fallback to movement dir
-                player_aim.raw_dir =
-                    input.get_xy_axes_raw(player.id, &InputAxis::MoveX, &InputAxis::MoveY);
+                player_aim.raw_dir = touch.get_move_dir(player.id).unwrap_or_else(|| {
+                    input.get_xy_axes_raw(player.id, &InputAxis::MoveX, &InputAxis::MoveY)
+                });
             }
 
             // dash
@@ -62,11 +77,19 @@ fn process_player_input(
                     } else {
                         player_aim.dir
                     });
-                    player_dash.timer = Timer::from_seconds(player_dash.duration_sec, false);
+                    let duration_sec = player_dash.duration_sec;
+                    player_dash.timer.restart(duration_sec);
                 }
             }
         }
 
+        // jump
+        if input.just_pressed(player.id, InputAction::Jump) {
+            if let PlayerActionStatus::Ready = player_jump.status {
+                player_jump.start();
+            }
+        }
+
         // swing
         // nice2have: on swing down cancel prev swing?
         if let Some(input_action_state) =
@@ -80,17 +103,27 @@ fn process_player_input(
                     player_swing.status = PlayerActionStatus::Charging(key_date.duration);
                 }
                 ActionState::Released(key_data) => {
-                    if let PlayerActionStatus::Ready | PlayerActionStatus::Charging(..) =
-                        player_swing.status
-                    {
-                        player_swing.status = PlayerActionStatus::Active(
-                            get_swing_multiplier_clamped(key_data.duration),
-                        );
-                        player_swing.timer = Timer::from_seconds(player_swing.duration_sec, false);
-                    }
+                    release_swing(&mut player_swing, key_data.duration);
                 }
                 _ => {}
             }
         }
+
+        match touch.get_swing(player.id) {
+            Some(TouchSwing::Held(duration)) => {
+                player_swing.status = PlayerActionStatus::Charging(duration);
+            }
+            Some(TouchSwing::Released(duration)) => release_swing(&mut player_swing, duration),
+            _ => {}
+        }
+    }
+}
+
+fn release_swing(player_swing: &mut PlayerSwing, duration: f32) {
+    if let PlayerActionStatus::Ready | PlayerActionStatus::Charging(..) = player_swing.status {
+        player_swing.status = PlayerActionStatus::Active(get_swing_multiplier_clamped(duration));
+        player_swing.overcharge = get_overcharge(duration);
+        let duration_sec = player_swing.duration_sec;
+        player_swing.timer.restart(duration_sec);
     }
 }
@@ -0,0 +1,451 @@
+use bevy::{input::InputSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cosmetic::{unlock_earned, Cosmetic, Loadout},
+    input_binding::ControlPreset,
+    leaderboard::get_rating_change,
+    locale::Locale,
+    palette::{Palette, PaletteColor, PaletteTheme},
+    player::is_left_player_id,
+    score::{PointOutcome, Score, ScoreChangedEvt},
+    storage,
+    ui_text::{get_text_style, ScaledText, TextSettings},
+};
+
+const PROFILES_DIR: &str = "profiles";
+const DEFAULT_RATING: f32 = 1000.;
+const RECENT_FORM_LEN: usize = 5;
+const MAX_NAME_LEN: usize = 12;
+// profile pick keys, indexed by player, with shift they cycle the control preset
+const SELECT_KEYS: [KeyCode; 2] = [KeyCode::Key1, KeyCode::Key2];
+
+pub struct ProfilePlugin;
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.insert_resource(Profiles::load())
+            .init_resource::<NameEntry>()
+            .add_startup_system(setup)
+            // before the game reads the keys, so typing doesn't trigger anything
+            .add_system_to_stage(CoreStage::PreUpdate, enter_name.after(InputSystem))
+            .add_system(select_profiles)
+            .add_system(apply_preferred_palette)
+            .add_system(update_stats)
+            .add_system(update_profiles_ui);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProfileStats {
+    pub matches_played: u32,
+    pub matches_won: u32,
+    pub points_won: u32,
+    // elo, only matches between 2 profiles count
+    #[serde(default = "get_default_rating")]
+    pub rating: f32,
+    // match results, the latest last
+    #[serde(default)]
+    pub recent_form: Vec<bool>,
+}
+
+impl Default for ProfileStats {
+    fn default() -> Self {
+        Self {
+            matches_played: 0,
+            matches_won: 0,
+            points_won: 0,
+            rating: DEFAULT_RATING,
+            recent_form: Vec::new(),
+        }
+    }
+}
+
+fn get_default_rating() -> f32 {
+    DEFAULT_RATING
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Profile {
+    pub name: String,
+    pub palette: Option<PaletteTheme>,
+    #[serde(default)]
+    pub controls: ControlPreset,
+    pub stats: ProfileStats,
+    #[serde(default)]
+    pub loadout: Loadout,
+    #[serde(default)]
+    pub unlocked: Vec<Cosmetic>,
+}
+
+impl Profile {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            palette: None,
+            controls: ControlPreset::default(),
+            stats: ProfileStats::default(),
+            loadout: Loadout::default(),
+            unlocked: Vec::new(),
+        }
+    }
+
+    pub fn save(&self) {
+        let result = ron::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|data| {
+                storage::save(&get_profile_key(&self.name), data.as_bytes())
+                    .map_err(|e| e.to_string())
+            });
+
+        if let Err(e) = result {
+            error!("Failed to save profile {}: {}", self.name, e);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Profiles {
+    pub all: Vec<Profile>,
+    // indexed by player
+    selected: [Option<usize>; 2],
+}
+
+impl Profiles {
+    fn load() -> Self {
+        let mut all: Vec<Profile> = storage::list(PROFILES_DIR)
+            .iter()
+            .filter_map(|key| storage::load(key).ok())
+            .filter_map(|bytes| ron::de::from_bytes::<Profile>(&bytes).ok())
+            .map(|mut profile| {
+                if !profile.stats.rating.is_finite() {
+                    profile.stats.rating = DEFAULT_RATING;
+                }
+                profile
+            })
+            .collect();
+        all.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            all,
+            ..Default::default()
+        }
+    }
+
+    // best rated first
+    pub fn get_ranked(&self) -> Vec<&Profile> {
+        let mut ranked: Vec<&Profile> = self.all.iter().collect();
+        // the ratings come from editable files, so a NaN mustn't panic the sort
+        ranked.sort_by(|a, b| b.stats.rating.total_cmp(&a.stats.rating));
+        ranked
+    }
+
+    pub fn get_selected(&self, player_id: usize) -> Option<&Profile> {
+        self.selected[get_player_index(player_id)].map(|i| &self.all[i])
+    }
+
+    pub fn get_selected_mut(&mut self, player_id: usize) -> Option<&mut Profile> {
+        self.selected[get_player_index(player_id)].map(move |i| &mut self.all[i])
+    }
+
+    // falls back to the side of the court
+    pub fn get_player_name(&self, player_id: usize, locale: &Locale) -> String {
+        match self.get_selected(player_id) {
+            Some(profile) => profile.name.clone(),
+            None => locale.get(if is_left_player_id(player_id) {
+                "score-left"
+            } else {
+                "score-right"
+            }),
+        }
+    }
+
+    // none -> every profile not picked by the other player -> none
+    fn cycle(&mut self, player_id: usize) {
+        let player_i = get_player_index(player_id);
+        let other = self.selected[1 - player_i];
+        let start = self.selected[player_i].map_or(0, |i| i + 1);
+
+        self.selected[player_i] = (start..self.all.len()).find(|i| Some(*i) != other);
+    }
+
+    // players without a profile keep the standard controls
+    pub fn get_controls(&self, player_id: usize) -> ControlPreset {
+        self.get_selected(player_id)
+            .map_or_else(ControlPreset::default, |p| p.controls)
+    }
+
+    fn cycle_controls(&mut self, player_id: usize) {
+        if let Some(profile) = self.get_selected_mut(player_id) {
+            profile.controls = profile.controls.get_next();
+            profile.save();
+        }
+    }
+
+    // names sharing a storage key are the same profile, e.g. 'Ann B' and 'ann_b'
+    fn select_or_add(&mut self, player_id: usize, name: &str) {
+        let key = get_profile_key(name);
+        let i = match self
+            .all
+            .iter()
+            .position(|p| get_profile_key(&p.name) == key)
+        {
+            Some(i) => i,
+            None => {
+                let profile = Profile::new(name);
+                profile.save();
+                self.all.push(profile);
+                self.all.len() - 1
+            }
+        };
+
+        let player_i = get_player_index(player_id);
+        if self.selected[1 - player_i] == Some(i) {
+            self.selected[1 - player_i] = None;
+        }
+        self.selected[player_i] = Some(i);
+    }
+}
+
+// lowercase with anything but letters and digits replaced, so it's a valid file name
+pub fn get_profile_key(name: &str) -> String {
+    let file_name: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{}/{}.ron", PROFILES_DIR, file_name)
+}
+
+fn get_player_index(player_id: usize) -> usize {
+    if is_left_player_id(player_id) {
+        0
+    } else {
+        1
+    }
+}
+
+// typing a new profile name - N for the left player, Shift+N for the right one
+#[derive(Default)]
+struct NameEntry {
+    active: bool,
+    player_id: usize,
+    name: String,
+}
+
+#[derive(Component)]
+struct ProfilesText;
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, text_settings: Res<TextSettings>) {
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.),
+                    right: Val::Px(15.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                get_text_style(&asset_server, &text_settings, 22.),
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(PaletteColor::Text)
+        .insert(ScaledText(22.))
+        .insert(ProfilesText)
+        .insert(Name::new("ProfilesText"));
+}
+
+fn enter_name(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut ev_r_char: EventReader<ReceivedCharacter>,
+    mut entry: ResMut<NameEntry>,
+    mut profiles: ResMut<Profiles>,
+    score: Res<Score>,
+) {
+    if !entry.active {
+        // skip whatever was typed in the meantime
+        ev_r_char.iter().for_each(drop);
+
+        if score.is_match_start() && keys.just_pressed(KeyCode::N) {
+            let shift = keys.pressed(KeyCode::LShift) || keys.pressed(KeyCode::RShift);
+            entry.active = true;
+            entry.player_id = if shift { 2 } else { 1 };
+            entry.name.clear();
+            keys.clear();
+        }
+
+        return;
+    }
+
+    for ev in ev_r_char.iter() {
+        if !ev.char.is_control() && entry.name.chars().count() < MAX_NAME_LEN {
+            entry.name.push(ev.char);
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Back) {
+        entry.name.pop();
+    }
+
+    // an empty name cancels the entry
+    if keys.just_pressed(KeyCode::Return) {
+        let name = entry.name.trim().to_string();
+        if !name.is_empty() {
+            profiles.select_or_add(entry.player_id, &name);
+        }
+        entry.active = false;
+    }
+
+    keys.clear();
+}
+
+fn select_profiles(keys: Res<Input<KeyCode>>, score: Res<Score>, mut profiles: ResMut<Profiles>) {
+    if !score.is_match_start() {
+        return;
+    }
+
+    let shift = keys.pressed(KeyCode::LShift) || keys.pressed(KeyCode::RShift);
+
+    for (i, key) in SELECT_KEYS.iter().enumerate() {
+        if keys.just_pressed(*key) {
+            if shift {
+                profiles.cycle_controls(i + 1);
+            } else {
+                profiles.cycle(i + 1);
+            }
+        }
+    }
+}
+
+// the left player's profile picks the palette and remembers changing it
+fn apply_preferred_palette(
+    mut profiles: ResMut<Profiles>,
+    mut palette: ResMut<Palette>,
+    mut applied_i: Local<Option<usize>>,
+) {
+    let selected_i = profiles.selected[0];
+
+    if selected_i != *applied_i {
+        *applied_i = selected_i;
+
+        if let Some(theme) = selected_i.and_then(|i| profiles.all[i].palette) {
+            if palette.get_theme() != theme {
+                *palette = theme.get_palette();
+            }
+        }
+    } else if palette.is_changed() {
+        if let Some(i) = selected_i {
+            let theme = palette.get_theme();
+            let profile = &mut profiles.all[i];
+            if profile.palette != Some(theme) {
+                profile.palette = Some(theme);
+                profile.save();
+            }
+        }
+    }
+}
+
+fn update_stats(mut ev_r_score: EventReader<ScoreChangedEvt>, mut profiles: ResMut<Profiles>) {
+    for ev in ev_r_score.iter() {
+        // both ratings change based on the ratings before the match
+        let rating_changes = match (profiles.selected[0], profiles.selected[1]) {
+            (Some(left_i), Some(right_i)) if ev.outcome == PointOutcome::Match => {
+                let left_rating = profiles.all[left_i].stats.rating;
+                let right_rating = profiles.all[right_i].stats.rating;
+                let left_won = is_left_player_id(ev.scoring_player_id);
+                Some([
+                    get_rating_change(left_rating, right_rating, left_won),
+                    get_rating_change(right_rating, left_rating, !left_won),
+                ])
+            }
+            _ => None,
+        };
+
+        for player_id in [1, 2] {
+            if let Some(i) = profiles.selected[get_player_index(player_id)] {
+                let scored = player_id == ev.scoring_player_id;
+                let profile = &mut profiles.all[i];
+
+                if scored {
+                    profile.stats.points_won += 1;
+                }
+
+                if ev.outcome == PointOutcome::Match {
+                    profile.stats.matches_played += 1;
+                    if scored {
+                        profile.stats.matches_won += 1;
+                    }
+
+                    if let Some(changes) = rating_changes {
+                        profile.stats.rating += changes[get_player_index(player_id)];
+                        profile.stats.recent_form.push(scored);
+                        let form_len = profile.stats.recent_form.len();
+                        if form_len > RECENT_FORM_LEN {
+                            profile
+                                .stats
+                                .recent_form
+                                .drain(..form_len - RECENT_FORM_LEN);
+                        }
+                    }
+
+                    for cosmetic in unlock_earned(profile) {
+                        info!("{} unlocked {:?}", profile.name, cosmetic);
+                    }
+                }
+
+                profile.save();
+            }
+        }
+    }
+}
+
+fn update_profiles_ui(
+    profiles: Res<Profiles>,
+    entry: Res<NameEntry>,
+    score: Res<Score>,
+    locale: Res<Locale>,
+    mut text_q: Query<&mut Text, With<ProfilesText>>,
+) {
+    if !profiles.is_changed() && !entry.is_changed() && !score.is_changed() && !locale.is_changed()
+    {
+        return;
+    }
+
+    let value = if entry.active {
+        format!(
+            "{:?} {}: {}_",
+            SELECT_KEYS[get_player_index(entry.player_id)],
+            locale.get("profile-new"),
+            entry.name
+        )
+    } else if score.is_match_start() {
+        let mut lines: Vec<String> = SELECT_KEYS
+            .iter()
+            .enumerate()
+            .map(|(i, key)| {
+                let name = profiles.get_selected(i + 1).map_or_else(
+                    || locale.get("profile-none"),
+                    |p| format!("{} ({})", p.name, locale.get(p.controls.get_name_key())),
+                );
+                format!("{:?} {}", key, name)
+            })
+            .collect();
+        lines.push(format!("Shift+1/2 {}", locale.get("controls")));
+        lines.push(format!("N/Shift+N {}", locale.get("profile-new")));
+        lines.push(format!("B {}", locale.get("leaderboard")));
+        lines.join("\n")
+    } else {
+        String::new()
+    };
+
+    for mut text in text_q.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{input_binding::InputAction, locale::Locale};
+
+pub struct PromptPlugin;
+impl Plugin for PromptPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<ButtonPrompts>()
+            .init_resource::<ActiveInputDevice>()
+            .add_system(detect_active_device)
+            .add_system(update_prompts);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputDevice {
+    Keyboard,
+    Gamepad,
+}
+
+pub struct ActiveInputDevice(pub InputDevice);
+
+impl Default for ActiveInputDevice {
+    fn default() -> Self {
+        Self(InputDevice::Keyboard)
+    }
+}
+
+// labels are filled in by the binding setup, so they always match the ActionMap
+#[derive(Default)]
+pub struct ButtonPrompts {
+    labels: HashMap<(usize, InputAction, InputDevice), String>,
+}
+
+impl ButtonPrompts {
+    pub fn add_key(&mut self, player_id: usize, action: InputAction, key: KeyCode) {
+        self.labels
+            .entry((player_id, action, InputDevice::Keyboard))
+            .or_insert_with(|| get_key_label(key));
+    }
+
+    pub fn add_gamepad_button(
+        &mut self,
+        player_id: usize,
+        action: InputAction,
+        button: GamepadButtonType,
+    ) {
+        self.labels
+            .entry((player_id, action, InputDevice::Gamepad))
+            .or_insert_with(|| get_gamepad_button_label(button).to_string());
+    }
+
+    pub fn add_gamepad_combo(
+        &mut self,
+        player_id: usize,
+        action: InputAction,
+        buttons: &[GamepadButtonType],
+    ) {
+        self.labels
+            .entry((player_id, action, InputDevice::Gamepad))
+            .or_insert_with(|| {
+                buttons
+                    .iter()
+                    .map(|b| get_gamepad_button_label(*b))
+                    .collect::<Vec<_>>()
+                    .join("+")
+            });
+    }
+
+    // falls back to the other device, if the action isn't bound on the active one
+    pub fn get(&self, player_id: usize, action: InputAction, device: InputDevice) -> Option<&str> {
+        let other_device = match device {
+            InputDevice::Keyboard => InputDevice::Gamepad,
+            InputDevice::Gamepad => InputDevice::Keyboard,
+        };
+
+        self.labels
+            .get(&(player_id, action, device))
+            .or_else(|| self.labels.get(&(player_id, action, other_device)))
+            .map(|l| l.as_str())
+    }
+}
+
+#[derive(Component)]
+pub struct ButtonPrompt {
+    pub player_id: usize,
+    pub action: InputAction,
+    // locale key, '{}' gets replaced by the button label
+    pub template_key: &'static str,
+}
+
+fn get_key_label(key: KeyCode) -> String {
+    match key {
+        KeyCode::Escape => "Esc".to_string(),
+        KeyCode::Delete => "Del".to_string(),
+        KeyCode::NumpadAdd => "Num +".to_string(),
+        KeyCode::Numpad0 => "Num 0".to_string(),
+        KeyCode::NumpadEnter => "Num Enter".to_string(),
+        KeyCode::NumpadSubtract => "Num -".to_string(),
+        _ => format!("{:?}", key),
+    }
+}
+
+fn get_gamepad_button_label(button: GamepadButtonType) -> &'static str {
+    match button {
+        GamepadButtonType::South => "A",
+        GamepadButtonType::East => "B",
+        GamepadButtonType::West => "X",
+        GamepadButtonType::North => "Y",
+        GamepadButtonType::LeftTrigger => "LB",
+        GamepadButtonType::RightTrigger => "RB",
+        GamepadButtonType::LeftTrigger2 => "LT",
+        GamepadButtonType::RightTrigger2 => "RT",
+        GamepadButtonType::Select => "Select",
+        GamepadButtonType::Start => "Start",
+        GamepadButtonType::LeftThumb => "LS",
+        GamepadButtonType::RightThumb => "RS",
+        _ => "?",
+    }
+}
+
+fn detect_active_device(
+    keys: Res<Input<KeyCode>>,
+    pad_buttons: Res<Input<GamepadButton>>,
+    mut device: ResMut<ActiveInputDevice>,
+) {
+    let used_device = if keys.get_just_pressed().next().is_some() {
+        InputDevice::Keyboard
+    } else if pad_buttons.get_just_pressed().next().is_some() {
+        InputDevice::Gamepad
+    } else {
+        return;
+    };
+
+    if device.0 != used_device {
+        device.0 = used_device;
+    }
+}
+
+fn update_prompts(
+    prompts: Res<ButtonPrompts>,
+    device: Res<ActiveInputDevice>,
+    locale: Res<Locale>,
+    mut prompt_q: Query<(&ButtonPrompt, &mut Text)>,
+) {
+    for (prompt, mut text) in prompt_q.iter_mut() {
+        let label = prompts
+            .get(prompt.player_id, prompt.action, device.0)
+            .unwrap_or("?");
+        let value = locale.format(prompt.template_key, &[label]);
+
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
@@ -0,0 +1,542 @@
+use std::io::{self, Read, Write};
+
+use bevy::prelude::*;
+
+use crate::{
+    ball::{Ball, BALL_SIZE},
+    camera::{MainCamera, ScriptedCamera},
+    checksum::{StateChecksum, SyncCheck},
+    input_binding::{InputAction, PlayerInput},
+    palette::PaletteColor,
+    player::Player,
+    render::{BALL_Z, PLAYER_Z},
+    reset::DespawnOnExit,
+    score::{GameOverEvt, Score},
+    storage, GameState,
+};
+
+const REPLAY_DIR: &str = "replays";
+const REPLAY_EXT: &str = "tobr";
+const REPLAY_MAGIC: &[u8; 4] = b"TOBR";
+const REPLAY_VERSION: u8 = 2;
+// before the checksums
+const REPLAY_VERSION_NO_CHECKSUMS: u8 = 1;
+// the time and both counts, without any players or balls
+const MIN_FRAME_BYTES: usize = 6;
+// e.g. the best rally on the results screen
+const SLOW_MOTION_SPEED: f32 = 0.5;
+const SLOW_MOTION_ZOOM_SCALE: f32 = 0.6;
+const SLOW_MOTION_ZOOM_SPEED: f32 = 3.;
+
+pub struct ReplayPlugin;
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<ReplayRecorder>()
+            .init_resource::<ReplayPlayback>()
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(start_recording))
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(record_frame)
+                    .with_system(export_on_game_over)
+                    .with_system(handle_replay_input),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Replay).with_system(start_playback))
+            .add_system_set(
+                SystemSet::on_update(GameState::Replay)
+                    .with_system(playback)
+                    .with_system(follow_slow_motion_ball),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Replay).with_system(stop_playback));
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct ReplayFrame {
+    pub time: f32,
+    pub players: Vec<(u8, Vec2)>,
+    // x, y and the bounce height
+    pub balls: Vec<Vec3>,
+}
+
+#[derive(Default, Clone)]
+pub struct Replay {
+    pub frames: Vec<ReplayFrame>,
+    // one per frame, only recorded in debug builds
+    pub checksums: Vec<StateChecksum>,
+}
+
+#[derive(Default)]
+pub struct ReplayRecorder {
+    pub replay: Replay,
+    start_time: f64,
+}
+
+#[derive(Default)]
+pub struct ReplayPlayback {
+    pub replay: Option<Replay>,
+    // plays at a lower speed with the camera zoomed in on the ball
+    pub slow_motion: bool,
+    time: f32,
+    frame: usize,
+    ghosts: ReplayGhosts,
+    sync_check: SyncCheck,
+}
+
+// sprites standing in for the recorded players and balls
+#[derive(Default)]
+pub struct ReplayGhosts {
+    player_entities: Vec<Entity>,
+    ball_entities: Vec<Entity>,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay: Some(replay),
+            ..Default::default()
+        }
+    }
+}
+
+impl ReplayGhosts {
+    pub fn clear(&mut self) {
+        self.player_entities.clear();
+        self.ball_entities.clear();
+    }
+}
+
+impl ReplayFrame {
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.time.to_le_bytes())?;
+        w.write_all(&[self.players.len() as u8])?;
+        for (id, pos) in self.players.iter() {
+            w.write_all(&[*id])?;
+            write_f32s(w, &[pos.x, pos.y])?;
+        }
+        w.write_all(&[self.balls.len() as u8])?;
+        for pos in self.balls.iter() {
+            write_f32s(w, &[pos.x, pos.y, pos.z])?;
+        }
+
+        Ok(())
+    }
+
+    pub fn read<R: Read>(r: &mut R) -> io::Result<Self> {
+        let time = read_f32(r)?;
+        let mut players = Vec::new();
+        for _ in 0..read_u8(r)? {
+            let id = read_u8(r)?;
+            players.push((id, Vec2::new(read_f32(r)?, read_f32(r)?)));
+        }
+        let mut balls = Vec::new();
+        for _ in 0..read_u8(r)? {
+            balls.push(Vec3::new(read_f32(r)?, read_f32(r)?, read_f32(r)?));
+        }
+
+        Ok(Self {
+            time,
+            players,
+            balls,
+        })
+    }
+}
+
+impl Replay {
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(REPLAY_MAGIC)?;
+        w.write_all(&[REPLAY_VERSION])?;
+        w.write_all(&(self.frames.len() as u32).to_le_bytes())?;
+
+        for frame in self.frames.iter() {
+            frame.write(w)?;
+        }
+
+        w.write_all(&(self.checksums.len() as u32).to_le_bytes())?;
+        for checksum in self.checksums.iter() {
+            w.write_all(&checksum.transforms.to_le_bytes())?;
+            match checksum.score {
+                Some(score) => {
+                    w.write_all(&[1])?;
+                    w.write_all(&score.to_le_bytes())?;
+                }
+                None => w.write_all(&[0])?,
+            }
+        }
+
+        Ok(())
+    }
+
+    // reads from the whole file, so the frame count can be checked against the bytes left
+    pub fn read(r: &mut &[u8]) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        let version = read_u8(r)?;
+        if &magic != REPLAY_MAGIC
+            || (version != REPLAY_VERSION && version != REPLAY_VERSION_NO_CHECKSUMS)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a supported replay file",
+            ));
+        }
+
+        let len = read_u32(r)? as usize;
+        // a broken or crafted file could ask for any length
+        let mut frames = Vec::with_capacity(len.min(r.len() / MIN_FRAME_BYTES));
+
+        for _ in 0..len {
+            frames.push(ReplayFrame::read(r)?);
+        }
+
+        let mut checksums = Vec::new();
+        if version != REPLAY_VERSION_NO_CHECKSUMS {
+            for _ in 0..read_u32(r)? {
+                let transforms = read_u64(r)?;
+                let score = if read_u8(r)? == 1 {
+                    Some(read_u64(r)?)
+                } else {
+                    None
+                };
+                checksums.push(StateChecksum { transforms, score });
+            }
+        }
+
+        Ok(Self { frames, checksums })
+    }
+
+    pub fn save(&self) -> io::Result<String> {
+        // zero padded, so the latest replay sorts last
+        let prefix = format!("{}/match_", REPLAY_DIR);
+        let number = storage::get_next_number(&storage::list(REPLAY_DIR), &prefix, REPLAY_EXT);
+        let key = format!("{}{:05}.{}", prefix, number, REPLAY_EXT);
+        let mut data = Vec::new();
+        self.write(&mut data)?;
+        storage::save(&key, &data)?;
+        Ok(key)
+    }
+
+    pub fn load(key: &str) -> io::Result<Self> {
+        Self::read(&mut storage::load(key)?.as_slice())
+    }
+
+    pub fn load_latest() -> io::Result<Self> {
+        let latest = storage::list(REPLAY_DIR)
+            .into_iter()
+            .filter(|k| k.ends_with(REPLAY_EXT))
+            .max()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no replays saved yet"))?;
+        Self::load(&latest)
+    }
+}
+
+pub fn write_f32s<W: Write>(w: &mut W, values: &[f32]) -> io::Result<()> {
+    for v in values.iter() {
+        w.write_all(&v.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+pub fn read_u8<R: Read>(r: &mut R) -> io::Result<u8> {
+    let mut buf = [0u8; 1];
+    r.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+pub fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+pub fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+pub fn read_f32<R: Read>(r: &mut R) -> io::Result<f32> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    Ok(f32::from_le_bytes(buf))
+}
+
+fn start_recording(mut recorder: ResMut<ReplayRecorder>, time: Res<Time>) {
+    recorder.replay.frames.clear();
+    recorder.replay.checksums.clear();
+    recorder.start_time = time.seconds_since_startup();
+}
+
+fn record_frame(
+    mut recorder: ResMut<ReplayRecorder>,
+    time: Res<Time>,
+    player_q: Query<(&Player, &GlobalTransform)>,
+    ball_q: Query<(&Ball, &GlobalTransform)>,
+    bounce_t_q: Query<&Transform>,
+    score: Res<Score>,
+) {
+    let frame = get_frame(
+        (time.seconds_since_startup() - recorder.start_time) as f32,
+        &player_q,
+        &ball_q,
+        &bounce_t_q,
+    );
+
+    // determinism checks are too costly for release builds
+    if cfg!(debug_assertions) {
+        recorder
+            .replay
+            .checksums
+            .push(StateChecksum::new(&frame, Some(&score)));
+    }
+
+    recorder.replay.frames.push(frame);
+}
+
+pub fn get_frame(
+    time: f32,
+    player_q: &Query<(&Player, &GlobalTransform)>,
+    ball_q: &Query<(&Ball, &GlobalTransform)>,
+    bounce_t_q: &Query<&Transform>,
+) -> ReplayFrame {
+    ReplayFrame {
+        time,
+        players: player_q
+            .iter()
+            .map(|(p, t)| (p.id as u8, t.translation.truncate()))
+            .collect(),
+        balls: ball_q
+            .iter()
+            .map(|(b, t)| {
+                let height = b
+                    .bounce_e
+                    .and_then(|e| bounce_t_q.get(e).ok())
+                    .map_or(0., |bounce_t| bounce_t.translation.y);
+                t.translation.truncate().extend(height)
+            })
+            .collect(),
+    }
+}
+
+fn export_on_game_over(
+    mut ev_r_game_over: EventReader<GameOverEvt>,
+    recorder: Res<ReplayRecorder>,
+) {
+    for _ in ev_r_game_over.iter() {
+        save_replay(&recorder.replay);
+    }
+}
+
+fn save_replay(replay: &Replay) {
+    match replay.save() {
+        Ok(key) => info!("Replay saved to {}", key),
+        Err(e) => warn!("Failed to save replay: {}", e),
+    }
+}
+
+// replays get picked from the menu before the match
+fn handle_replay_input(
+    mut input: ResMut<PlayerInput>,
+    score: Res<Score>,
+    recorder: Res<ReplayRecorder>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut state: ResMut<State<GameState>>,
+) {
+    for id in 1..=4 {
+        if input.just_pressed(id, InputAction::SaveReplay) {
+            save_replay(&recorder.replay);
+        } else if score.is_match_start() && input.just_pressed(id, InputAction::PlayReplay) {
+            input.use_button_action(id, InputAction::PlayReplay);
+
+            match Replay::load_latest() {
+                Ok(replay) => {
+                    playback.replay = Some(replay);
+                    state.overwrite_replace(GameState::Replay).unwrap();
+                }
+                Err(e) => warn!("Failed to load replay: {}", e),
+            }
+            break;
+        }
+    }
+}
+
+fn start_playback(
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    cam_q: Query<Entity, With<MainCamera>>,
+) {
+    if playback.slow_motion {
+        for cam_e in cam_q.iter() {
+            commands.entity(cam_e).insert(ScriptedCamera);
+        }
+    }
+
+    playback.time = 0.;
+    playback.frame = 0;
+    playback.ghosts.clear();
+
+    let checksums = playback
+        .replay
+        .as_ref()
+        .map_or(Vec::new(), |replay| replay.checksums.clone());
+    playback.sync_check.start(checksums);
+}
+
+fn playback(
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    mut transform_q: Query<(&mut Transform, &mut Visibility)>,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let speed = if playback.slow_motion {
+        SLOW_MOTION_SPEED
+    } else {
+        1.
+    };
+    playback.time += time.delta_seconds() * speed;
+
+    let frame = match &playback.replay {
+        Some(replay) => {
+            let mut frame_i = playback.frame;
+            while frame_i + 1 < replay.frames.len()
+                && replay.frames[frame_i + 1].time <= playback.time
+            {
+                frame_i += 1;
+            }
+
+            if frame_i + 1 >= replay.frames.len() {
+                // done
+                state.overwrite_replace(GameState::Reset).unwrap();
+                return;
+            }
+
+            playback.frame = frame_i;
+            replay.frames[frame_i].clone()
+        }
+        None => return,
+    };
+
+    if cfg!(debug_assertions) {
+        let frame_i = playback.frame;
+        playback
+            .sync_check
+            .check(frame_i, StateChecksum::new(&frame, None));
+    }
+
+    show_ghosts(
+        &mut commands,
+        &mut playback.ghosts,
+        &frame,
+        &mut transform_q,
+        &asset_server,
+        GameState::Replay,
+    );
+}
+
+pub fn show_ghosts(
+    commands: &mut Commands,
+    ghosts: &mut ReplayGhosts,
+    frame: &ReplayFrame,
+    transform_q: &mut Query<(&mut Transform, &mut Visibility)>,
+    asset_server: &Res<AssetServer>,
+    state: GameState,
+) {
+    let player_positions: Vec<Vec3> = frame
+        .players
+        .iter()
+        .map(|(_, pos)| pos.extend(PLAYER_Z))
+        .collect();
+    let ball_positions: Vec<Vec3> = frame
+        .balls
+        .iter()
+        .map(|pos| Vec3::new(pos.x, pos.y + pos.z, BALL_Z))
+        .collect();
+
+    for (entities, positions, texture, size, color) in [
+        (
+            &mut ghosts.player_entities,
+            player_positions,
+            "art-ish/player_body.png",
+            None,
+            PaletteColor::Player,
+        ),
+        (
+            &mut ghosts.ball_entities,
+            ball_positions,
+            "art-ish/ball.png",
+            Some(Vec2::ONE * BALL_SIZE),
+            PaletteColor::Ball,
+        ),
+    ] {
+        while entities.len() < positions.len() {
+            entities.push(
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        texture: asset_server.load(texture),
+                        sprite: Sprite {
+                            custom_size: size,
+                            ..Default::default()
+                        },
+                        transform: Transform::from_translation(positions[entities.len()]),
+                        ..Default::default()
+                    })
+                    .insert(color)
+                    .insert(Name::new("ReplayGhost"))
+                    .insert(DespawnOnExit(state.clone()))
+                    .id(),
+            );
+        }
+
+        for (i, e) in entities.iter().enumerate() {
+            if let Ok((mut t, mut visibility)) = transform_q.get_mut(*e) {
+                visibility.is_visible = i < positions.len();
+
+                if let Some(pos) = positions.get(i) {
+                    t.translation = *pos;
+                }
+            }
+        }
+    }
+}
+
+// keeps the ball of the current frame in the middle, the players are left to the zoom
+fn follow_slow_motion_ball(
+    playback: Res<ReplayPlayback>,
+    mut cam_q: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
+    time: Res<Time>,
+) {
+    if !playback.slow_motion {
+        return;
+    }
+
+    let ball_pos = match playback
+        .replay
+        .as_ref()
+        .and_then(|replay| replay.frames.get(playback.frame))
+        .and_then(|frame| frame.balls.first())
+    {
+        Some(pos) => Vec2::new(pos.x, pos.y + pos.z),
+        None => return,
+    };
+
+    let zoom_t = (SLOW_MOTION_ZOOM_SPEED * time.delta_seconds()).min(1.);
+    for (mut cam_t, mut projection) in cam_q.iter_mut() {
+        let target = ball_pos.extend(cam_t.translation.z);
+        cam_t.translation = cam_t.translation.lerp(target, zoom_t);
+        projection.scale += (SLOW_MOTION_ZOOM_SCALE - projection.scale) * zoom_t;
+    }
+}
+
+fn stop_playback(
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    cam_q: Query<Entity, With<MainCamera>>,
+) {
+    playback.replay = None;
+    playback.slow_motion = false;
+
+    for cam_e in cam_q.iter() {
+        commands.entity(cam_e).remove::<ScriptedCamera>();
+    }
+}
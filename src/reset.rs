@@ -1,39 +1,166 @@
 use crate::{
-    input_binding::{InputAction, PlayerInput},
+    input_binding::{is_pad_combo_held, InputAction, PlayerInput, RESET_PAD_COMBO},
+    locale::Locale,
+    palette::PaletteColor,
+    timer::{GameClock, GameTimer},
+    touch::TouchControls,
+    ui_text::{get_text_style, ScaledText, TextSettings},
     GameState,
 };
 use bevy::prelude::*;
 
+// restarts have to be held, so a stray press doesn't wipe the match
+const RESET_HOLD_SEC: f32 = 0.8;
+
 pub struct ResetPlugin;
 impl Plugin for ResetPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_system_set(SystemSet::on_enter(GameState::Reset).with_system(reset))
-            .add_system_set(SystemSet::on_update(GameState::Game).with_system(handle_reset_input));
+        app.init_resource::<ResetHold>()
+            .add_system_set(SystemSet::on_enter(GameState::Reset).with_system(reset))
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(setup))
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(handle_reset_input)
+                    .with_system(update_reset_hold_text),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::PreMatch).with_system(handle_reset_input),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Replay).with_system(handle_reset_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Challenge).with_system(handle_reset_input),
+            );
+
+        for state in [
+            GameState::Game,
+            GameState::PreMatch,
+            GameState::Reset,
+            GameState::Replay,
+            GameState::Challenge,
+            GameState::Spectate,
+        ] {
+            app.add_system_set(SystemSet::on_exit(state).with_system(despawn_state_scoped));
+        }
     }
 }
 
+// despawned (recursively) once the given state is left
+// entities without it live for the whole app
 #[derive(Component)]
-pub struct Persistent;
+pub struct DespawnOnExit(pub GameState);
+
+struct ResetHold {
+    // real time, the hold works the same in slow motion and on top of the paused game
+    timer: GameTimer,
+    // the held input would restart the fresh match otherwise
+    wait_for_release: bool,
+}
+
+impl Default for ResetHold {
+    fn default() -> Self {
+        Self {
+            timer: GameTimer::unscaled(RESET_HOLD_SEC),
+            wait_for_release: false,
+        }
+    }
+}
+
+#[derive(Component)]
+struct ResetHoldText;
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, text_settings: Res<TextSettings>) {
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(10.0),
+                    right: Val::Px(15.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                get_text_style(&asset_server, &text_settings, 30.0),
+                TextAlignment::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(PaletteColor::Text)
+        .insert(ScaledText(30.0))
+        .insert(ResetHoldText)
+        .insert(Name::new("ResetHoldText"))
+        .insert(DespawnOnExit(GameState::Game));
+}
+
+fn handle_reset_input(
+    mut input: ResMut<PlayerInput>,
+    mut state: ResMut<State<GameState>>,
+    mut hold: ResMut<ResetHold>,
+    touch: Res<TouchControls>,
+    gamepads: Res<Gamepads>,
+    pad_buttons: Res<Input<GamepadButton>>,
+    clock: GameClock,
+) {
+    let held = touch.reset_held
+        || is_pad_combo_held(&gamepads, &pad_buttons, &RESET_PAD_COMBO)
+        || (1..=4).any(|id| input.held(id, InputAction::Reset));
+    if !held {
+        hold.wait_for_release = false;
+    }
+    if hold.wait_for_release {
+        return;
+    }
+
+    if held {
+        hold.timer.tick(&clock);
+    } else if hold.timer.get_elapsed_sec() > 0. {
+        hold.timer.reset();
+    }
 
-fn handle_reset_input(mut input: ResMut<PlayerInput>, mut state: ResMut<State<GameState>>) {
-    for id in 1..=4 {
-        if input.just_pressed(id, InputAction::Reset) {
+    if hold.timer.finished() {
+        for id in 1..=4 {
             input.use_button_action(id, InputAction::Reset);
-            state.overwrite_push(GameState::Reset).unwrap();
-            break;
         }
+        hold.timer.reset();
+        hold.wait_for_release = true;
+        // the pushed states (e.g. the prematch intro) would leave the game underneath otherwise
+        state.overwrite_replace(GameState::Reset).unwrap();
     }
 }
 
-fn reset(
+fn update_reset_hold_text(
+    hold: Res<ResetHold>,
+    locale: Res<Locale>,
+    mut text_q: Query<&mut Text, With<ResetHoldText>>,
+) {
+    if hold.is_changed() {
+        for mut text in text_q.iter_mut() {
+            text.sections[0].value = if hold.timer.get_elapsed_sec() > 0. {
+                let percent = (hold.timer.get_fraction() * 100.) as u32;
+                locale.format("reset-hold", &[&percent.to_string()])
+            } else {
+                String::new()
+            };
+        }
+    }
+}
+
+// the exited state is still the current one while the exit systems run
+fn despawn_state_scoped(
     mut commands: Commands,
-    mut state: ResMut<State<GameState>>,
-    despawn_q: Query<Entity, (Without<Persistent>, Without<Parent>)>,
+    state: Res<State<GameState>>,
+    scoped_q: Query<(Entity, &DespawnOnExit)>,
 ) {
-    // todo: tween out the removed entities first
-    for e in despawn_q.iter() {
-        commands.entity(e).despawn_recursive();
+    for (e, scope) in scoped_q.iter() {
+        if scope.0 == *state.current() {
+            commands.entity(e).despawn_recursive();
+        }
     }
+}
 
-    state.overwrite_push(GameState::Game).unwrap();
+fn reset(mut state: ResMut<State<GameState>>) {
+    // todo: tween out the removed entities first
+    state.overwrite_set(GameState::Game).unwrap();
 }
@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Error, RngCore, SeedableRng};
+
+use crate::{GameSetupPhase, GameState};
+
+// set to reproduce a match, e.g. TUG_OF_BALL_SEED=42
+const SEED_ENV_VAR: &str = "TUG_OF_BALL_SEED";
+
+pub struct RngPlugin;
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<GameRng>().add_system_set(
+            SystemSet::on_enter(GameState::Game).with_system(reseed.before(GameSetupPhase::Level)),
+        );
+    }
+}
+
+// all gameplay randomness goes through this, so a match can be reproduced from its seed
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        let seed = std::env::var(SEED_ENV_VAR)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(rand::random);
+        info!("Rng seed {}", seed);
+
+        Self::new(seed)
+    }
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn reseed(&mut self) {
+        self.rng = StdRng::seed_from_u64(self.seed);
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+// every match plays out the same for the same seed
+fn reseed(mut rng: ResMut<GameRng>) {
+    rng.reseed();
+}
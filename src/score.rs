@@ -1,34 +1,393 @@
-use crate::{palette::PaletteColor, reset::Persistent, GameState};
+use crate::{
+    ball::Ball,
+    hud_layout::HudLayout,
+    input_binding::InputAction,
+    locale::Locale,
+    mode::GameMode,
+    palette::{Palette, PaletteColor},
+    profile::Profiles,
+    prompt::ButtonPrompt,
+    ui_text::{get_text_style, ScaledText, TextSettings, TEXT_SETTINGS_LABEL},
+    GameState,
+};
 use bevy::prelude::*;
+#[cfg(feature = "inspector")]
 use bevy_inspector_egui::Inspectable;
+use serde::Serialize;
+
+const SCORE_ANIM_LABEL: &str = "score_anim";
+const POINTS_TEXT_SIZE: f32 = 100.;
+const CALLOUT_TEXT_SIZE: f32 = 50.;
+// the scoring side's number grows by this and shrinks back
+const POINT_POP_SCALE: f32 = 0.4;
+const POINT_POP_SEC: f32 = 0.35;
+const CALLOUT_IN_SEC: f32 = 0.3;
+const GAME_SHAKE_SEC: f32 = 0.4;
+const GAME_SHAKE_PX: f32 = 8.;
 
 pub struct ScorePlugin;
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.init_resource::<Score>()
+            .init_resource::<MatchRules>()
+            .init_resource::<PointValue>()
+            .add_event::<GameOverEvt>()
+            .add_event::<ScoreChangedEvt>()
             .add_startup_system(setup)
             .add_system_set(SystemSet::on_enter(GameState::Game).with_system(reset_score))
-            .add_system(update_score_ui);
+            .add_system(update_score_ui)
+            .add_system(start_score_animations.label(SCORE_ANIM_LABEL))
+            // the points text sizes pop, so they have the final say over the text settings
+            .add_system(
+                animate_score_ui
+                    .after(SCORE_ANIM_LABEL)
+                    .after(TEXT_SETTINGS_LABEL),
+            )
+            .add_system(update_sets_ui)
+            .add_system(update_restart_prompt);
     }
 }
 
-#[derive(Component, Inspectable)]
+// sections: deuce/advantage/golden point callout (or the winner), left points, separator, right points
+#[derive(Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 struct PointsText;
 
-#[derive(Default)]
+// restarted by ScoreChangedEvt, the timers only run up to their duration
+#[derive(Component)]
+struct ScoreAnimation {
+    pop_left_player: bool,
+    pop_sec: f32,
+    callout_sec: f32,
+    shake_sec: f32,
+}
+
+impl Default for ScoreAnimation {
+    fn default() -> Self {
+        Self {
+            pop_left_player: true,
+            pop_sec: POINT_POP_SEC,
+            callout_sec: CALLOUT_IN_SEC,
+            shake_sec: GAME_SHAKE_SEC,
+        }
+    }
+}
+
+#[derive(Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
+struct SetsText;
+
+#[derive(Component)]
+struct RestartPrompt;
+
+#[derive(Default, Clone)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct Score {
     pub left_player: PlayerScore,
     pub right_player: PlayerScore,
+    // finished sets as (left games, right games)
+    #[cfg_attr(feature = "inspector", inspectable(ignore))]
+    pub set_history: Vec<(u8, u8)>,
+    // extra net tug in games from the point kinds of the current game, positive favours the left player
+    pub net_bonus: f32,
 }
 
-#[derive(Default, Component, Inspectable)]
+impl Score {
+    pub fn is_tiebreak(&self, rules: &MatchRules) -> bool {
+        self.left_player.games == self.right_player.games
+            && self.left_player.games >= rules.tiebreak_at
+    }
+
+    // serve box alternates each point, starting from the deuce side
+    pub fn is_deuce_side(&self) -> bool {
+        (self.left_player.points + self.right_player.points) % 2 == 0
+    }
+
+    // serve swaps each game, in a tiebreak after the 1st point and then every 2 points
+    pub fn is_serve_swap(&self, rules: &MatchRules, outcome: PointOutcome) -> bool {
+        outcome.is_game_won()
+            || (self.is_tiebreak(rules)
+                && (self.left_player.points + self.right_player.points) % 2 == 1)
+    }
+
+    // deuce and advantage show as 3-3 and 4-3, however long the game goes
+    pub fn get_display_points(&self, rules: &MatchRules) -> (u8, u8) {
+        let (left, right) = (self.left_player.points, self.right_player.points);
+        if self.is_tiebreak(rules) {
+            return (left, right);
+        }
+
+        let extra = left.min(right).saturating_sub(3);
+        (left - extra, right - extra)
+    }
+
+    // the calls derived from the raw points, none in a tiebreak, which counts plain points
+    pub fn get_game_points(&self, rules: &MatchRules) -> Option<(GamePoint, GamePoint)> {
+        if self.is_tiebreak(rules) {
+            return None;
+        }
+
+        let (left, right) = self.get_display_points(rules);
+        Some((
+            GamePoint::from_points(left, right),
+            GamePoint::from_points(right, left),
+        ))
+    }
+
+    pub fn get_game_phase(&self, rules: &MatchRules) -> GamePhase {
+        match self.get_game_points(rules) {
+            Some((GamePoint::Forty, GamePoint::Forty)) if rules.no_ad => GamePhase::GoldenPoint,
+            Some((GamePoint::Forty, GamePoint::Forty)) => GamePhase::Deuce,
+            Some((GamePoint::Advantage, _)) => GamePhase::Advantage { left_player: true },
+            Some((_, GamePoint::Advantage)) => GamePhase::Advantage { left_player: false },
+            _ => GamePhase::Regular,
+        }
+    }
+
+    pub fn is_match_start(&self) -> bool {
+        self.set_history.is_empty()
+            && self.left_player.points + self.right_player.points == 0
+            && self.left_player.games + self.right_player.games == 0
+    }
+
+    pub fn games_played(&self) -> u8 {
+        self.set_history.iter().map(|(l, r)| l + r).sum::<u8>()
+            + self.left_player.games
+            + self.right_player.games
+    }
+
+    // the player would win the game with the next point
+    pub fn is_game_point(&self, rules: &MatchRules, for_left_player: bool) -> bool {
+        let (scoring, other) = if for_left_player {
+            (&self.left_player, &self.right_player)
+        } else {
+            (&self.right_player, &self.left_player)
+        };
+        let min_points = if self.is_tiebreak(rules) {
+            rules.tiebreak_points
+        } else {
+            4
+        };
+
+        // a golden point is a game point for both players
+        let no_ad = rules.no_ad && !self.is_tiebreak(rules);
+        scoring.points + 1 >= min_points
+            && (scoring.points > other.points || (no_ad && scoring.points == other.points))
+    }
+
+    pub fn get_winner_id(&self, rules: &MatchRules) -> Option<usize> {
+        if self.left_player.sets >= rules.sets_to_win() {
+            Some(1)
+        } else if self.right_player.sets >= rules.sets_to_win() {
+            Some(2)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Default, Clone, Component)]
+#[cfg_attr(feature = "inspector", derive(Inspectable))]
 pub struct PlayerScore {
+    // raw points won in the current game, deuce and advantage are derived by Score
     pub points: u8,
     pub games: u8,
-    // pub sets: u8,
+    pub sets: u8,
+}
+
+// points awarded for winning the current rally
+pub struct PointValue(pub u8);
+
+impl Default for PointValue {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointStart {
+    Serve,
+    // casual - no serves or faults, the ball is dropped at the center instead
+    DropBall,
+}
+
+impl PointStart {
+    pub fn get_name_key(&self) -> &'static str {
+        match self {
+            PointStart::Serve => "point-start-serve",
+            PointStart::DropBall => "point-start-drop-ball",
+        }
+    }
+}
+
+pub struct MatchRules {
+    // 1, 3 or 5
+    pub best_of_sets: u8,
+    pub games_per_set: u8,
+    // bounces allowed on the receiving side
+    pub bounce_limit: usize,
+    pub tiebreak_at: u8,
+    pub tiebreak_points: u8,
+    pub tiebreak_net_offset_mult: f32,
+    pub point_start: PointStart,
+    // anti-stalling - seconds to return the ball after it bounces on the receiver's side
+    pub shot_clock_sec: Option<f32>,
+    // extra net tug in games for aces and full charge winners
+    pub ace_net_bonus: Option<f32>,
+    pub full_charge_net_bonus: Option<f32>,
+    // no advantage - the point after deuce wins the game, tiebreaks still need the margin
+    pub no_ad: bool,
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self {
+            best_of_sets: 3,
+            games_per_set: 6,
+            bounce_limit: 1,
+            tiebreak_at: 6,
+            tiebreak_points: 7,
+            tiebreak_net_offset_mult: 0.25,
+            point_start: PointStart::Serve,
+            shot_clock_sec: None,
+            ace_net_bonus: None,
+            full_charge_net_bonus: None,
+            no_ad: false,
+        }
+    }
+}
+
+impl MatchRules {
+    pub fn sets_to_win(&self) -> u8 {
+        self.best_of_sets / 2 + 1
+    }
+
+    // 2 game margin or winning the tiebreak
+    pub fn is_set_won(&self, games: u8, other_games: u8) -> bool {
+        games >= self.games_per_set
+            && (games.saturating_sub(other_games) >= 2 || other_games >= self.tiebreak_at)
+    }
+
+    pub fn get_net_bonus(&self, kind: PointKind) -> f32 {
+        match kind {
+            PointKind::Regular => None,
+            PointKind::Ace => self.ace_net_bonus,
+            PointKind::FullChargeWinner => self.full_charge_net_bonus,
+        }
+        .unwrap_or(0.)
+    }
+}
+
+// one player's call in a regular game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamePoint {
+    Love,
+    Fifteen,
+    Thirty,
+    Forty,
+    Advantage,
+}
+
+impl GamePoint {
+    // the points of both players, as they'd never win the game
+    fn from_points(points: u8, other_points: u8) -> Self {
+        match points {
+            0 => GamePoint::Love,
+            1 => GamePoint::Fifteen,
+            2 => GamePoint::Thirty,
+            _ if other_points >= 3 && points > other_points => GamePoint::Advantage,
+            _ => GamePoint::Forty,
+        }
+    }
+
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            GamePoint::Love => "0",
+            GamePoint::Fifteen => "15",
+            GamePoint::Thirty => "30",
+            GamePoint::Forty => "40",
+            GamePoint::Advantage => "AD",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamePhase {
+    Regular,
+    Deuce,
+    Advantage { left_player: bool },
+    // deuce without advantage, the next point takes the game
+    GoldenPoint,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointOutcome {
+    Point,
+    Game,
+    Set,
+    Match,
+}
+
+impl PointOutcome {
+    pub fn is_game_won(&self) -> bool {
+        *self != PointOutcome::Point
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointReason {
+    DoubleFault,
+    Out,
+    TooManyBounces,
+    ShotClock,
+    // a challenged call got reversed, replaces the point given to the other player
+    Overturned,
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+impl PointReason {
+    // the point was won by the scoring player's shot rather than the loser's mistake
+    pub fn is_winner(&self) -> bool {
+        matches!(self, PointReason::TooManyBounces | PointReason::Overturned)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointKind {
+    Regular,
+    // the serve was never touched by the receiver
+    Ace,
+    // won by a shot hit at full charge
+    FullChargeWinner,
+}
+
+impl PointKind {
+    pub fn classify(reason: PointReason, ball: &Ball) -> Self {
+        if reason == PointReason::TooManyBounces && ball.served && ball.hit_count == 1 {
+            PointKind::Ace
+        } else if reason.is_winner() && ball.last_swing_mult >= 1. {
+            PointKind::FullChargeWinner
+        } else {
+            PointKind::Regular
+        }
+    }
+}
+
+// sent once per won rally
+pub struct ScoreChangedEvt {
+    pub scoring_player_id: usize,
+    pub outcome: PointOutcome,
+    pub reason: PointReason,
+    pub kind: PointKind,
+}
+
+pub struct GameOverEvt {
+    pub winner_id: usize,
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, text_settings: Res<TextSettings>) {
     commands
         .spawn_bundle(TextBundle {
             style: Style {
@@ -42,38 +401,275 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                 },
                 ..Default::default()
             },
+            text: Text {
+                sections: [
+                    CALLOUT_TEXT_SIZE,
+                    POINTS_TEXT_SIZE,
+                    POINTS_TEXT_SIZE,
+                    POINTS_TEXT_SIZE,
+                ]
+                .iter()
+                .map(|size| TextSection {
+                    value: String::new(),
+                    style: get_text_style(&asset_server, &text_settings, *size),
+                })
+                .collect(),
+                alignment: TextAlignment {
+                    horizontal: HorizontalAlign::Center,
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        })
+        // colored and sized by the animation, as the sections differ
+        .insert(ScaledText(POINTS_TEXT_SIZE))
+        .insert(PointsText)
+        .insert(ScoreAnimation::default())
+        .insert(Name::new("ScoreText"));
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
             text: Text::with_section(
                 "",
-                TextStyle {
-                    font: asset_server.load("fonts/Typo_Round_Regular_Demo.otf"),
-                    font_size: 100.0,
-                    color: Color::WHITE,
-                },
-                // Note: You can use `Default::default()` in place of the `TextAlignment`
-                TextAlignment {
-                    horizontal: HorizontalAlign::Center,
+                get_text_style(&asset_server, &text_settings, 40.0),
+                TextAlignment::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(PaletteColor::Text)
+        .insert(ScaledText(40.0))
+        .insert(SetsText)
+        .insert(Name::new("SetsText"));
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(60.0),
+                    left: Val::Px(10.0),
                     ..Default::default()
                 },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                get_text_style(&asset_server, &text_settings, 40.0),
+                TextAlignment::default(),
             ),
+            visibility: Visibility { is_visible: false },
             ..Default::default()
         })
         .insert(PaletteColor::Text)
-        .insert(PointsText)
-        .insert(Name::new("ScoreText"))
-        .insert(Persistent);
+        .insert(ScaledText(40.0))
+        .insert(ButtonPrompt {
+            player_id: 1,
+            action: InputAction::Reset,
+            template_key: "prompt-restart",
+        })
+        .insert(RestartPrompt)
+        .insert(Name::new("RestartPrompt"));
+}
+
+fn update_score_ui(
+    score: Res<Score>,
+    rules: Res<MatchRules>,
+    mode: Res<GameMode>,
+    locale: Res<Locale>,
+    profiles: Res<Profiles>,
+    layout: Res<HudLayout>,
+    mut points_text_q: Query<&mut Text, With<PointsText>>,
+) {
+    if score.is_changed()
+        || mode.is_changed()
+        || locale.is_changed()
+        || profiles.is_changed()
+        || layout.is_changed()
+    {
+        let values = if !mode.has_opponent() {
+            // practice modes show their own score
+            [String::new(), String::new(), String::new(), String::new()]
+        } else if let Some(winner_id) = score.get_winner_id(&rules) {
+            let winner = profiles.get_player_name(winner_id, &locale);
+            [
+                locale.format("score-has-won", &[&winner]),
+                String::new(),
+                String::new(),
+                String::new(),
+            ]
+        } else {
+            let (left, right) = match score.get_game_points(&rules) {
+                Some((left, right)) => {
+                    (left.get_label().to_string(), right.get_label().to_string())
+                }
+                None => {
+                    let (left, right) = score.get_display_points(&rules);
+                    (left.to_string(), right.to_string())
+                }
+            };
+            let (left, right) = layout.order(left, right);
+            let callout = match score.get_game_phase(&rules) {
+                GamePhase::Regular => String::new(),
+                GamePhase::Deuce => format!("{}\n", locale.get("score-deuce")),
+                GamePhase::Advantage { .. } => format!("{}\n", locale.get("score-advantage")),
+                GamePhase::GoldenPoint => format!("{}\n", locale.get("score-golden-point")),
+            };
+            [callout, left, " | ".to_string(), right]
+        };
+
+        for (section, value) in points_text_q.single_mut().sections.iter_mut().zip(values) {
+            section.value = value;
+        }
+    }
+}
+
+fn start_score_animations(
+    mut ev_r_score: EventReader<ScoreChangedEvt>,
+    score: Res<Score>,
+    rules: Res<MatchRules>,
+    mut anim_q: Query<&mut ScoreAnimation>,
+) {
+    for ev in ev_r_score.iter() {
+        for mut anim in anim_q.iter_mut() {
+            anim.pop_left_player = ev.scoring_player_id == 1;
+            anim.pop_sec = 0.;
+            if ev.outcome == PointOutcome::Match
+                || score.get_game_phase(&rules) != GamePhase::Regular
+            {
+                anim.callout_sec = 0.;
+            }
+            if ev.outcome.is_game_won() {
+                anim.shake_sec = 0.;
+            }
+        }
+    }
+}
+
+fn animate_score_ui(
+    mut anim_q: Query<(&mut ScoreAnimation, &mut Text, &mut Style)>,
+    palette: Res<Palette>,
+    text_settings: Res<TextSettings>,
+    layout: Res<HudLayout>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    let text_col = palette.get_color(&PaletteColor::Text);
+
+    for (mut anim, mut text, mut style) in anim_q.iter_mut() {
+        let settled = anim.pop_sec >= POINT_POP_SEC
+            && anim.callout_sec >= CALLOUT_IN_SEC
+            && anim.shake_sec >= GAME_SHAKE_SEC;
+        if settled && !palette.is_changed() && !text_settings.is_changed() {
+            continue;
+        }
+
+        anim.pop_sec = (anim.pop_sec + dt).min(POINT_POP_SEC);
+        anim.callout_sec = (anim.callout_sec + dt).min(CALLOUT_IN_SEC);
+        anim.shake_sec = (anim.shake_sec + dt).min(GAME_SHAKE_SEC);
+
+        // pops out in the scorer's color and settles back
+        let pop_t = 1. - anim.pop_sec / POINT_POP_SEC;
+        let accent: Vec4 = palette
+            .get_color(if anim.pop_left_player {
+                &PaletteColor::PlayerLeft
+            } else {
+                &PaletteColor::PlayerRight
+            })
+            .into();
+        let pop_col: Color = Vec4::from(text_col).lerp(accent, pop_t).into();
+        let pop_i = if anim.pop_left_player != layout.mirrored {
+            1
+        } else {
+            3
+        };
+
+        let callout_t = anim.callout_sec / CALLOUT_IN_SEC;
+        let mut callout_col = text_col;
+        callout_col.set_a(text_col.a() * callout_t);
+
+        // the winner takes the whole text
+        let callout_size = if text.sections[1].value.is_empty() {
+            POINTS_TEXT_SIZE
+        } else {
+            CALLOUT_TEXT_SIZE
+        };
+
+        for (i, section) in text.sections.iter_mut().enumerate() {
+            let (size, color) = if i == 0 {
+                (callout_size * (0.5 + 0.5 * callout_t), callout_col)
+            } else if i == pop_i {
+                (POINTS_TEXT_SIZE * (1. + POINT_POP_SCALE * pop_t), pop_col)
+            } else {
+                (POINTS_TEXT_SIZE, text_col)
+            };
+            section.style.font_size = size * text_settings.scale;
+            section.style.color = color;
+        }
+
+        // a quick fading rattle on game wins
+        let shake_t = 1. - anim.shake_sec / GAME_SHAKE_SEC;
+        let shake = Vec2::new((anim.shake_sec * 70.).sin(), (anim.shake_sec * 55.).cos())
+            * GAME_SHAKE_PX
+            * shake_t;
+        style.position.left = Val::Px(shake.x);
+        style.position.top = Val::Px(shake.y);
+    }
 }
 
-fn update_score_ui(score: Res<Score>, mut points_text_q: Query<&mut Text, With<PointsText>>) {
+fn update_sets_ui(
+    score: Res<Score>,
+    rules: Res<MatchRules>,
+    locale: Res<Locale>,
+    layout: Res<HudLayout>,
+    mut sets_text_q: Query<&mut Text, With<SetsText>>,
+) {
+    if score.is_changed() || locale.is_changed() || layout.is_changed() {
+        let mut sets: Vec<String> = score
+            .set_history
+            .iter()
+            .map(|(left, right)| {
+                let (first, second) = layout.order(left, right);
+                format!("{}-{}", first, second)
+            })
+            .collect();
+        let (first, second) = layout.order(score.left_player.games, score.right_player.games);
+        sets.push(format!("({}-{})", first, second));
+
+        if score.is_tiebreak(&rules) {
+            sets.push(locale.get("score-tiebreak"));
+        }
+
+        sets_text_q.single_mut().sections[0].value = sets.join("  ");
+    }
+}
+
+fn update_restart_prompt(
+    score: Res<Score>,
+    rules: Res<MatchRules>,
+    mut prompt_q: Query<&mut Visibility, With<RestartPrompt>>,
+) {
     if score.is_changed() {
-        points_text_q.single_mut().sections[0].value = format!(
-            "{} | {}",
-            score.left_player.points, score.right_player.points
-        );
+        for mut visibility in prompt_q.iter_mut() {
+            visibility.is_visible = score.get_winner_id(&rules).is_some();
+        }
     }
 }
 
-pub fn add_point_to_score(score: &mut Score, add_to_left_player: bool) -> bool {
-    let (mut scoring, mut other) = if add_to_left_player {
+pub fn add_point_to_score(
+    score: &mut Score,
+    rules: &MatchRules,
+    add_to_left_player: bool,
+) -> PointOutcome {
+    let (scoring, other) = if add_to_left_player {
         (&mut score.left_player, &mut score.right_player)
     } else {
         (&mut score.right_player, &mut score.left_player)
@@ -81,7 +677,10 @@ pub fn add_point_to_score(score: &mut Score, add_to_left_player: bool) -> bool {
 
     scoring.points += 1;
 
-    let mut required_points = (other.points + 2).max(4);
+    let tiebreak = scoring.games == other.games && scoring.games >= rules.tiebreak_at;
+    let min_points = if tiebreak { rules.tiebreak_points } else { 4 };
+    let margin = if rules.no_ad && !tiebreak { 1 } else { 2 };
+    let mut required_points = (other.points + margin).max(min_points);
     if cfg!(feature = "debug") {
         required_points = 100;
     }
@@ -90,22 +689,36 @@ pub fn add_point_to_score(score: &mut Score, add_to_left_player: bool) -> bool {
         scoring.games += 1;
         scoring.points = 0;
         other.points = 0;
-        return true;
-    } else if scoring.points == other.points && scoring.points > 3 {
-        // hacky way to get ADV in the UI
-        // nice2have: redo
-        scoring.points = 3;
-        other.points = 3;
-    }
+        score.net_bonus = 0.;
+
+        if !rules.is_set_won(scoring.games, other.games) {
+            return PointOutcome::Game;
+        }
 
-    // // todo: endgame scoring - either too high or difference high enough
-    // if scoring.games >= 6 {
-    // }
+        scoring.sets += 1;
+        let match_won = scoring.sets >= rules.sets_to_win();
+        let set = if add_to_left_player {
+            (scoring.games, other.games)
+        } else {
+            (other.games, scoring.games)
+        };
+        scoring.games = 0;
+        other.games = 0;
+        score.set_history.push(set);
+
+        return if match_won {
+            PointOutcome::Match
+        } else {
+            PointOutcome::Set
+        };
+    }
 
-    false
+    PointOutcome::Point
 }
 
 fn reset_score(mut score: ResMut<Score>) {
     score.left_player = PlayerScore::default();
     score.right_player = PlayerScore::default();
+    score.set_history.clear();
+    score.net_bonus = 0.;
 }
@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+use bevy_time::{ScaledTime, ScaledTimeDelta};
+
+use crate::{
+    ball::{Ball, BallBouncedEvt, BallHitEvt, BallStatus},
+    mode::GameMode,
+    palette::PaletteColor,
+    player::Player,
+    score::MatchRules,
+    ui_text::{get_text_style, ScaledText, TextSettings},
+    GameState,
+};
+
+// anti-stalling - the receiver has to return the ball in time after it bounces on their side
+pub struct ShotClockPlugin;
+impl Plugin for ShotClockPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<ShotClockExpiredEvt>().add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .with_system(start_shot_clock)
+                .with_system(stop_shot_clock)
+                .with_system(tick_shot_clock),
+        );
+    }
+}
+
+// resolved like any other lost point
+pub struct ShotClockExpiredEvt {
+    pub ball_e: Entity,
+    pub player_id: usize,
+}
+
+// a countdown above the receiving player
+#[derive(Component)]
+struct ShotClock {
+    ball_e: Entity,
+    player_id: usize,
+    remaining_sec: f32,
+}
+
+fn start_shot_clock(
+    mut commands: Commands,
+    mut ev_r_bounce: EventReader<BallBouncedEvt>,
+    ball_q: Query<&BallStatus, With<Ball>>,
+    player_q: Query<(Entity, &Player)>,
+    rules: Res<MatchRules>,
+    mode: Res<GameMode>,
+    asset_server: Res<AssetServer>,
+    text_settings: Res<TextSettings>,
+) {
+    let limit_sec = match rules.shot_clock_sec {
+        Some(limit_sec) if mode.has_opponent() => limit_sec,
+        _ => return,
+    };
+
+    for ev in ev_r_bounce.iter() {
+        if ev.bounce_count != 1 {
+            continue;
+        }
+
+        let hitter_id = match ball_q.get(ev.ball_e) {
+            Ok(BallStatus::Rally(hitter_id)) => *hitter_id,
+            _ => continue,
+        };
+
+        if let Some((player_e, player)) = player_q
+            .iter()
+            .find(|(_, p)| p.is_left() == (ev.side < 0.) && p.id != hitter_id)
+        {
+            let clock_e = commands
+                .spawn_bundle(Text2dBundle {
+                    text: Text::with_section(
+                        "",
+                        get_text_style(&asset_server, &text_settings, 36.),
+                        TextAlignment {
+                            horizontal: HorizontalAlign::Center,
+                            vertical: VerticalAlign::Center,
+                        },
+                    ),
+                    transform: Transform::from_xyz(0., 110., 1.),
+                    ..Default::default()
+                })
+                .insert(ShotClock {
+                    ball_e: ev.ball_e,
+                    player_id: player.id,
+                    remaining_sec: limit_sec,
+                })
+                .insert(PaletteColor::Text)
+                .insert(ScaledText(36.))
+                .insert(Name::new("ShotClock"))
+                .id();
+            commands.entity(player_e).add_child(clock_e);
+        }
+    }
+}
+
+// returned or the ball is gone
+fn stop_shot_clock(
+    mut commands: Commands,
+    mut ev_r_hit: EventReader<BallHitEvt>,
+    clock_q: Query<(Entity, &ShotClock)>,
+    ball_q: Query<&BallStatus, With<Ball>>,
+) {
+    let hit_balls: Vec<Entity> = ev_r_hit.iter().map(|ev| ev.ball_e).collect();
+
+    for (clock_e, clock) in clock_q.iter() {
+        let ball_in_play = matches!(ball_q.get(clock.ball_e), Ok(BallStatus::Rally(..)));
+        if !ball_in_play || hit_balls.contains(&clock.ball_e) {
+            commands.entity(clock_e).despawn_recursive();
+        }
+    }
+}
+
+fn tick_shot_clock(
+    mut commands: Commands,
+    mut clock_q: Query<(Entity, &mut ShotClock, &mut Text)>,
+    mut ev_w_expired: EventWriter<ShotClockExpiredEvt>,
+    time: ScaledTime,
+) {
+    for (clock_e, mut clock, mut text) in clock_q.iter_mut() {
+        clock.remaining_sec -= time.scaled_delta_seconds();
+
+        if clock.remaining_sec <= 0. {
+            ev_w_expired.send(ShotClockExpiredEvt {
+                ball_e: clock.ball_e,
+                player_id: clock.player_id,
+            });
+            commands.entity(clock_e).despawn_recursive();
+            continue;
+        }
+
+        let value = format!("{}", clock.remaining_sec.ceil());
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
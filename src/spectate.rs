@@ -0,0 +1,268 @@
+use std::{
+    env,
+    io::{self, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{sync_channel, SyncSender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    ball::Ball,
+    level::{Net, NetOffset},
+    player::Player,
+    replay::{get_frame, read_f32, read_u8, show_ghosts, write_f32s, ReplayFrame, ReplayGhosts},
+    score::{PlayerScore, Score},
+    GameState,
+};
+
+// "host" streams the match, an address (e.g. TUG_OF_BALL_SPECTATE=192.168.0.5:9002) watches it
+// "host=<addr>" streams on the given address, e.g. "host=0.0.0.0:9002" for the whole LAN
+const SPECTATE_ENV_VAR: &str = "TUG_OF_BALL_SPECTATE";
+const HOST_PREFIX: &str = "host=";
+// only this machine by default
+const HOST_ADDR: &str = "127.0.0.1:9002";
+// frames queued for the spectators, older ones get dropped
+const HOST_QUEUE_LEN: usize = 60;
+// a spectator taking longer to accept a frame gets dropped, so it can't stall the others
+const CLIENT_WRITE_TIMEOUT_MS: u64 = 50;
+const RECONNECT_SEC: u64 = 2;
+
+// read-only spectating over tcp
+// the simulation isn't fixed-step (yet), so the host streams the transforms instead of per-point seeds
+pub struct SpectatePlugin;
+impl Plugin for SpectatePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        match get_spectate_mode() {
+            Some(SpectateMode::Host(addr)) => match start_host(&addr) {
+                Ok(host) => {
+                    info!("Hosting spectators on {}", addr);
+                    app.insert_resource(host).add_system(stream_frame);
+                }
+                Err(e) => error!("Failed to start the spectator host: {}", e),
+            },
+            Some(SpectateMode::View(addr)) => {
+                app.insert_resource(start_viewer(addr)).add_system_set(
+                    SystemSet::on_update(GameState::Spectate)
+                        .with_system(show_match)
+                        .with_system(place_net),
+                );
+            }
+            None => {}
+        }
+    }
+}
+
+pub enum SpectateMode {
+    Host(String),
+    View(String),
+}
+
+pub fn get_spectate_mode() -> Option<SpectateMode> {
+    env::var(SPECTATE_ENV_VAR).ok().map(|value| {
+        if value == "host" {
+            SpectateMode::Host(HOST_ADDR.to_string())
+        } else if let Some(addr) = value.strip_prefix(HOST_PREFIX) {
+            SpectateMode::Host(addr.to_string())
+        } else {
+            SpectateMode::View(value)
+        }
+    })
+}
+
+// a replay frame plus what can't be derived from the transforms
+struct SpectateMsg {
+    frame: ReplayFrame,
+    net_offset: f32,
+    // (points, games, sets) of the left and the right player
+    score: [(u8, u8, u8); 2],
+}
+
+impl SpectateMsg {
+    fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_f32s(w, &[self.net_offset])?;
+        for (points, games, sets) in self.score.iter() {
+            w.write_all(&[*points, *games, *sets])?;
+        }
+        self.frame.write(w)
+    }
+
+    fn read<R: Read>(r: &mut R) -> io::Result<Self> {
+        let net_offset = read_f32(r)?;
+        let mut score = [(0, 0, 0); 2];
+        for player_score in score.iter_mut() {
+            *player_score = (read_u8(r)?, read_u8(r)?, read_u8(r)?);
+        }
+
+        Ok(Self {
+            frame: ReplayFrame::read(r)?,
+            net_offset,
+            score,
+        })
+    }
+}
+
+pub struct SpectateHost {
+    tx: SyncSender<Vec<u8>>,
+}
+
+pub struct SpectateViewer {
+    // only the latest frame is shown
+    latest: Arc<Mutex<Option<SpectateMsg>>>,
+    ghosts: ReplayGhosts,
+}
+
+fn start_host(addr: &str) -> io::Result<SpectateHost> {
+    let listener = TcpListener::bind(addr)?;
+    let clients = Arc::new(Mutex::new(Vec::<TcpStream>::new()));
+    let (tx, rx) = sync_channel::<Vec<u8>>(HOST_QUEUE_LEN);
+
+    let accept_clients = clients.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // small messages every frame
+            stream.set_nodelay(true).ok();
+            if let Err(e) =
+                stream.set_write_timeout(Some(Duration::from_millis(CLIENT_WRITE_TIMEOUT_MS)))
+            {
+                warn!("Failed to set the spectator write timeout: {}", e);
+                continue;
+            }
+            info!("Spectator connected from {:?}", stream.peer_addr());
+            accept_clients.lock().unwrap().push(stream);
+        }
+    });
+
+    thread::spawn(move || {
+        for msg in rx.iter() {
+            // drop disconnected and lagging clients, a timed out write leaves a partial message anyway
+            let mut clients = clients.lock().unwrap();
+            *clients = clients
+                .drain(..)
+                .filter_map(|mut stream| stream.write_all(&msg).is_ok().then(|| stream))
+                .collect();
+        }
+    });
+
+    Ok(SpectateHost { tx })
+}
+
+fn start_viewer(addr: String) -> SpectateViewer {
+    let latest = Arc::new(Mutex::new(None));
+
+    let read_latest = latest.clone();
+    thread::spawn(move || loop {
+        // keeps retrying, so the viewer can be started before the host
+        match TcpStream::connect(&addr) {
+            Ok(stream) => {
+                info!("Spectating {}", addr);
+                let mut reader = BufReader::new(stream);
+                while let Ok(msg) = SpectateMsg::read(&mut reader) {
+                    *read_latest.lock().unwrap() = Some(msg);
+                }
+                warn!("Lost the connection to {}", addr);
+            }
+            Err(e) => warn!("Failed to connect to {}: {}", addr, e),
+        }
+
+        thread::sleep(Duration::from_secs(RECONNECT_SEC));
+    });
+
+    SpectateViewer {
+        latest,
+        ghosts: ReplayGhosts::default(),
+    }
+}
+
+fn stream_frame(
+    host: Res<SpectateHost>,
+    time: Res<Time>,
+    score: Res<Score>,
+    net: Res<NetOffset>,
+    player_q: Query<(&Player, &GlobalTransform)>,
+    ball_q: Query<(&Ball, &GlobalTransform)>,
+    bounce_t_q: Query<&Transform>,
+) {
+    let msg = SpectateMsg {
+        frame: get_frame(
+            time.seconds_since_startup() as f32,
+            &player_q,
+            &ball_q,
+            &bounce_t_q,
+        ),
+        net_offset: net.0,
+        score: [
+            get_score(&score.left_player),
+            get_score(&score.right_player),
+        ],
+    };
+
+    let mut data = Vec::new();
+    if msg.write(&mut data).is_ok() {
+        // a lagging spectator skips frames instead of stalling the game
+        host.tx.try_send(data).ok();
+    }
+}
+
+fn show_match(
+    mut commands: Commands,
+    mut viewer: ResMut<SpectateViewer>,
+    mut score: ResMut<Score>,
+    mut net: ResMut<NetOffset>,
+    mut transform_q: Query<(&mut Transform, &mut Visibility)>,
+    asset_server: Res<AssetServer>,
+) {
+    let msg = match viewer.latest.lock().unwrap().take() {
+        Some(msg) => msg,
+        None => return,
+    };
+
+    show_ghosts(
+        &mut commands,
+        &mut viewer.ghosts,
+        &msg.frame,
+        &mut transform_q,
+        &asset_server,
+        GameState::Spectate,
+    );
+
+    // only touch the score when it changes, so the score ui doesn't update every frame
+    if msg.score
+        != [
+            get_score(&score.left_player),
+            get_score(&score.right_player),
+        ]
+    {
+        let score = &mut *score;
+        for (player_score, (points, games, sets)) in
+            [&mut score.left_player, &mut score.right_player]
+                .into_iter()
+                .zip(msg.score)
+        {
+            player_score.points = points;
+            player_score.games = games;
+            player_score.sets = sets;
+        }
+    }
+
+    if net.0 != msg.net_offset {
+        net.0 = msg.net_offset;
+    }
+}
+
+fn place_net(net: Res<NetOffset>, mut net_q: Query<&mut Transform, With<Net>>) {
+    if net.is_changed() {
+        for mut net_t in net_q.iter_mut() {
+            net_t.translation.x = net.0;
+        }
+    }
+}
+
+fn get_score(score: &PlayerScore) -> (u8, u8, u8) {
+    (score.points, score.games, score.sets)
+}
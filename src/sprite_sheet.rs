@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use bevy_time::{ScaledTime, ScaledTimeDelta};
+use serde::Deserialize;
+
+use crate::player_animation::{AgentAnimationData, AnimationStartedEvt, PlayerAnimation};
+
+const CLIP_SWITCH_LABEL: &str = "clip_switch";
+
+// frame based body animations played on top of the transform tweens
+// states without a sheet keep the plain body sprite
+pub struct SpriteSheetPlugin;
+impl Plugin for SpriteSheetPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_asset::<BodySheets>()
+            .init_asset_loader::<BodySheetsLoader>()
+            .add_startup_system(setup)
+            .add_system(on_sheets_loaded)
+            .add_system(switch_body_clip.label(CLIP_SWITCH_LABEL))
+            .add_system(play_sprite_sheets.after(CLIP_SWITCH_LABEL));
+    }
+}
+
+// see assets/art-ish/body.sheets.ron
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "b4a2c7de-53f1-4e0a-8d6b-91c3f2a7e5d4"]
+pub struct BodySheets {
+    clips: HashMap<PlayerAnimation, SheetDef>,
+}
+
+// a grid sheet read left to right, top to bottom
+#[derive(Deserialize)]
+struct SheetDef {
+    texture: String,
+    frame_size: (f32, f32),
+    columns: usize,
+    rows: usize,
+    frame_ms: u64,
+    looping: bool,
+}
+
+#[derive(Clone)]
+struct SheetClip {
+    atlas: Handle<TextureAtlas>,
+    frame_count: usize,
+    frame_sec: f32,
+    looping: bool,
+}
+
+#[derive(Default)]
+struct BodyClips {
+    handle: Handle<BodySheets>,
+    clips: HashMap<PlayerAnimation, SheetClip>,
+}
+
+// sits on the body sprite, the rest sprite is restored once a clip ends or there's none
+#[derive(Component)]
+pub struct SpriteSheetPlayer {
+    rest_atlas: Handle<TextureAtlas>,
+    rest_index: usize,
+    clip: Option<SheetClip>,
+    frame: usize,
+    frame_elapsed_sec: f32,
+}
+
+impl SpriteSheetPlayer {
+    pub fn new(rest_atlas: Handle<TextureAtlas>, rest_index: usize) -> Self {
+        Self {
+            rest_atlas,
+            rest_index,
+            clip: None,
+            frame: 0,
+            frame_elapsed_sec: 0.,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct BodySheetsLoader;
+
+impl AssetLoader for BodySheetsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let sheets: BodySheets = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(sheets));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sheets.ron"]
+    }
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(BodyClips {
+        handle: asset_server.load("art-ish/body.sheets.ron"),
+        ..Default::default()
+    });
+}
+
+fn on_sheets_loaded(
+    mut ev_asset: EventReader<AssetEvent<BodySheets>>,
+    sheets: Res<Assets<BodySheets>>,
+    asset_server: Res<AssetServer>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+    mut body_clips: ResMut<BodyClips>,
+) {
+    for ev in ev_asset.iter() {
+        match ev {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle }
+                if *handle == body_clips.handle =>
+            {
+                if let Some(loaded) = sheets.get(handle) {
+                    body_clips.clips = loaded
+                        .clips
+                        .iter()
+                        .filter(|(_, def)| def.columns * def.rows > 0)
+                        .map(|(anim, def)| {
+                            let atlas = TextureAtlas::from_grid(
+                                asset_server.load(def.texture.as_str()),
+                                Vec2::new(def.frame_size.0, def.frame_size.1),
+                                def.columns,
+                                def.rows,
+                            );
+                            let clip = SheetClip {
+                                atlas: atlases.add(atlas),
+                                frame_count: def.columns * def.rows,
+                                frame_sec: def.frame_ms.max(1) as f32 / 1000.,
+                                looping: def.looping,
+                            };
+                            (*anim, clip)
+                        })
+                        .collect();
+                    info!("Body sheets loaded ({} clips)", body_clips.clips.len());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn switch_body_clip(
+    mut ev_r_started: EventReader<AnimationStartedEvt>,
+    anim_q: Query<&AgentAnimationData>,
+    mut sheet_q: Query<(
+        &mut SpriteSheetPlayer,
+        &mut TextureAtlasSprite,
+        &mut Handle<TextureAtlas>,
+    )>,
+    body_clips: Res<BodyClips>,
+) {
+    for ev in ev_r_started.iter() {
+        let body_e = match anim_q.get(ev.agent_e) {
+            Ok(anim) => anim.body_e,
+            Err(_) => continue,
+        };
+
+        if let Ok((mut player, mut sprite, mut atlas)) = sheet_q.get_mut(body_e) {
+            player.clip = body_clips.clips.get(&ev.animation).cloned();
+            player.frame = 0;
+            player.frame_elapsed_sec = 0.;
+
+            if let Some(clip) = &player.clip {
+                *atlas = clip.atlas.clone();
+                sprite.index = 0;
+            } else {
+                *atlas = player.rest_atlas.clone();
+                sprite.index = player.rest_index;
+            }
+        }
+    }
+}
+
+fn play_sprite_sheets(
+    mut sheet_q: Query<(
+        &mut SpriteSheetPlayer,
+        &mut TextureAtlasSprite,
+        &mut Handle<TextureAtlas>,
+    )>,
+    time: ScaledTime,
+) {
+    for (mut player, mut sprite, mut atlas) in sheet_q.iter_mut() {
+        let clip = match &player.clip {
+            Some(clip) => clip.clone(),
+            None => continue,
+        };
+
+        player.frame_elapsed_sec += time.scaled_delta_seconds();
+        while player.frame_elapsed_sec >= clip.frame_sec {
+            player.frame_elapsed_sec -= clip.frame_sec;
+            player.frame += 1;
+        }
+
+        if player.frame < clip.frame_count {
+            sprite.index = player.frame;
+        } else if clip.looping {
+            player.frame %= clip.frame_count;
+            sprite.index = player.frame;
+        } else {
+            player.clip = None;
+            *atlas = player.rest_atlas.clone();
+            sprite.index = player.rest_index;
+        }
+    }
+}
@@ -0,0 +1,814 @@
+use bevy::prelude::*;
+use bevy_time::{ScaledTime, ScaledTimeDelta};
+use serde::Serialize;
+
+use crate::{
+    ball::{Ball, BallBouncedEvt, BallCrossedNetEvt, BallHitEvt, BallStatus, ShotRisk},
+    best_rally::BestRally,
+    celebration::Celebration,
+    config::GameConfig,
+    input_binding::{InputAction, PlayerInput},
+    level::{CourtSettings, NetOffset},
+    locale::Locale,
+    mode::GameMode,
+    palette::{Palette, PaletteColor},
+    player::{Player, PlayerDash},
+    player_action::PlayerActionStatus,
+    profile::Profiles,
+    score::{GameOverEvt, MatchRules, PointKind, PointReason, Score, ScoreChangedEvt},
+    storage,
+    ui_text::{get_text_style, ScaledText, TextSettings},
+    GameState,
+};
+
+// inclusive hit count ranges of the rally length distribution
+const RALLY_BUCKETS: [(u32, u32); 4] = [(0, 2), (3, 5), (6, 9), (10, u32::MAX)];
+// shots this close to the max ball speed get their speed shown
+const SPEED_READOUT_RATIO: f32 = 0.75;
+const SPEED_READOUT_SEC: f32 = 1.;
+const SPEED_READOUT_RISE: f32 = 40.;
+// the max ball speed makes for a ~200 km/h shot
+const KMH_PER_SPEED: f32 = 0.18;
+const SPARKLINE_BARS: usize = 60;
+const SPARKLINE_BAR_WIDTH: f32 = 5.;
+// each way from the middle line
+const SPARKLINE_HALF_HEIGHT: f32 = 30.;
+const NET_HISTORY_DIR: &str = "stats";
+// shot placement grid over the whole court
+const HEATMAP_COLS: usize = 16;
+const HEATMAP_ROWS: usize = 8;
+const HEATMAP_CELL_SIZE: f32 = 15.;
+const HEATMAP_MAX_ALPHA: f32 = 0.9;
+
+pub struct StatsPlugin;
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<MatchStats>()
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(reset_stats))
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(track_stats)
+                    .with_system(count_dashes)
+                    .with_system(count_netted_shots)
+                    .with_system(track_net_offset)
+                    .with_system(track_shot_landings)
+                    .with_system(export_net_offset_history)
+                    .with_system(show_shot_speed)
+                    .with_system(fade_shot_speed),
+            )
+            .add_system(show_stats_card)
+            .add_system(draw_net_sparkline)
+            .add_system(draw_shot_heatmap)
+            .add_system(update_fastest_shot_ui);
+    }
+}
+
+// collected over the current match
+#[derive(Default)]
+pub struct MatchStats {
+    // hit count of every finished rally
+    pub rally_lengths: Vec<u32>,
+    pub shot_speeds: Vec<f32>,
+    // indexed by player
+    pub players: [PlayerStats; 2],
+    // (sec since the match start, net offset) whenever the net moved, positive favours the left player
+    pub net_offset_history: Vec<(f32, f32)>,
+    // every point in the order they were scored
+    pub points: Vec<PointRecord>,
+    // (hitter id, position) of the 1st bounce of every shot, outs included
+    pub shot_landings: Vec<(usize, Vec2)>,
+    current_rally: u32,
+    start_sec: f64,
+}
+
+impl MatchStats {
+    pub fn get_max_shot_speed(&self) -> f32 {
+        self.shot_speeds.iter().cloned().fold(0., f32::max)
+    }
+
+    pub fn get_match_sec(&self, time: &Time) -> f32 {
+        (time.seconds_since_startup() - self.start_sec) as f32
+    }
+
+    pub fn get_net_offset_at(&self, sec: f32) -> f32 {
+        self.net_offset_history
+            .iter()
+            .rev()
+            .find(|(t, _)| *t <= sec)
+            .map_or(0., |(_, offset)| *offset)
+    }
+
+    pub fn get_net_offset_csv(&self) -> String {
+        let mut csv = String::from("sec,net_offset\n");
+        for (sec, offset) in self.net_offset_history.iter() {
+            csv.push_str(&format!("{:.3},{:.3}\n", sec, offset));
+        }
+        csv
+    }
+
+    // landing count per grid cell, row by row from the bottom left, landings outside of the court are left out
+    pub fn get_landing_bins(&self, player_id: usize, court: &CourtSettings) -> Vec<u32> {
+        let mut bins = vec![0; HEATMAP_COLS * HEATMAP_ROWS];
+        let size = Vec2::new(court.right - court.left, court.top - court.bottom);
+
+        for (_, pos) in self.shot_landings.iter().filter(|(id, _)| *id == player_id) {
+            let ratio = (*pos - Vec2::new(court.left, court.bottom)) / size;
+            if ratio.cmplt(Vec2::ZERO).any() || ratio.cmpgt(Vec2::ONE).any() {
+                continue;
+            }
+
+            let col = ((ratio.x * HEATMAP_COLS as f32) as usize).min(HEATMAP_COLS - 1);
+            let row = ((ratio.y * HEATMAP_ROWS as f32) as usize).min(HEATMAP_ROWS - 1);
+            bins[row * HEATMAP_COLS + col] += 1;
+        }
+
+        bins
+    }
+
+    fn get_player_mut(&mut self, player_id: usize) -> &mut PlayerStats {
+        &mut self.players[player_id - 1]
+    }
+}
+
+#[derive(Clone, Copy, Serialize)]
+pub struct PointRecord {
+    pub sec: f32,
+    pub scoring_player_id: usize,
+    pub reason: PointReason,
+    pub kind: PointKind,
+    pub rally_hits: u32,
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct PlayerStats {
+    // points won by shots the opponent couldn't return
+    pub winners: u32,
+    // points won by the opponent's faults
+    pub won_on_faults: u32,
+    pub dashes: u32,
+    pub shots: u32,
+    // shots stopped by the net, serves included
+    pub netted: u32,
+    shot_speed_sum: f32,
+}
+
+impl PlayerStats {
+    pub fn get_avg_shot_speed(&self) -> f32 {
+        if self.shots > 0 {
+            self.shot_speed_sum / self.shots as f32
+        } else {
+            0.
+        }
+    }
+}
+
+#[derive(Component)]
+struct StatsCard;
+
+#[derive(Component)]
+struct StatsCardText;
+
+// the net offset over the match, left player's momentum above the middle line
+#[derive(Component)]
+struct SparklineBar(usize);
+
+// results screen only, unlike the rest of the card
+#[derive(Component)]
+struct ShotHeatmap;
+
+#[derive(Component)]
+struct ShotHeatmapCell {
+    col: usize,
+    row: usize,
+}
+
+// floats above the hitter for a bit
+#[derive(Component)]
+struct ShotSpeedReadout {
+    remaining_sec: f32,
+}
+
+// shown along with the match result
+#[derive(Component)]
+struct FastestShotText;
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, text_settings: Res<TextSettings>) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(Name::new("StatsCard"))
+        .with_children(|b| {
+            b.spawn_bundle(NodeBundle {
+                style: Style {
+                    padding: Rect::all(Val::Px(25.)),
+                    flex_direction: FlexDirection::ColumnReverse,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                visibility: Visibility { is_visible: false },
+                ..Default::default()
+            })
+            .insert(PaletteColor::Background)
+            .insert(StatsCard)
+            .with_children(|b| {
+                b.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "",
+                        get_text_style(&asset_server, &text_settings, 30.),
+                        TextAlignment {
+                            horizontal: HorizontalAlign::Center,
+                            ..Default::default()
+                        },
+                    ),
+                    visibility: Visibility { is_visible: false },
+                    ..Default::default()
+                })
+                .insert(PaletteColor::Text)
+                .insert(ScaledText(30.))
+                .insert(StatsCard)
+                .insert(StatsCardText);
+
+                b.spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(
+                            Val::Px(SPARKLINE_BARS as f32 * SPARKLINE_BAR_WIDTH),
+                            Val::Px(SPARKLINE_HALF_HEIGHT * 2.),
+                        ),
+                        margin: Rect {
+                            top: Val::Px(15.),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    color: Color::NONE.into(),
+                    visibility: Visibility { is_visible: false },
+                    ..Default::default()
+                })
+                .insert(StatsCard)
+                .with_children(|b| {
+                    for i in 0..SPARKLINE_BARS {
+                        b.spawn_bundle(NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                ..Default::default()
+                            },
+                            visibility: Visibility { is_visible: false },
+                            ..Default::default()
+                        })
+                        .insert(PaletteColor::Text)
+                        .insert(StatsCard)
+                        .insert(SparklineBar(i));
+                    }
+                });
+
+                b.spawn_bundle(TextBundle {
+                    style: Style {
+                        margin: Rect {
+                            top: Val::Px(15.),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    text: Text::with_section(
+                        "",
+                        get_text_style(&asset_server, &text_settings, 30.),
+                        Default::default(),
+                    ),
+                    visibility: Visibility { is_visible: false },
+                    ..Default::default()
+                })
+                .insert(PaletteColor::Text)
+                .insert(ScaledText(30.))
+                .insert(ShotHeatmap);
+
+                // a court diagram, each player's shots land on the other half
+                b.spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(
+                            Val::Px(HEATMAP_COLS as f32 * HEATMAP_CELL_SIZE),
+                            Val::Px(HEATMAP_ROWS as f32 * HEATMAP_CELL_SIZE),
+                        ),
+                        margin: Rect {
+                            top: Val::Px(5.),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    visibility: Visibility { is_visible: false },
+                    ..Default::default()
+                })
+                .insert(PaletteColor::Court)
+                .insert(ShotHeatmap)
+                .with_children(|b| {
+                    for row in 0..HEATMAP_ROWS {
+                        for col in 0..HEATMAP_COLS {
+                            b.spawn_bundle(NodeBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    position: Rect {
+                                        left: Val::Px(col as f32 * HEATMAP_CELL_SIZE),
+                                        bottom: Val::Px(row as f32 * HEATMAP_CELL_SIZE),
+                                        ..Default::default()
+                                    },
+                                    size: Size::new(
+                                        Val::Px(HEATMAP_CELL_SIZE),
+                                        Val::Px(HEATMAP_CELL_SIZE),
+                                    ),
+                                    ..Default::default()
+                                },
+                                color: Color::NONE.into(),
+                                visibility: Visibility { is_visible: false },
+                                ..Default::default()
+                            })
+                            .insert(ShotHeatmap)
+                            .insert(ShotHeatmapCell { col, row });
+                        }
+                    }
+
+                    // net
+                    b.spawn_bundle(NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            position: Rect {
+                                left: Val::Px(HEATMAP_COLS as f32 * HEATMAP_CELL_SIZE / 2. - 1.),
+                                bottom: Val::Px(0.),
+                                ..Default::default()
+                            },
+                            size: Size::new(
+                                Val::Px(2.),
+                                Val::Px(HEATMAP_ROWS as f32 * HEATMAP_CELL_SIZE),
+                            ),
+                            ..Default::default()
+                        },
+                        visibility: Visibility { is_visible: false },
+                        ..Default::default()
+                    })
+                    .insert(PaletteColor::CourtLines)
+                    .insert(ShotHeatmap);
+                });
+            });
+        });
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(110.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                get_text_style(&asset_server, &text_settings, 40.0),
+                TextAlignment::default(),
+            ),
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(PaletteColor::Text)
+        .insert(ScaledText(40.0))
+        .insert(FastestShotText)
+        .insert(Name::new("FastestShotText"));
+}
+
+fn reset_stats(mut stats: ResMut<MatchStats>, time: Res<Time>) {
+    *stats = MatchStats {
+        start_sec: time.seconds_since_startup(),
+        ..Default::default()
+    };
+}
+
+fn track_net_offset(net: Res<NetOffset>, mut stats: ResMut<MatchStats>, time: Res<Time>) {
+    if net.is_changed() || stats.net_offset_history.is_empty() {
+        let sec = stats.get_match_sec(&time);
+        stats.net_offset_history.push((sec, net.0));
+    }
+}
+
+fn export_net_offset_history(mut ev_r_game_over: EventReader<GameOverEvt>, stats: Res<MatchStats>) {
+    for _ in ev_r_game_over.iter() {
+        // zero padded, so the latest match sorts last
+        let prefix = format!("{}/net_offset_", NET_HISTORY_DIR);
+        let number = storage::get_next_number(&storage::list(NET_HISTORY_DIR), &prefix, "csv");
+        let key = format!("{}{:05}.csv", prefix, number);
+        match storage::save(&key, stats.get_net_offset_csv().as_bytes()) {
+            Ok(_) => info!("Net offset history saved to {}", key),
+            Err(e) => warn!("Failed to save the net offset history: {}", e),
+        }
+    }
+}
+
+fn track_stats(
+    mut ev_r_hit: EventReader<BallHitEvt>,
+    mut ev_r_score: EventReader<ScoreChangedEvt>,
+    mut stats: ResMut<MatchStats>,
+    ball_q: Query<&Ball>,
+    time: Res<Time>,
+) {
+    for ev in ev_r_hit.iter() {
+        stats.current_rally += 1;
+
+        if let Ok(ball) = ball_q.get(ev.ball_e) {
+            let speed = ball.dir.length() * ball.max_speed;
+            stats.shot_speeds.push(speed);
+            let player = stats.get_player_mut(ev.player_id);
+            player.shots += 1;
+            player.shot_speed_sum += speed;
+        }
+    }
+
+    // hits go first, a rally can end on the same frame as the last hit
+    for ev in ev_r_score.iter() {
+        if ev.reason == PointReason::Overturned {
+            // the rally is already counted, but the point went to the other player as a fault
+            let other_id = if ev.scoring_player_id == 1 { 2 } else { 1 };
+            let other = stats.get_player_mut(other_id);
+            other.won_on_faults = other.won_on_faults.saturating_sub(1);
+        } else {
+            let rally = stats.current_rally;
+            stats.rally_lengths.push(rally);
+            stats.current_rally = 0;
+        }
+
+        let record = PointRecord {
+            sec: stats.get_match_sec(&time),
+            scoring_player_id: ev.scoring_player_id,
+            reason: ev.reason,
+            kind: ev.kind,
+            rally_hits: stats.rally_lengths.last().cloned().unwrap_or(0),
+        };
+        stats.points.push(record);
+
+        let player = stats.get_player_mut(ev.scoring_player_id);
+        if ev.reason.is_winner() {
+            player.winners += 1;
+        } else {
+            player.won_on_faults += 1;
+        }
+    }
+}
+
+// the 1st bounce of a shot, before the next hit resets the count
+fn track_shot_landings(
+    mut ev_r_bounce: EventReader<BallBouncedEvt>,
+    ball_q: Query<(&BallStatus, &GlobalTransform), With<Ball>>,
+    mut stats: ResMut<MatchStats>,
+) {
+    for ev in ev_r_bounce.iter().filter(|ev| ev.bounce_count == 1) {
+        let (hitter_id, pos) = match ball_q.get(ev.ball_e) {
+            Ok((BallStatus::Rally(player_id), ball_t))
+            | Ok((BallStatus::Fault(_, player_id), ball_t)) => {
+                (*player_id, ball_t.translation.truncate())
+            }
+            _ => continue,
+        };
+        stats.shot_landings.push((hitter_id, pos));
+    }
+}
+
+fn count_dashes(
+    dash_q: Query<(&Player, &PlayerDash)>,
+    mut stats: ResMut<MatchStats>,
+    mut was_dashing: Local<[bool; 2]>,
+) {
+    for (player, dash) in dash_q.iter() {
+        let dashing = matches!(dash.status, PlayerActionStatus::Active(_));
+        let i = player.id - 1;
+        if dashing && !was_dashing[i] {
+            stats.get_player_mut(player.id).dashes += 1;
+        }
+        was_dashing[i] = dashing;
+    }
+}
+
+fn count_netted_shots(
+    mut ev_r_crossed_net: EventReader<BallCrossedNetEvt>,
+    ball_q: Query<&BallStatus>,
+    mut stats: ResMut<MatchStats>,
+    court_set: Res<CourtSettings>,
+    mode: Res<GameMode>,
+) {
+    if mode.has_wall() {
+        return;
+    }
+
+    for ev in ev_r_crossed_net.iter() {
+        if ev.get_shot_risk(court_set.net_extent) != Some(ShotRisk::Net) {
+            continue;
+        }
+
+        let hitter_id = match ball_q.get(ev.ball_e) {
+            Ok(BallStatus::Rally(player_id)) | Ok(BallStatus::Serve(_, _, player_id)) => *player_id,
+            _ => continue,
+        };
+        stats.get_player_mut(hitter_id).netted += 1;
+    }
+}
+
+fn show_shot_speed(
+    mut commands: Commands,
+    mut ev_r_hit: EventReader<BallHitEvt>,
+    ball_q: Query<&Ball>,
+    player_q: Query<(Entity, &Player)>,
+    config: Res<GameConfig>,
+    asset_server: Res<AssetServer>,
+    text_settings: Res<TextSettings>,
+) {
+    for ev in ev_r_hit.iter() {
+        let speed = match ball_q.get(ev.ball_e) {
+            Ok(ball) => ball.dir.length() * ball.max_speed,
+            Err(_) => continue,
+        };
+
+        if speed < config.ball.max_speed * SPEED_READOUT_RATIO {
+            continue;
+        }
+
+        if let Some((player_e, _)) = player_q.iter().find(|(_, p)| p.id == ev.player_id) {
+            let readout_e = commands
+                .spawn_bundle(Text2dBundle {
+                    text: Text::with_section(
+                        get_kmh_label(speed),
+                        get_text_style(&asset_server, &text_settings, 30.),
+                        TextAlignment {
+                            horizontal: HorizontalAlign::Center,
+                            vertical: VerticalAlign::Center,
+                        },
+                    ),
+                    transform: Transform::from_xyz(0., 150., 1.),
+                    ..Default::default()
+                })
+                .insert(ShotSpeedReadout {
+                    remaining_sec: SPEED_READOUT_SEC,
+                })
+                .insert(PaletteColor::Text)
+                .insert(ScaledText(30.))
+                .insert(Name::new("ShotSpeedReadout"))
+                .id();
+            commands.entity(player_e).add_child(readout_e);
+        }
+    }
+}
+
+fn fade_shot_speed(
+    mut commands: Commands,
+    mut readout_q: Query<(Entity, &mut ShotSpeedReadout, &mut Transform, &mut Text)>,
+    time: ScaledTime,
+) {
+    for (readout_e, mut readout, mut readout_t, mut text) in readout_q.iter_mut() {
+        let dt = time.scaled_delta_seconds();
+        readout.remaining_sec -= dt;
+
+        if readout.remaining_sec <= 0. {
+            commands.entity(readout_e).despawn_recursive();
+            continue;
+        }
+
+        readout_t.translation.y += SPEED_READOUT_RISE / SPEED_READOUT_SEC * dt;
+        text.sections[0]
+            .style
+            .color
+            .set_a(readout.remaining_sec / SPEED_READOUT_SEC);
+    }
+}
+
+fn update_fastest_shot_ui(
+    score: Res<Score>,
+    rules: Res<MatchRules>,
+    mode: Res<GameMode>,
+    stats: Res<MatchStats>,
+    locale: Res<Locale>,
+    mut text_q: Query<(&mut Text, &mut Visibility), With<FastestShotText>>,
+) {
+    if score.is_changed() || stats.is_changed() || locale.is_changed() {
+        let visible = mode.has_opponent() && score.get_winner_id(&rules).is_some();
+        for (mut text, mut visibility) in text_q.iter_mut() {
+            visibility.is_visible = visible;
+            if visible {
+                text.sections[0].value = locale.format(
+                    "stats-fastest-shot",
+                    &[&get_kmh_label(stats.get_max_shot_speed())],
+                );
+            }
+        }
+    }
+}
+
+// held by either player or toggled by tab, once the match is under way (tab picks the mode before that)
+// also comes up on its own after the victory celebration
+fn show_stats_card(
+    keys: Res<Input<KeyCode>>,
+    input: Res<PlayerInput>,
+    state: Res<State<GameState>>,
+    score: Res<Score>,
+    stats: Res<MatchStats>,
+    profiles: Res<Profiles>,
+    locale: Res<Locale>,
+    celebration: Option<Res<Celebration>>,
+    best_rally: Res<BestRally>,
+    mut toggled: Local<bool>,
+    mut card_q: Query<(&mut Visibility, Option<&mut Text>), With<StatsCard>>,
+) {
+    if *state.current() != GameState::Game {
+        *toggled = false;
+    } else if !score.is_match_start() && keys.just_pressed(KeyCode::Tab) {
+        *toggled = !*toggled;
+    }
+
+    let match_over = celebration.map_or(false, |c| c.is_done());
+    let visible = *state.current() == GameState::Game
+        && (*toggled || (1..=2).any(|id| input.held(id, InputAction::ShowStats)) || match_over);
+
+    for (mut visibility, text) in card_q.iter_mut() {
+        let shown = visible && !visibility.is_visible;
+        if visibility.is_visible != visible {
+            visibility.is_visible = visible;
+        }
+
+        if let Some(mut text) = text {
+            if visible && (shown || stats.is_changed() || locale.is_changed()) {
+                // the clip can only be watched from the results screen
+                let best_rally_hits = best_rally
+                    .replay
+                    .as_ref()
+                    .and(match_over.then(|| best_rally.hits));
+                text.sections[0].value =
+                    get_stats_text(&stats, &profiles, &locale, match_over, best_rally_hits);
+            }
+        }
+    }
+}
+
+// samples the offset at evenly spaced times, so the graph spans the whole match
+fn draw_net_sparkline(
+    stats: Res<MatchStats>,
+    court: Res<CourtSettings>,
+    mut bar_q: Query<(&SparklineBar, &mut Style)>,
+) {
+    if !stats.is_changed() {
+        return;
+    }
+
+    let end_sec = stats.net_offset_history.last().map_or(0., |(t, _)| *t);
+    for (bar, mut style) in bar_q.iter_mut() {
+        let sec = end_sec * (bar.0 + 1) as f32 / SPARKLINE_BARS as f32;
+        let ratio = (stats.get_net_offset_at(sec) / court.right).clamp(-1., 1.);
+        // at least a sliver, so a level match still shows the middle line
+        let height = (ratio.abs() * SPARKLINE_HALF_HEIGHT).max(1.);
+
+        style.size = Size::new(Val::Px(SPARKLINE_BAR_WIDTH - 1.), Val::Px(height));
+        style.position = Rect {
+            left: Val::Px(bar.0 as f32 * SPARKLINE_BAR_WIDTH),
+            bottom: Val::Px(if ratio >= 0. {
+                SPARKLINE_HALF_HEIGHT
+            } else {
+                SPARKLINE_HALF_HEIGHT - height
+            }),
+            ..Default::default()
+        };
+    }
+}
+
+// cells are tinted by the player landing more shots there, relative to the busiest cell
+fn draw_shot_heatmap(
+    state: Res<State<GameState>>,
+    stats: Res<MatchStats>,
+    court: Res<CourtSettings>,
+    palette: Res<Palette>,
+    locale: Res<Locale>,
+    celebration: Option<Res<Celebration>>,
+    mut was_visible: Local<bool>,
+    mut heatmap_q: Query<
+        (
+            &mut Visibility,
+            Option<&ShotHeatmapCell>,
+            Option<&mut UiColor>,
+            Option<&mut Text>,
+        ),
+        With<ShotHeatmap>,
+    >,
+) {
+    let visible = *state.current() == GameState::Game && celebration.map_or(false, |c| c.is_done());
+    let redraw = visible
+        && (!*was_visible || stats.is_changed() || palette.is_changed() || locale.is_changed());
+    let visibility_changed = visible != *was_visible;
+    *was_visible = visible;
+
+    if !redraw && !visibility_changed {
+        return;
+    }
+
+    let bins = [1, 2].map(|id| stats.get_landing_bins(id, &court));
+    let max_count = bins.iter().flatten().cloned().max().unwrap_or(0).max(1);
+    let colors =
+        [PaletteColor::PlayerLeft, PaletteColor::PlayerRight].map(|c| palette.get_color(&c));
+
+    for (mut visibility, cell, ui_color, text) in heatmap_q.iter_mut() {
+        visibility.is_visible = visible;
+        if !redraw {
+            continue;
+        }
+
+        if let Some(mut text) = text {
+            text.sections[0].value = locale.get("stats-shot-heatmap");
+        }
+
+        if let (Some(cell), Some(mut ui_color)) = (cell, ui_color) {
+            let i = cell.row * HEATMAP_COLS + cell.col;
+            let (count, mut color) = if bins[0][i] >= bins[1][i] {
+                (bins[0][i], colors[0])
+            } else {
+                (bins[1][i], colors[1])
+            };
+            color.set_a(count as f32 / max_count as f32 * HEATMAP_MAX_ALPHA);
+            ui_color.0 = color;
+        }
+    }
+}
+
+fn get_stats_text(
+    stats: &MatchStats,
+    profiles: &Profiles,
+    locale: &Locale,
+    show_export_hint: bool,
+    best_rally_hits: Option<u32>,
+) -> String {
+    let [left, right] = stats.players;
+    let rallies: Vec<String> = RALLY_BUCKETS
+        .iter()
+        .map(|(min, max)| {
+            let count = stats
+                .rally_lengths
+                .iter()
+                .filter(|len| *len >= min && *len <= max)
+                .count();
+            let range = if *max == u32::MAX {
+                format!("{}+", min)
+            } else {
+                format!("{}-{}", min, max)
+            };
+            format!("{}: {}", range, count)
+        })
+        .collect();
+
+    [
+        locale.get("stats-title"),
+        format!(
+            "{} | {}",
+            profiles.get_player_name(1, locale),
+            profiles.get_player_name(2, locale)
+        ),
+        locale.format(
+            "stats-winners",
+            &[&left.winners.to_string(), &right.winners.to_string()],
+        ),
+        locale.format(
+            "stats-faults",
+            &[
+                &left.won_on_faults.to_string(),
+                &right.won_on_faults.to_string(),
+            ],
+        ),
+        locale.format(
+            "stats-avg-speed",
+            &[
+                &format!("{:.0}", left.get_avg_shot_speed()),
+                &format!("{:.0}", right.get_avg_shot_speed()),
+            ],
+        ),
+        locale.format(
+            "stats-netted",
+            &[&left.netted.to_string(), &right.netted.to_string()],
+        ),
+        locale.format(
+            "stats-dashes",
+            &[&left.dashes.to_string(), &right.dashes.to_string()],
+        ),
+        locale.format("stats-rally-lengths", &[&rallies.join("  ")]),
+    ]
+    .into_iter()
+    // the export key only works on the results screen
+    .chain(show_export_hint.then(|| locale.get("stats-export-hint")))
+    .chain(best_rally_hits.map(|hits| locale.format("stats-best-rally-hint", &[&hits.to_string()])))
+    // right above the sparkline
+    .chain([locale.get("stats-momentum")])
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+fn get_kmh_label(speed: f32) -> String {
+    format!("{:.0} km/h", speed * KMH_PER_SPEED)
+}
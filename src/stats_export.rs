@@ -0,0 +1,233 @@
+use std::{env, io};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    celebration::Celebration,
+    locale::Locale,
+    profile::Profiles,
+    stats::{MatchStats, PointRecord},
+    storage, GameState,
+};
+
+// set to export every finished match, e.g. TUG_OF_BALL_EXPORT_STATS=csv
+const EXPORT_ENV_VAR: &str = "TUG_OF_BALL_EXPORT_STATS";
+const EXPORT_DIR: &str = "stats";
+const EXPORT_KEY: KeyCode = KeyCode::X;
+
+// writes the results screen stats and the point log for external tracking and balance tooling
+pub struct StatsExportPlugin;
+impl Plugin for StatsExportPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.insert_resource(get_auto_export_format())
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(export_on_demand)
+                    .with_system(export_on_match_over),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsFormat {
+    Json,
+    // a summary and a point log file
+    Csv,
+}
+
+impl StatsFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "json" => Some(StatsFormat::Json),
+            "csv" => Some(StatsFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+// the format finished matches get exported in, if any
+pub struct AutoExportFormat(pub Option<StatsFormat>);
+
+#[derive(Serialize)]
+struct StatsExport<'a> {
+    players: Vec<PlayerExport>,
+    rally_lengths: &'a [u32],
+    max_shot_speed: f32,
+    points: &'a [PointRecord],
+    // (sec, net offset)
+    net_offset_history: &'a [(f32, f32)],
+}
+
+#[derive(Serialize)]
+struct PlayerExport {
+    id: usize,
+    name: String,
+    winners: u32,
+    won_on_faults: u32,
+    shots: u32,
+    avg_shot_speed: f32,
+    netted: u32,
+    dashes: u32,
+}
+
+fn get_auto_export_format() -> AutoExportFormat {
+    let format = env::var(EXPORT_ENV_VAR).ok().and_then(|value| {
+        let format = StatsFormat::parse(&value);
+        if format.is_none() {
+            warn!("Unknown stats export format '{}'", value);
+        }
+        format
+    });
+
+    AutoExportFormat(format)
+}
+
+fn get_export(stats: &MatchStats, profiles: &Profiles, locale: &Locale) -> StatsExport<'_> {
+    StatsExport {
+        players: stats
+            .players
+            .iter()
+            .enumerate()
+            .map(|(i, p)| PlayerExport {
+                id: i + 1,
+                name: profiles.get_player_name(i + 1, locale),
+                winners: p.winners,
+                won_on_faults: p.won_on_faults,
+                shots: p.shots,
+                avg_shot_speed: p.get_avg_shot_speed(),
+                netted: p.netted,
+                dashes: p.dashes,
+            })
+            .collect(),
+        rally_lengths: &stats.rally_lengths,
+        max_shot_speed: stats.get_max_shot_speed(),
+        points: &stats.points,
+        net_offset_history: &stats.net_offset_history,
+    }
+}
+
+fn get_summary_csv(export: &StatsExport) -> String {
+    let mut csv = String::from("stat");
+    for p in export.players.iter() {
+        csv.push_str(&format!(",{}", p.name.replace(',', " ")));
+    }
+    csv.push('\n');
+
+    let rows: [(&str, fn(&PlayerExport) -> String); 6] = [
+        ("winners", |p| p.winners.to_string()),
+        ("won_on_faults", |p| p.won_on_faults.to_string()),
+        ("shots", |p| p.shots.to_string()),
+        ("avg_shot_speed", |p| format!("{:.1}", p.avg_shot_speed)),
+        ("netted", |p| p.netted.to_string()),
+        ("dashes", |p| p.dashes.to_string()),
+    ];
+    for (stat, get_value) in rows.iter() {
+        csv.push_str(stat);
+        for p in export.players.iter() {
+            csv.push_str(&format!(",{}", get_value(p)));
+        }
+        csv.push('\n');
+    }
+
+    csv
+}
+
+fn get_points_csv(export: &StatsExport) -> String {
+    let mut csv = String::from("sec,scoring_player_id,reason,kind,rally_hits\n");
+    for p in export.points.iter() {
+        csv.push_str(&format!(
+            "{:.3},{},{:?},{:?},{}\n",
+            p.sec, p.scoring_player_id, p.reason, p.kind, p.rally_hits
+        ));
+    }
+    csv
+}
+
+// all the formats share the match number, returns the saved storage keys
+pub fn export_stats(
+    stats: &MatchStats,
+    profiles: &Profiles,
+    locale: &Locale,
+    formats: &[StatsFormat],
+) -> io::Result<Vec<String>> {
+    let export = get_export(stats, profiles, locale);
+    let match_count = storage::list(EXPORT_DIR)
+        .iter()
+        .filter_map(|k| k.split("/match_").nth(1)?.get(..5)?.parse::<usize>().ok())
+        .max()
+        .unwrap_or(0);
+    // zero padded, so the latest match sorts last
+    let name = format!("{}/match_{:05}", EXPORT_DIR, match_count + 1);
+
+    let mut files = Vec::new();
+    for format in formats.iter() {
+        match format {
+            StatsFormat::Json => files.push((
+                format!("{}.json", name),
+                serde_json::to_string_pretty(&export)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            )),
+            StatsFormat::Csv => {
+                files.push((format!("{}_summary.csv", name), get_summary_csv(&export)));
+                files.push((format!("{}_points.csv", name), get_points_csv(&export)));
+            }
+        }
+    }
+
+    for (key, data) in files.iter() {
+        storage::save(key, data.as_bytes())?;
+    }
+
+    Ok(files.into_iter().map(|(key, _)| key).collect())
+}
+
+fn log_export(result: io::Result<Vec<String>>) {
+    match result {
+        Ok(keys) => info!("Match stats saved to {}", keys.join(", ")),
+        Err(e) => warn!("Failed to export the match stats: {}", e),
+    }
+}
+
+// both formats at once from the results screen
+fn export_on_demand(
+    keys: Res<Input<KeyCode>>,
+    celebration: Option<Res<Celebration>>,
+    stats: Res<MatchStats>,
+    profiles: Res<Profiles>,
+    locale: Res<Locale>,
+) {
+    if keys.just_pressed(EXPORT_KEY) && celebration.map_or(false, |c| c.is_done()) {
+        log_export(export_stats(
+            &stats,
+            &profiles,
+            &locale,
+            &[StatsFormat::Json, StatsFormat::Csv],
+        ));
+    }
+}
+
+// once the celebration is over, so the last point is in
+fn export_on_match_over(
+    auto_format: Res<AutoExportFormat>,
+    celebration: Option<Res<Celebration>>,
+    stats: Res<MatchStats>,
+    profiles: Res<Profiles>,
+    locale: Res<Locale>,
+    mut exported: Local<bool>,
+) {
+    let format = match auto_format.0 {
+        Some(format) => format,
+        None => return,
+    };
+
+    match celebration {
+        Some(celebration) if celebration.is_done() => {
+            if !*exported {
+                *exported = true;
+                log_export(export_stats(&stats, &profiles, &locale, &[format]));
+            }
+        }
+        _ => *exported = false,
+    }
+}
@@ -0,0 +1,402 @@
+use bevy::{
+    core_pipeline::{draw_2d_graph, node, Transparent2d},
+    prelude::*,
+    render::{
+        camera::{ActiveCameras, ExtractedCameraNames},
+        render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext, SlotValue},
+        render_phase::RenderPhase,
+        renderer::RenderContext,
+        RenderApp, RenderStage,
+    },
+    window::{CreateWindow, WindowId},
+};
+
+use crate::{
+    ball::Ball,
+    level::{CourtSettings, Net},
+    locale::Locale,
+    palette::PaletteColor,
+    player::Player,
+    stats::MatchStats,
+    ui_text::{get_text_style, ScaledText, TextSettings},
+    NAME,
+};
+
+const STATS_CAMERA: &str = "stats_camera";
+const STATS_PASS_DRIVER: &str = "stats_pass_driver";
+const WINDOW_SIZE: Vec2 = Vec2::new(960., 540.);
+// sprites get drawn by every camera, so the hud lives far away from the court
+const HUD_ORIGIN: Vec2 = Vec2::new(0., 10000.);
+const OVERVIEW_Y: f32 = 110.;
+const OVERVIEW_SCALE: f32 = 0.3;
+const DOT_SIZE: f32 = 14.;
+const LINE_THICKNESS: f32 = 3.;
+const CHART_SIZE: Vec2 = Vec2::new(400., 150.);
+const CHART_X: f32 = 230.;
+const CHART_BOTTOM: f32 = -235.;
+const BAR_GAP: f32 = 4.;
+// last n rallies
+const RALLY_BAR_COUNT: usize = 20;
+const SPEED_BUCKET_COUNT: usize = 8;
+
+// optional 2nd window with a court overview and live match stats
+// bevy_ui only renders to the primary window, so the hud is made of sprites and 2d text
+pub struct StatsWindowPlugin;
+impl Plugin for StatsWindowPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(create_window)
+            .add_startup_system(setup)
+            .add_system(spawn_dots)
+            .add_system(update_dots)
+            .add_system(update_overview)
+            .add_system(update_charts)
+            .add_system(update_titles);
+
+        let render_app = app.sub_app_mut(RenderApp);
+        render_app.add_system_to_stage(RenderStage::Extract, extract_stats_camera_phases);
+        let mut graph = render_app.world.get_resource_mut::<RenderGraph>().unwrap();
+        graph.add_node(STATS_PASS_DRIVER, StatsPassDriver);
+        graph
+            .add_node_edge(node::MAIN_PASS_DEPENDENCIES, STATS_PASS_DRIVER)
+            .unwrap();
+    }
+}
+
+#[derive(Component)]
+struct StatsCamera;
+
+#[derive(Component)]
+struct StatsHudRoot;
+
+#[derive(Component)]
+struct OverviewCourt;
+
+#[derive(Component)]
+struct OverviewNet;
+
+#[derive(Component)]
+struct OverviewDot {
+    target_e: Entity,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Chart {
+    Rallies,
+    ShotSpeed,
+}
+
+impl Chart {
+    fn get_bar_count(&self) -> usize {
+        match self {
+            Chart::Rallies => RALLY_BAR_COUNT,
+            Chart::ShotSpeed => SPEED_BUCKET_COUNT,
+        }
+    }
+
+    fn get_x(&self) -> f32 {
+        match self {
+            Chart::Rallies => -CHART_X,
+            Chart::ShotSpeed => CHART_X,
+        }
+    }
+
+    fn get_title_key(&self) -> &'static str {
+        match self {
+            Chart::Rallies => "stats-rallies",
+            Chart::ShotSpeed => "stats-shot-speed",
+        }
+    }
+
+    fn get_color(&self) -> PaletteColor {
+        match self {
+            Chart::Rallies => PaletteColor::PlayerLeft,
+            Chart::ShotSpeed => PaletteColor::PlayerRight,
+        }
+    }
+
+    fn get_bar_width(&self) -> f32 {
+        CHART_SIZE.x / self.get_bar_count() as f32 - BAR_GAP
+    }
+
+    // normalized bar heights
+    fn get_values(&self, stats: &MatchStats) -> Vec<f32> {
+        let values: Vec<f32> = match self {
+            Chart::Rallies => {
+                let skip = stats.rally_lengths.len().saturating_sub(RALLY_BAR_COUNT);
+                stats.rally_lengths[skip..]
+                    .iter()
+                    .map(|len| *len as f32)
+                    .collect()
+            }
+            Chart::ShotSpeed => {
+                let max_speed = stats.get_max_shot_speed();
+                let mut buckets = vec![0.; SPEED_BUCKET_COUNT];
+                if max_speed > 0. {
+                    for speed in stats.shot_speeds.iter() {
+                        let i = (speed / max_speed * SPEED_BUCKET_COUNT as f32) as usize;
+                        buckets[i.min(SPEED_BUCKET_COUNT - 1)] += 1.;
+                    }
+                }
+                buckets
+            }
+        };
+
+        let max = values.iter().cloned().fold(0., f32::max);
+        values
+            .iter()
+            .map(|v| if max > 0. { v / max } else { 0. })
+            .collect()
+    }
+}
+
+#[derive(Component)]
+struct ChartBar {
+    chart: Chart,
+    index: usize,
+}
+
+#[derive(Component)]
+struct ChartTitle(Chart);
+
+fn create_window(
+    mut commands: Commands,
+    mut ev_w_create: EventWriter<CreateWindow>,
+    mut active_cameras: ResMut<ActiveCameras>,
+) {
+    let window_id = WindowId::new();
+    ev_w_create.send(CreateWindow {
+        id: window_id,
+        descriptor: WindowDescriptor {
+            title: format!("{} - stats", NAME),
+            width: WINDOW_SIZE.x,
+            height: WINDOW_SIZE.y,
+            resizable: false,
+            ..Default::default()
+        },
+    });
+
+    let mut camera = OrthographicCameraBundle::new_2d();
+    camera.camera.window = window_id;
+    camera.camera.name = Some(STATS_CAMERA.to_string());
+    camera.transform.translation = HUD_ORIGIN.extend(camera.transform.translation.z);
+    commands
+        .spawn_bundle(camera)
+        .insert(StatsCamera)
+        .insert(Name::new("StatsCamera"));
+    active_cameras.add(STATS_CAMERA);
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, text_settings: Res<TextSettings>) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(WINDOW_SIZE),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(HUD_ORIGIN.extend(0.)),
+            ..Default::default()
+        })
+        .insert(PaletteColor::Background)
+        .insert(StatsHudRoot)
+        .insert(Name::new("StatsHud"))
+        .with_children(|b| {
+            // sized by the court settings
+            b.spawn_bundle(SpriteBundle {
+                transform: Transform::from_xyz(0., OVERVIEW_Y, 1.),
+                ..Default::default()
+            })
+            .insert(PaletteColor::Court)
+            .insert(OverviewCourt);
+
+            b.spawn_bundle(SpriteBundle {
+                transform: Transform::from_xyz(0., OVERVIEW_Y, 2.),
+                ..Default::default()
+            })
+            .insert(PaletteColor::CourtLines)
+            .insert(OverviewNet);
+
+            for chart in [Chart::Rallies, Chart::ShotSpeed] {
+                let bar_w = chart.get_bar_width();
+                for index in 0..chart.get_bar_count() {
+                    let x = chart.get_x() - CHART_SIZE.x / 2.
+                        + (bar_w + BAR_GAP) * (index as f32 + 0.5);
+                    b.spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::new(bar_w, 0.)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(x, CHART_BOTTOM, 1.),
+                        ..Default::default()
+                    })
+                    .insert(chart.get_color())
+                    .insert(ChartBar { chart, index });
+                }
+
+                // baseline
+                b.spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::new(CHART_SIZE.x, LINE_THICKNESS)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(chart.get_x(), CHART_BOTTOM, 2.),
+                    ..Default::default()
+                })
+                .insert(PaletteColor::Text);
+
+                b.spawn_bundle(Text2dBundle {
+                    text: Text::with_section(
+                        "",
+                        get_text_style(&asset_server, &text_settings, 26.),
+                        TextAlignment {
+                            horizontal: HorizontalAlign::Center,
+                            vertical: VerticalAlign::Center,
+                        },
+                    ),
+                    transform: Transform::from_xyz(
+                        chart.get_x(),
+                        CHART_BOTTOM + CHART_SIZE.y + 25.,
+                        2.,
+                    ),
+                    ..Default::default()
+                })
+                .insert(PaletteColor::Text)
+                .insert(ScaledText(26.))
+                .insert(ChartTitle(chart));
+            }
+        });
+}
+
+fn spawn_dots(
+    mut commands: Commands,
+    root_q: Query<Entity, With<StatsHudRoot>>,
+    player_q: Query<Entity, Added<Player>>,
+    ball_q: Query<Entity, Added<Ball>>,
+) {
+    if let Ok(root_e) = root_q.get_single() {
+        let new_dots = player_q
+            .iter()
+            .map(|e| (e, PaletteColor::Player, 1.))
+            .chain(ball_q.iter().map(|e| (e, PaletteColor::Ball, 0.7)));
+
+        for (target_e, color, size_mult) in new_dots {
+            let dot_e = commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::splat(DOT_SIZE * size_mult)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(0., OVERVIEW_Y, 3.),
+                    ..Default::default()
+                })
+                .insert(color)
+                .insert(OverviewDot { target_e })
+                .id();
+            commands.entity(root_e).add_child(dot_e);
+        }
+    }
+}
+
+fn update_dots(
+    mut commands: Commands,
+    mut dot_q: Query<(Entity, &OverviewDot, &mut Transform)>,
+    transform_q: Query<&GlobalTransform>,
+) {
+    for (dot_e, dot, mut dot_t) in dot_q.iter_mut() {
+        if let Ok(t) = transform_q.get(dot.target_e) {
+            let pos = get_overview_pos(t.translation.truncate());
+            dot_t.translation.x = pos.x;
+            dot_t.translation.y = pos.y;
+        } else {
+            commands.entity(dot_e).despawn_recursive();
+        }
+    }
+}
+
+fn update_overview(
+    court: Option<Res<CourtSettings>>,
+    net_q: Query<&GlobalTransform, With<Net>>,
+    mut court_q: Query<&mut Sprite, (With<OverviewCourt>, Without<OverviewNet>)>,
+    mut overview_net_q: Query<(&mut Sprite, &mut Transform), With<OverviewNet>>,
+) {
+    let court = match court {
+        Some(court) => court,
+        None => return,
+    };
+
+    let size = Vec2::new(court.right - court.left, court.top - court.bottom) * OVERVIEW_SCALE;
+    if court.is_changed() {
+        for mut sprite in court_q.iter_mut() {
+            sprite.custom_size = Some(size);
+        }
+    }
+
+    if let Ok(net_t) = net_q.get_single() {
+        for (mut sprite, mut t) in overview_net_q.iter_mut() {
+            sprite.custom_size = Some(Vec2::new(LINE_THICKNESS, size.y));
+            t.translation.x = get_overview_pos(net_t.translation.truncate()).x;
+        }
+    }
+}
+
+fn update_charts(
+    stats: Res<MatchStats>,
+    mut bar_q: Query<(&ChartBar, &mut Sprite, &mut Transform)>,
+) {
+    if !stats.is_changed() {
+        return;
+    }
+
+    let rallies = Chart::Rallies.get_values(&stats);
+    let shot_speeds = Chart::ShotSpeed.get_values(&stats);
+
+    for (bar, mut sprite, mut t) in bar_q.iter_mut() {
+        let values = match bar.chart {
+            Chart::Rallies => &rallies,
+            Chart::ShotSpeed => &shot_speeds,
+        };
+        let h = values.get(bar.index).cloned().unwrap_or_default() * CHART_SIZE.y;
+        sprite.custom_size = Some(Vec2::new(bar.chart.get_bar_width(), h));
+        // sprites are centered
+        t.translation.y = CHART_BOTTOM + h / 2.;
+    }
+}
+
+fn update_titles(locale: Res<Locale>, mut text_q: Query<(&ChartTitle, &mut Text)>) {
+    for (title, mut text) in text_q.iter_mut() {
+        if locale.is_changed() || text.sections[0].value.is_empty() {
+            text.sections[0].value = locale.get(title.0.get_title_key());
+        }
+    }
+}
+
+fn get_overview_pos(pos: Vec2) -> Vec2 {
+    pos * OVERVIEW_SCALE + Vec2::new(0., OVERVIEW_Y)
+}
+
+fn extract_stats_camera_phases(mut commands: Commands, active_cameras: Res<ActiveCameras>) {
+    if let Some(camera) = active_cameras.get(STATS_CAMERA) {
+        if let Some(camera_e) = camera.entity {
+            commands
+                .get_or_spawn(camera_e)
+                .insert(RenderPhase::<Transparent2d>::default());
+        }
+    }
+}
+
+// the 2d graph only drives the primary camera, this runs it for the stats window as well
+struct StatsPassDriver;
+impl Node for StatsPassDriver {
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        _render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let extracted_cameras = world.get_resource::<ExtractedCameraNames>().unwrap();
+        if let Some(camera_e) = extracted_cameras.entities.get(STATS_CAMERA) {
+            graph.run_sub_graph(draw_2d_graph::NAME, vec![SlotValue::Entity(*camera_e)])?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::{Read, Write},
+};
+
+use bevy::prelude::*;
+use steamworks::{Client, SingleClient};
+
+use crate::{
+    cosmetic::{BallSkin, ConfettiStyle, Cosmetic, PlayerSkin, TrailShape},
+    profile::Profiles,
+    storage,
+    timer::{GameClock, GameTimer},
+};
+
+// settings, profiles and stats - replays and recordings are too big and only matter locally
+const CLOUD_DIRS: [&str; 3] = ["settings", "profiles", "stats"];
+const CLOUD_UPLOAD_INTERVAL_SEC: f32 = 5.;
+
+// achievements and cloud saves, without steam running it does nothing
+// there's no achievement system of its own yet, so the achievements mirror the cosmetic unlocks
+// needs to be added before the plugins loading their saves, so they get the cloud copies
+pub struct SteamPlugin;
+impl Plugin for SteamPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        let (client, single) = match Client::init() {
+            Ok(client) => client,
+            Err(e) => {
+                warn!(
+                    "Steam is not available, achievements and cloud saves are off: {}",
+                    e
+                );
+                return;
+            }
+        };
+
+        restore_cloud_saves(&client);
+
+        app.insert_resource(SteamClient(client))
+            .insert_non_send_resource(single)
+            .init_resource::<CloudSync>()
+            .add_system(run_steam_callbacks)
+            .add_system(unlock_achievements)
+            .add_system(upload_cloud_saves);
+    }
+}
+
+pub struct SteamClient(pub Client);
+
+// what got uploaded last, so unchanged files aren't sent again
+struct CloudSync {
+    timer: GameTimer,
+    uploaded: HashMap<String, Vec<u8>>,
+}
+
+impl Default for CloudSync {
+    fn default() -> Self {
+        Self {
+            timer: GameTimer::unscaled(CLOUD_UPLOAD_INTERVAL_SEC),
+            uploaded: HashMap::new(),
+        }
+    }
+}
+
+// the api names set up in the steamworks partner site
+fn get_achievement_name(cosmetic: &Cosmetic) -> Option<&'static str> {
+    match cosmetic {
+        Cosmetic::PlayerSkin(PlayerSkin::Team) => Some("UNLOCK_TEAM_SKIN"),
+        Cosmetic::PlayerSkin(PlayerSkin::Charge) => Some("UNLOCK_CHARGE_SKIN"),
+        Cosmetic::BallSkin(BallSkin::Ring) => Some("UNLOCK_RING_BALL"),
+        Cosmetic::TrailShape(TrailShape::Thin) => Some("UNLOCK_THIN_TRAIL"),
+        Cosmetic::TrailShape(TrailShape::Comet) => Some("UNLOCK_COMET_TRAIL"),
+        Cosmetic::Confetti(ConfettiStyle::Classic) => Some("UNLOCK_CONFETTI"),
+        Cosmetic::Confetti(ConfettiStyle::Streamers) => Some("UNLOCK_STREAMERS"),
+        _ => None,
+    }
+}
+
+// the newer copy wins - a local save newer than the cloud one gets uploaded on the first poll
+fn restore_cloud_saves(client: &Client) {
+    let remote_storage = client.remote_storage();
+    if !remote_storage.is_cloud_enabled_for_app() {
+        return;
+    }
+
+    for file_info in remote_storage.files() {
+        if !CLOUD_DIRS
+            .iter()
+            .any(|dir| file_info.name.starts_with(&format!("{}/", dir)))
+        {
+            continue;
+        }
+
+        let file = remote_storage.file(&file_info.name);
+        if let Ok(local_secs) = storage::get_modified_secs(&file_info.name) {
+            if local_secs >= file.timestamp() {
+                continue;
+            }
+        }
+
+        let mut data = Vec::new();
+        let result = file
+            .read()
+            .read_to_end(&mut data)
+            .and_then(|_| storage::save(&file_info.name, &data));
+
+        match result {
+            Ok(_) => debug!("Restored {} from the cloud", file_info.name),
+            Err(e) => warn!("Failed to restore {} from the cloud: {}", file_info.name, e),
+        }
+    }
+}
+
+fn run_steam_callbacks(single: NonSend<SingleClient>) {
+    single.run_callbacks();
+}
+
+// any profile played on this account counts
+fn unlock_achievements(
+    steam: Res<SteamClient>,
+    profiles: Res<Profiles>,
+    mut unlocked: Local<HashSet<&'static str>>,
+) {
+    if !profiles.is_changed() {
+        return;
+    }
+
+    let user_stats = steam.0.user_stats();
+    let mut any_new = false;
+
+    for name in profiles
+        .all
+        .iter()
+        .flat_map(|p| p.unlocked.iter())
+        .filter_map(get_achievement_name)
+    {
+        if unlocked.insert(name) {
+            if user_stats.achievement(name).set().is_err() {
+                warn!("Failed to set the {} achievement", name);
+            }
+            any_new = true;
+        }
+    }
+
+    if any_new && user_stats.store_stats().is_err() {
+        warn!("Failed to store the steam stats");
+    }
+}
+
+// the saves are written all over the place, so the save dirs get polled instead
+fn upload_cloud_saves(steam: Res<SteamClient>, mut sync: ResMut<CloudSync>, clock: GameClock) {
+    if !sync.timer.tick(&clock).finished() {
+        return;
+    }
+    sync.timer.reset();
+
+    let remote_storage = steam.0.remote_storage();
+    if !remote_storage.is_cloud_enabled_for_app() {
+        return;
+    }
+
+    for key in CLOUD_DIRS.iter().flat_map(|dir| storage::list(dir)) {
+        let data = match storage::load(&key) {
+            Ok(data) => data,
+            Err(_) => continue,
+        };
+
+        if sync.uploaded.get(&key) == Some(&data) {
+            continue;
+        }
+
+        match remote_storage.file(&key).write().write_all(&data) {
+            Ok(_) => {
+                debug!("Uploaded {} to the cloud", key);
+                sync.uploaded.insert(key, data);
+            }
+            Err(e) => warn!("Failed to upload {} to the cloud: {}", key, e),
+        }
+    }
+}
@@ -0,0 +1,141 @@
+// key/value persistence - files on native, localStorage on the web
+// keys are '/' separated paths, e.g. "replays/match_00001.tobr"
+use std::io;
+
+pub use platform::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use std::{
+        fs, io,
+        path::{Path, PathBuf},
+        time::UNIX_EPOCH,
+    };
+
+    const SAVE_DIR: &str = "save";
+
+    fn get_path(key: &str) -> PathBuf {
+        Path::new(SAVE_DIR).join(key)
+    }
+
+    pub fn save(key: &str, data: &[u8]) -> io::Result<()> {
+        let path = get_path(key);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, data)
+    }
+
+    pub fn load(key: &str) -> io::Result<Vec<u8>> {
+        fs::read(get_path(key))
+    }
+
+    // unix seconds of the last save, e.g. to compare with the cloud copy
+    pub fn get_modified_secs(key: &str) -> io::Result<i64> {
+        let modified = fs::metadata(get_path(key))?.modified()?;
+        modified
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
+
+    pub fn list(dir: &str) -> Vec<String> {
+        fs::read_dir(get_path(dir))
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter_map(|e| e.file_name().into_string().ok())
+                    .map(|name| format!("{}/{}", dir, name))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use std::io;
+    use web_sys::Storage;
+
+    use super::{decode_hex, encode_hex, to_io_error};
+
+    const KEY_PREFIX: &str = "tug_of_ball/";
+
+    fn get_storage() -> io::Result<Storage> {
+        web_sys::window()
+            .and_then(|w| w.local_storage().ok().flatten())
+            .ok_or_else(|| to_io_error("localStorage is not available"))
+    }
+
+    pub fn save(key: &str, data: &[u8]) -> io::Result<()> {
+        get_storage()?
+            .set_item(&format!("{}{}", KEY_PREFIX, key), &encode_hex(data))
+            .map_err(|_| to_io_error("localStorage is full"))
+    }
+
+    pub fn load(key: &str) -> io::Result<Vec<u8>> {
+        let value = get_storage()?
+            .get_item(&format!("{}{}", KEY_PREFIX, key))
+            .ok()
+            .flatten()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, key.to_string()))?;
+        decode_hex(&value)
+    }
+
+    pub fn get_modified_secs(_key: &str) -> io::Result<i64> {
+        Err(to_io_error("localStorage has no modification times"))
+    }
+
+    pub fn list(dir: &str) -> Vec<String> {
+        let prefix = format!("{}{}/", KEY_PREFIX, dir);
+        let storage = match get_storage() {
+            Ok(storage) => storage,
+            Err(_) => return Vec::new(),
+        };
+
+        (0..storage.length().unwrap_or(0))
+            .filter_map(|i| storage.key(i).ok().flatten())
+            .filter(|k| k.starts_with(&prefix))
+            .map(|k| k[KEY_PREFIX.len()..].to_string())
+            .collect()
+    }
+}
+
+// one past the highest numbered key, e.g. 4 for "replays/match_00003.tobr"
+// counting the keys instead would reuse a number once an older save got deleted
+pub fn get_next_number(keys: &[String], prefix: &str, ext: &str) -> u32 {
+    keys.iter()
+        .filter_map(|k| {
+            k.strip_prefix(prefix)?
+                .strip_suffix(ext)?
+                .strip_suffix('.')?
+                .parse::<u32>()
+                .ok()
+        })
+        .max()
+        .map_or(1, |n| n + 1)
+}
+
+// localStorage can only hold strings
+#[allow(dead_code)]
+fn encode_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[allow(dead_code)]
+fn decode_hex(value: &str) -> io::Result<Vec<u8>> {
+    (0..value.len())
+        .step_by(2)
+        .map(|i| {
+            value
+                .get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| to_io_error("invalid stored data"))
+        })
+        .collect()
+}
+
+#[allow(dead_code)]
+fn to_io_error(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, msg.to_string())
+}
@@ -0,0 +1,409 @@
+use bevy::prelude::*;
+use rand::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset::SpriteAtlas,
+    ball::{despawn_ball, spawn_ball, Ball, BallBouncedEvt, BallHitEvt, BallStatus},
+    config::GameConfig,
+    cosmetic::Loadouts,
+    input_binding::InputAction,
+    level::{CourtSettings, NetOffset, ServingRegion},
+    locale::Locale,
+    mode::GameMode,
+    palette::PaletteColor,
+    prompt::ButtonPrompt,
+    render::COURT_LINE_Z,
+    reset::DespawnOnExit,
+    rng::GameRng,
+    storage,
+    ui_text::{get_text_style, ScaledText, TextSettings},
+    GameSetupPhase, GameState,
+};
+
+const SHOT_COUNT: usize = 10;
+const TARGET_RADIUS: f32 = 70.;
+// landing further from the center than this scores no points
+const SCORE_RADIUS: f32 = 200.;
+const ACCURACY_POINTS: f32 = 100.;
+// on top of the accuracy, scaled by how close to the max speed the shot was
+const SPEED_POINTS: f32 = 50.;
+// keeps the target off the lines and the net
+const TARGET_MARGIN: f32 = 90.;
+const BEST_STORAGE_KEY: &str = "target_practice.ron";
+
+// solo mode - land 10 shots as close to the target as possible
+pub struct TargetPlugin;
+impl Plugin for TargetPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.insert_resource(TargetPractice::load())
+            .add_system_set(
+                SystemSet::on_enter(GameState::Game)
+                    .with_system(setup.after(GameSetupPhase::Level)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(track_shot_speed)
+                    .with_system(score_landing)
+                    .with_system(update_target_ui),
+            );
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct TargetBest {
+    points: u32,
+}
+
+pub struct TargetPractice {
+    // points of every taken shot
+    pub shots: Vec<u32>,
+    pub best: u32,
+    shot_speed: f32,
+    new_best: bool,
+}
+
+impl TargetPractice {
+    fn load() -> Self {
+        let best: TargetBest = storage::load(BEST_STORAGE_KEY)
+            .ok()
+            .and_then(|bytes| ron::de::from_bytes(&bytes).ok())
+            .unwrap_or_default();
+
+        Self {
+            shots: Vec::new(),
+            best: best.points,
+            shot_speed: 0.,
+            new_best: false,
+        }
+    }
+
+    fn save_best(&self) {
+        let result = ron::to_string(&TargetBest { points: self.best })
+            .map_err(|e| e.to_string())
+            .and_then(|data| {
+                storage::save(BEST_STORAGE_KEY, data.as_bytes()).map_err(|e| e.to_string())
+            });
+
+        if let Err(e) = result {
+            error!("Failed to save the target practice best: {}", e);
+        }
+    }
+
+    pub fn get_total(&self) -> u32 {
+        self.shots.iter().sum()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.shots.len() >= SHOT_COUNT
+    }
+}
+
+#[derive(Component)]
+struct TargetRing;
+
+#[derive(Component)]
+struct TargetText;
+
+#[derive(Component)]
+struct TargetResults;
+
+#[derive(Component)]
+struct TargetResultsText;
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    text_settings: Res<TextSettings>,
+    mode: Res<GameMode>,
+    court: Res<CourtSettings>,
+    net: Res<NetOffset>,
+    mut rng: ResMut<GameRng>,
+    mut practice: ResMut<TargetPractice>,
+) {
+    if *mode != GameMode::TargetPractice {
+        return;
+    }
+
+    practice.shots.clear();
+    practice.new_best = false;
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: asset_server.load("art-ish/player_circle.png"),
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(TARGET_RADIUS * 2.)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(
+                get_target_pos(&court, net.0, &mut rng).extend(COURT_LINE_Z),
+            ),
+            ..Default::default()
+        })
+        .insert(PaletteColor::PlayerAim)
+        .insert(TargetRing)
+        .insert(Name::new("TargetRing"))
+        .insert(DespawnOnExit(GameState::Game));
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::Center,
+                position_type: PositionType::Relative,
+                margin: Rect {
+                    top: Val::Auto,
+                    bottom: Val::Px(10.0),
+                    right: Val::Auto,
+                    left: Val::Auto,
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                get_text_style(&asset_server, &text_settings, 50.0),
+                TextAlignment {
+                    horizontal: HorizontalAlign::Center,
+                    ..Default::default()
+                },
+            ),
+            ..Default::default()
+        })
+        .insert(PaletteColor::Text)
+        .insert(ScaledText(50.0))
+        .insert(TargetText)
+        .insert(Name::new("TargetText"))
+        .insert(DespawnOnExit(GameState::Game));
+
+    // results screen, shown once all the shots are taken
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(TargetResults)
+        .insert(Name::new("TargetResults"))
+        .insert(DespawnOnExit(GameState::Game))
+        .with_children(|b| {
+            b.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "",
+                    get_text_style(&asset_server, &text_settings, 40.0),
+                    TextAlignment {
+                        horizontal: HorizontalAlign::Center,
+                        ..Default::default()
+                    },
+                ),
+                visibility: Visibility { is_visible: false },
+                ..Default::default()
+            })
+            .insert(PaletteColor::Text)
+            .insert(ScaledText(40.0))
+            .insert(TargetResultsText);
+
+            b.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "",
+                    get_text_style(&asset_server, &text_settings, 30.0),
+                    Default::default(),
+                ),
+                visibility: Visibility { is_visible: false },
+                ..Default::default()
+            })
+            .insert(PaletteColor::Text)
+            .insert(ScaledText(30.0))
+            .insert(ButtonPrompt {
+                player_id: 1,
+                action: InputAction::Reset,
+                template_key: "prompt-restart",
+            })
+            .insert(TargetResultsText);
+        });
+}
+
+fn track_shot_speed(
+    mut ev_r_hit: EventReader<BallHitEvt>,
+    ball_q: Query<&Ball>,
+    mode: Res<GameMode>,
+    mut practice: ResMut<TargetPractice>,
+) {
+    if *mode != GameMode::TargetPractice {
+        return;
+    }
+
+    for ev in ev_r_hit.iter() {
+        if let Ok(ball) = ball_q.get(ev.ball_e) {
+            practice.shot_speed = ball.dir.length() * ball.max_speed;
+        }
+    }
+}
+
+// every shot is over on its 1st bounce
+fn score_landing(
+    mut commands: Commands,
+    mut ev_r_ball_bounced: EventReader<BallBouncedEvt>,
+    mut ball_q: Query<(&Ball, &mut BallStatus, &Transform)>,
+    mut ring_q: Query<&mut Transform, (With<TargetRing>, Without<Ball>)>,
+    entity_q: Query<Entity>,
+    atlas: Res<SpriteAtlas>,
+    serving_region: Res<ServingRegion>,
+    court_set: Res<CourtSettings>,
+    net: Res<NetOffset>,
+    config: Res<GameConfig>,
+    loadouts: Res<Loadouts>,
+    mut rng: ResMut<GameRng>,
+    mode: Res<GameMode>,
+    mut practice: ResMut<TargetPractice>,
+) {
+    if *mode != GameMode::TargetPractice {
+        return;
+    }
+
+    for ev in ev_r_ball_bounced.iter() {
+        let (ball, mut status, ball_t) = match ball_q.get_mut(ev.ball_e) {
+            Ok(ball) => ball,
+            Err(_) => continue,
+        };
+
+        // lets get replayed
+        if matches!(*status, BallStatus::Used) {
+            continue;
+        }
+
+        let mut ring_t = match ring_q.get_single_mut() {
+            Ok(ring_t) => ring_t,
+            Err(_) => continue,
+        };
+
+        // the solo player is on the left, so only the right side counts
+        let points = if ball.region.is_right() {
+            let dist = ball_t
+                .translation
+                .truncate()
+                .distance(ring_t.translation.truncate());
+            get_shot_points(dist, practice.shot_speed / config.ball.max_speed)
+        } else {
+            0
+        };
+        debug!(
+            "Target shot {} for {} points",
+            practice.shots.len() + 1,
+            points
+        );
+        practice.shots.push(points);
+        practice.shot_speed = 0.;
+
+        despawn_ball(
+            &mut commands,
+            ev.ball_e,
+            &mut status,
+            ball_t,
+            entity_q.get(ball.trail_e.unwrap()).ok(),
+        );
+
+        if practice.is_done() {
+            let total = practice.get_total();
+            if total > practice.best {
+                practice.best = total;
+                practice.new_best = true;
+                practice.save_best();
+            }
+        } else {
+            let pos = get_target_pos(&court_set, net.0, &mut rng);
+            ring_t.translation.x = pos.x;
+            ring_t.translation.y = pos.y;
+
+            spawn_ball(
+                &mut commands,
+                &atlas,
+                serving_region.0,
+                0,
+                serving_region.0.get_player_id(),
+                &court_set,
+                &config,
+                &loadouts,
+                &mut rng,
+            );
+        }
+    }
+}
+
+fn update_target_ui(
+    practice: Res<TargetPractice>,
+    locale: Res<Locale>,
+    mut text_q: Query<&mut Text, (With<TargetText>, Without<TargetResultsText>)>,
+    mut results_text_q: Query<
+        (&mut Text, &mut Visibility, Option<&ButtonPrompt>),
+        With<TargetResultsText>,
+    >,
+    mut results_q: Query<&mut Visibility, (With<TargetResults>, Without<TargetResultsText>)>,
+) {
+    if !practice.is_changed() && !locale.is_changed() {
+        return;
+    }
+
+    let done = practice.is_done();
+    for mut text in text_q.iter_mut() {
+        text.sections[0].value = if done {
+            String::new()
+        } else {
+            locale.format(
+                "target-score",
+                &[
+                    &(practice.shots.len() + 1).to_string(),
+                    &SHOT_COUNT.to_string(),
+                    &practice.get_total().to_string(),
+                    &practice.best.to_string(),
+                ],
+            )
+        };
+    }
+
+    for mut visibility in results_q.iter_mut() {
+        visibility.is_visible = done;
+    }
+
+    for (mut text, mut visibility, prompt) in results_text_q.iter_mut() {
+        visibility.is_visible = done;
+
+        // the prompt fills itself in
+        if done && prompt.is_none() {
+            let shots: Vec<String> = practice.shots.iter().map(|p| p.to_string()).collect();
+            text.sections[0].value = format!(
+                "{}\n{}\n{}",
+                shots.join(" | "),
+                locale.format("target-total", &[&practice.get_total().to_string()]),
+                if practice.new_best {
+                    locale.get("target-new-best")
+                } else {
+                    locale.format("target-best", &[&practice.best.to_string()])
+                }
+            );
+        }
+    }
+}
+
+fn get_target_pos(court: &CourtSettings, net_x: f32, rng: &mut GameRng) -> Vec2 {
+    Vec2::new(
+        rng.gen_range((net_x + TARGET_MARGIN)..=(court.right - TARGET_MARGIN)),
+        rng.gen_range((court.bottom + TARGET_MARGIN)..=(court.top - TARGET_MARGIN)),
+    )
+}
+
+// speed only counts for shots that score on accuracy
+fn get_shot_points(dist: f32, speed_ratio: f32) -> u32 {
+    let accuracy = (1. - dist / SCORE_RADIUS).max(0.);
+    if accuracy == 0. {
+        return 0;
+    }
+
+    (accuracy * ACCURACY_POINTS + speed_ratio.clamp(0., 1.) * SPEED_POINTS).round() as u32
+}
@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_time::{ScaledTime, ScaledTimeDelta};
+
+use crate::GameState;
+
+// the clock a GameTimer ticks against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerClock {
+    // follows the game time scale and stops while the game is paused
+    Scaled,
+    // real time, keeps going through pauses and slow motion - input holds, UI
+    Unscaled,
+}
+
+impl Default for TimerClock {
+    fn default() -> Self {
+        TimerClock::Scaled
+    }
+}
+
+// a non-repeating timer which knows its clock, so the systems ticking it can't mix them up
+#[derive(Debug, Clone, Default)]
+pub struct GameTimer {
+    timer: Timer,
+    clock: TimerClock,
+}
+
+impl GameTimer {
+    pub fn scaled(duration_sec: f32) -> Self {
+        Self::new(duration_sec, TimerClock::Scaled)
+    }
+
+    pub fn unscaled(duration_sec: f32) -> Self {
+        Self::new(duration_sec, TimerClock::Unscaled)
+    }
+
+    pub fn new(duration_sec: f32, clock: TimerClock) -> Self {
+        Self {
+            timer: Timer::from_seconds(duration_sec, false),
+            clock,
+        }
+    }
+
+    // starts over with the new duration, on the same clock
+    pub fn restart(&mut self, duration_sec: f32) {
+        self.timer = Timer::from_seconds(duration_sec, false);
+    }
+
+    pub fn reset(&mut self) {
+        self.timer.reset();
+    }
+
+    pub fn tick(&mut self, clock: &GameClock) -> &Self {
+        self.tick_delta(clock.get_delta(self.clock))
+    }
+
+    // for deltas not coming from a GameClock, e.g. in tests and benches
+    pub fn tick_delta(&mut self, delta: Duration) -> &Self {
+        self.timer.tick(delta);
+        self
+    }
+
+    pub fn finished(&self) -> bool {
+        self.timer.finished()
+    }
+
+    pub fn just_finished(&self) -> bool {
+        self.timer.just_finished()
+    }
+
+    pub fn get_elapsed_sec(&self) -> f32 {
+        self.timer.elapsed_secs()
+    }
+
+    pub fn get_remaining_sec(&self) -> f32 {
+        (self.timer.duration().as_secs_f32() - self.timer.elapsed_secs()).max(0.)
+    }
+
+    // 0 at the start, 1 once finished
+    pub fn get_fraction(&self) -> f32 {
+        self.timer.percent()
+    }
+
+    // 1 at the start, 0 once finished - e.g. for draining UI bars
+    pub fn get_fraction_remaining(&self) -> f32 {
+        self.timer.percent_left()
+    }
+}
+
+// the deltas of both timer clocks
+#[derive(SystemParam)]
+pub struct GameClock<'w, 's> {
+    time: Res<'w, Time>,
+    scaled_time: ScaledTime<'w, 's>,
+    state: Res<'w, State<GameState>>,
+}
+
+impl<'w, 's> GameClock<'w, 's> {
+    // the intro and the challenge review get pushed on top of the game
+    pub fn is_paused(&self) -> bool {
+        self.state.inactives().contains(&GameState::Game)
+    }
+
+    pub fn get_delta(&self, clock: TimerClock) -> Duration {
+        match clock {
+            TimerClock::Scaled if self.is_paused() => Duration::ZERO,
+            TimerClock::Scaled => self.scaled_time.scaled_delta(),
+            TimerClock::Unscaled => self.time.delta(),
+        }
+    }
+}
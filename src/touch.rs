@@ -0,0 +1,127 @@
+use bevy::{input::touch::Touch, prelude::*};
+
+use crate::player::SWING_LABEL;
+
+// touch devices only get a single local player
+pub const TOUCH_PLAYER_ID: usize = 1;
+// drag distance for a full stick tilt
+const STICK_RADIUS_PX: f32 = 80.;
+const STICK_DEADZONE: f32 = 0.15;
+
+pub struct TouchPlugin;
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<TouchControls>()
+            .add_system(update_touch_controls.before(SWING_LABEL));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TouchSwing {
+    None,
+    Held(f32),
+    Released(f32),
+}
+
+impl Default for TouchSwing {
+    fn default() -> Self {
+        TouchSwing::None
+    }
+}
+
+// virtual stick on the left half of the screen, aim & swing on the right
+#[derive(Default)]
+pub struct TouchControls {
+    pub move_dir: Vec2,
+    pub aim_dir: Vec2,
+    pub swing: TouchSwing,
+    // a third finger held down restarts the match
+    pub reset_held: bool,
+    move_touch_id: Option<u64>,
+    aim_touch_id: Option<u64>,
+}
+
+impl TouchControls {
+    pub fn get_move_dir(&self, player_id: usize) -> Option<Vec2> {
+        (player_id == TOUCH_PLAYER_ID && self.move_dir != Vec2::ZERO).then(|| self.move_dir)
+    }
+
+    pub fn get_aim_dir(&self, player_id: usize) -> Option<Vec2> {
+        (player_id == TOUCH_PLAYER_ID && self.aim_dir != Vec2::ZERO).then(|| self.aim_dir)
+    }
+
+    pub fn get_swing(&self, player_id: usize) -> Option<TouchSwing> {
+        (player_id == TOUCH_PLAYER_ID && self.swing != TouchSwing::None).then(|| self.swing)
+    }
+}
+
+fn update_touch_controls(
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+    time: Res<Time>,
+    mut controls: ResMut<TouchControls>,
+) {
+    let width = match windows.get_primary() {
+        Some(window) => window.width(),
+        None => return,
+    };
+
+    controls.reset_held = touches.iter().count() >= 3;
+
+    // a touch belongs to the half it started in
+    for touch in touches.iter_just_pressed() {
+        if touch.start_position().x < width / 2. {
+            if controls.move_touch_id.is_none() {
+                controls.move_touch_id = Some(touch.id());
+            }
+        } else if controls.aim_touch_id.is_none() {
+            controls.aim_touch_id = Some(touch.id());
+        }
+    }
+
+    match controls.move_touch_id.map(|id| touches.get_pressed(id)) {
+        Some(Some(touch)) => controls.move_dir = get_stick_dir(touch),
+        _ => {
+            controls.move_touch_id = None;
+            controls.move_dir = Vec2::ZERO;
+        }
+    }
+
+    match controls.aim_touch_id.map(|id| touches.get_pressed(id)) {
+        Some(Some(touch)) => {
+            // keep the last aim, so lifting the finger doesn't reset it
+            let dir = get_stick_dir(touch);
+            if dir != Vec2::ZERO {
+                controls.aim_dir = dir;
+            }
+
+            controls.swing = match controls.swing {
+                TouchSwing::Held(duration) => TouchSwing::Held(duration + time.delta_seconds()),
+                _ => TouchSwing::Held(0.),
+            };
+        }
+        Some(None) => {
+            controls.aim_touch_id = None;
+            controls.swing = match controls.swing {
+                TouchSwing::Held(duration) => TouchSwing::Released(duration),
+                _ => TouchSwing::None,
+            };
+        }
+        // the aim dir stays from the last aim touch
+        None => {
+            controls.swing = TouchSwing::None;
+        }
+    }
+}
+
+fn get_stick_dir(touch: &Touch) -> Vec2 {
+    let delta = touch.position() - touch.start_position();
+    // touch y goes down the screen
+    let dir = Vec2::new(delta.x, -delta.y) / STICK_RADIUS_PX;
+
+    if dir.length() < STICK_DEADZONE {
+        Vec2::ZERO
+    } else {
+        dir.clamp_length_max(1.)
+    }
+}
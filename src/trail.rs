@@ -1,13 +1,42 @@
 use bevy::{math::Vec2, prelude::*};
 use bevy_prototype_lyon::prelude::*;
 use bevy_time::{ScaledTime, ScaledTimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::{palette::PaletteColor, render::PLAYER_Z, reset::DespawnOnExit, GameState};
+
+// distance between the dots of a dotted trail
+const DOT_SPACING: f32 = 18.;
+const COMET_PARTICLE_INTERVAL_SEC: f32 = 0.025;
+const COMET_PARTICLE_SEC: f32 = 0.35;
+const COMET_PARTICLE_DRIFT: f32 = 60.;
+// of the trail width
+const COMET_PARTICLE_SIZE_RATIO: f32 = 0.3;
 
 pub struct TrailPlugin;
 impl Plugin for TrailPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_system_to_stage(CoreStage::PostUpdate, store_path_points)
             .add_system_to_stage(CoreStage::Last, draw_trail)
-            .add_system(fadeout_trail);
+            .add_system(fadeout_trail)
+            .add_system(emit_comet_particles)
+            .add_system(fade_comet_particles);
+    }
+}
+
+// how the trail points get rendered
+#[derive(Component, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum TrailStyle {
+    Ribbon,
+    Dotted,
+    // a ribbon shedding particles
+    Comet,
+    None,
+}
+
+impl Default for TrailStyle {
+    fn default() -> Self {
+        TrailStyle::Ribbon
     }
 }
 
@@ -24,13 +53,19 @@ pub struct Trail {
     pub max_width: f32,
 }
 
+#[derive(Component)]
+struct CometParticle {
+    velocity: Vec2,
+    remaining_sec: f32,
+}
+
 #[derive(Component, Default)]
 pub struct FadeOutTrail {
     pub decrease_duration_by: f32,
     pub stop_trail: bool,
 }
 
-fn store_path_points(
+pub fn store_path_points(
     mut path_q: Query<(Entity, &mut Trail, Option<&FadeOutTrail>)>,
     transform_q: Query<&GlobalTransform>,
     time: Res<Time>,
@@ -66,7 +101,7 @@ fn store_path_points(
         }
 
         let duration = trail.duration_sec as f64;
-        trail.points.drain_filter(|p| p.time + duration < curr_time);
+        trail.points.retain(|p| p.time + duration >= curr_time);
 
         if trail.points.is_empty() {
             commands.entity(e).despawn_recursive();
@@ -74,43 +109,87 @@ fn store_path_points(
     }
 }
 
-fn draw_trail(mut path_q: Query<(&mut Path, &mut Trail)>, time: Res<Time>) {
-    for (mut path, trail) in path_q.iter_mut() {
+pub fn draw_trail(mut path_q: Query<(&mut Path, &Trail, &TrailStyle)>, time: Res<Time>) {
+    for (mut path, trail, style) in path_q.iter_mut() {
         if trail.points.len() > 1 {
-            let mut path_builder = PathBuilder::new();
-            let last = trail.points.last().unwrap();
-            let trail_dur = last.time - trail.points[0].time;
-            let mut points_back = Vec::with_capacity(trail.points.len());
-
-            // nice2have: the offset points should be angled (vertical movement breaks this right now, but that doesn't matter for the ball)
-            for (i, p) in trail.points.iter().rev().enumerate() {
-                let time_delta = time.seconds_since_startup() - p.time;
-                let w = (1. - (time_delta / trail_dur as f64)).clamp(0., 1.)
-                    * (trail.max_width as f64 / 2.);
-                let pos = p.position + Vec2::Y * w as f32;
-
-                if i == 0 {
-                    path_builder.move_to(pos);
-                } else {
-                    path_builder.line_to(pos);
-                }
+            let now = time.seconds_since_startup();
+            let line = match style {
+                TrailStyle::Ribbon | TrailStyle::Comet => build_ribbon(trail, now),
+                TrailStyle::Dotted => build_dots(trail, now),
+                TrailStyle::None => continue,
+            };
+            path.0 = line.0;
+        }
+    }
+}
 
-                if w == 0. {
-                    break;
-                }
+// half width of the trail at the point, tapering off with the point age
+fn get_half_width(trail: &Trail, point: &TrailPoint, now: f64) -> f32 {
+    let trail_dur = trail.points.last().unwrap().time - trail.points[0].time;
+    let time_delta = now - point.time;
+    ((1. - (time_delta / trail_dur)).clamp(0., 1.) * (trail.max_width as f64 / 2.)) as f32
+}
 
-                points_back.push(p.position - Vec2::Y * w as f32);
-            }
+fn build_ribbon(trail: &Trail, now: f64) -> Path {
+    let mut path_builder = PathBuilder::new();
+    let mut points_back = Vec::with_capacity(trail.points.len());
 
-            for p in points_back.iter().rev() {
-                path_builder.line_to(*p);
-            }
+    // nice2have: the offset points should be angled (vertical movement breaks this right now, but that doesn't matter for the ball)
+    for (i, p) in trail.points.iter().rev().enumerate() {
+        let w = get_half_width(trail, p, now);
+        let pos = p.position + Vec2::Y * w;
 
-            path_builder.close();
-            let line = path_builder.build();
-            path.0 = line.0;
+        if i == 0 {
+            path_builder.move_to(pos);
+        } else {
+            path_builder.line_to(pos);
+        }
+
+        if w == 0. {
+            break;
+        }
+
+        points_back.push(p.position - Vec2::Y * w);
+    }
+
+    for p in points_back.iter().rev() {
+        path_builder.line_to(*p);
+    }
+
+    path_builder.close();
+    path_builder.build()
+}
+
+// diamonds spaced along the travelled path, shrinking with age
+fn build_dots(trail: &Trail, now: f64) -> Path {
+    let mut path_builder = PathBuilder::new();
+    let mut prev_pos = None;
+    let mut dist = DOT_SPACING;
+
+    for p in trail.points.iter().rev() {
+        if let Some(prev_pos) = prev_pos {
+            dist += p.position.distance(prev_pos);
+        }
+        prev_pos = Some(p.position);
+
+        if dist < DOT_SPACING {
+            continue;
+        }
+        dist = 0.;
+
+        let w = get_half_width(trail, p, now);
+        if w == 0. {
+            break;
         }
+
+        path_builder.move_to(p.position + Vec2::Y * w);
+        path_builder.line_to(p.position + Vec2::X * w);
+        path_builder.line_to(p.position - Vec2::Y * w);
+        path_builder.line_to(p.position - Vec2::X * w);
+        path_builder.close();
     }
+
+    path_builder.build()
 }
 
 fn fadeout_trail(mut path_q: Query<(&FadeOutTrail, &mut Trail)>, time: ScaledTime) {
@@ -119,3 +198,77 @@ fn fadeout_trail(mut path_q: Query<(&FadeOutTrail, &mut Trail)>, time: ScaledTim
             (trail.duration_sec - fade.decrease_duration_by * time.scaled_delta_seconds()).max(0.);
     }
 }
+
+// sheds particles from the head of moving comet trails, sideways so they don't just cover the ribbon
+fn emit_comet_particles(
+    mut commands: Commands,
+    trail_q: Query<(&Trail, &TrailStyle, Option<&FadeOutTrail>)>,
+    time: ScaledTime,
+    mut emit_sec: Local<f32>,
+    mut emitted: Local<u32>,
+) {
+    *emit_sec += time.scaled_delta_seconds();
+    if *emit_sec < COMET_PARTICLE_INTERVAL_SEC {
+        return;
+    }
+    *emit_sec = 0.;
+
+    for (trail, style, fadeout) in trail_q.iter() {
+        if *style != TrailStyle::Comet || fadeout.map_or(false, |f| f.stop_trail) {
+            continue;
+        }
+
+        let (head, prev) = match trail.points.as_slice() {
+            [.., prev, head] => (head.position, prev.position),
+            _ => continue,
+        };
+        if head == prev {
+            continue;
+        }
+
+        // alternates the sides instead of rolling the game rng
+        *emitted += 1;
+        let side = if *emitted % 2 == 0 { 1. } else { -1. };
+        let dir = (head - prev).normalize();
+        let velocity = (dir.perp() * side - dir * 0.5) * COMET_PARTICLE_DRIFT;
+
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(trail.max_width * COMET_PARTICLE_SIZE_RATIO)),
+                    ..Default::default()
+                },
+                transform: Transform {
+                    translation: head.extend(PLAYER_Z + 0.5),
+                    rotation: Quat::from_rotation_z(std::f32::consts::FRAC_PI_4),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(CometParticle {
+                velocity,
+                remaining_sec: COMET_PARTICLE_SEC,
+            })
+            .insert(PaletteColor::BallTrail)
+            .insert(Name::new("CometParticle"))
+            .insert(DespawnOnExit(GameState::Game));
+    }
+}
+
+fn fade_comet_particles(
+    mut commands: Commands,
+    mut particle_q: Query<(Entity, &mut CometParticle, &mut Transform)>,
+    time: ScaledTime,
+) {
+    let dt = time.scaled_delta_seconds();
+    for (particle_e, mut particle, mut particle_t) in particle_q.iter_mut() {
+        particle.remaining_sec -= dt;
+        if particle.remaining_sec <= 0. {
+            commands.entity(particle_e).despawn_recursive();
+            continue;
+        }
+
+        particle_t.translation += (particle.velocity * dt).extend(0.);
+        particle_t.scale = Vec3::splat(particle.remaining_sec / COMET_PARTICLE_SEC);
+    }
+}
@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+
+use crate::{
+    ball::{apply_drag, get_bounce_velocity},
+    config::BallConfig,
+};
+
+const SIM_STEP_SEC: f32 = 1. / 60.;
+const SIM_MAX_SEC: f32 = 3.;
+
+// the ball right after being hit
+pub struct Shot {
+    pub pos: Vec2,
+    pub height: f32,
+    pub dir: Vec2,
+    pub bounce_velocity: f32,
+}
+
+// steps the same movement and bounce as the ball does
+// returns the ball height when it crosses x or None if it stops short
+pub fn get_height_at_x(shot: &Shot, x: f32, config: &BallConfig) -> Option<f32> {
+    let mut pos = shot.pos;
+    let mut height = shot.height;
+    let mut dir = shot.dir;
+    let mut velocity = shot.bounce_velocity;
+    let mut elapsed = 0.;
+
+    while elapsed < SIM_MAX_SEC {
+        dir = apply_drag(dir, SIM_STEP_SEC);
+        if dir == Vec2::ZERO {
+            return None;
+        }
+
+        let prev_x = pos.x;
+        pos += dir * config.max_speed * SIM_STEP_SEC;
+
+        velocity += config.gravity * SIM_STEP_SEC;
+        height += velocity * SIM_STEP_SEC;
+        if height <= 0. {
+            height = 0.01;
+            velocity = get_bounce_velocity(dir.length(), config.max_bounce_velocity);
+        }
+
+        if (prev_x < x) != (pos.x < x) {
+            return Some(height);
+        }
+
+        elapsed += SIM_STEP_SEC;
+    }
+
+    None
+}
+
+// where the ball first touches the ground or None if it stops short
+pub fn get_landing_pos(shot: &Shot, config: &BallConfig) -> Option<Vec2> {
+    let mut pos = shot.pos;
+    let mut height = shot.height;
+    let mut dir = shot.dir;
+    let mut velocity = shot.bounce_velocity;
+    let mut elapsed = 0.;
+
+    while elapsed < SIM_MAX_SEC {
+        dir = apply_drag(dir, SIM_STEP_SEC);
+        if dir == Vec2::ZERO {
+            return None;
+        }
+
+        pos += dir * config.max_speed * SIM_STEP_SEC;
+
+        velocity += config.gravity * SIM_STEP_SEC;
+        height += velocity * SIM_STEP_SEC;
+        if height <= 0. {
+            return Some(pos);
+        }
+
+        elapsed += SIM_STEP_SEC;
+    }
+
+    None
+}
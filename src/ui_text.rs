@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{score::Score, storage};
+
+const TEXT_SETTINGS_STORAGE_KEY: &str = "settings/text.ron";
+const SCALE_STEP: f32 = 0.125;
+const MIN_SCALE: f32 = 0.75;
+const MAX_SCALE: f32 = 1.5;
+// animated text scaling its own sizes has to run after this
+pub const TEXT_SETTINGS_LABEL: &str = "text_settings";
+
+pub struct UiTextPlugin;
+impl Plugin for UiTextPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        // inserted right away, so startup systems spawn the text with the saved settings
+        app.insert_resource(TextSettings::load())
+            .add_system(change_text_settings)
+            .add_system(apply_text_settings.label(TEXT_SETTINGS_LABEL));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UiFont {
+    Default,
+    // dyslexia-friendly - a wide, plain sans-serif with distinct I, l and 1 (DejaVu Sans)
+    Readable,
+}
+
+impl UiFont {
+    fn get_path(&self) -> &'static str {
+        match self {
+            UiFont::Default => "fonts/Typo_Round_Regular_Demo.otf",
+            UiFont::Readable => "fonts/DejaVuSans.ttf",
+        }
+    }
+
+    pub fn get_name_key(&self) -> &'static str {
+        match self {
+            UiFont::Default => "font-default",
+            UiFont::Readable => "font-readable",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TextSettings {
+    pub font: UiFont,
+    pub scale: f32,
+}
+
+impl Default for TextSettings {
+    fn default() -> Self {
+        Self {
+            font: UiFont::Default,
+            scale: 1.,
+        }
+    }
+}
+
+impl TextSettings {
+    fn load() -> Self {
+        storage::load(TEXT_SETTINGS_STORAGE_KEY)
+            .ok()
+            .and_then(|bytes| ron::de::from_bytes(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let result = ron::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|data| {
+                storage::save(TEXT_SETTINGS_STORAGE_KEY, data.as_bytes()).map_err(|e| e.to_string())
+            });
+
+        if let Err(e) = result {
+            error!("Failed to save the text settings: {}", e);
+        }
+    }
+}
+
+// unscaled font size, every section gets restyled when the settings change
+#[derive(Component)]
+pub struct ScaledText(pub f32);
+
+// all the UI text goes through this, so the settings apply everywhere
+pub fn get_text_style(
+    asset_server: &AssetServer,
+    settings: &TextSettings,
+    font_size: f32,
+) -> TextStyle {
+    TextStyle {
+        font: asset_server.load(settings.font.get_path()),
+        font_size: font_size * settings.scale,
+        color: Color::WHITE,
+    }
+}
+
+// same as the other settings - only before the 1st point of a match
+fn change_text_settings(
+    keys: Res<Input<KeyCode>>,
+    score: Res<Score>,
+    mut settings: ResMut<TextSettings>,
+) {
+    if !score.is_match_start() {
+        return;
+    }
+
+    let mut new_settings = *settings;
+
+    if keys.just_pressed(KeyCode::F) {
+        new_settings.font = match new_settings.font {
+            UiFont::Default => UiFont::Readable,
+            UiFont::Readable => UiFont::Default,
+        };
+    }
+
+    if keys.just_pressed(KeyCode::Minus) {
+        new_settings.scale -= SCALE_STEP;
+    } else if keys.just_pressed(KeyCode::Equals) {
+        new_settings.scale += SCALE_STEP;
+    }
+    new_settings.scale = new_settings.scale.clamp(MIN_SCALE, MAX_SCALE);
+
+    if new_settings != *settings {
+        *settings = new_settings;
+        settings.save();
+    }
+}
+
+fn apply_text_settings(
+    settings: Res<TextSettings>,
+    asset_server: Res<AssetServer>,
+    mut text_q: Query<(&ScaledText, &mut Text)>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (scaled, mut text) in text_q.iter_mut() {
+        let style = get_text_style(&asset_server, &settings, scaled.0);
+
+        for section in text.sections.iter_mut() {
+            // keep the palette color
+            section.style.font = style.font.clone();
+            section.style.font_size = style.font_size;
+        }
+    }
+}
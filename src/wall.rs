@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+
+use crate::{
+    asset::SpriteAtlas,
+    ball::{despawn_ball, spawn_ball, Ball, BallBouncedEvt, BallStatus, WallHitEvt},
+    config::GameConfig,
+    cosmetic::Loadouts,
+    level::{CourtSettings, ServingRegion},
+    locale::Locale,
+    mode::GameMode,
+    palette::PaletteColor,
+    render::NET_Z,
+    reset::DespawnOnExit,
+    rng::GameRng,
+    ui_text::{get_text_style, ScaledText, TextSettings},
+    GameSetupPhase, GameState,
+};
+
+pub struct WallPlugin;
+impl Plugin for WallPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<WallPractice>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Game)
+                    .with_system(setup.after(GameSetupPhase::Level)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(count_returns)
+                    .with_system(on_ball_bounced)
+                    .with_system(update_returns_ui),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct Wall;
+
+#[derive(Component)]
+struct ReturnsText;
+
+// consecutive returns against the wall
+#[derive(Default)]
+pub struct WallPractice {
+    pub returns: u32,
+    pub best: u32,
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    text_settings: Res<TextSettings>,
+    mode: Res<GameMode>,
+    court: Res<CourtSettings>,
+    mut practice: ResMut<WallPractice>,
+) {
+    if !mode.has_wall() {
+        return;
+    }
+
+    practice.returns = 0;
+
+    // covers the net, so the ball rebounds where it would usually cross it
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform: Transform::from_xyz(0., 0., NET_Z + 1.),
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(30., court.net_extent * 2. + 60.)),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(PaletteColor::CourtPost)
+        .insert(Wall)
+        .insert(Name::new("Wall"))
+        .insert(DespawnOnExit(GameState::Game));
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::Center,
+                position_type: PositionType::Relative,
+                margin: Rect {
+                    top: Val::Auto,
+                    bottom: Val::Px(10.0),
+                    right: Val::Auto,
+                    left: Val::Auto,
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                get_text_style(&asset_server, &text_settings, 60.0),
+                TextAlignment {
+                    horizontal: HorizontalAlign::Center,
+                    ..Default::default()
+                },
+            ),
+            ..Default::default()
+        })
+        .insert(PaletteColor::Text)
+        .insert(ScaledText(60.0))
+        .insert(ReturnsText)
+        .insert(Name::new("ReturnsText"))
+        .insert(DespawnOnExit(GameState::Game));
+}
+
+fn count_returns(mut ev_r_wall_hit: EventReader<WallHitEvt>, mut practice: ResMut<WallPractice>) {
+    for _ in ev_r_wall_hit.iter() {
+        practice.returns += 1;
+    }
+}
+
+// the rally is over once the ball bounces twice or lands out
+fn on_ball_bounced(
+    mut commands: Commands,
+    mut ev_r_ball_bounced: EventReader<BallBouncedEvt>,
+    mut ball_q: Query<(&Ball, &mut BallStatus, &Transform)>,
+    entity_q: Query<Entity>,
+    atlas: Res<SpriteAtlas>,
+    serving_region: Res<ServingRegion>,
+    court_set: Res<CourtSettings>,
+    config: Res<GameConfig>,
+    loadouts: Res<Loadouts>,
+    mut rng: ResMut<GameRng>,
+    mode: Res<GameMode>,
+    mut practice: ResMut<WallPractice>,
+) {
+    if !mode.has_wall() {
+        return;
+    }
+
+    for ev in ev_r_ball_bounced.iter() {
+        if let Ok((ball, mut status, ball_t)) = ball_q.get_mut(ev.ball_e) {
+            if matches!(*status, BallStatus::Used)
+                || (ev.bounce_count < 2 && !ball.region.is_out_of_bounds())
+            {
+                continue;
+            }
+
+            debug!(
+                "Wall practice rally over after {} returns",
+                practice.returns
+            );
+            practice.best = practice.best.max(practice.returns);
+            practice.returns = 0;
+
+            despawn_ball(
+                &mut commands,
+                ev.ball_e,
+                &mut status,
+                ball_t,
+                entity_q.get(ball.trail_e.unwrap()).ok(),
+            );
+            spawn_ball(
+                &mut commands,
+                &atlas,
+                serving_region.0,
+                0,
+                serving_region.0.get_player_id(),
+                &court_set,
+                &config,
+                &loadouts,
+                &mut rng,
+            );
+        }
+    }
+}
+
+fn update_returns_ui(
+    practice: Res<WallPractice>,
+    locale: Res<Locale>,
+    mut text_q: Query<&mut Text, With<ReturnsText>>,
+) {
+    for mut text in text_q.iter_mut() {
+        let value = locale.format(
+            "wall-returns",
+            &[&practice.returns.to_string(), &practice.best.to_string()],
+        );
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
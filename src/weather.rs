@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    level::CourtSettings,
+    palette::{Palette, PaletteColor, PaletteTheme},
+    render::{COURT_LINE_Z, COURT_Z},
+    reset::DespawnOnExit,
+    rng::GameRng,
+    score::ScoreChangedEvt,
+    GameSetupPhase, GameState,
+};
+
+const PUDDLE_COUNT: usize = 3;
+const PUDDLE_TRACTION: f32 = 0.3;
+const PUDDLE_BOUNCE_MULT: f32 = 0.55;
+
+pub struct WeatherPlugin;
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Game).with_system(setup.after(GameSetupPhase::Level)),
+        )
+        .add_system_set(SystemSet::on_update(GameState::Game).with_system(refresh_puddles));
+    }
+}
+
+// a slick court patch, sampled by the player and ball movement
+#[derive(Component)]
+pub struct Puddle {
+    pub half_size: Vec2,
+    // 1 is full grip, lower values make the momentum carry
+    pub traction: f32,
+    pub bounce_mult: f32,
+}
+
+impl Puddle {
+    fn contains(&self, puddle_pos: Vec2, pos: Vec2) -> bool {
+        let offset = (pos - puddle_pos).abs();
+        offset.x <= self.half_size.x && offset.y <= self.half_size.y
+    }
+}
+
+pub fn get_puddle_at<'a>(
+    puddles: impl Iterator<Item = (&'a Puddle, &'a GlobalTransform)>,
+    pos: Vec2,
+) -> Option<&'a Puddle> {
+    puddles
+        .filter(|(puddle, puddle_t)| puddle.contains(puddle_t.translation.truncate(), pos))
+        .map(|(puddle, _)| puddle)
+        .next()
+}
+
+fn setup(
+    mut commands: Commands,
+    palette: Res<Palette>,
+    court_set: Res<CourtSettings>,
+    mut rng: ResMut<GameRng>,
+) {
+    if palette.get_theme() == PaletteTheme::Rain {
+        spawn_puddles(&mut commands, &court_set, &mut rng);
+    }
+}
+
+// puddles only last for a game (or until the rain stops)
+fn refresh_puddles(
+    mut commands: Commands,
+    mut ev_r_score: EventReader<ScoreChangedEvt>,
+    palette: Res<Palette>,
+    court_set: Res<CourtSettings>,
+    mut rng: ResMut<GameRng>,
+    puddle_q: Query<Entity, With<Puddle>>,
+) {
+    let game_won = ev_r_score.iter().any(|ev| ev.outcome.is_game_won());
+    if !game_won && !palette.is_changed() {
+        return;
+    }
+
+    for e in puddle_q.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+
+    if palette.get_theme() == PaletteTheme::Rain {
+        spawn_puddles(&mut commands, &court_set, &mut rng);
+    }
+}
+
+fn spawn_puddles(commands: &mut Commands, court_set: &CourtSettings, rng: &mut GameRng) {
+    for _ in 0..PUDDLE_COUNT {
+        let size = Vec2::new(rng.gen_range(90.0..=170.0), rng.gen_range(60.0..=110.0));
+        let half_size = size / 2.;
+        let x = rng.gen_range((court_set.left + half_size.x)..=(court_set.right - half_size.x));
+        let y = rng.gen_range((court_set.bottom + half_size.y)..=(court_set.top - half_size.y));
+
+        commands
+            .spawn_bundle(SpriteBundle {
+                // between the court and its lines
+                transform: Transform::from_xyz(x, y, (COURT_Z + COURT_LINE_Z) / 2.),
+                sprite: Sprite {
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(Puddle {
+                half_size,
+                traction: PUDDLE_TRACTION,
+                bounce_mult: PUDDLE_BOUNCE_MULT,
+            })
+            .insert(PaletteColor::Puddle)
+            .insert(Name::new("Puddle"))
+            .insert(DespawnOnExit(GameState::Game));
+    }
+}
@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+use heron::{CollisionData, CollisionEvent, CollisionLayers};
+use tug_of_ball::{
+    ball::{self, despawn_ball, Ball, BallBounce, BallHitEvt, BallStatus},
+    config::GameConfig,
+    fatigue::PlayerFatigue,
+    level::CourtSettings,
+    momentum::Momentum,
+    physics::PhysLayer,
+    player::{Player, PlayerAim, PlayerJump, PlayerSwing},
+    player_action::PlayerActionStatus,
+    rng::GameRng,
+};
+
+fn get_app() -> App {
+    let mut app = App::new();
+    app.add_event::<CollisionEvent>()
+        .add_event::<BallHitEvt>()
+        .insert_resource(GameConfig::default())
+        .insert_resource(CourtSettings::default())
+        .insert_resource(Momentum::default())
+        .insert_resource(GameRng::new(1))
+        .add_system(ball::handle_collisions);
+    app
+}
+
+fn spawn_rally_ball(world: &mut World) -> Entity {
+    let bounce_e = world
+        .spawn()
+        .insert(BallBounce::default())
+        .insert(Transform::default())
+        .id();
+
+    world
+        .spawn()
+        .insert(Ball {
+            bounce_e: Some(bounce_e),
+            ..Default::default()
+        })
+        .insert(BallStatus::Rally(2))
+        .insert(PhysLayer::Ball.get_layers())
+        .insert(Transform::default())
+        .insert(GlobalTransform::default())
+        .push_children(&[bounce_e])
+        .id()
+}
+
+// mid swing, aiming across the net
+fn spawn_swinging_player(world: &mut World) -> Entity {
+    let aim_e = world
+        .spawn()
+        .insert(PlayerAim {
+            dir: Vec2::X,
+            ..Default::default()
+        })
+        .id();
+
+    world
+        .spawn()
+        .insert(Player::new(1, aim_e, aim_e))
+        .insert(PlayerSwing {
+            status: PlayerActionStatus::Active(1.),
+            ..Default::default()
+        })
+        .insert(PlayerFatigue::new())
+        .insert(PlayerJump::default())
+        .insert(GlobalTransform::default())
+        .id()
+}
+
+fn take_balls_out_of_play(
+    mut commands: Commands,
+    mut ball_q: Query<(Entity, &mut BallStatus, &Transform)>,
+) {
+    for (ball_e, mut status, ball_t) in ball_q.iter_mut() {
+        despawn_ball(&mut commands, ball_e, &mut status, ball_t, None);
+    }
+}
+
+fn swing_at(app: &mut App, ball_e: Entity, player_e: Entity) -> usize {
+    let swing_layers = PhysLayer::SwingHitbox.get_layers();
+    app.world
+        .get_resource_mut::<Events<CollisionEvent>>()
+        .unwrap()
+        .send(CollisionEvent::Started(
+            CollisionData::new(ball_e, ball_e, PhysLayer::Ball.get_layers(), Vec::new()),
+            CollisionData::new(player_e, player_e, swing_layers, Vec::new()),
+        ));
+    app.update();
+
+    let hits = app.world.get_resource::<Events<BallHitEvt>>().unwrap();
+    hits.get_reader().iter(hits).count()
+}
+
+#[test]
+fn rally_ball_can_be_hit() {
+    let mut app = get_app();
+    let ball_e = spawn_rally_ball(&mut app.world);
+    let player_e = spawn_swinging_player(&mut app.world);
+
+    assert_eq!(swing_at(&mut app, ball_e, player_e), 1);
+}
+
+// a swing in progress used to hit the scaling out ball after the point was resolved
+#[test]
+fn despawned_ball_cannot_be_hit() {
+    let mut app = get_app();
+    let ball_e = spawn_rally_ball(&mut app.world);
+    let player_e = spawn_swinging_player(&mut app.world);
+
+    let mut stage = SystemStage::single(take_balls_out_of_play);
+    stage.run(&mut app.world);
+
+    let swing_layers = PhysLayer::SwingHitbox.get_layers();
+    assert!(!app
+        .world
+        .get::<CollisionLayers>(ball_e)
+        .unwrap()
+        .interacts_with(swing_layers));
+    assert!(!app.world.get::<BallStatus>(ball_e).unwrap().is_in_play());
+
+    assert_eq!(swing_at(&mut app, ball_e, player_e), 0);
+}
+
+#[test]
+fn only_used_ball_is_out_of_play() {
+    assert!(BallStatus::Drop.is_in_play());
+    assert!(BallStatus::Rally(1).is_in_play());
+    assert!(BallStatus::Fault(1, 2).is_in_play());
+    assert!(!BallStatus::Used.is_in_play());
+}
@@ -0,0 +1,39 @@
+use tug_of_ball::{mode::GameMode, player::get_rally_bounce_reason, score::PointReason};
+
+// the good serve turns the ball into a rally ball on its 1st bounce
+#[test]
+fn served_ball_in_volley_mode_is_not_a_point() {
+    let limit = GameMode::Volley.get_bounce_limit();
+
+    assert_eq!(get_rally_bounce_reason(false, 1, limit, true), None);
+}
+
+#[test]
+fn volley_mode_gives_the_point_on_a_rally_bounce() {
+    let limit = GameMode::Volley.get_bounce_limit();
+
+    assert_eq!(
+        get_rally_bounce_reason(false, 1, limit, false),
+        Some(PointReason::TooManyBounces)
+    );
+    // the receiver let the served ball bounce again
+    assert_eq!(
+        get_rally_bounce_reason(false, 2, limit, false),
+        Some(PointReason::TooManyBounces)
+    );
+}
+
+#[test]
+fn match_mode_allows_one_bounce() {
+    let limit = GameMode::Match.get_bounce_limit();
+
+    assert_eq!(get_rally_bounce_reason(false, 1, limit, false), None);
+    assert_eq!(
+        get_rally_bounce_reason(false, 2, limit, false),
+        Some(PointReason::TooManyBounces)
+    );
+    assert_eq!(
+        get_rally_bounce_reason(true, 1, limit, false),
+        Some(PointReason::Out)
+    );
+}
@@ -0,0 +1,58 @@
+use tug_of_ball::{
+    checksum::StateChecksum,
+    input_recording::{GamepadFrame, InputFrame, InputRecording},
+};
+
+fn get_recording() -> InputRecording {
+    InputRecording {
+        seed: 1234,
+        frames: (0..3)
+            .map(|i| InputFrame {
+                delta_seconds: 1. / 60.,
+                keys: 1 << i | 1 << 63,
+                gamepads: [
+                    GamepadFrame {
+                        buttons: 1 << i,
+                        axes: [0.5, -0.5, 0., 1., -1., i as f32],
+                    },
+                    GamepadFrame::default(),
+                ],
+                checksum: StateChecksum {
+                    transforms: i,
+                    score: Some(i * 2),
+                },
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn recording_survives_write_and_read() {
+    let recording = get_recording();
+    let mut data = Vec::new();
+    recording.write(&mut data).unwrap();
+
+    let read = InputRecording::read(&mut data.as_slice()).unwrap();
+
+    assert_eq!(read.seed, recording.seed);
+    assert_eq!(read.frames.len(), recording.frames.len());
+    for (read, frame) in read.frames.iter().zip(recording.frames.iter()) {
+        assert_eq!(read.delta_seconds, frame.delta_seconds);
+        assert_eq!(read.keys, frame.keys);
+        for (read, gamepad) in read.gamepads.iter().zip(frame.gamepads.iter()) {
+            assert_eq!(read.buttons, gamepad.buttons);
+            assert_eq!(read.axes, gamepad.axes);
+        }
+        assert_eq!(read.checksum, frame.checksum);
+    }
+}
+
+#[test]
+fn bogus_frame_count_fails_to_read() {
+    let mut data = Vec::new();
+    get_recording().write(&mut data).unwrap();
+    // the frame count right after the magic, the version and the seed
+    data[13..17].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    assert!(InputRecording::read(&mut data.as_slice()).is_err());
+}
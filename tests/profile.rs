@@ -0,0 +1,12 @@
+use tug_of_ball::profile::get_profile_key;
+
+#[test]
+fn names_differing_in_case_and_symbols_share_a_key() {
+    assert_eq!(get_profile_key("Ann B"), get_profile_key("ann_b"));
+    assert_eq!(get_profile_key("ANN-B"), get_profile_key("ann b"));
+}
+
+#[test]
+fn different_names_get_different_keys() {
+    assert_ne!(get_profile_key("Ann"), get_profile_key("Anna"));
+}
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+use tug_of_ball::{
+    checksum::StateChecksum,
+    replay::{Replay, ReplayFrame},
+};
+
+fn get_replay() -> Replay {
+    Replay {
+        frames: (0..3)
+            .map(|i| ReplayFrame {
+                time: i as f32 * 0.5,
+                players: vec![(1, Vec2::new(-200., i as f32)), (2, Vec2::new(200., 0.))],
+                balls: vec![Vec3::new(i as f32 * 10., 5., 20.)],
+            })
+            .collect(),
+        checksums: vec![
+            StateChecksum {
+                transforms: 42,
+                score: None,
+            },
+            StateChecksum {
+                transforms: 7,
+                score: Some(3),
+            },
+        ],
+    }
+}
+
+#[test]
+fn replay_survives_write_and_read() {
+    let replay = get_replay();
+    let mut data = Vec::new();
+    replay.write(&mut data).unwrap();
+
+    let read = Replay::read(&mut data.as_slice()).unwrap();
+
+    assert_eq!(read.frames.len(), replay.frames.len());
+    for (read, frame) in read.frames.iter().zip(replay.frames.iter()) {
+        assert_eq!(read.time, frame.time);
+        assert_eq!(read.players, frame.players);
+        assert_eq!(read.balls, frame.balls);
+    }
+    assert_eq!(read.checksums, replay.checksums);
+}
+
+#[test]
+fn bogus_frame_count_fails_to_read() {
+    let mut data = Vec::new();
+    get_replay().write(&mut data).unwrap();
+    // the frame count right after the magic and the version
+    data[5..9].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    assert!(Replay::read(&mut data.as_slice()).is_err());
+}
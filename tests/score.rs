@@ -0,0 +1,142 @@
+use tug_of_ball::score::{
+    add_point_to_score, GamePhase, GamePoint, MatchRules, PointOutcome, Score,
+};
+
+fn play(score: &mut Score, rules: &MatchRules, points: &str) -> PointOutcome {
+    points
+        .chars()
+        .map(|p| add_point_to_score(score, rules, p == 'l'))
+        .last()
+        .unwrap()
+}
+
+#[test]
+fn deuce_keeps_raw_points() {
+    let rules = MatchRules::default();
+    let mut score = Score::default();
+
+    play(&mut score, &rules, "lllrrrlr");
+    assert_eq!(
+        (score.left_player.points, score.right_player.points),
+        (4, 4)
+    );
+    assert_eq!(score.get_display_points(&rules), (3, 3));
+    assert_eq!(score.get_game_phase(&rules), GamePhase::Deuce);
+}
+
+#[test]
+fn advantage_goes_to_the_leader() {
+    let rules = MatchRules::default();
+    let mut score = Score::default();
+
+    play(&mut score, &rules, "lllrrrlrr");
+    assert_eq!(score.get_display_points(&rules), (3, 4));
+    assert_eq!(
+        score.get_game_phase(&rules),
+        GamePhase::Advantage { left_player: false }
+    );
+    assert!(score.is_game_point(&rules, false));
+    assert!(!score.is_game_point(&rules, true));
+}
+
+#[test]
+fn game_needs_a_two_point_margin() {
+    let rules = MatchRules::default();
+    let mut score = Score::default();
+
+    assert_eq!(play(&mut score, &rules, "lllrrrl"), PointOutcome::Point);
+    assert_eq!(play(&mut score, &rules, "rlr"), PointOutcome::Point);
+    assert_eq!(play(&mut score, &rules, "l"), PointOutcome::Point);
+    assert_eq!(play(&mut score, &rules, "l"), PointOutcome::Game);
+    assert_eq!(score.left_player.games, 1);
+    assert_eq!(
+        (score.left_player.points, score.right_player.points),
+        (0, 0)
+    );
+    assert_eq!(score.get_game_phase(&rules), GamePhase::Regular);
+}
+
+#[test]
+fn tiebreak_has_no_deuce() {
+    let rules = MatchRules::default();
+    let mut score = Score::default();
+    score.left_player.games = rules.tiebreak_at;
+    score.right_player.games = rules.tiebreak_at;
+
+    play(&mut score, &rules, "lrlrlrlrlr");
+    assert_eq!(score.get_display_points(&rules), (5, 5));
+    assert_eq!(score.get_game_phase(&rules), GamePhase::Regular);
+}
+
+#[test]
+fn no_ad_deuce_is_a_golden_point() {
+    let rules = MatchRules {
+        no_ad: true,
+        ..Default::default()
+    };
+    let mut score = Score::default();
+
+    play(&mut score, &rules, "lllrrr");
+    assert_eq!(score.get_game_phase(&rules), GamePhase::GoldenPoint);
+    assert!(score.is_game_point(&rules, true));
+    assert!(score.is_game_point(&rules, false));
+    assert_eq!(play(&mut score, &rules, "r"), PointOutcome::Game);
+    assert_eq!(score.right_player.games, 1);
+}
+
+#[test]
+fn no_ad_tiebreak_still_needs_a_margin() {
+    let rules = MatchRules {
+        no_ad: true,
+        ..Default::default()
+    };
+    let mut score = Score::default();
+    score.left_player.games = rules.tiebreak_at;
+    score.right_player.games = rules.tiebreak_at;
+
+    assert_eq!(
+        play(&mut score, &rules, "lrlrlrlrlrlr"),
+        PointOutcome::Point
+    );
+    assert_eq!(score.get_game_phase(&rules), GamePhase::Regular);
+    assert_eq!(play(&mut score, &rules, "l"), PointOutcome::Point);
+}
+
+#[test]
+fn game_points_are_called_from_raw_points() {
+    let rules = MatchRules::default();
+    let mut score = Score::default();
+
+    assert_eq!(
+        score.get_game_points(&rules),
+        Some((GamePoint::Love, GamePoint::Love))
+    );
+    play(&mut score, &rules, "llr");
+    assert_eq!(
+        score.get_game_points(&rules),
+        Some((GamePoint::Thirty, GamePoint::Fifteen))
+    );
+    play(&mut score, &rules, "lrr");
+    assert_eq!(
+        score.get_game_points(&rules),
+        Some((GamePoint::Forty, GamePoint::Forty))
+    );
+    // long deuce games still read as advantage
+    play(&mut score, &rules, "lrlrl");
+    assert_eq!(
+        score.get_game_points(&rules),
+        Some((GamePoint::Advantage, GamePoint::Forty))
+    );
+    assert_eq!(GamePoint::Advantage.get_label(), "AD");
+}
+
+#[test]
+fn tiebreak_has_no_game_point_calls() {
+    let rules = MatchRules::default();
+    let mut score = Score::default();
+    score.left_player.games = rules.tiebreak_at;
+    score.right_player.games = rules.tiebreak_at;
+
+    play(&mut score, &rules, "lr");
+    assert_eq!(score.get_game_points(&rules), None);
+}
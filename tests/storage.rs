@@ -0,0 +1,29 @@
+use tug_of_ball::storage::get_next_number;
+
+fn get_keys(keys: &[&str]) -> Vec<String> {
+    keys.iter().map(|k| k.to_string()).collect()
+}
+
+#[test]
+fn first_save_is_number_one() {
+    assert_eq!(get_next_number(&[], "replays/match_", "tobr"), 1);
+}
+
+#[test]
+fn deleted_saves_do_not_reuse_numbers() {
+    // match_00002 got deleted
+    let keys = get_keys(&["replays/match_00001.tobr", "replays/match_00003.tobr"]);
+
+    assert_eq!(get_next_number(&keys, "replays/match_", "tobr"), 4);
+}
+
+#[test]
+fn other_keys_are_ignored() {
+    let keys = get_keys(&[
+        "replays/match_00002.tobr",
+        "replays/match_00009.bak",
+        "replays/notes.txt",
+    ]);
+
+    assert_eq!(get_next_number(&keys, "replays/match_", "tobr"), 3);
+}